@@ -8,46 +8,54 @@ extern crate nimiq_hash as hash;
 extern crate nimiq_keys as keys;
 extern crate nimiq_mempool as mempool;
 extern crate nimiq_primitives as primitives;
+extern crate nimiq_transaction as transaction;
+extern crate nimiq_vrf as vrf;
 
 use std::sync::Arc;
 
+pub use signer::{Signer, SignerError};
+
 use block::MicroJustification;
 use block::{ForkProof, MacroBlock};
 use block::{MacroBody, MacroHeader, MicroBlock, MicroBody, MicroHeader, ViewChangeProof, ViewChanges};
 use blockchain::blockchain::Blockchain;
 use blockchain::history_store::ExtendedTransaction;
-use bls::KeyPair;
 
 use hash::{Blake2bHash, Hash};
 use mempool::Mempool;
 use nimiq_account::Inherent;
 use primitives::policy;
+use vrf::VrfSeed;
+
+pub mod signer;
 
 /// Struct that contains all necessary information to actually produce blocks. It has the current
-/// blockchain store and state, the current mempool for this validator and the validator key for
-/// this validator.
+/// blockchain store and state, the current mempool for this validator and the signer of the
+/// validator key for this validator.
 pub struct BlockProducer {
     pub blockchain: Arc<Blockchain>,
     pub mempool: Option<Arc<Mempool>>,
-    pub validator_key: KeyPair,
+    pub signer: Arc<dyn Signer>,
 }
 
 impl BlockProducer {
-    /// Creates a new BlockProducer struct given a blockchain, a mempool and a validator key.
-    pub fn new(blockchain: Arc<Blockchain>, mempool: Arc<Mempool>, validator_key: KeyPair) -> Self {
+    /// Creates a new BlockProducer struct given a blockchain, a mempool and a signer for the
+    /// validator key (e.g. the key pair itself).
+    pub fn new<S: Signer + 'static>(blockchain: Arc<Blockchain>, mempool: Arc<Mempool>, signer: S) -> Self {
         BlockProducer {
             blockchain,
             mempool: Some(mempool),
-            validator_key,
+            signer: Arc::new(signer),
         }
     }
 
-    /// Creates a new BlockProducer struct without a mempool given a blockchain and a validator key.
-    pub fn new_without_mempool(blockchain: Arc<Blockchain>, validator_key: KeyPair) -> Self {
+    /// Creates a new BlockProducer struct without a mempool given a blockchain and a signer for
+    /// the validator key.
+    pub fn new_without_mempool<S: Signer + 'static>(blockchain: Arc<Blockchain>, signer: S) -> Self {
         BlockProducer {
             blockchain,
             mempool: None,
-            validator_key,
+            signer: Arc::new(signer),
         }
     }
 
@@ -67,6 +75,37 @@ impl BlockProducer {
         fork_proofs: Vec<ForkProof>,
         // Extra data for this block. It has no a priori use.
         extra_data: Vec<u8>,
+    ) -> Result<MicroBlock, SignerError> {
+        // Calculate the seed for this block by signing the previous block seed with the validator
+        // key.
+        let seed = self.signer.sign_seed(self.blockchain.head().seed())?;
+
+        // Create the block without its justification.
+        let mut block = self.next_unsigned_micro_block(timestamp, view_number, seed, fork_proofs, extra_data);
+
+        // Signs the block header using the validator key.
+        let signature = self.signer.sign_micro_header(&block.header)?.compress();
+
+        // Returns the micro block.
+        block.justification = Some(MicroJustification { signature, view_change_proof });
+        Ok(block)
+    }
+
+    /// Creates the next micro block without a justification, given its seed. This allows signing
+    /// the block without holding the Blockchain lock.
+    // Note: Needs to be called with the Blockchain lock held.
+    pub fn next_unsigned_micro_block(
+        &self,
+        // The timestamp for the block.
+        timestamp: u64,
+        // The view number for the block.
+        view_number: u32,
+        // The seed for this block, i.e. the previous block seed signed with the validator key.
+        seed: VrfSeed,
+        // Proofs of any forks created by malicious validators.
+        fork_proofs: Vec<ForkProof>,
+        // Extra data for this block. It has no a priori use.
+        extra_data: Vec<u8>,
     ) -> MicroBlock {
         // Calculate the block number. It is simply the previous block number incremented by one.
         let block_number = self.blockchain.block_number() + 1;
@@ -78,10 +117,6 @@ impl BlockProducer {
         // Get the hash of the latest block. It can be any block type.
         let parent_hash = self.blockchain.head_hash();
 
-        // Calculate the seed for this block by signing the previous block seed with the validator
-        // key.
-        let seed = self.blockchain.head().seed().sign_next(&self.validator_key.secret_key);
-
         // Calculate the maximum allowed size for the micro block body.
         let max_size = MicroBlock::MAX_SIZE - MicroHeader::SIZE - MicroBody::get_metadata_size(fork_proofs.len());
 
@@ -125,14 +160,11 @@ impl BlockProducer {
             body_root: body.hash(),
         };
 
-        // Signs the block header using the validator key.
-        let signature = self.validator_key.sign(&header).compress();
-
         // Returns the micro block.
         MicroBlock {
             header,
             body: Some(body),
-            justification: Some(MicroJustification { signature, view_change_proof }),
+            justification: None,
         }
     }
 
@@ -148,7 +180,7 @@ impl BlockProducer {
         view_number: u32,
         // Extra data for this block. It has no a priori use.
         extra_data: Vec<u8>,
-    ) -> MacroBlock {
+    ) -> Result<MacroBlock, SignerError> {
         // Calculate the block number. It is simply the previous block number incremented by one.
         let block_number = self.blockchain.block_number() + 1;
 
@@ -164,7 +196,7 @@ impl BlockProducer {
 
         // Calculate the seed for this block by signing the previous block seed with the validator
         // key.
-        let seed = self.blockchain.head().seed().sign_next(&self.validator_key.secret_key);
+        let seed = self.signer.sign_seed(self.blockchain.head().seed())?;
 
         // Create the header for the macro block without the state root and the transactions root.
         // We need several fields of this header in order to calculate the transactions and the
@@ -240,11 +272,11 @@ impl BlockProducer {
         header.body_root = body.hash();
 
         // Returns the block proposal.
-        MacroBlock {
+        Ok(MacroBlock {
             header,
             body: Some(body),
             justification: None,
-        }
+        })
     }
 }
 
@@ -264,18 +296,20 @@ pub mod test_utils {
         let init_height = blockchain.block_number();
         let macro_block_number = policy::macro_block_after(init_height + 1);
         for i in (init_height + 1)..macro_block_number {
-            let last_micro_block = producer.next_micro_block(blockchain.time.now() + i as u64 * 1000, 0, None, vec![], vec![0x42]);
+            let last_micro_block = producer
+                .next_micro_block(blockchain.time.now() + i as u64 * 1000, 0, None, vec![], vec![0x42])
+                .unwrap();
             assert_eq!(blockchain.push(Block::Micro(last_micro_block)), Ok(PushResult::Extended));
         }
         assert_eq!(blockchain.block_number(), macro_block_number - 1);
     }
 
-    pub fn sign_macro_block(keypair: &KeyPair, header: MacroHeader, body: Option<MacroBody>) -> MacroBlock {
+    pub fn sign_macro_block(signer: &dyn Signer, header: MacroHeader, body: Option<MacroBody>) -> MacroBlock {
         // Calculate block hash.
         let block_hash = header.hash::<Blake2bHash>();
 
         // Calculate the validator Merkle root (used in the nano sync).
-        let validator_merkle_root = pk_tree_construct(vec![keypair.public_key.public_key; SLOTS as usize]);
+        let validator_merkle_root = pk_tree_construct(vec![signer.public_key().public_key; SLOTS as usize]);
 
         // Create the precommit tendermint vote.
         let precommit = TendermintVote {
@@ -289,7 +323,7 @@ pub mod test_utils {
         };
 
         // Create signed precommit.
-        let signed_precommit = keypair.secret_key.sign(&precommit);
+        let signed_precommit = signer.sign_tendermint_vote(&precommit).unwrap();
 
         // Create signers Bitset.
         let mut signers = BitSet::new();
@@ -353,9 +387,11 @@ pub mod test_utils {
             fill_micro_blocks(producer, blockchain);
 
             let _next_block_height = blockchain.block_number() + 1;
-            let macro_block = producer.next_macro_block_proposal(blockchain.time.now() + blockchain.block_number() as u64 * 1000, 0u32, vec![]);
+            let macro_block = producer
+                .next_macro_block_proposal(blockchain.time.now() + blockchain.block_number() as u64 * 1000, 0u32, vec![])
+                .unwrap();
 
-            let block = sign_macro_block(&producer.validator_key, macro_block.header, macro_block.body);
+            let block = sign_macro_block(&producer.signer, macro_block.header, macro_block.body);
             assert_eq!(blockchain.push(Block::Macro(block)), Ok(PushResult::Extended));
         }
     }
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use block::{Message, MicroHeader, TendermintProposal, TendermintVote, ViewChange};
+use bls::{KeyPair, PublicKey, Signature};
+use transaction::Transaction;
+use vrf::VrfSeed;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SignerError {
+    /// The signer refused to sign, e.g. because the message conflicts with one that it signed before.
+    #[error("Refused to sign: {0}")]
+    Refused(String),
+
+    /// The message is not of the kind that it claims to be.
+    #[error("Invalid message")]
+    InvalidMessage,
+
+    /// The signer could not be reached.
+    #[error("Signer unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Signs everything that a validator signs with its BLS key. A validator never needs to sign arbitrary data, so that
+/// signers which don't trust the validator (e.g. remote signers) can check every message before they sign it.
+pub trait Signer: Send + Sync {
+    fn public_key(&self) -> &PublicKey;
+
+    /// Computes the seed of our next block from the seed of its predecessor.
+    fn sign_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError>;
+
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Signature, SignerError>;
+
+    fn sign_view_change(&self, view_change: &ViewChange) -> Result<Signature, SignerError>;
+
+    fn sign_tendermint_proposal(&self, proposal: &TendermintProposal) -> Result<Signature, SignerError>;
+
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<Signature, SignerError>;
+
+    /// Signs the serialized DHT record that maps our validator key to our peer ID.
+    fn sign_validator_record(&self, record: &[u8]) -> Result<Signature, SignerError>;
+
+    /// Signs the signalling proof of an `UnparkValidator` transaction for our validator.
+    fn sign_unpark_transaction(&self, transaction: &Transaction) -> Result<Signature, SignerError>;
+}
+
+impl Signer for KeyPair {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn sign_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        Ok(prev_seed.sign_next(&self.secret_key))
+    }
+
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Signature, SignerError> {
+        Ok(self.sign(header))
+    }
+
+    fn sign_view_change(&self, view_change: &ViewChange) -> Result<Signature, SignerError> {
+        Ok(view_change.sign(&self.secret_key))
+    }
+
+    fn sign_tendermint_proposal(&self, proposal: &TendermintProposal) -> Result<Signature, SignerError> {
+        Ok(proposal.sign(&self.secret_key))
+    }
+
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<Signature, SignerError> {
+        Ok(self.sign(vote))
+    }
+
+    fn sign_validator_record(&self, record: &[u8]) -> Result<Signature, SignerError> {
+        Ok(self.sign(&record.to_vec()))
+    }
+
+    fn sign_unpark_transaction(&self, transaction: &Transaction) -> Result<Signature, SignerError> {
+        Ok(self.sign(&transaction.serialize_content().as_slice()))
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Arc<S> {
+    fn public_key(&self) -> &PublicKey {
+        (**self).public_key()
+    }
+
+    fn sign_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        (**self).sign_seed(prev_seed)
+    }
+
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Signature, SignerError> {
+        (**self).sign_micro_header(header)
+    }
+
+    fn sign_view_change(&self, view_change: &ViewChange) -> Result<Signature, SignerError> {
+        (**self).sign_view_change(view_change)
+    }
+
+    fn sign_tendermint_proposal(&self, proposal: &TendermintProposal) -> Result<Signature, SignerError> {
+        (**self).sign_tendermint_proposal(proposal)
+    }
+
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<Signature, SignerError> {
+        (**self).sign_tendermint_vote(vote)
+    }
+
+    fn sign_validator_record(&self, record: &[u8]) -> Result<Signature, SignerError> {
+        (**self).sign_validator_record(record)
+    }
+
+    fn sign_unpark_transaction(&self, transaction: &Transaction) -> Result<Signature, SignerError> {
+        (**self).sign_unpark_transaction(transaction)
+    }
+}
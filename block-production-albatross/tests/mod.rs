@@ -27,7 +27,7 @@ fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
     let init_height = blockchain.block_number();
     let macro_block_number = policy::macro_block_after(init_height + 1);
     for i in (init_height + 1)..macro_block_number {
-        let last_micro_block = producer.next_micro_block(blockchain.time.now() + i as u64 * 1000, 0, None, vec![], vec![0x42]);
+        let last_micro_block = producer.next_micro_block(blockchain.time.now() + i as u64 * 1000, 0, None, vec![], vec![0x42]).unwrap();
         assert_eq!(blockchain.push(Block::Micro(last_micro_block)), Ok(PushResult::Extended));
     }
     assert_eq!(blockchain.block_number(), macro_block_number - 1);
@@ -117,7 +117,7 @@ fn it_can_produce_micro_blocks() {
     let producer = BlockProducer::new(Arc::clone(&blockchain), mempool, keypair.clone());
 
     // #1.0: Empty standard micro block
-    let block = producer.next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x41]);
+    let block = producer.next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x41]).unwrap();
     assert_eq!(blockchain.push(Block::Micro(block.clone())), Ok(PushResult::Extended));
     assert_eq!(blockchain.block_number(), 1);
 
@@ -138,14 +138,14 @@ fn it_can_produce_micro_blocks() {
     }
 
     // #2.0: Empty micro block with fork proof
-    let block = producer.next_micro_block(blockchain.time.now() + 1000, 0, None, vec![fork_proof], vec![0x41]);
+    let block = producer.next_micro_block(blockchain.time.now() + 1000, 0, None, vec![fork_proof], vec![0x41]).unwrap();
     assert_eq!(blockchain.push(Block::Micro(block)), Ok(PushResult::Extended));
     assert_eq!(blockchain.block_number(), 2);
     assert_eq!(blockchain.view_number(), 0);
 
     // #2.1: Empty view-changed micro block (wrong prev_hash)
     let view_change = sign_view_change(VrfSeed::default(), 3, 1);
-    let block = producer.next_micro_block(blockchain.time.now() + 2000, 1, Some(view_change), vec![], vec![0x41]);
+    let block = producer.next_micro_block(blockchain.time.now() + 2000, 1, Some(view_change), vec![], vec![0x41]).unwrap();
 
     // the block justification is ok, the view_change justification is not.
     assert_eq!(
@@ -155,7 +155,7 @@ fn it_can_produce_micro_blocks() {
 
     // #2.2: Empty view-changed micro block
     let view_change = sign_view_change(blockchain.head().seed().clone(), 3, 1);
-    let block = producer.next_micro_block(blockchain.time.now() + 2000, 1, Some(view_change), vec![], vec![0x41]);
+    let block = producer.next_micro_block(blockchain.time.now() + 2000, 1, Some(view_change), vec![], vec![0x41]).unwrap();
     assert_eq!(blockchain.push(Block::Micro(block)), Ok(PushResult::Extended));
     assert_eq!(blockchain.block_number(), 3);
     assert_eq!(blockchain.next_view_number(), 1);
@@ -172,7 +172,7 @@ fn it_can_produce_macro_blocks() {
 
     fill_micro_blocks(&producer, &blockchain);
 
-    let macro_block = producer.next_macro_block_proposal(blockchain.time.now() + blockchain.block_number() as u64 * 1000, 0u32, vec![]);
+    let macro_block = producer.next_macro_block_proposal(blockchain.time.now() + blockchain.block_number() as u64 * 1000, 0u32, vec![]).unwrap();
 
     let block = sign_macro_block(macro_block.header, macro_block.body);
     assert_eq!(blockchain.push(Block::Macro(block)), Ok(PushResult::Extended));
@@ -192,7 +192,7 @@ fn it_can_produce_election_blocks() {
     while policy::epoch_at(blockchain.block_number()) < 2 {
         fill_micro_blocks(&producer, &blockchain);
 
-        let macro_block = producer.next_macro_block_proposal(blockchain.time.now() + blockchain.block_number() as u64 * 1000, 0u32, vec![0x42]);
+        let macro_block = producer.next_macro_block_proposal(blockchain.time.now() + blockchain.block_number() as u64 * 1000, 0u32, vec![0x42]).unwrap();
 
         let block = sign_macro_block(macro_block.header, macro_block.body);
 
@@ -1,5 +1,6 @@
 pub use blockchain::Blockchain;
 pub use chain_ordering::ChainOrdering;
+pub use signalling::UpgradeSignals;
 
 mod accounts;
 mod blockchain;
@@ -7,6 +8,7 @@ mod chain_ordering;
 mod history_sync;
 mod inherents;
 mod push;
+mod signalling;
 mod slots;
 mod verify;
 mod wrappers;
@@ -0,0 +1,66 @@
+use std::collections::{BTreeMap, HashMap};
+
+use block::{Block, UpgradeSignal};
+use database::ReadTransaction;
+use primitives::policy;
+use primitives::slot::SlotBand;
+
+use crate::Blockchain;
+
+/// How many slots of the current epoch signal readiness for each protocol version.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UpgradeSignals {
+    pub epoch_number: u32,
+    /// Number of micro blocks of the epoch so far.
+    pub micro_blocks: u32,
+    /// Number of slots per signalled version. The slots of a validator count for the version that it signalled in its
+    /// latest micro block of the epoch. Validators that didn't signal in that block, or that didn't produce a micro
+    /// block yet, don't count.
+    pub slots: BTreeMap<u16, u16>,
+}
+
+impl UpgradeSignals {
+    /// Returns the number of slots that are ready for `version`, i.e. that signal it or a later version.
+    pub fn slots_ready_for(&self, version: u16) -> u16 {
+        self.slots.range(version..).map(|(_, slots)| slots).sum()
+    }
+}
+
+/// Implements methods to tally the upgrade signals of the validators.
+impl Blockchain {
+    /// Tallies the upgrade signals in the micro blocks of the current epoch.
+    pub fn upgrade_signals(&self) -> UpgradeSignals {
+        let txn = ReadTransaction::new(&self.env);
+        let head_number = self.block_number();
+        let epoch_number = policy::epoch_at(head_number + 1);
+
+        // The latest signal of each validator that produced a micro block in this epoch.
+        let mut signals = HashMap::new();
+        let mut micro_blocks = 0;
+        for block_number in policy::first_block_of(epoch_number)..=head_number {
+            let header = match self.chain_store.get_block_at(block_number, false, Some(&txn)) {
+                Some(Block::Micro(block)) => block.header,
+                _ => continue,
+            };
+            let (slot, _) = self.get_slot_owner_at(block_number, header.view_number, Some(&txn));
+            signals.insert(
+                slot.public_key().compressed().clone(),
+                UpgradeSignal::from_extra_data(&header.extra_data),
+            );
+            micro_blocks += 1;
+        }
+
+        let mut slots = BTreeMap::new();
+        for band in self.current_validators().iter() {
+            if let Some(Some(signal)) = signals.get(band.public_key().compressed()) {
+                *slots.entry(signal.version).or_default() += band.num_slots();
+            }
+        }
+
+        UpgradeSignals {
+            epoch_number,
+            micro_blocks,
+            slots,
+        }
+    }
+}
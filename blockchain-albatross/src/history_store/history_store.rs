@@ -8,6 +8,7 @@ use mmr::mmr::partial::PartialMerkleMountainRange;
 use mmr::mmr::proof::RangeProof;
 use mmr::mmr::MerkleMountainRange;
 use mmr::store::memory::MemoryStore;
+use primitives::policy;
 use std::cmp;
 
 /// A struct that contains databases to store history trees (which are Merkle Mountain Ranges
@@ -256,6 +257,40 @@ impl HistoryStore {
         Some(ext_txs)
     }
 
+    /// Gets the extended transactions of the block at `block_number`. The history tree of its epoch
+    /// is searched from the end, so this only reads the transactions of that block and of the
+    /// blocks after it.
+    pub fn get_block_transactions(&self, block_number: u32, txn_option: Option<&Transaction>) -> Vec<ExtendedTransaction> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        // Get history tree for the epoch of the block.
+        let tree = MerkleMountainRange::new(MMRStore::with_read_transaction(&self.hist_tree_db, txn, policy::epoch_at(block_number)));
+
+        // Get the extended transactions of the block, stopping at the first one of an earlier block.
+        let mut ext_txs = vec![];
+
+        for i in (0..tree.num_leaves()).rev() {
+            let leaf_hash = tree.get_leaf(i).unwrap();
+            let ext_tx = self.get_extended_tx(&leaf_hash.to_blake2b(), Some(txn)).unwrap();
+            if ext_tx.block_number < block_number {
+                break;
+            }
+            if ext_tx.block_number == block_number {
+                ext_txs.push(ext_tx);
+            }
+        }
+
+        ext_txs.reverse();
+        ext_txs
+    }
+
     /// Gets an extended transaction by its hash. Note that this hash is the leaf hash (see MMRHash)
     /// of the transaction, not a simple Blake2b hash of the transaction.
     pub fn get_extended_tx(&self, hash: &Blake2bHash, txn_option: Option<&Transaction>) -> Option<ExtendedTransaction> {
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use beserial::Deserialize;
+use beserial::{Deserialize, Serialize};
 use nimiq_block_albatross::{
     create_pk_tree_root, Block, MacroBlock, MacroBody, MultiSignature, TendermintIdentifier, TendermintProof, TendermintProposal, TendermintStep,
     TendermintVote,
@@ -23,7 +23,7 @@ fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
     let init_height = blockchain.block_number();
     let macro_block_number = policy::macro_block_after(init_height + 1);
     for i in (init_height + 1)..macro_block_number {
-        let last_micro_block = producer.next_micro_block(blockchain.time.now() + i as u64 * 1000, 0, None, vec![], vec![0x42]);
+        let last_micro_block = producer.next_micro_block(blockchain.time.now() + i as u64 * 1000, 0, None, vec![], vec![0x42]).unwrap();
         assert_eq!(blockchain.push(Block::Micro(last_micro_block)), Ok(PushResult::Extended));
     }
     assert_eq!(blockchain.block_number(), macro_block_number - 1);
@@ -37,7 +37,7 @@ fn produce_macro_blocks(num_macro: usize, producer: &BlockProducer, blockchain:
         assert!(slots.is_some());
 
         let next_block_height = blockchain.block_number() + 1;
-        let macro_block_proposal = producer.next_macro_block_proposal(blockchain.time.now() + next_block_height as u64 * 1000, 0u32, vec![]);
+        let macro_block_proposal = producer.next_macro_block_proposal(blockchain.time.now() + next_block_height as u64 * 1000, 0u32, vec![]).unwrap();
 
         let block = sign_macro_block(
             TendermintProposal {
@@ -134,3 +134,33 @@ fn it_can_history_sync() {
 }
 
 // TODO: Test using blocks with transactions.
+
+#[test]
+fn it_can_get_block_transactions() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
+
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), keypair);
+    produce_macro_blocks(2, &producer, &blockchain);
+
+    // Both the last macro block and the one before it must only get their own transactions.
+    let epoch_txs = blockchain
+        .history_store
+        .get_epoch_transactions(policy::epoch_at(blockchain.block_number()), None)
+        .unwrap();
+    for block_number in [blockchain.block_number(), blockchain.block_number() - policy::BATCH_LENGTH].iter() {
+        let expected: Vec<Vec<u8>> = epoch_txs
+            .iter()
+            .filter(|ext_tx| ext_tx.block_number == *block_number)
+            .map(Serialize::serialize_to_vec)
+            .collect();
+        let ext_txs: Vec<Vec<u8>> = blockchain
+            .history_store
+            .get_block_transactions(*block_number, None)
+            .iter()
+            .map(Serialize::serialize_to_vec)
+            .collect();
+        assert_eq!(ext_txs, expected);
+    }
+}
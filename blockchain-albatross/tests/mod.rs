@@ -4,7 +4,7 @@ use std::sync::RwLock;
 use beserial::Deserialize;
 use nimiq_block_albatross::{
     create_pk_tree_root, Block, MacroBlock, MacroBody, MultiSignature, SignedViewChange, TendermintIdentifier, TendermintProof, TendermintProposal,
-    TendermintStep, TendermintVote, ViewChange, ViewChangeProof,
+    TendermintStep, TendermintVote, UpgradeSignal, ViewChange, ViewChangeProof,
 };
 use nimiq_block_production_albatross::BlockProducer;
 use nimiq_blockchain_albatross::{Blockchain, ForkEvent, PushError, PushResult};
@@ -46,7 +46,7 @@ impl TemporaryBlockProducer {
         let block = if policy::is_macro_block_at(height) {
             let macro_block_proposal = self
                 .producer
-                .next_macro_block_proposal(self.blockchain.time.now() + height as u64 * 1000, 0u32, extra_data);
+                .next_macro_block_proposal(self.blockchain.time.now() + height as u64 * 1000, 0u32, extra_data).unwrap();
             // Get validator set and make sure it exists.
             let validators = self.blockchain.get_validators_for_epoch(policy::epoch_at(self.blockchain.block_number() + 1));
             assert!(validators.is_some());
@@ -74,7 +74,7 @@ impl TemporaryBlockProducer {
                 view_change_proof,
                 vec![],
                 extra_data,
-            ))
+            ).unwrap())
         };
 
         assert_eq!(self.push(block.clone()), Ok(PushResult::Extended));
@@ -324,3 +324,23 @@ fn create_fork_proof() {
     // Verify that the fork proof was generated
     assert_eq!(*event1_rc1.read().unwrap(), true);
 }
+
+#[test]
+fn it_tallies_upgrade_signals() {
+    let temp_producer = TemporaryBlockProducer::new();
+    assert_eq!(temp_producer.blockchain.upgrade_signals().micro_blocks, 0);
+
+    temp_producer.next_block(0, UpgradeSignal::new(2).to_extra_data(b"pool"));
+    let signals = temp_producer.blockchain.upgrade_signals();
+    assert_eq!(signals.epoch_number, 1);
+    assert_eq!(signals.micro_blocks, 1);
+    assert_eq!(signals.slots_ready_for(1), policy::SLOTS);
+    assert_eq!(signals.slots_ready_for(2), policy::SLOTS);
+    assert_eq!(signals.slots_ready_for(3), 0);
+
+    // Only the latest micro block of a validator counts.
+    temp_producer.next_block(0, b"pool".to_vec());
+    let signals = temp_producer.blockchain.upgrade_signals();
+    assert_eq!(signals.micro_blocks, 2);
+    assert_eq!(signals.slots_ready_for(2), 0);
+}
@@ -6,8 +6,8 @@ use futures::stream::FuturesUnordered;
 use futures::task::{Context, Poll};
 use futures::{Future, FutureExt, StreamExt};
 use hash::Blake2bHash;
-use network_interface::{peer::Peer, request_response::RequestError};
-use std::collections::HashSet;
+use network_interface::peer::{Peer, RequestError};
+use std::collections::HashMap;
 use std::mem;
 use std::pin::Pin;
 use std::sync::{Arc, Weak};
@@ -18,17 +18,36 @@ pub struct HeadRequests<TPeer: Peer + 'static> {
     peers: Vec<Arc<ConsensusAgent<TPeer>>>,
     head_hashes: FuturesUnordered<BoxFuture<'static, (usize, Result<Blake2bHash, RequestError>)>>,
     head_blocks: FuturesUnordered<BoxFuture<'static, Result<Option<Block>, RequestError>>>,
-    requested_hashes: HashSet<Blake2bHash>,
+    /// The unknown head hashes we requested, together with the number of peers that reported them.
+    requested_hashes: HashMap<Blake2bHash, usize>,
     blockchain: Arc<Blockchain>,
     num_known_blocks: usize,
     num_unknown_blocks: usize,
     unknown_blocks: Vec<Block>,
+    peer_head_numbers: Vec<u32>,
 }
 
 pub struct HeadRequestsResult {
     pub num_known_blocks: usize,
     pub num_unknown_blocks: usize,
     pub unknown_blocks: Vec<Block>,
+    /// The block numbers of the heads reported by our peers, sorted in descending order.
+    /// Heads that we neither know nor could retrieve are not included.
+    pub peer_head_numbers: Vec<u32>,
+}
+
+impl HeadRequestsResult {
+    /// The block number of the best head reported by our peers.
+    pub fn best_peer_head_number(&self) -> Option<u32> {
+        self.peer_head_numbers.first().cloned()
+    }
+
+    /// The highest block number that a strict majority of the responding peers have reached.
+    pub fn majority_peer_head_number(&self) -> Option<u32> {
+        self.peer_head_numbers
+            .get(self.peer_head_numbers.len() / 2)
+            .cloned()
+    }
 }
 
 impl<TPeer: Peer + 'static> HeadRequests<TPeer> {
@@ -56,6 +75,7 @@ impl<TPeer: Peer + 'static> HeadRequests<TPeer> {
             num_known_blocks: 0,
             num_unknown_blocks: 0,
             unknown_blocks: vec![],
+            peer_head_numbers: vec![],
         }
     }
 
@@ -73,13 +93,16 @@ impl<TPeer: Peer + 'static> Future for HeadRequests<TPeer> {
             // If we got a result, check it and classify it as known block/unknown block.
             match result {
                 Ok(hash) => {
-                    if self.blockchain.get_block(&hash, false).is_some() {
+                    if let Some(block) = self.blockchain.get_block(&hash, false) {
                         self.num_known_blocks += 1;
+                        self.peer_head_numbers.push(block.block_number());
                     } else {
                         // Request unknown blocks from peer that gave it to us.
                         self.num_unknown_blocks += 1;
-                        if !self.requested_hashes.contains(&hash) {
-                            self.requested_hashes.insert(hash.clone());
+                        if let Some(num_peers) = self.requested_hashes.get_mut(&hash) {
+                            *num_peers += 1;
+                        } else {
+                            self.requested_hashes.insert(hash.clone(), 1);
                             let peer = Arc::clone(&self.peers[i]);
                             self.head_blocks
                                 .push(async move { peer.request_block(hash).await }.boxed());
@@ -96,6 +119,14 @@ impl<TPeer: Peer + 'static> Future for HeadRequests<TPeer> {
         while let Poll::Ready(Some(result)) = self.head_blocks.poll_next_unpin(cx) {
             match result {
                 Ok(Some(block)) => {
+                    let num_peers = self
+                        .requested_hashes
+                        .get(&block.hash())
+                        .cloned()
+                        .unwrap_or(1);
+                    for _ in 0..num_peers {
+                        self.peer_head_numbers.push(block.block_number());
+                    }
                     self.unknown_blocks.push(block);
                 }
                 _ => {
@@ -106,10 +137,12 @@ impl<TPeer: Peer + 'static> Future for HeadRequests<TPeer> {
 
         // We're done if both queues are empty.
         if self.is_finished() {
+            self.peer_head_numbers.sort_unstable_by(|a, b| b.cmp(a));
             return Poll::Ready(HeadRequestsResult {
                 num_known_blocks: self.num_known_blocks,
                 num_unknown_blocks: self.num_unknown_blocks,
                 unknown_blocks: mem::take(&mut self.unknown_blocks),
+                peer_head_numbers: mem::take(&mut self.peer_head_numbers),
             });
         }
 
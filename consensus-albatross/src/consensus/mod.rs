@@ -1,27 +1,36 @@
 use std::sync::{Arc, Weak};
 
 use futures::{FutureExt, Stream, StreamExt};
+use parking_lot::RwLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::broadcast::{
     channel as broadcast, Receiver as BroadcastReceiver, Sender as BroadcastSender,
 };
+use tokio::sync::watch;
 
 use block_albatross::Block;
 use blockchain_albatross::Blockchain;
 use database::Environment;
+use hash::Blake2bHash;
 use mempool::{Mempool, ReturnCode};
-use network_interface::network::Network;
+use network_interface::network::{Network, PubsubId};
+use network_interface::peer::Peer;
 use transaction::Transaction;
 
 use crate::consensus::head_requests::{HeadRequests, HeadRequestsResult};
 use crate::consensus_agent::ConsensusAgent;
 use crate::sync::block_queue::{BlockQueue, BlockQueueConfig, BlockQueueEvent, BlockTopic};
+use crate::sync::compact_block::{CompactBlockRelay, CompactBlockTopic, CompactMicroBlock};
+use crate::sync::history::HistorySyncProgress;
 use crate::sync::request_component::BlockRequestComponent;
-use futures::stream::BoxStream;
+use futures::future::BoxFuture;
+use futures::stream::{BoxStream, FuturesUnordered};
 use futures::task::{Context, Poll};
 use nimiq_network_interface::network::Topic;
+use primitives::policy;
 use std::pin::Pin;
 use std::time::{Duration, Instant};
+use tokio::time::{interval, Interval};
 
 mod head_requests;
 mod request_response;
@@ -41,11 +50,87 @@ impl Topic for TransactionTopic {
     }
 }
 
+/// Determines when consensus is considered established and when it is lost again.
+#[derive(Clone, Debug)]
+pub struct EstablishedConfig {
+    /// Minimum number of peers for consensus to be established.
+    pub min_peers_established: usize,
+    /// Minimum number of block announcements extending the chain for consensus to be established.
+    pub min_blocks_established: usize,
+    /// Timeout after which head requests will be performed again to determine consensus established state.
+    pub head_requests_timeout: Duration,
+    /// Consensus is lost if our head falls more than this number of blocks behind the majority
+    /// of our peers' heads.
+    pub max_blocks_behind: u32,
+    /// Consensus is lost if no block was received within this time. Disabled if `None`.
+    ///
+    /// Defaults to 30 block times, which leaves room for a couple of view changes before we give up on the chain.
+    pub block_timeout: Option<Duration>,
+}
+
+impl Default for EstablishedConfig {
+    fn default() -> Self {
+        Self {
+            min_peers_established: 3,
+            min_blocks_established: 5,
+            head_requests_timeout: Duration::from_secs(20), // currently 2 * view change delay
+            max_blocks_behind: policy::BATCH_LENGTH,
+            block_timeout: Some(Duration::from_millis(30 * policy::BLOCK_SEPARATION_TIME)),
+        }
+    }
+}
+
+/// The phase the node's synchronization is currently in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncPhase {
+    /// We are still downloading the history of past epochs.
+    History,
+    /// The history is synced and we are catching up with the head of the chain via the block queue.
+    BlockQueue,
+    /// Consensus is established.
+    Established,
+}
+
+/// A snapshot of the synchronization state of the node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncStatus {
+    pub phase: SyncPhase,
+    pub head_block_number: u32,
+    pub head_hash: Blake2bHash,
+    /// The block numbers of the heads reported by our peers during the last head requests, sorted
+    /// in descending order.
+    pub peer_head_numbers: Vec<u32>,
+    pub num_agents: usize,
+    pub num_buffered_blocks: usize,
+    pub remaining_epochs: usize,
+}
+
+impl SyncStatus {
+    fn new(blockchain: &Blockchain) -> Self {
+        SyncStatus {
+            phase: SyncPhase::BlockQueue,
+            head_block_number: blockchain.block_number(),
+            head_hash: blockchain.head_hash(),
+            peer_head_numbers: vec![],
+            num_agents: 0,
+            num_buffered_blocks: 0,
+            remaining_epochs: 0,
+        }
+    }
+
+    /// The block number of the best head reported by our peers.
+    pub fn best_peer_head_number(&self) -> Option<u32> {
+        self.peer_head_numbers.first().cloned()
+    }
+}
+
 pub struct ConsensusProxy<N: Network> {
     pub blockchain: Arc<Blockchain>,
     pub network: Arc<N>,
     pub mempool: Arc<Mempool>,
     established_flag: Arc<AtomicBool>,
+    history_sync_progress: Arc<RwLock<HistorySyncProgress>>,
+    sync_status: Arc<RwLock<SyncStatus>>,
 }
 
 impl<N: Network> Clone for ConsensusProxy<N> {
@@ -55,6 +140,8 @@ impl<N: Network> Clone for ConsensusProxy<N> {
             network: Arc::clone(&self.network),
             mempool: Arc::clone(&self.mempool),
             established_flag: Arc::clone(&self.established_flag),
+            history_sync_progress: Arc::clone(&self.history_sync_progress),
+            sync_status: Arc::clone(&self.sync_status),
         }
     }
 }
@@ -75,6 +162,14 @@ impl<N: Network> ConsensusProxy<N> {
     pub fn is_established(&self) -> bool {
         self.established_flag.load(Ordering::Acquire)
     }
+
+    pub fn history_sync_progress(&self) -> HistorySyncProgress {
+        self.history_sync_progress.read().clone()
+    }
+
+    pub fn sync_status(&self) -> SyncStatus {
+        self.sync_status.read().clone()
+    }
 }
 
 pub enum ConsensusEvent<N: Network> {
@@ -82,6 +177,8 @@ pub enum ConsensusEvent<N: Network> {
     PeerLeft,
     Established,
     Lost,
+    HistorySyncProgress(HistorySyncProgress),
+    SyncPhaseChanged(SyncPhase),
 }
 
 impl<N: Network> Clone for ConsensusEvent<N> {
@@ -91,6 +188,8 @@ impl<N: Network> Clone for ConsensusEvent<N> {
             ConsensusEvent::Established => ConsensusEvent::Established,
             ConsensusEvent::Lost => ConsensusEvent::Lost,
             ConsensusEvent::PeerLeft => ConsensusEvent::PeerLeft,
+            ConsensusEvent::HistorySyncProgress(progress) => ConsensusEvent::HistorySyncProgress(progress.clone()),
+            ConsensusEvent::SyncPhaseChanged(phase) => ConsensusEvent::SyncPhaseChanged(*phase),
         }
     }
 }
@@ -101,22 +200,33 @@ pub struct Consensus<N: Network> {
     pub network: Arc<N>,
     pub env: Environment,
 
+    config: EstablishedConfig,
     block_queue: BlockQueue<N::PeerType, BlockRequestComponent<N::PeerType>>,
+    compact_block_stream: BoxStream<'static, (CompactMicroBlock, N::PubsubId)>,
+    compact_block_relay: CompactBlockRelay<N::PeerType, N::PubsubId>,
+    /// Forwards the gossip messages of compact blocks that were accepted.
+    compact_block_validations: FuturesUnordered<BoxFuture<'static, ()>>,
     tx_stream: BoxStream<'static, Transaction>,
 
     events: BroadcastSender<ConsensusEvent<N>>,
     established_flag: Arc<AtomicBool>,
     head_requests: Option<HeadRequests<N::PeerType>>,
     head_requests_time: Option<Instant>,
+    accepted_announcements_offset: usize,
+    last_head_hash: Blake2bHash,
+    last_block_time: Instant,
+    check_interval: Interval,
+
+    history_sync_progress_rx: Option<watch::Receiver<HistorySyncProgress>>,
+    history_sync_progress: Arc<RwLock<HistorySyncProgress>>,
+
+    peer_head_numbers: Vec<u32>,
+    sync_status: Arc<RwLock<SyncStatus>>,
 }
 
 impl<N: Network> Consensus<N> {
-    /// Minimum number of peers for consensus to be established.
-    const MIN_PEERS_ESTABLISHED: usize = 3;
-    /// Minimum number of block announcements extending the chain for consensus to be established.
-    const MIN_BLOCKS_ESTABLISHED: usize = 5;
-    /// Timeout after which head requests will be performed again to determine consensus established state.
-    const HEAD_REQUESTS_TIMEOUT: Duration = Duration::from_secs(20); // currently 2 * view change delay
+    /// Interval in which the established state is re-evaluated even if nothing else happens.
+    const CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
     pub async fn from_network(
         env: Environment,
@@ -124,6 +234,25 @@ impl<N: Network> Consensus<N> {
         mempool: Arc<Mempool>,
         network: Arc<N>,
         sync_protocol: BoxStream<'static, Arc<ConsensusAgent<N::PeerType>>>,
+    ) -> Self {
+        Self::from_network_with_config(
+            EstablishedConfig::default(),
+            env,
+            blockchain,
+            mempool,
+            network,
+            sync_protocol,
+        )
+        .await
+    }
+
+    pub async fn from_network_with_config(
+        config: EstablishedConfig,
+        env: Environment,
+        blockchain: Arc<Blockchain>,
+        mempool: Arc<Mempool>,
+        network: Arc<N>,
+        sync_protocol: BoxStream<'static, Arc<ConsensusAgent<N::PeerType>>>,
     ) -> Self {
         let block_stream = network
             .subscribe::<BlockTopic>(&BlockTopic::default())
@@ -132,6 +261,12 @@ impl<N: Network> Consensus<N> {
             .map(|(block, _peer_id)| block)
             .boxed();
 
+        let compact_block_stream = network
+            .subscribe::<CompactBlockTopic>(&CompactBlockTopic::default())
+            .await
+            .unwrap()
+            .boxed();
+
         let tx_stream = network
             .subscribe::<TransactionTopic>(&TransactionTopic::default())
             .await
@@ -140,22 +275,26 @@ impl<N: Network> Consensus<N> {
             .boxed();
 
         Self::new(
+            config,
             env,
             blockchain,
             mempool,
             network,
             block_stream,
+            compact_block_stream,
             tx_stream,
             sync_protocol,
         )
     }
 
     pub fn new(
+        config: EstablishedConfig,
         env: Environment,
         blockchain: Arc<Blockchain>,
         mempool: Arc<Mempool>,
         network: Arc<N>,
         block_stream: BoxStream<'static, Block>,
+        compact_block_stream: BoxStream<'static, (CompactMicroBlock, N::PubsubId)>,
         tx_stream: BoxStream<'static, Transaction>,
         sync_protocol: BoxStream<'static, Arc<ConsensusAgent<N::PeerType>>>,
     ) -> Self {
@@ -173,19 +312,38 @@ impl<N: Network> Consensus<N> {
 
         Self::init_network_requests(&network, &blockchain);
 
+        let compact_block_relay = CompactBlockRelay::new(Arc::clone(&mempool));
+
+        let sync_status = SyncStatus::new(&blockchain);
+        let last_head_hash = blockchain.head_hash();
+
         Consensus {
             blockchain,
             mempool,
             network,
             env,
 
+            config,
             block_queue,
+            compact_block_stream,
+            compact_block_relay,
+            compact_block_validations: FuturesUnordered::new(),
             tx_stream,
             events: tx,
 
             established_flag: Arc::new(AtomicBool::new(false)),
             head_requests: None,
             head_requests_time: None,
+            accepted_announcements_offset: 0,
+            last_head_hash,
+            last_block_time: Instant::now(),
+            check_interval: interval(Self::CHECK_INTERVAL),
+
+            history_sync_progress_rx: None,
+            history_sync_progress: Arc::new(RwLock::new(HistorySyncProgress::default())),
+
+            peer_head_numbers: vec![],
+            sync_status: Arc::new(RwLock::new(sync_status)),
         }
     }
 
@@ -207,9 +365,20 @@ impl<N: Network> Consensus<N> {
             network: Arc::clone(&self.network),
             mempool: Arc::clone(&self.mempool),
             established_flag: Arc::clone(&self.established_flag),
+            history_sync_progress: Arc::clone(&self.history_sync_progress),
+            sync_status: Arc::clone(&self.sync_status),
         }
     }
 
+    pub fn sync_status(&self) -> SyncStatus {
+        self.sync_status.read().clone()
+    }
+
+    /// Reports the progress of the given history sync through `ConsensusEvent`s and the proxy.
+    pub fn track_history_sync(&mut self, progress: watch::Receiver<HistorySyncProgress>) {
+        self.history_sync_progress_rx = Some(progress);
+    }
+
     /// Forcefully sets consensus established, should be used for tests only.
     pub fn force_established(&mut self) {
         trace!("Consensus forcefully established.");
@@ -222,44 +391,69 @@ impl<N: Network> Consensus<N> {
     }
 
     /// Calculates and sets established state, returns a ConsensusEvent if the state changed.
-    /// Once consensus is established, we can only loose it if we loose all our peers.
-    /// To reach consensus established state, we need at least `MIN_PEERS_ESTABLISHED` peers and
+    /// To reach consensus established state, we need at least `min_peers_established` peers and
     /// one of the following conditions must be true:
-    /// - we accepted at least `MIN_BLOCKS_ESTABLISHED` block announcements
+    /// - we accepted at least `min_blocks_established` block announcements
     /// - we know at least 2/3 of the head blocks of our peers
     ///
     /// The latter check is started immediately once we reach the minimum number of peers
-    /// and is potentially repeated in an interval of `HEAD_REQUESTS_TIMEOUT` until one
+    /// and is potentially repeated in an interval of `head_requests_timeout` until one
     /// of the conditions above is true.
     /// Any unknown blocks resulting of the head check are handled similarly as block announcements
     /// via the block queue.
+    ///
+    /// Once consensus is established, the head requests are repeated in the same interval and
+    /// consensus is lost again if
+    /// - we lose all our peers,
+    /// - our head falls more than `max_blocks_behind` blocks behind the majority of our peers' heads, or
+    /// - we didn't receive a block within `block_timeout` (if configured).
     fn set_established(
         &mut self,
         finished_head_request: Option<HeadRequestsResult>,
     ) -> Option<ConsensusEvent<N>> {
-        // We can only loose established state right now if we loose all our peers.
         if self.is_established() {
             if self.num_agents() == 0 {
-                warn!("Lost consensus!");
-                self.established_flag.swap(false, Ordering::Release);
-                return Some(ConsensusEvent::Lost);
+                warn!("Lost consensus, all peers disconnected!");
+                return Some(self.lose_consensus());
             }
+
+            if self.is_block_timeout() {
+                warn!(
+                    "Lost consensus, no block received for {:?}!",
+                    self.last_block_time.elapsed()
+                );
+                return Some(self.lose_consensus());
+            }
+
+            if let Some(head_request) = finished_head_request {
+                if let Some(majority_head) = head_request.majority_peer_head_number() {
+                    let block_number = self.blockchain.block_number();
+                    if majority_head > block_number + self.config.max_blocks_behind {
+                        warn!(
+                            "Lost consensus, our head #{} is behind the majority of peer heads #{}!",
+                            block_number, majority_head
+                        );
+                        return Some(self.lose_consensus());
+                    }
+                }
+            }
+
+            // Keep checking that we are not falling behind our peers.
+            self.start_head_requests_if_due();
         } else {
             // We have two conditions on whether we move to the established state.
             // First, we always need a minimum number of peers connected.
             // Then, we check that we either:
             // - accepted a minimum number of block announcements, or
             // - know the head state of a majority of our peers
-            if self.num_agents() >= Self::MIN_PEERS_ESTABLISHED {
+            // If blocks are required to arrive in time, we additionally wait for a new block.
+            if self.num_agents() >= self.config.min_peers_established && !self.is_block_timeout() {
                 trace!("Trying to establish consensus, number of synced peers satisfied.");
-                if self.block_queue.accepted_block_announcements() >= Self::MIN_BLOCKS_ESTABLISHED {
+                let accepted_announcements = self.block_queue.accepted_block_announcements()
+                    - self.accepted_announcements_offset;
+                if accepted_announcements >= self.config.min_blocks_established {
                     trace!("Consensus established, number of accepted announcements satisfied.");
-                    self.established_flag.swap(true, Ordering::Release);
-
-                    // Also stop any other checks.
-                    self.head_requests = None;
-                    self.head_requests_time = None;
-                    return Some(ConsensusEvent::Established);
+                    return Some(self.establish_consensus());
                 } else {
                     // The head state check is carried out immediately after we reach the minimum
                     // number of peers and then after certain time intervals until consensus is reached.
@@ -269,28 +463,131 @@ impl<N: Network> Consensus<N> {
                         // We would like that 2/3 of our peers have a known state.
                         if head_request.num_known_blocks > 2 * head_request.num_unknown_blocks {
                             trace!("Consensus established, 2/3 of heads known.");
-                            self.established_flag.swap(true, Ordering::Release);
-                            return Some(ConsensusEvent::Established);
-                        }
-                    }
-                    // If there's no ongoing head request, check whether we should start a new one.
-                    if self.head_requests.is_none() {
-                        // This is the case if `head_requests_time` is unset or the timeout is hit.
-                        let should_start_request = self
-                            .head_requests_time
-                            .map(|time| time.elapsed() >= Self::HEAD_REQUESTS_TIMEOUT)
-                            .unwrap_or(true);
-                        if should_start_request {
-                            trace!("Trying to establish consensus, initiating head requests.");
-                            self.head_requests = Some(HeadRequests::new(
-                                self.block_queue.peers(),
-                                Arc::clone(&self.blockchain),
-                            ));
-                            self.head_requests_time = Some(Instant::now());
+                            return Some(self.establish_consensus());
                         }
                     }
+                    self.start_head_requests_if_due();
+                }
+            }
+        }
+        None
+    }
+
+    fn establish_consensus(&mut self) -> ConsensusEvent<N> {
+        self.established_flag.swap(true, Ordering::Release);
+
+        // Restart the head requests interval.
+        self.head_requests = None;
+        self.head_requests_time = Some(Instant::now());
+        ConsensusEvent::Established
+    }
+
+    fn lose_consensus(&mut self) -> ConsensusEvent<N> {
+        self.established_flag.swap(false, Ordering::Release);
+
+        // Only count block announcements accepted from now on and check the heads of our peers
+        // again immediately.
+        self.accepted_announcements_offset = self.block_queue.accepted_block_announcements();
+        self.head_requests = None;
+        self.head_requests_time = None;
+        ConsensusEvent::Lost
+    }
+
+    /// Starts new head requests if there's no ongoing one and `head_requests_timeout` passed
+    /// since the last one was started.
+    fn start_head_requests_if_due(&mut self) {
+        if self.head_requests.is_some() {
+            return;
+        }
+
+        // This is the case if `head_requests_time` is unset or the timeout is hit.
+        let should_start_request = self
+            .head_requests_time
+            .map(|time| time.elapsed() >= self.config.head_requests_timeout)
+            .unwrap_or(true);
+        if should_start_request {
+            trace!("Initiating head requests.");
+            self.head_requests = Some(HeadRequests::new(
+                self.block_queue.peers(),
+                Arc::clone(&self.blockchain),
+            ));
+            self.head_requests_time = Some(Instant::now());
+        }
+    }
+
+    /// Pushes a reconstructed compact block into the block queue. The gossip message is only
+    /// forwarded to our peers if the block was accepted.
+    fn push_compact_block(&mut self, block: Block, pubsub_id: N::PubsubId) {
+        if !self.block_queue.push_announced_block(block) {
+            return;
+        }
+
+        let network = Arc::clone(&self.network);
+        self.compact_block_validations.push(
+            async move {
+                if let Err(e) = network.validate_message(pubsub_id).await {
+                    warn!("Failed to forward compact block: {}", e);
                 }
             }
+            .boxed(),
+        );
+    }
+
+    /// Returns true if blocks are required to arrive in time and we didn't receive one within
+    /// `block_timeout`.
+    fn is_block_timeout(&self) -> bool {
+        self.config
+            .block_timeout
+            .map(|timeout| self.last_block_time.elapsed() >= timeout)
+            .unwrap_or(false)
+    }
+
+    /// Remembers when our head last changed.
+    fn update_last_block_time(&mut self) {
+        let head_hash = self.blockchain.head_hash();
+        if head_hash != self.last_head_hash {
+            self.last_head_hash = head_hash;
+            self.last_block_time = Instant::now();
+        }
+    }
+
+    fn sync_phase(&self) -> SyncPhase {
+        if self.is_established() {
+            return SyncPhase::Established;
+        }
+
+        let progress = self.history_sync_progress.read();
+        if progress.epochs_done < progress.epochs_total {
+            SyncPhase::History
+        } else {
+            SyncPhase::BlockQueue
+        }
+    }
+
+    /// Updates the sync status shared with the proxy, returns a ConsensusEvent if the sync phase changed.
+    fn update_sync_status(&mut self) -> Option<ConsensusEvent<N>> {
+        let progress = self.history_sync_progress.read().clone();
+        let status = SyncStatus {
+            phase: self.sync_phase(),
+            head_block_number: self.blockchain.block_number(),
+            head_hash: self.blockchain.head_hash(),
+            peer_head_numbers: self.peer_head_numbers.clone(),
+            num_agents: self.num_agents(),
+            num_buffered_blocks: self
+                .block_queue
+                .buffered_blocks()
+                .map(|(_, blocks)| blocks.len())
+                .sum(),
+            remaining_epochs: progress.epochs_total.saturating_sub(progress.epochs_done),
+        };
+
+        let mut sync_status = self.sync_status.write();
+        let previous_phase = sync_status.phase;
+        *sync_status = status;
+
+        if previous_phase != sync_status.phase {
+            debug!("Sync phase changed from {:?} to {:?}", previous_phase, sync_status.phase);
+            return Some(ConsensusEvent::SyncPhaseChanged(sync_status.phase));
         }
         None
     }
@@ -307,7 +604,37 @@ impl<N: Network> Stream for Consensus<N> {
             };
         }
 
-        // 1. Poll and advance block queue
+        // Wake up regularly to re-evaluate timeouts.
+        while let Poll::Ready(Some(_)) = self.check_interval.poll_next_unpin(cx) {}
+
+        // 1. Reconstruct compact blocks and hand them to the block queue.
+        while let Poll::Ready(Some((compact_block, pubsub_id))) =
+            self.compact_block_stream.poll_next_unpin(cx)
+        {
+            let peer_id = pubsub_id.propagation_source();
+            let (mut peer, other_peers): (Vec<_>, Vec<_>) = self
+                .block_queue
+                .peers()
+                .iter()
+                .filter_map(Weak::upgrade)
+                .partition(|agent| agent.peer.id() == peer_id);
+            if let Some((block, pubsub_id)) = self.compact_block_relay.on_compact_block(
+                compact_block,
+                pubsub_id,
+                peer.pop(),
+                other_peers,
+            ) {
+                self.push_compact_block(block, pubsub_id);
+            }
+        }
+        while let Poll::Ready(Some((block, pubsub_id))) =
+            self.compact_block_relay.poll_next_unpin(cx)
+        {
+            self.push_compact_block(block, pubsub_id);
+        }
+        while let Poll::Ready(Some(())) = self.compact_block_validations.poll_next_unpin(cx) {}
+
+        // 2. Poll and advance block queue
         while let Poll::Ready(Some(event)) = self.block_queue.poll_next_unpin(cx) {
             match event {
                 BlockQueueEvent::PeerMacroSynced(peer) => {
@@ -322,11 +649,12 @@ impl<N: Network> Stream for Consensus<N> {
         }
 
         // Check consensus established state on changes.
+        self.update_last_block_time();
         if let Some(event) = self.set_established(None) {
             return_event!(event);
         }
 
-        // 2. Poll and push transactions once consensus is established.
+        // 3. Poll and push transactions once consensus is established.
         if self.is_established() {
             while let Poll::Ready(Some(tx)) = self.tx_stream.poll_next_unpin(cx) {
                 // TODO: React on result.
@@ -334,13 +662,23 @@ impl<N: Network> Stream for Consensus<N> {
             }
         }
 
-        // 3. Poll any head requests if active.
+        // 4. Forward history sync progress updates.
+        if let Some(ref mut progress_rx) = self.history_sync_progress_rx {
+            if let Poll::Ready(Some(progress)) = progress_rx.poll_next_unpin(cx) {
+                *self.history_sync_progress.write() = progress.clone();
+                return_event!(ConsensusEvent::HistorySyncProgress(progress));
+            }
+        }
+
+        // 5. Poll any head requests if active.
         if let Some(ref mut head_requests) = self.head_requests {
             if let Poll::Ready(mut result) = head_requests.poll_unpin(cx) {
+                self.head_requests = None;
                 // Push unknown blocks to the block queue, trying to sync.
                 for block in result.unknown_blocks.drain(..) {
                     self.block_queue.push_block(block);
                 }
+                self.peer_head_numbers = result.peer_head_numbers.clone();
                 // Update established state using the result.
                 if let Some(event) = self.set_established(Some(result)) {
                     return_event!(event);
@@ -348,6 +686,11 @@ impl<N: Network> Stream for Consensus<N> {
             }
         }
 
+        // 6. Update the sync status and report phase changes.
+        if let Some(event) = self.update_sync_status() {
+            return_event!(event);
+        }
+
         Poll::Pending
     }
 }
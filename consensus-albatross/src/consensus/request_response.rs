@@ -4,8 +4,8 @@ use futures::StreamExt;
 
 use crate::messages::handlers::Handle;
 use crate::messages::{
-    RequestBatchSet, RequestBlock, RequestBlockHashes, RequestHead, RequestHistoryChunk,
-    RequestMissingBlocks,
+    RequestBatchSet, RequestBlock, RequestBlockHashes, RequestBlockTransactions, RequestHead,
+    RequestHistoryChunk, RequestMissingBlocks,
 };
 use crate::Consensus;
 
@@ -16,9 +16,10 @@ impl<N: Network> Consensus<N> {
     pub(super) fn init_network_requests(network: &Arc<N>, blockchain: &Arc<Blockchain>) {
         let blockchain_outer = blockchain;
         let blockchain = Arc::clone(blockchain_outer);
-        let mut stream = network.receive_from_all::<RequestBlockHashes>();
+        let mut stream = network.receive_requests_from_all::<RequestBlockHashes>();
         tokio::spawn(async move {
-            while let Some((msg, peer)) = stream.next().await {
+            while let Some((request, peer)) = stream.next().await {
+                let msg = &request.request;
                 trace!(
                     "[REQUEST_BLOCK_HASHES] {} block locators received from {:?}",
                     msg.locators.len(),
@@ -27,15 +28,16 @@ impl<N: Network> Consensus<N> {
 
                 if let Some(response) = msg.handle(&blockchain) {
                     // We do not care about the result.
-                    let _ = peer.send(&response).await;
+                    let _ = request.respond(&response);
                 }
             }
         });
 
         let blockchain = Arc::clone(blockchain_outer);
-        let mut stream = network.receive_from_all::<RequestBatchSet>();
+        let mut stream = network.receive_requests_from_all::<RequestBatchSet>();
         tokio::spawn(async move {
-            while let Some((msg, peer)) = stream.next().await {
+            while let Some((request, peer)) = stream.next().await {
+                let msg = &request.request;
                 trace!(
                     "[REQUEST_EPOCH] for block {:?} received from {:?}",
                     msg.hash,
@@ -44,15 +46,16 @@ impl<N: Network> Consensus<N> {
 
                 if let Some(response) = msg.handle(&blockchain) {
                     // We do not care about the result.
-                    let _ = peer.send(&response).await;
+                    let _ = request.respond(&response);
                 }
             }
         });
 
         let blockchain = Arc::clone(blockchain_outer);
-        let mut stream = network.receive_from_all::<RequestHistoryChunk>();
+        let mut stream = network.receive_requests_from_all::<RequestHistoryChunk>();
         tokio::spawn(async move {
-            while let Some((msg, peer)) = stream.next().await {
+            while let Some((request, peer)) = stream.next().await {
+                let msg = &request.request;
                 trace!(
                     "[REQUEST_HISTORY_CHUNK] for epoch {}, chunk {} received from {:?}",
                     msg.epoch_number,
@@ -62,15 +65,16 @@ impl<N: Network> Consensus<N> {
 
                 if let Some(response) = msg.handle(&blockchain) {
                     // We do not care about the result.
-                    let _ = peer.send(&response).await;
+                    let _ = request.respond(&response);
                 }
             }
         });
 
         let blockchain = Arc::clone(blockchain_outer);
-        let mut stream = network.receive_from_all::<RequestBlock>();
+        let mut stream = network.receive_requests_from_all::<RequestBlock>();
         tokio::spawn(async move {
-            while let Some((msg, peer)) = stream.next().await {
+            while let Some((request, peer)) = stream.next().await {
+                let msg = &request.request;
                 trace!(
                     "[REQUEST_BLOCK] for block hash {} received from {:?}",
                     msg.hash,
@@ -79,15 +83,35 @@ impl<N: Network> Consensus<N> {
 
                 if let Some(response) = msg.handle(&blockchain) {
                     // We do not care about the result.
-                    let _ = peer.send(&response).await;
+                    let _ = request.respond(&response);
                 }
             }
         });
 
         let blockchain = Arc::clone(blockchain_outer);
-        let mut stream = network.receive_from_all::<RequestMissingBlocks>();
+        let mut stream = network.receive_requests_from_all::<RequestBlockTransactions>();
         tokio::spawn(async move {
-            while let Some((msg, peer)) = stream.next().await {
+            while let Some((request, peer)) = stream.next().await {
+                let msg = &request.request;
+                trace!(
+                    "[REQUEST_BLOCK_TRANSACTIONS] {} transactions of block {} received from {:?}",
+                    msg.indices.len(),
+                    msg.hash,
+                    peer.id()
+                );
+
+                if let Some(response) = msg.handle(&blockchain) {
+                    // We do not care about the result.
+                    let _ = request.respond(&response);
+                }
+            }
+        });
+
+        let blockchain = Arc::clone(blockchain_outer);
+        let mut stream = network.receive_requests_from_all::<RequestMissingBlocks>();
+        tokio::spawn(async move {
+            while let Some((request, peer)) = stream.next().await {
+                let msg = &request.request;
                 trace!(
                     "[REQUEST_MISSING_BLOCKS] for target_hash {} received from {:?}",
                     msg.target_hash,
@@ -96,20 +120,21 @@ impl<N: Network> Consensus<N> {
 
                 if let Some(response) = msg.handle(&blockchain) {
                     // We do not care about the result.
-                    let _ = peer.send(&response).await;
+                    let _ = request.respond(&response);
                 }
             }
         });
 
         let blockchain = Arc::clone(blockchain_outer);
-        let mut stream = network.receive_from_all::<RequestHead>();
+        let mut stream = network.receive_requests_from_all::<RequestHead>();
         tokio::spawn(async move {
-            while let Some((msg, peer)) = stream.next().await {
+            while let Some((request, peer)) = stream.next().await {
+                let msg = &request.request;
                 trace!("[REQUEST_HEAD] received from {:?}", peer.id());
 
                 if let Some(response) = msg.handle(&blockchain) {
                     // We do not care about the result.
-                    let _ = peer.send(&response).await;
+                    let _ = request.respond(&response);
                 }
             }
         });
@@ -1,14 +1,13 @@
 use std::fmt::{Debug, Formatter, Result as FmtResult};
 use std::sync::Arc;
-use std::time::Duration;
 
 use parking_lot::RwLock;
 
 use block_albatross::Block;
 use hash::Blake2bHash;
-use network_interface::peer::Peer;
-use network_interface::request_response::{RequestError, RequestResponse};
+use network_interface::peer::{Peer, RequestError};
 use nimiq_subscription::Subscription;
+use transaction::Transaction;
 
 use crate::messages::*;
 
@@ -28,13 +27,6 @@ pub struct ConsensusAgent<P: Peer> {
     pub peer: Arc<P>,
 
     pub(crate) state: RwLock<ConsensusAgentState>,
-
-    block_hashes_requests: RequestResponse<P, RequestBlockHashes, BlockHashes>,
-    epoch_requests: RequestResponse<P, RequestBatchSet, BatchSetInfo>,
-    history_chunk_requests: RequestResponse<P, RequestHistoryChunk, HistoryChunk>,
-    block_requests: RequestResponse<P, RequestBlock, ResponseBlock>,
-    missing_block_requests: RequestResponse<P, RequestMissingBlocks, ResponseBlocks>,
-    head_requests: RequestResponse<P, RequestHead, HeadResponse>,
 }
 
 impl<P: Peer> Debug for ConsensusAgent<P> {
@@ -45,53 +37,53 @@ impl<P: Peer> Debug for ConsensusAgent<P> {
 
 impl<P: Peer> ConsensusAgent<P> {
     pub fn new(peer: Arc<P>) -> Self {
-        // TODO: Timeout
-        let block_hashes_requests = RequestResponse::new(Arc::clone(&peer), Duration::new(10, 0));
-        let epoch_requests = RequestResponse::new(Arc::clone(&peer), Duration::new(10, 0));
-        let history_chunk_requests = RequestResponse::new(Arc::clone(&peer), Duration::new(10, 0));
-        let block_requests = RequestResponse::new(Arc::clone(&peer), Duration::new(10, 0));
-        let missing_block_requests = RequestResponse::new(Arc::clone(&peer), Duration::new(10, 0));
-        let head_requests = RequestResponse::new(Arc::clone(&peer), Duration::new(10, 0));
-
         ConsensusAgent {
             peer,
             state: RwLock::new(ConsensusAgentState {
                 local_subscription: Default::default(),
                 remote_subscription: Default::default(),
             }),
-            block_hashes_requests,
-            epoch_requests,
-            history_chunk_requests,
-            block_requests,
-            missing_block_requests,
-            head_requests,
         }
     }
 
     pub async fn request_block(&self, hash: Blake2bHash) -> Result<Option<Block>, RequestError> {
-        let result = self
-            .block_requests
-            .request(RequestBlock {
-                hash,
-                request_identifier: 0, // will automatically be set at a later point
-            })
-            .await;
+        let result = self.peer.request::<RequestBlock>(&RequestBlock { hash }).await;
 
         result.map(|response_block| response_block.block)
     }
 
-    pub async fn request_epoch(&self, hash: Blake2bHash) -> Result<BatchSetInfo, RequestError> {
+    pub async fn request_block_transactions(
+        &self,
+        hash: Blake2bHash,
+        indices: Vec<u16>,
+    ) -> Result<Vec<Transaction>, RequestError> {
+        let num_transactions = indices.len();
         let result = self
-            .epoch_requests
-            .request(RequestBatchSet {
-                hash,
-                request_identifier: 0, // will automatically be set at a later point
-            })
-            .await;
+            .peer
+            .request::<RequestBlockTransactions>(&RequestBlockTransactions { hash, indices })
+            .await?;
+
+        // The peer either doesn't know the block or sent the wrong number of transactions.
+        if result.transactions.len() != num_transactions {
+            return Err(RequestError::InvalidResponse);
+        }
 
-        // TODO verify that hash of returned epoch matches the one we requested
+        Ok(result.transactions)
+    }
 
-        result
+    pub async fn request_epoch(&self, hash: Blake2bHash) -> Result<BatchSetInfo, RequestError> {
+        let result = self
+            .peer
+            .request::<RequestBatchSet>(&RequestBatchSet { hash: hash.clone() })
+            .await?;
+
+        // Reject batch sets that do not contain the block we asked for.
+        if result.block.hash() != hash {
+            debug!("Received batch set for wrong block from {:?}: expected {}", self.peer.id(), hash);
+            return Err(RequestError::InvalidResponse);
+        }
+
+        Ok(result)
     }
 
     pub async fn request_block_hashes(
@@ -101,12 +93,11 @@ impl<P: Peer> ConsensusAgent<P> {
         filter: RequestBlockHashesFilter,
     ) -> Result<BlockHashes, RequestError> {
         let result = self
-            .block_hashes_requests
-            .request(RequestBlockHashes {
+            .peer
+            .request::<RequestBlockHashes>(&RequestBlockHashes {
                 locators,
                 max_blocks,
                 filter,
-                request_identifier: 0, // will automatically be set at a later point
             })
             .await;
 
@@ -119,11 +110,10 @@ impl<P: Peer> ConsensusAgent<P> {
         chunk_index: usize,
     ) -> Result<HistoryChunk, RequestError> {
         let result = self
-            .history_chunk_requests
-            .request(RequestHistoryChunk {
+            .peer
+            .request::<RequestHistoryChunk>(&RequestHistoryChunk {
                 epoch_number,
                 chunk_index: chunk_index as u64,
-                request_identifier: 0, // will automatically be set at a later point
             })
             .await;
 
@@ -138,11 +128,10 @@ impl<P: Peer> ConsensusAgent<P> {
         locators: Vec<Blake2bHash>,
     ) -> Result<Vec<Block>, RequestError> {
         let result = self
-            .missing_block_requests
-            .request(RequestMissingBlocks {
+            .peer
+            .request::<RequestMissingBlocks>(&RequestMissingBlocks {
                 locators,
                 target_hash: target_block_hash,
-                request_identifier: 0, // will automatically be set at a later point
             })
             .await;
 
@@ -150,12 +139,7 @@ impl<P: Peer> ConsensusAgent<P> {
     }
 
     pub async fn request_head(&self) -> Result<Blake2bHash, RequestError> {
-        let result = self
-            .head_requests
-            .request(RequestHead {
-                request_identifier: 0, // will automatically be set at a later point
-            })
-            .await;
+        let result = self.peer.request::<RequestHead>(&RequestHead {}).await;
 
         result.map(|response_blocks| response_blocks.hash)
     }
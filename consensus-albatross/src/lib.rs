@@ -24,7 +24,9 @@ extern crate nimiq_utils as utils;
 #[macro_use]
 extern crate pin_project;
 
-pub use consensus::{Consensus, ConsensusEvent, ConsensusProxy};
+pub use consensus::{
+    Consensus, ConsensusEvent, ConsensusProxy, EstablishedConfig, SyncPhase, SyncStatus,
+};
 pub use error::Error;
 
 pub mod consensus;
@@ -1,7 +1,6 @@
 use crate::messages::*;
 use block_albatross::Block;
 use blockchain_albatross::{history_store::CHUNK_SIZE, Blockchain, Direction};
-use network_interface::message::ResponseMessage;
 use nimiq_genesis::NetworkInfo;
 use primitives::policy;
 use std::sync::Arc;
@@ -68,10 +67,7 @@ impl Handle<BlockHashes> for RequestBlockHashes {
             }
         }
 
-        Some(BlockHashes {
-            hashes,
-            request_identifier: self.get_request_identifier(),
-        })
+        Some(BlockHashes { hashes })
     }
 }
 
@@ -83,7 +79,6 @@ impl Handle<BatchSetInfo> for RequestBatchSet {
             let response = BatchSetInfo {
                 block,
                 history_len: history_len as u32,
-                request_identifier: self.get_request_identifier(),
             };
 
             Some(response)
@@ -101,10 +96,7 @@ impl Handle<HistoryChunk> for RequestHistoryChunk {
             self.chunk_index as usize,
             None,
         );
-        let response = HistoryChunk {
-            chunk,
-            request_identifier: self.get_request_identifier(),
-        };
+        let response = HistoryChunk { chunk };
         Some(response)
     }
 }
@@ -112,14 +104,29 @@ impl Handle<HistoryChunk> for RequestHistoryChunk {
 impl Handle<ResponseBlock> for RequestBlock {
     fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<ResponseBlock> {
         let block = blockchain.get_block(&self.hash, true);
-        let response = ResponseBlock {
-            block,
-            request_identifier: self.get_request_identifier(),
-        };
+        let response = ResponseBlock { block };
         Some(response)
     }
 }
 
+impl Handle<ResponseBlockTransactions> for RequestBlockTransactions {
+    fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<ResponseBlockTransactions> {
+        let transactions = match blockchain.get_block(&self.hash, true) {
+            Some(Block::Micro(block)) => {
+                let body_transactions = block.body.map(|body| body.transactions).unwrap_or_default();
+                self.indices
+                    .iter()
+                    .map(|&index| body_transactions.get(index as usize).cloned())
+                    .collect::<Option<Vec<_>>>()
+                    .unwrap_or_default()
+            }
+            _ => vec![],
+        };
+
+        Some(ResponseBlockTransactions { transactions })
+    }
+}
+
 impl Handle<ResponseBlocks> for RequestMissingBlocks {
     fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<ResponseBlocks> {
         // Behaviour of our missing blocks request:
@@ -156,20 +163,14 @@ impl Handle<ResponseBlocks> for RequestMissingBlocks {
         let blocks =
             blockchain.get_blocks(&start_block.hash(), num_blocks, true, Direction::Forward);
 
-        Some(ResponseBlocks {
-            blocks,
-            request_identifier: self.get_request_identifier(),
-        })
+        Some(ResponseBlocks { blocks })
     }
 }
 
 impl Handle<HeadResponse> for RequestHead {
     fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<HeadResponse> {
         let hash = blockchain.head_hash();
-        let response = HeadResponse {
-            hash,
-            request_identifier: self.get_request_identifier(),
-        };
+        let response = HeadResponse { hash };
         Some(response)
     }
 }
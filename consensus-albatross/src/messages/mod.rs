@@ -2,20 +2,21 @@ use beserial::{Deserialize, Serialize};
 use block_albatross::{Block, MacroBlock};
 use blockchain_albatross::history_store::HistoryTreeChunk;
 use hash::Blake2bHash;
-use network_interface::message::*;
+use network_interface::peer::RequestResponse;
 use std::fmt::Debug;
-
-use crate::request_response;
+use transaction::Transaction;
 
 pub(crate) mod handlers;
-mod request_response;
 
 /*
-The consensus module uses the following messages:
-200 RequestResponseMessage<RequestBlockHashes>
-201 RequestResponseMessage<BlockHashes>
-202 RequestResponseMessage<RequestEpoch>
-203 RequestResponseMessage<Epoch>
+The consensus module uses the following requests:
+200 RequestBlockHashes -> BlockHashes
+202 RequestBatchSet -> BatchSetInfo
+204 RequestHistoryChunk -> HistoryChunk
+207 RequestBlock -> ResponseBlock
+209 RequestMissingBlocks -> ResponseBlocks
+210 RequestHead -> HeadResponse
+212 RequestBlockTransactions -> ResponseBlockTransactions
 */
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -75,12 +76,6 @@ impl<'a> From<&'a Block> for BlockHashType {
 pub struct BlockHashes {
     #[beserial(len_type(u16))]
     pub hashes: Vec<(BlockHashType, Blake2bHash)>,
-    pub request_identifier: u32,
-}
-request_response!(BlockHashes);
-
-impl Message for BlockHashes {
-    const TYPE_ID: u64 = 201;
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
@@ -97,22 +92,24 @@ pub struct RequestBlockHashes {
     pub locators: Vec<Blake2bHash>,
     pub max_blocks: u16,
     pub filter: RequestBlockHashesFilter,
-    pub request_identifier: u32,
 }
-request_response!(RequestBlockHashes);
 
-impl Message for RequestBlockHashes {
+impl RequestResponse for RequestBlockHashes {
+    type Request = Self;
+    type Response = BlockHashes;
+
     const TYPE_ID: u64 = 200;
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RequestBatchSet {
     pub hash: Blake2bHash,
-    pub request_identifier: u32,
 }
-request_response!(RequestBatchSet);
 
-impl Message for RequestBatchSet {
+impl RequestResponse for RequestBatchSet {
+    type Request = Self;
+    type Response = BatchSetInfo;
+
     const TYPE_ID: u64 = 202;
 }
 
@@ -122,12 +119,6 @@ impl Message for RequestBatchSet {
 pub struct BatchSetInfo {
     pub block: MacroBlock,
     pub history_len: u32,
-    pub request_identifier: u32,
-}
-request_response!(BatchSetInfo);
-
-impl Message for BatchSetInfo {
-    const TYPE_ID: u64 = 203;
 }
 
 /// This message contains a chunk of the history.
@@ -135,11 +126,12 @@ impl Message for BatchSetInfo {
 pub struct RequestHistoryChunk {
     pub epoch_number: u32,
     pub chunk_index: u64,
-    pub request_identifier: u32,
 }
-request_response!(RequestHistoryChunk);
 
-impl Message for RequestHistoryChunk {
+impl RequestResponse for RequestHistoryChunk {
+    type Request = Self;
+    type Response = HistoryChunk;
+
     const TYPE_ID: u64 = 204;
 }
 
@@ -147,33 +139,22 @@ impl Message for RequestHistoryChunk {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HistoryChunk {
     pub chunk: Option<HistoryTreeChunk>,
-    pub request_identifier: u32,
-}
-request_response!(HistoryChunk);
-
-impl Message for HistoryChunk {
-    const TYPE_ID: u64 = 205;
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ResponseBlock {
     pub block: Option<Block>,
-    pub request_identifier: u32,
-}
-request_response!(ResponseBlock);
-
-impl Message for ResponseBlock {
-    const TYPE_ID: u64 = 206;
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RequestBlock {
     pub hash: Blake2bHash,
-    pub request_identifier: u32,
 }
-request_response!(RequestBlock);
 
-impl Message for RequestBlock {
+impl RequestResponse for RequestBlock {
+    type Request = Self;
+    type Response = ResponseBlock;
+
     const TYPE_ID: u64 = 207;
 }
 
@@ -182,12 +163,6 @@ pub struct ResponseBlocks {
     // TODO: Set to sensible limit (2 * BATCH_SIZE for example).
     #[beserial(len_type(u16, limit = 256))]
     pub blocks: Vec<Block>,
-    pub request_identifier: u32,
-}
-request_response!(ResponseBlocks);
-
-impl Message for ResponseBlocks {
-    const TYPE_ID: u64 = 208;
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -195,31 +170,49 @@ pub struct RequestMissingBlocks {
     pub target_hash: Blake2bHash,
     #[beserial(len_type(u16, limit = 128))]
     pub locators: Vec<Blake2bHash>,
-    pub request_identifier: u32,
 }
-request_response!(RequestMissingBlocks);
 
-impl Message for RequestMissingBlocks {
+impl RequestResponse for RequestMissingBlocks {
+    type Request = Self;
+    type Response = ResponseBlocks;
+
     const TYPE_ID: u64 = 209;
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct RequestHead {
-    pub request_identifier: u32,
-}
-request_response!(RequestHead);
+pub struct RequestHead {}
+
+impl RequestResponse for RequestHead {
+    type Request = Self;
+    type Response = HeadResponse;
 
-impl Message for RequestHead {
     const TYPE_ID: u64 = 210;
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HeadResponse {
     pub hash: Blake2bHash,
-    pub request_identifier: u32,
 }
-request_response!(HeadResponse);
 
-impl Message for HeadResponse {
-    const TYPE_ID: u64 = 211;
+/// Requests the transactions at the given indices of the body of a micro block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestBlockTransactions {
+    pub hash: Blake2bHash,
+    #[beserial(len_type(u16))]
+    pub indices: Vec<u16>,
+}
+
+impl RequestResponse for RequestBlockTransactions {
+    type Request = Self;
+    type Response = ResponseBlockTransactions;
+
+    const TYPE_ID: u64 = 212;
 }
+
+/// The requested transactions, in the order of the requested indices. Empty if the block is unknown.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResponseBlockTransactions {
+    #[beserial(len_type(u16))]
+    pub transactions: Vec<Transaction>,
+}
+
@@ -329,6 +329,19 @@ impl<TPeer: Peer, TReq: RequestComponent<TPeer>> BlockQueue<TPeer, TReq> {
         self.inner
             .on_block_announced(block, Pin::new(&mut self.request_component));
     }
+
+    /// Pushes a block that was announced outside of the gossipsub block stream, e.g. a
+    /// reconstructed compact block. Counts towards the accepted block announcements. Returns
+    /// whether the block was accepted.
+    pub fn push_announced_block(&mut self, block: Block) -> bool {
+        let result = self
+            .inner
+            .on_block_announced(block, Pin::new(&mut self.request_component));
+        if result {
+            self.accepted_announcements = self.accepted_announcements.saturating_add(1);
+        }
+        result
+    }
 }
 
 impl<TPeer: Peer, TReq: RequestComponent<TPeer>> Stream for BlockQueue<TPeer, TReq> {
@@ -0,0 +1,128 @@
+use std::io;
+
+use beserial::{Deserialize, Serialize};
+use blockchain_albatross::history_store::ExtendedTransaction;
+use database::{Database, Environment, FromDatabaseValue, IntoDatabaseValue, ReadTransaction, WriteTransaction};
+use hash::Blake2bHash;
+
+/// A chunk of history that was downloaded and verified during history sync, but whose batch set
+/// has not been pushed to the blockchain yet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredHistoryChunk {
+    /// The hash of the macro block whose history root this chunk was verified against.
+    pub block_hash: Blake2bHash,
+    #[beserial(len_type(u32))]
+    pub history: Vec<ExtendedTransaction>,
+}
+
+impl IntoDatabaseValue for StoredHistoryChunk {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for StoredHistoryChunk {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+/// Persists verified history chunks of the batch sets that are currently being downloaded, so that
+/// history sync can resume from the last verified chunk after a restart.
+pub struct HistoryChunkStore {
+    env: Environment,
+    chunk_db: Database,
+}
+
+impl HistoryChunkStore {
+    const CHUNK_DB_NAME: &'static str = "HistorySyncChunks";
+
+    pub fn new(env: Environment) -> Self {
+        let chunk_db = env.open_database(Self::CHUNK_DB_NAME.to_string());
+        HistoryChunkStore { env, chunk_db }
+    }
+
+    fn key(epoch_number: u32, chunk_index: usize) -> u64 {
+        (u64::from(epoch_number) << 32) | chunk_index as u64
+    }
+
+    /// Stores a verified chunk of the history of the given epoch.
+    pub fn put_chunk(&self, epoch_number: u32, chunk_index: usize, block_hash: &Blake2bHash, history: &[ExtendedTransaction]) {
+        let chunk = StoredHistoryChunk {
+            block_hash: block_hash.clone(),
+            history: history.to_vec(),
+        };
+
+        let mut txn = WriteTransaction::new(&self.env);
+        txn.put_reserve(&self.chunk_db, &Self::key(epoch_number, chunk_index), &chunk);
+        txn.commit();
+    }
+
+    /// Returns the consecutive chunks starting at `start_index` that were stored for the given
+    /// epoch and block.
+    pub fn get_chunks(&self, epoch_number: u32, block_hash: &Blake2bHash, start_index: usize) -> Vec<Vec<ExtendedTransaction>> {
+        let txn = ReadTransaction::new(&self.env);
+
+        let mut chunks = vec![];
+        for chunk_index in start_index.. {
+            match txn.get::<u64, StoredHistoryChunk>(&self.chunk_db, &Self::key(epoch_number, chunk_index)) {
+                Some(chunk) if &chunk.block_hash == block_hash => chunks.push(chunk.history),
+                _ => break,
+            }
+        }
+        chunks
+    }
+
+    /// Removes all chunks stored for the given epoch.
+    pub fn remove_epoch(&self, epoch_number: u32) {
+        let mut txn = WriteTransaction::new(&self.env);
+
+        for chunk_index in 0.. {
+            let key = Self::key(epoch_number, chunk_index);
+            if txn.get::<u64, StoredHistoryChunk>(&self.chunk_db, &key).is_none() {
+                break;
+            }
+            txn.remove(&self.chunk_db, &key);
+        }
+
+        txn.commit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use database::volatile::VolatileEnvironment;
+
+    #[test]
+    fn it_returns_consecutive_chunks_of_the_same_block() {
+        let env = VolatileEnvironment::new(1).unwrap();
+        let store = HistoryChunkStore::new(env);
+
+        let hash1 = Blake2bHash::from([1u8; 32]);
+        let hash2 = Blake2bHash::from([2u8; 32]);
+
+        store.put_chunk(3, 0, &hash1, &[]);
+        store.put_chunk(3, 1, &hash1, &[]);
+        store.put_chunk(3, 3, &hash1, &[]);
+        store.put_chunk(4, 0, &hash2, &[]);
+
+        assert_eq!(store.get_chunks(3, &hash1, 0).len(), 2);
+        assert_eq!(store.get_chunks(3, &hash1, 1).len(), 1);
+        assert_eq!(store.get_chunks(3, &hash2, 0).len(), 0);
+        assert_eq!(store.get_chunks(4, &hash2, 0).len(), 1);
+
+        store.remove_epoch(3);
+        assert_eq!(store.get_chunks(3, &hash1, 0).len(), 0);
+        assert_eq!(store.get_chunks(4, &hash2, 0).len(), 1);
+    }
+}
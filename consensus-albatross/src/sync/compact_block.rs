@@ -0,0 +1,315 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::task::{Context, Poll};
+use futures::{FutureExt, Stream, StreamExt};
+
+use beserial::{Deserialize, Serialize};
+use block_albatross::{
+    Block, ForkProof, MicroBlock, MicroBody, MicroHeader, MicroJustification,
+};
+use hash::{Blake2bHash, Hash};
+use mempool::Mempool;
+use network_interface::network::Topic;
+use network_interface::peer::Peer;
+use transaction::Transaction;
+
+use crate::consensus_agent::ConsensusAgent;
+
+pub use mempool::{short_tx_id, ShortTxId};
+
+/// A micro block whose transactions are replaced by short transaction IDs. Receivers reconstruct
+/// the body from their mempool and only request the transactions they are missing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompactMicroBlock {
+    pub header: MicroHeader,
+    pub justification: Option<MicroJustification>,
+    #[beserial(len_type(u16))]
+    pub fork_proofs: Vec<ForkProof>,
+    #[beserial(len_type(u16))]
+    pub short_tx_ids: Vec<ShortTxId>,
+}
+
+impl CompactMicroBlock {
+    /// Creates the compact encoding of the given block. Returns `None` if the block has no body.
+    pub fn from_block(block: &MicroBlock) -> Option<Self> {
+        let body = block.body.as_ref()?;
+        Some(CompactMicroBlock {
+            header: block.header.clone(),
+            justification: block.justification.clone(),
+            fork_proofs: body.fork_proofs.clone(),
+            short_tx_ids: body
+                .transactions
+                .iter()
+                .map(|tx| short_tx_id(&tx.hash()))
+                .collect(),
+        })
+    }
+
+    pub fn hash(&self) -> Blake2bHash {
+        self.header.hash()
+    }
+
+    /// Looks up the transactions of this block in the mempool. Transactions that are not in the
+    /// mempool are `None`.
+    pub fn lookup_transactions(&self, mempool: &Mempool) -> Vec<Option<Transaction>> {
+        mempool
+            .get_transactions_by_short_ids(&self.short_tx_ids)
+            .into_iter()
+            .map(|tx| tx.map(|tx| Transaction::clone(&tx)))
+            .collect()
+    }
+
+    /// Assembles the full block from the given transactions. Returns `None` if the resulting body
+    /// doesn't match the body root of the header, e.g. because of a short ID collision.
+    pub fn into_block(self, transactions: Vec<Transaction>) -> Option<MicroBlock> {
+        let body = MicroBody {
+            fork_proofs: self.fork_proofs,
+            transactions,
+        };
+
+        if body.hash::<Blake2bHash>() != self.header.body_root {
+            return None;
+        }
+
+        Some(MicroBlock {
+            header: self.header,
+            justification: self.justification,
+            body: Some(body),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CompactBlockTopic;
+
+impl Topic for CompactBlockTopic {
+    type Item = CompactMicroBlock;
+
+    fn topic(&self) -> String {
+        "compact-blocks".to_owned()
+    }
+
+    fn validate(&self) -> bool {
+        // Compact blocks are only forwarded once the reconstructed block was accepted.
+        true
+    }
+}
+
+/// Reconstructs micro blocks received in compact form. Blocks that can be completed from the
+/// mempool are returned immediately, the missing transactions of all other blocks are requested
+/// from the peer that relayed the block. If that fails, the full block is requested instead, from
+/// the relaying peer first and then from our other peers.
+///
+/// Every block is returned together with an identifier `TId` that was passed in along with the
+/// compact block, e.g. the ID of the gossip message to forward once the block was accepted.
+///
+/// The number of reconstructions in progress is limited, per relaying peer and in total, such that
+/// peers can't make us buffer and request an unbounded number of blocks.
+pub struct CompactBlockRelay<TPeer: Peer + 'static, TId: Send + 'static = ()> {
+    mempool: Arc<Mempool>,
+    pending: FuturesUnordered<BoxFuture<'static, Reconstruction<TPeer, TId>>>,
+    pending_per_peer: HashMap<TPeer::Id, usize>,
+}
+
+/// The outcome of a reconstruction that needed help from our peers.
+type Reconstruction<TPeer, TId> = (Option<Arc<ConsensusAgent<TPeer>>>, TId, Option<Block>);
+
+impl<TPeer: Peer + 'static, TId: Send + 'static> CompactBlockRelay<TPeer, TId> {
+    /// Maximum number of compact blocks that are reconstructed at the same time.
+    pub const MAX_PENDING: usize = 32;
+    /// Maximum number of compact blocks relayed by a single peer that are reconstructed at the
+    /// same time.
+    pub const MAX_PENDING_PER_PEER: usize = 4;
+
+    pub fn new(mempool: Arc<Mempool>) -> Self {
+        CompactBlockRelay {
+            mempool,
+            pending: Default::default(),
+            pending_per_peer: HashMap::new(),
+        }
+    }
+
+    pub fn num_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Handles a compact block relayed by `peer`. Returns the block if it could be reconstructed
+    /// from the mempool right away. Otherwise, the missing data is requested from `peer` and, as a
+    /// last resort, the full block from `other_peers`.
+    pub fn on_compact_block(
+        &mut self,
+        compact_block: CompactMicroBlock,
+        id: TId,
+        peer: Option<Arc<ConsensusAgent<TPeer>>>,
+        other_peers: Vec<Arc<ConsensusAgent<TPeer>>>,
+    ) -> Option<(Block, TId)> {
+        let hash = compact_block.hash();
+        let mut transactions = compact_block.lookup_transactions(&self.mempool);
+        let missing_indices: Vec<u16> = transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| tx.is_none())
+            .map(|(index, _)| index as u16)
+            .collect();
+
+        if missing_indices.is_empty() {
+            let complete = transactions.iter().cloned().map(Option::unwrap).collect();
+            if let Some(block) = compact_block.clone().into_block(complete) {
+                return Some((Block::Micro(block), id));
+            }
+            debug!("Failed to reconstruct compact block {} from mempool", hash);
+        }
+
+        if peer.is_none() && other_peers.is_empty() {
+            debug!("Dropping compact block {}, no peer to request it from", hash);
+            return None;
+        }
+
+        if !self.reserve(peer.as_ref()) {
+            debug!(
+                "Dropping compact block {}, too many reconstructions in progress",
+                hash
+            );
+            return None;
+        }
+
+        self.pending.push(
+            async move {
+                let mut block = None;
+
+                // Ask the relaying peer for the transactions that are missing in our mempool.
+                if let Some(peer) = peer.as_ref().filter(|_| !missing_indices.is_empty()) {
+                    trace!(
+                        "Requesting {} missing transactions of compact block {} from {:?}",
+                        missing_indices.len(),
+                        hash,
+                        peer.peer.id()
+                    );
+                    if let Ok(missing) = peer
+                        .request_block_transactions(hash.clone(), missing_indices.clone())
+                        .await
+                    {
+                        for (index, tx) in missing_indices.into_iter().zip(missing) {
+                            transactions[index as usize] = Some(tx);
+                        }
+                        let transactions = transactions.into_iter().map(Option::unwrap).collect();
+                        block = compact_block.into_block(transactions).map(Block::Micro);
+                    }
+                }
+
+                // Fall back to requesting the full block.
+                if block.is_none() {
+                    debug!("Failed to reconstruct compact block {}, requesting full block", hash);
+                    for agent in peer.iter().chain(other_peers.iter()) {
+                        if let Ok(Some(full_block)) = agent.request_block(hash.clone()).await {
+                            if full_block.hash() == hash {
+                                block = Some(full_block);
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                (peer, id, block)
+            }
+            .boxed(),
+        );
+
+        None
+    }
+
+    /// Reserves a slot for a reconstruction of a block relayed by `peer`. Returns false if the
+    /// limits are exhausted.
+    fn reserve(&mut self, peer: Option<&Arc<ConsensusAgent<TPeer>>>) -> bool {
+        if self.pending.len() >= Self::MAX_PENDING {
+            return false;
+        }
+
+        if let Some(peer) = peer {
+            let count = self.pending_per_peer.entry(peer.peer.id()).or_insert(0);
+            if *count >= Self::MAX_PENDING_PER_PEER {
+                return false;
+            }
+            *count += 1;
+        }
+        true
+    }
+
+    fn release(&mut self, peer: Option<&Arc<ConsensusAgent<TPeer>>>) {
+        if let Some(peer) = peer {
+            if let Entry::Occupied(mut entry) = self.pending_per_peer.entry(peer.peer.id()) {
+                *entry.get_mut() -= 1;
+                if *entry.get() == 0 {
+                    entry.remove();
+                }
+            }
+        }
+    }
+}
+
+impl<TPeer: Peer + 'static, TId: Send + 'static> Stream for CompactBlockRelay<TPeer, TId> {
+    type Item = (Block, TId);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        while let Poll::Ready(Some((peer, id, block))) = self.pending.poll_next_unpin(cx) {
+            self.release(peer.as_ref());
+            if let Some(block) = block {
+                return Poll::Ready(Some((block, id)));
+            }
+        }
+
+        // We never terminate, new blocks may be added at any time.
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn micro_block(body: MicroBody) -> MicroBlock {
+        MicroBlock {
+            header: MicroHeader {
+                version: 1,
+                block_number: 1,
+                view_number: 0,
+                timestamp: 0,
+                parent_hash: Blake2bHash::default(),
+                seed: Default::default(),
+                extra_data: vec![],
+                state_root: Blake2bHash::default(),
+                body_root: body.hash(),
+            },
+            justification: None,
+            body: Some(body),
+        }
+    }
+
+    #[test]
+    fn short_tx_id_uses_hash_prefix() {
+        let mut bytes = [9u8; 32];
+        bytes[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(short_tx_id(&Blake2bHash::from(bytes)), 0x0102030405060708);
+    }
+
+    #[test]
+    fn it_checks_the_body_root_when_reconstructing() {
+        let block = micro_block(MicroBody {
+            fork_proofs: vec![],
+            transactions: vec![],
+        });
+
+        let compact_block = CompactMicroBlock::from_block(&block).unwrap();
+        assert_eq!(compact_block.hash(), block.hash());
+        assert_eq!(compact_block.clone().into_block(vec![]), Some(block));
+
+        let mut invalid_block = compact_block;
+        invalid_block.header.body_root = Blake2bHash::default();
+        assert_eq!(invalid_block.into_block(vec![]), None);
+    }
+}
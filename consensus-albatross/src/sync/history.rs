@@ -7,27 +7,30 @@ use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
 use futures::task::{Context, Poll};
 use futures::{FutureExt, Stream, StreamExt};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 
-use block_albatross::{Block, MacroBlock};
+use block_albatross::{Block, BlockError, MacroBlock};
 use blockchain_albatross::history_store;
-use blockchain_albatross::history_store::ExtendedTransaction;
+use blockchain_albatross::history_store::{ExtendedTransaction, HistoryStore};
 use blockchain_albatross::Blockchain;
-use hash::Blake2bHash;
+use hash::{Blake2bHash, Hash};
 use network_interface::prelude::{CloseReason, Network, NetworkEvent, Peer};
 use primitives::policy;
+use primitives::slot::ValidatorSlots;
 use utils::math::CeilingDiv;
 
 use crate::consensus_agent::ConsensusAgent;
 use crate::messages::{BatchSetInfo, BlockHashType, HistoryChunk, RequestBlockHashesFilter};
+use crate::sync::chunk_store::HistoryChunkStore;
 use crate::sync::sync_queue::SyncQueue;
 
-struct PendingBatchSet {
+struct PendingBatchSet<TPeer: Peer> {
     block: MacroBlock,
     history_len: usize,
     history: Vec<ExtendedTransaction>,
+    sender: Weak<ConsensusAgent<TPeer>>,
 }
-impl PendingBatchSet {
+impl<TPeer: Peer> PendingBatchSet<TPeer> {
     fn is_complete(&self) -> bool {
         self.history_len == self.history.len()
     }
@@ -35,6 +38,14 @@ impl PendingBatchSet {
     fn epoch_number(&self) -> u32 {
         policy::epoch_at(self.block.header.block_number)
     }
+
+    fn num_chunks(&self) -> usize {
+        self.history_len.ceiling_div(history_store::CHUNK_SIZE)
+    }
+
+    fn num_chunks_done(&self) -> usize {
+        self.history.len().ceiling_div(history_store::CHUNK_SIZE)
+    }
 }
 
 pub struct BatchSet {
@@ -42,37 +53,63 @@ pub struct BatchSet {
     history: Vec<ExtendedTransaction>,
 }
 
+/// The progress of the history sync. Epochs are counted from genesis, chunks are counted for the
+/// batch sets that are currently being downloaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HistorySyncProgress {
+    pub epochs_done: usize,
+    pub epochs_total: usize,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+}
+
 struct SyncCluster<TPeer: Peer> {
     ids: Vec<Blake2bHash>,
     epoch_offset: usize,
 
-    batch_set_queue: SyncQueue<TPeer, Blake2bHash, BatchSetInfo>,
-    history_queue: SyncQueue<TPeer, (u32, usize), (u32, HistoryChunk)>,
+    batch_set_queue: SyncQueue<TPeer, Blake2bHash, (BatchSetInfo, Weak<ConsensusAgent<TPeer>>)>,
+    history_queue: SyncQueue<TPeer, (u32, usize), (u32, HistoryChunk, Weak<ConsensusAgent<TPeer>>)>,
 
-    pending_batch_sets: VecDeque<PendingBatchSet>,
+    pending_batch_sets: VecDeque<PendingBatchSet<TPeer>>,
 
     adopted_batch_set: bool,
     blockchain: Arc<Blockchain>,
+    chunk_store: Arc<HistoryChunkStore>,
 }
 
 impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
     const NUM_PENDING_BATCH_SETS: usize = 5;
-    const NUM_PENDING_CHUNKS: usize = 12;
-
-    fn new(ids: Vec<Blake2bHash>, epoch_offset: usize, peers: Vec<Weak<ConsensusAgent<TPeer>>>, blockchain: Arc<Blockchain>) -> Self {
+    /// The number of concurrent chunk requests is adapted between the minimum and the maximum
+    /// number per peer in the cluster.
+    const MIN_PENDING_CHUNKS: usize = 4;
+    const MAX_PENDING_CHUNKS_PER_PEER: usize = 12;
+
+    fn new(
+        ids: Vec<Blake2bHash>,
+        epoch_offset: usize,
+        peers: Vec<Weak<ConsensusAgent<TPeer>>>,
+        blockchain: Arc<Blockchain>,
+        chunk_store: Arc<HistoryChunkStore>,
+    ) -> Self {
         let batch_set_queue = SyncQueue::new(ids.clone(), peers.clone(), Self::NUM_PENDING_BATCH_SETS, |id, peer| {
-            async move { peer.request_epoch(id).await.ok() }.boxed()
+            async move {
+                let sender = Arc::downgrade(&peer);
+                peer.request_epoch(id).await.ok().map(|epoch| (epoch, sender))
+            }
+            .boxed()
         });
-        let history_queue = SyncQueue::new(
+        let history_queue = SyncQueue::with_adaptive_size(
             Vec::<(u32, usize)>::new(),
             peers,
-            Self::NUM_PENDING_CHUNKS,
+            Self::MIN_PENDING_CHUNKS,
+            Self::MAX_PENDING_CHUNKS_PER_PEER,
             move |(epoch_number, chunk_index), peer| {
                 async move {
+                    let sender = Arc::downgrade(&peer);
                     peer.request_history_chunk(epoch_number, chunk_index)
                         .await
                         .ok()
-                        .map(|chunk| (epoch_number, chunk))
+                        .map(|chunk| (epoch_number, chunk, sender))
                 }
                 .boxed()
             },
@@ -85,23 +122,84 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
             pending_batch_sets: VecDeque::with_capacity(Self::NUM_PENDING_BATCH_SETS),
             adopted_batch_set: false,
             blockchain,
+            chunk_store,
+        }
+    }
+
+    /// Returns the hash and validators of the most recent election block preceding
+    /// the next batch set. This is either the last pending election block or, if there is none,
+    /// the election head of our blockchain.
+    fn latest_election(&self) -> (Blake2bHash, ValidatorSlots) {
+        for pending_batch_set in self.pending_batch_sets.iter().rev() {
+            if let Some(ref validators) = pending_batch_set.block.body.as_ref().and_then(|body| body.validators.as_ref()) {
+                return (pending_batch_set.block.hash(), validators.clone());
+            }
         }
+        (self.blockchain.election_head_hash(), self.blockchain.current_validators().clone())
+    }
+
+    /// Returns the block number of the last macro block that precedes the next batch set.
+    fn latest_block_number(&self) -> u32 {
+        self.pending_batch_sets
+            .back()
+            .map(|pending_batch_set| pending_batch_set.block.header.block_number)
+            .unwrap_or_else(|| self.blockchain.macro_head().header.block_number)
     }
 
-    fn on_epoch_received(&mut self, epoch: BatchSetInfo) -> Result<(), SyncClusterResult> {
-        // TODO Verify macro blocks and their ordering
-        // Currently we only do a very basic check here
+    /// Verifies that the given macro block is a valid successor of the previous batch set, i.e.
+    /// that it is a macro block at a later block number that is built on top of the latest
+    /// election block and is correctly justified by the validators elected in that block.
+    fn verify_macro_block(&self, block: &MacroBlock) -> Result<(), BlockError> {
+        if !policy::is_macro_block_at(block.header.block_number) {
+            return Err(BlockError::InvalidBlockNumber);
+        }
+
+        if block.header.block_number <= self.latest_block_number() {
+            return Err(BlockError::InvalidBlockNumber);
+        }
+
+        let (election_hash, validators) = self.latest_election();
+        if block.header.parent_election_hash != election_hash {
+            return Err(BlockError::InvalidParentElectionHash);
+        }
+
+        let body = block.body.as_ref().ok_or(BlockError::MissingBody)?;
+        if body.hash::<Blake2bHash>() != block.header.body_root {
+            return Err(BlockError::BodyHashMismatch);
+        }
+
+        if block.is_election_block() != body.validators.is_some() {
+            return Err(BlockError::InvalidValidators);
+        }
+
+        let justification = block.justification.as_ref().ok_or(BlockError::NoJustification)?;
+        if !justification.verify(block.hash(), block.header.block_number, &validators) {
+            return Err(BlockError::InvalidJustification);
+        }
+
+        Ok(())
+    }
+
+    fn on_epoch_received(&mut self, epoch: BatchSetInfo, sender: Weak<ConsensusAgent<TPeer>>) -> Result<(), SyncClusterResult> {
         let current_block_number = self.blockchain.block_number();
         if epoch.block.header.block_number < current_block_number {
             debug!("Received outdated epoch at block {}", current_block_number);
             return Err(SyncClusterResult::Outdated);
         }
 
+        // Verify the macro block and its ordering before downloading any history for it.
+        if let Err(e) = self.verify_macro_block(&epoch.block) {
+            warn!("Rejecting batch set at block {}: {}", epoch.block.header.block_number, e);
+            Self::close_sender(&sender);
+            return Err(SyncClusterResult::Error);
+        }
+
         // Prepare pending info.
         let mut pending_batch_set = PendingBatchSet {
             block: epoch.block,
             history_len: epoch.history_len as usize,
             history: Vec::new(),
+            sender,
         };
 
         // If the block is in the same epoch, add already known history.
@@ -119,6 +217,16 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
             pending_batch_set.history = known_chunk.history;
         }
 
+        // Resume from the chunks that we already downloaded and verified before a restart.
+        let block_hash = pending_batch_set.block.hash();
+        for mut chunk in self.chunk_store.get_chunks(epoch_number, &block_hash, start_index) {
+            pending_batch_set.history.append(&mut chunk);
+            start_index += 1;
+        }
+        if start_index > 0 {
+            debug!("Resuming history download for epoch {} at chunk {}", epoch_number, start_index);
+        }
+
         // Queue history chunks for the given epoch for download.
         let history_chunk_ids = (start_index..((epoch.history_len as usize).ceiling_div(history_store::CHUNK_SIZE)))
             .map(|i| (epoch_number, i))
@@ -132,21 +240,34 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
         Ok(())
     }
 
-    fn on_history_chunk_received(&mut self, epoch_number: u32, history_chunk: HistoryChunk) -> Result<(), SyncClusterResult> {
+    fn on_history_chunk_received(
+        &mut self,
+        epoch_number: u32,
+        history_chunk: HistoryChunk,
+        sender: Weak<ConsensusAgent<TPeer>>,
+    ) -> Result<(), SyncClusterResult> {
         // Find epoch in pending_epochs.
         let first_epoch_number = self.pending_batch_sets[0].epoch_number();
         let epoch_index = (epoch_number - first_epoch_number) as usize;
         let epoch = &mut self.pending_batch_sets[epoch_index];
 
-        // TODO: This assumes that we have already filtered responses with no chunk.
-        // Verify chunk.
-        let chunk = history_chunk.chunk.expect("History chunk missing");
-        if !chunk
-            .verify(epoch.block.body.as_ref().expect("Missing body").history_root.clone(), epoch.history.len())
-            .unwrap_or(false)
-        {
+        // Verify chunk. The body is guaranteed to exist since the block has been verified already.
+        let valid = history_chunk
+            .chunk
+            .as_ref()
+            .and_then(|chunk| chunk.verify(epoch.block.body.as_ref().unwrap().history_root.clone(), epoch.history.len()))
+            .unwrap_or(false);
+        if !valid {
+            warn!("Rejecting invalid history chunk for epoch {}", epoch_number);
+            Self::close_sender(&sender);
             return Err(SyncClusterResult::Error);
         }
+        let chunk = history_chunk.chunk.unwrap();
+
+        // Persist the verified chunk, so that we don't need to download it again after a restart.
+        let chunk_index = epoch.history.len() / history_store::CHUNK_SIZE;
+        self.chunk_store.put_chunk(epoch_number, chunk_index, &epoch.block.hash(), &chunk.history);
+
         // Add the received history chunk to the pending epoch.
         let mut chunk = chunk.history;
         epoch.history.append(&mut chunk);
@@ -154,6 +275,13 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
         Ok(())
     }
 
+    /// Disconnects a peer that sent us invalid data.
+    fn close_sender(sender: &Weak<ConsensusAgent<TPeer>>) {
+        if let Some(agent) = Weak::upgrade(sender) {
+            agent.peer.close(CloseReason::Other);
+        }
+    }
+
     fn add_peer(&mut self, peer: Weak<ConsensusAgent<TPeer>>) -> bool {
         // TODO keep only one list of peers
         if !self.batch_set_queue.has_peer(&peer) {
@@ -179,7 +307,41 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
         // Remove the split-off ids from our epoch queue.
         self.batch_set_queue.truncate_ids(at);
 
-        Self::new(ids, offset, self.batch_set_queue.peers.clone(), Arc::clone(&self.blockchain))
+        Self::new(
+            ids,
+            offset,
+            self.batch_set_queue.peers.clone(),
+            Arc::clone(&self.blockchain),
+            Arc::clone(&self.chunk_store),
+        )
+    }
+
+    /// Removes the first pending batch set if its history is complete and checks that the history
+    /// matches the history root of the block.
+    fn pop_complete_batch_set(&mut self) -> Option<Result<BatchSet, SyncClusterResult>> {
+        if !self.pending_batch_sets.front()?.is_complete() {
+            return None;
+        }
+        let epoch = self.pending_batch_sets.pop_front().unwrap();
+
+        let history_root = epoch.block.body.as_ref().map(|body| &body.history_root);
+        if HistoryStore::root_from_ext_txs(&epoch.history).as_ref() != history_root {
+            warn!("Rejecting batch set at block {}: history root mismatch", epoch.block.header.block_number);
+            Self::close_sender(&epoch.sender);
+            return Some(Err(SyncClusterResult::Error));
+        }
+
+        Some(Ok(BatchSet {
+            block: epoch.block,
+            history: epoch.history,
+        }))
+    }
+
+    /// Returns the number of downloaded and total chunks of the pending batch sets.
+    fn chunk_progress(&self) -> (usize, usize) {
+        self.pending_batch_sets
+            .iter()
+            .fold((0, 0), |(done, total), batch_set| (done + batch_set.num_chunks_done(), total + batch_set.num_chunks()))
     }
 
     fn remove_front(&mut self, at: usize) {
@@ -196,8 +358,8 @@ impl<TPeer: Peer + 'static> Stream for SyncCluster<TPeer> {
         if self.pending_batch_sets.len() < Self::NUM_PENDING_BATCH_SETS {
             while let Poll::Ready(Some(result)) = self.batch_set_queue.poll_next_unpin(cx) {
                 match result {
-                    Ok(epoch) => {
-                        if let Err(e) = self.on_epoch_received(epoch) {
+                    Ok((epoch, sender)) => {
+                        if let Err(e) = self.on_epoch_received(epoch, sender) {
                             return Poll::Ready(Some(Err(e)));
                         }
                     }
@@ -210,19 +372,14 @@ impl<TPeer: Peer + 'static> Stream for SyncCluster<TPeer> {
 
         while let Poll::Ready(Some(result)) = self.history_queue.poll_next_unpin(cx) {
             match result {
-                Ok((epoch_number, history_chunk)) => {
-                    if let Err(e) = self.on_history_chunk_received(epoch_number, history_chunk) {
+                Ok((epoch_number, history_chunk, sender)) => {
+                    if let Err(e) = self.on_history_chunk_received(epoch_number, history_chunk, sender) {
                         return Poll::Ready(Some(Err(e)));
                     }
 
                     // Emit finished epochs.
-                    if self.pending_batch_sets[0].is_complete() {
-                        let epoch = self.pending_batch_sets.pop_front().unwrap();
-                        let epoch = BatchSet {
-                            block: epoch.block,
-                            history: epoch.history,
-                        };
-                        return Poll::Ready(Some(Ok(epoch)));
+                    if let Some(result) = self.pop_complete_batch_set() {
+                        return Poll::Ready(Some(result));
                     }
                 }
                 Err(_e) => {
@@ -231,6 +388,12 @@ impl<TPeer: Peer + 'static> Stream for SyncCluster<TPeer> {
             }
         }
 
+        // Batch sets can also be complete without receiving any chunks, e.g. if their history was
+        // restored from disk.
+        if let Some(result) = self.pop_complete_batch_set() {
+            return Poll::Ready(Some(result));
+        }
+
         // We're done if there are no more epochs to process.
         if self.batch_set_queue.is_empty() && self.pending_batch_sets.is_empty() {
             return Poll::Ready(None);
@@ -310,12 +473,17 @@ pub struct HistorySync<TNetwork: Network> {
     epoch_sync_clusters: Vec<SyncCluster<TNetwork::PeerType>>,
     checkpoint_sync_clusters: Vec<SyncCluster<TNetwork::PeerType>>,
     agents: HashMap<Arc<TNetwork::PeerType>, (Arc<ConsensusAgent<TNetwork::PeerType>>, usize)>,
+    chunk_store: Arc<HistoryChunkStore>,
+    progress_tx: watch::Sender<HistorySyncProgress>,
+    progress_rx: watch::Receiver<HistorySyncProgress>,
 }
 
 impl<TNetwork: Network> HistorySync<TNetwork> {
     const MAX_CLUSTERS: usize = 100;
 
     pub fn new(blockchain: Arc<Blockchain>, network_event_rx: broadcast::Receiver<NetworkEvent<TNetwork::PeerType>>) -> Self {
+        let chunk_store = Arc::new(HistoryChunkStore::new(blockchain.env.clone()));
+        let (progress_tx, progress_rx) = watch::channel(HistorySyncProgress::default());
         Self {
             blockchain,
             network_event_rx,
@@ -323,6 +491,9 @@ impl<TNetwork: Network> HistorySync<TNetwork> {
             epoch_sync_clusters: Vec::new(),
             checkpoint_sync_clusters: Vec::new(),
             agents: HashMap::new(),
+            chunk_store,
+            progress_tx,
+            progress_rx,
         }
     }
 
@@ -330,6 +501,46 @@ impl<TNetwork: Network> HistorySync<TNetwork> {
         self.agents.values().map(|(agent, _)| agent)
     }
 
+    /// Returns a receiver that is notified whenever the progress of the history sync changes.
+    pub fn subscribe_progress(&self) -> watch::Receiver<HistorySyncProgress> {
+        self.progress_rx.clone()
+    }
+
+    /// Computes the current progress from our blockchain state and the sync clusters.
+    fn progress(&self) -> HistorySyncProgress {
+        let epochs_done = policy::epoch_at(self.blockchain.election_head().header.block_number) as usize;
+        let epochs_total = self
+            .epoch_sync_clusters
+            .iter()
+            .map(|cluster| (cluster.epoch_offset + cluster.ids.len()).saturating_sub(1))
+            .fold(epochs_done, usize::max);
+
+        let (chunks_done, chunks_total) = self
+            .epoch_sync_clusters
+            .iter()
+            .chain(self.checkpoint_sync_clusters.iter())
+            .map(SyncCluster::chunk_progress)
+            .fold((0, 0), |(done, total), (cluster_done, cluster_total)| (done + cluster_done, total + cluster_total));
+
+        HistorySyncProgress {
+            epochs_done,
+            epochs_total,
+            chunks_done,
+            chunks_total,
+        }
+    }
+
+    /// Pushes a downloaded batch set into the blockchain. Once the batch set is adopted, its
+    /// persisted history chunks are not needed anymore.
+    fn push_batch_set(&self, batch_set: BatchSet) -> SyncClusterResult {
+        let epoch_number = policy::epoch_at(batch_set.block.header.block_number);
+        let result = SyncClusterResult::from(self.blockchain.push_history_sync(Block::Macro(batch_set.block), &batch_set.history));
+        if result == SyncClusterResult::EpochSuccessful {
+            self.chunk_store.remove_epoch(epoch_number);
+        }
+        result
+    }
+
     async fn request_epoch_ids(blockchain: Arc<Blockchain>, agent: Arc<ConsensusAgent<TNetwork::PeerType>>) -> Option<EpochIds<TNetwork::PeerType>> {
         let (locator, epoch_number) = {
             let election_head = blockchain.election_head();
@@ -445,6 +656,7 @@ impl<TNetwork: Network> HistorySync<TNetwork> {
                 epoch_ids.offset + id_index,
                 vec![Arc::downgrade(&agent)],
                 Arc::clone(&self.blockchain),
+                Arc::clone(&self.chunk_store),
             ));
             // We do not increment the num_clusters here, as this is done in the loop later on.
         }
@@ -476,6 +688,7 @@ impl<TNetwork: Network> HistorySync<TNetwork> {
                     checkpoint_epoch_offset,
                     vec![Arc::downgrade(&agent)],
                     Arc::clone(&self.blockchain),
+                    Arc::clone(&self.chunk_store),
                 );
                 self.checkpoint_sync_clusters.push(cluster);
                 self.checkpoint_sync_clusters.sort();
@@ -506,6 +719,20 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
     type Item = Arc<ConsensusAgent<TNetwork::PeerType>>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let result = self.poll_sync(cx);
+
+        // Notify subscribers if our progress changed.
+        let progress = self.progress();
+        if *self.progress_rx.borrow() != progress {
+            self.progress_tx.broadcast(progress).ok();
+        }
+
+        result
+    }
+}
+
+impl<TNetwork: Network> HistorySync<TNetwork> {
+    fn poll_sync(&mut self, cx: &mut Context<'_>) -> Poll<Option<Arc<ConsensusAgent<TNetwork::PeerType>>>> {
         while let Poll::Ready(Some(result)) = self.network_event_rx.poll_next_unpin(cx) {
             match result {
                 Ok(NetworkEvent::PeerLeft(peer)) => {
@@ -559,7 +786,7 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
             let best_cluster = self.epoch_sync_clusters.last_mut().expect("sync_clusters no empty");
 
             let result = match ready!(best_cluster.poll_next_unpin(cx)) {
-                Some(Ok(epoch)) => SyncClusterResult::from(self.blockchain.push_history_sync(Block::Macro(epoch.block), &epoch.history)),
+                Some(Ok(epoch)) => self.push_batch_set(epoch),
                 Some(Err(_)) => SyncClusterResult::Error,
                 None => SyncClusterResult::NoMoreEpochs,
             };
@@ -607,12 +834,11 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
                             // epoch_ids and dropped otherwise.
                             self.agents.remove(&agent.peer);
 
-                            if result == SyncClusterResult::NoMoreEpochs && cluster.adopted_batch_set {
+                            // Peers that sent invalid data have already been disconnected by the cluster,
+                            // so we can give the remaining peers another chance after an error.
+                            if (result == SyncClusterResult::NoMoreEpochs && cluster.adopted_batch_set) || result == SyncClusterResult::Error {
                                 let future = Self::request_epoch_ids(Arc::clone(&self.blockchain), agent).boxed();
                                 self.epoch_ids_stream.push(future);
-                            } else {
-                                // FIXME: Disconnect peer
-                                // agent.peer.close()
                             }
                         }
                     }
@@ -632,7 +858,7 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
                 result = SyncClusterResult::NoMoreEpochs;
             } else {
                 result = match ready!(best_cluster.poll_next_unpin(cx)) {
-                    Some(Ok(batch)) => SyncClusterResult::from(self.blockchain.push_history_sync(Block::Macro(batch.block), &batch.history)),
+                    Some(Ok(batch)) => self.push_batch_set(batch),
                     Some(Err(e)) => e,
                     None => SyncClusterResult::NoMoreEpochs,
                 };
@@ -660,13 +886,9 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
                         // epoch_ids and dropped otherwise.
                         self.agents.remove(&agent.peer);
 
-                        if result != SyncClusterResult::Error {
-                            let future = Self::request_epoch_ids(Arc::clone(&self.blockchain), agent).boxed();
-                            self.epoch_ids_stream.push(future);
-                        } else {
-                            // FIXME: Disconnect peer
-                            // agent.peer.close()
-                        }
+                        // Peers that sent invalid data have already been disconnected by the cluster.
+                        let future = Self::request_epoch_ids(Arc::clone(&self.blockchain), agent).boxed();
+                        self.epoch_ids_stream.push(future);
                     }
                 }
             }
@@ -1,4 +1,6 @@
 pub mod block_queue;
+pub mod chunk_store;
+pub mod compact_block;
 pub mod history;
 pub mod request_component;
 mod sync_queue;
@@ -69,9 +69,15 @@ impl<TOutput> Ord for QueuedOutput<TOutput> {
 /// The SyncQueue will request a list of ids from a set of peers
 /// and implements an ordered stream over the resulting objects.
 /// The stream returns an error if an id could not be resolved.
+///
+/// The number of concurrent requests can either be fixed or adapt to the observed request
+/// outcomes: The window grows by one with every successful request (up to a maximum that scales
+/// with the number of peers) and is halved on every failed request.
 pub struct SyncQueue<TPeer: Peer, TId, TOutput> {
     pub(crate) peers: Vec<Weak<ConsensusAgent<TPeer>>>,
     desired_pending_size: usize,
+    min_pending_size: usize,
+    max_pending_size_per_peer: usize,
     ids_to_request: VecDeque<TId>,
     pending_futures: FuturesUnordered<OrderWrapper<TId, BoxFuture<'static, Option<TOutput>>>>,
     queued_outputs: BinaryHeap<QueuedOutput<TOutput>>,
@@ -92,10 +98,24 @@ where
         peers: Vec<Weak<ConsensusAgent<TPeer>>>,
         desired_pending_size: usize,
         request_fn: fn(TId, Arc<ConsensusAgent<TPeer>>) -> BoxFuture<'static, Option<TOutput>>,
+    ) -> Self {
+        Self::with_adaptive_size(ids, peers, desired_pending_size, 0, request_fn)
+    }
+
+    /// Creates a SyncQueue whose number of concurrent requests adapts between `min_pending_size`
+    /// and `max_pending_size_per_peer` times the number of peers.
+    pub fn with_adaptive_size(
+        ids: Vec<TId>,
+        peers: Vec<Weak<ConsensusAgent<TPeer>>>,
+        min_pending_size: usize,
+        max_pending_size_per_peer: usize,
+        request_fn: fn(TId, Arc<ConsensusAgent<TPeer>>) -> BoxFuture<'static, Option<TOutput>>,
     ) -> Self {
         SyncQueue {
             peers,
-            desired_pending_size,
+            desired_pending_size: min_pending_size,
+            min_pending_size,
+            max_pending_size_per_peer,
             ids_to_request: VecDeque::from(ids),
             pending_futures: FuturesUnordered::new(),
             queued_outputs: BinaryHeap::new(),
@@ -121,6 +141,24 @@ where
         None
     }
 
+    /// The upper bound for the number of concurrent requests given the current number of peers.
+    pub fn max_pending_size(&self) -> usize {
+        cmp::max(self.min_pending_size, self.max_pending_size_per_peer * self.peers.len())
+    }
+
+    /// The current number of concurrent requests.
+    pub fn pending_size(&self) -> usize {
+        self.desired_pending_size
+    }
+
+    fn on_request_succeeded(&mut self) {
+        self.desired_pending_size = cmp::min(self.desired_pending_size + 1, self.max_pending_size());
+    }
+
+    fn on_request_failed(&mut self) {
+        self.desired_pending_size = cmp::max(self.desired_pending_size / 2, self.min_pending_size);
+    }
+
     fn try_push_futures(&mut self) {
         // Determine number of new futures required to maintain desired_pending_size.
         let num_ids_to_request = cmp::min(
@@ -212,6 +250,7 @@ where
                 Some(result) => {
                     match result.data {
                         Some(output) => {
+                            self.on_request_succeeded();
                             if result.index == self.next_outgoing_index {
                                 self.next_outgoing_index += 1;
                                 return Poll::Ready(Some(Ok(output)));
@@ -223,6 +262,8 @@ where
                             }
                         }
                         None => {
+                            self.on_request_failed();
+
                             // If we tried all peers for this hash, return an error.
                             // TODO max number of tries
                             if result.num_tries >= self.peers.len() {
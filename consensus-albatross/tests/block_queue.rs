@@ -119,7 +119,7 @@ async fn send_single_micro_block_to_block_queue() {
 
     // push one micro block to the queue
     let block =
-        Block::Micro(producer.next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x42]));
+        Block::Micro(producer.next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x42]).unwrap());
     tx.send(block).await.unwrap();
 
     assert_eq!(blockchain.block_number(), 0);
@@ -159,7 +159,7 @@ async fn send_two_micro_blocks_out_of_order() {
         None,
         vec![],
         vec![0x42],
-    ));
+    ).unwrap());
     blockchain2.push(block1.clone()).unwrap(); // push it, so the producer actually produces a block at height 2
     let block2 = Block::Micro(producer.next_micro_block(
         blockchain2.time.now() + 1000,
@@ -167,7 +167,7 @@ async fn send_two_micro_blocks_out_of_order() {
         None,
         vec![],
         vec![0x42],
-    ));
+    ).unwrap());
 
     // send block2 first
     tx.send(block2.clone()).await.unwrap();
@@ -231,7 +231,7 @@ async fn send_block_with_gap_and_respond_to_missing_request() {
         None,
         vec![],
         vec![0x42],
-    ));
+    ).unwrap());
     blockchain2.push(block1.clone()).unwrap(); // push it, so the producer actually produces a block at height 2
     let block2 = Block::Micro(producer.next_micro_block(
         blockchain2.time.now() + 1000,
@@ -239,7 +239,7 @@ async fn send_block_with_gap_and_respond_to_missing_request() {
         None,
         vec![],
         vec![0x42],
-    ));
+    ).unwrap());
 
     // send block2 first
     tx.send(block2.clone()).await.unwrap();
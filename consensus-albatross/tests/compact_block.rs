@@ -0,0 +1,254 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use beserial::{Deserialize, Serialize};
+use futures::StreamExt;
+
+use nimiq_account::{Inherent, InherentType};
+use nimiq_block_albatross::{Block, MicroBlock};
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::{Blockchain, PushResult};
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_consensus_albatross::consensus::{Consensus, EstablishedConfig};
+use nimiq_consensus_albatross::consensus_agent::ConsensusAgent;
+use nimiq_consensus_albatross::sync::compact_block::{
+    short_tx_id, CompactBlockRelay, CompactMicroBlock,
+};
+use nimiq_consensus_albatross::sync::history::HistorySync;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_database::{Environment, WriteTransaction};
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::{Address, KeyPair as SchnorrKeyPair, PrivateKey};
+use nimiq_mempool::{Mempool, MempoolConfig, ReturnCode};
+use nimiq_network_interface::prelude::Network;
+use nimiq_network_mock::{MockHub, MockNetwork, MockPeer};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::{SignatureProof, Transaction};
+
+/// Secret key of validator. Tests run with `genesis/src/genesis/unit-albatross.toml`
+const SECRET_KEY: &str =
+    "196ffdb1a8acc7cbd76a251aeac0600a1d68b3aba1eba823b5e4dc5dbdcdc730afa752c05ab4f6ef8518384ad514f403c5a088a22b17bf1bc14f8ff8decc2a512c0a200f68d7bdf5a319b30356fe8d1d75ef510aed7a8660968c216c328a0000";
+
+fn sender_key() -> SchnorrKeyPair {
+    SchnorrKeyPair::from(PrivateKey::from([1u8; PrivateKey::SIZE]))
+}
+
+/// Creates a blockchain in which the sender has a balance to pay for transactions.
+fn funded_blockchain() -> (Environment, Arc<Blockchain>) {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let reward = Inherent {
+        ty: InherentType::Reward,
+        target: Address::from(&sender_key().public),
+        value: Coin::from_u64_unchecked(10000),
+        data: vec![],
+    };
+    let mut txn = WriteTransaction::new(&env);
+    blockchain
+        .state()
+        .accounts()
+        .commit(&mut txn, &[], &[reward], 0, 0)
+        .unwrap();
+    txn.commit();
+
+    (env, blockchain)
+}
+
+fn transaction(value: u64) -> Transaction {
+    let key = sender_key();
+    let mut tx = Transaction::new_basic(
+        Address::from(&key.public),
+        Address::from([2u8; Address::SIZE]),
+        Coin::from_u64_unchecked(value),
+        Coin::from_u64_unchecked(0),
+        1,
+        NetworkId::UnitAlbatross,
+    );
+    let signature_proof = SignatureProof::from(key.public, key.sign(&tx.serialize_content()));
+    tx.proof = signature_proof.serialize_to_vec();
+    tx
+}
+
+/// Produces a micro block containing the given transactions on a remote node and returns the
+/// block together with a local mempool and an agent for the remote node.
+async fn remote_block(
+    transactions: &[Transaction],
+) -> (MicroBlock, Arc<Mempool>, Arc<ConsensusAgent<MockPeer>>) {
+    let mut hub = MockHub::default();
+
+    let (env2, blockchain2) = funded_blockchain();
+    let mempool2 = Mempool::new(Arc::clone(&blockchain2), MempoolConfig::default());
+    for tx in transactions {
+        assert_eq!(mempool2.push_transaction(tx.clone()), ReturnCode::Accepted);
+    }
+
+    let keypair =
+        KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new(Arc::clone(&blockchain2), Arc::clone(&mempool2), keypair);
+    let block = producer
+        .next_micro_block(blockchain2.time.now() + 1000, 0, None, vec![], vec![0x42])
+        .unwrap();
+    assert_eq!(
+        blockchain2.push(Block::Micro(block.clone())),
+        Ok(PushResult::Extended)
+    );
+    assert_eq!(
+        block.body.as_ref().unwrap().transactions.len(),
+        transactions.len()
+    );
+
+    // The remote consensus answers our requests.
+    let net2 = Arc::new(hub.new_network());
+    let sync = HistorySync::<MockNetwork>::new(Arc::clone(&blockchain2), net2.subscribe_events());
+    let consensus2 = Consensus::from_network_with_config(
+        EstablishedConfig::default(),
+        env2,
+        blockchain2,
+        mempool2,
+        Arc::clone(&net2),
+        sync.boxed(),
+    )
+    .await;
+    tokio::spawn(consensus2.for_each(|_| async {}));
+
+    let net1 = hub.new_network();
+    net1.dial_mock(&net2);
+    let agent = Arc::new(ConsensusAgent::new(net1.get_peer(net2.peer_id()).unwrap()));
+
+    let (_env1, blockchain1) = funded_blockchain();
+    let mempool1 = Mempool::new(blockchain1, MempoolConfig::default());
+
+    (block, mempool1, agent)
+}
+
+async fn next_block(relay: &mut CompactBlockRelay<MockPeer>) -> Block {
+    let (block, _) = tokio::time::timeout(Duration::from_secs(1), relay.next())
+        .await
+        .expect("Relay should reconstruct the block")
+        .unwrap();
+    block
+}
+
+#[tokio::test]
+async fn it_reconstructs_compact_blocks_from_the_mempool() {
+    let transactions = vec![transaction(10), transaction(20)];
+    let (block, mempool, agent) = remote_block(&transactions).await;
+    for tx in transactions {
+        assert_eq!(mempool.push_transaction(tx), ReturnCode::Accepted);
+    }
+
+    let mut relay = CompactBlockRelay::new(mempool);
+    let compact_block = CompactMicroBlock::from_block(&block).unwrap();
+    assert_eq!(
+        relay.on_compact_block(compact_block, (), Some(agent), vec![]),
+        Some((Block::Micro(block), ()))
+    );
+    assert_eq!(relay.num_pending(), 0);
+}
+
+#[tokio::test]
+async fn it_requests_missing_transactions_of_compact_blocks() {
+    let transactions = vec![transaction(10), transaction(20)];
+    let (block, mempool, agent) = remote_block(&transactions).await;
+    assert_eq!(
+        mempool.push_transaction(transactions[0].clone()),
+        ReturnCode::Accepted
+    );
+
+    let mut relay = CompactBlockRelay::new(mempool);
+    let compact_block = CompactMicroBlock::from_block(&block).unwrap();
+
+    // Without a peer to ask, the block can't be completed.
+    assert_eq!(
+        relay.on_compact_block(compact_block.clone(), (), None, vec![]),
+        None
+    );
+    assert_eq!(relay.num_pending(), 0);
+
+    assert_eq!(
+        relay.on_compact_block(compact_block, (), Some(agent), vec![]),
+        None
+    );
+    assert_eq!(relay.num_pending(), 1);
+    assert_eq!(next_block(&mut relay).await, Block::Micro(block));
+}
+
+#[tokio::test]
+async fn it_requests_the_full_block_if_reconstruction_fails() {
+    let transactions = vec![transaction(10), transaction(20)];
+    let (block, mempool, agent) = remote_block(&transactions).await;
+
+    // Another transaction in our mempool shares its short ID with the second transaction of the
+    // block, so the reconstructed body doesn't match the block.
+    let other = transaction(30);
+    assert_eq!(
+        mempool.push_transaction(transactions[0].clone()),
+        ReturnCode::Accepted
+    );
+    assert_eq!(
+        mempool.push_transaction(other.clone()),
+        ReturnCode::Accepted
+    );
+    let mut compact_block = CompactMicroBlock::from_block(&block).unwrap();
+    let index = block
+        .body
+        .as_ref()
+        .unwrap()
+        .transactions
+        .iter()
+        .position(|tx| tx == &transactions[1])
+        .unwrap();
+    compact_block.short_tx_ids[index] = short_tx_id(&other.hash::<Blake2bHash>());
+
+    let mut relay = CompactBlockRelay::new(mempool);
+    assert_eq!(
+        relay.on_compact_block(compact_block, (), Some(agent), vec![]),
+        None
+    );
+    assert_eq!(relay.num_pending(), 1);
+    assert_eq!(next_block(&mut relay).await, Block::Micro(block));
+}
+
+#[tokio::test]
+async fn it_requests_the_full_block_from_other_peers_if_the_relaying_peer_is_unknown() {
+    let transactions = vec![transaction(10)];
+    let (block, mempool, agent) = remote_block(&transactions).await;
+
+    let mut relay = CompactBlockRelay::new(mempool);
+    let compact_block = CompactMicroBlock::from_block(&block).unwrap();
+    assert_eq!(
+        relay.on_compact_block(compact_block, (), None, vec![agent]),
+        None
+    );
+    assert_eq!(relay.num_pending(), 1);
+    assert_eq!(next_block(&mut relay).await, Block::Micro(block));
+}
+
+#[tokio::test]
+async fn it_limits_the_reconstructions_per_peer() {
+    let transactions = vec![transaction(10)];
+    let (block, mempool, agent) = remote_block(&transactions).await;
+
+    let mut relay = CompactBlockRelay::new(mempool);
+    let compact_block = CompactMicroBlock::from_block(&block).unwrap();
+    let max = CompactBlockRelay::<MockPeer>::MAX_PENDING_PER_PEER;
+    for _ in 0..max + 1 {
+        assert_eq!(
+            relay.on_compact_block(compact_block.clone(), (), Some(Arc::clone(&agent)), vec![]),
+            None
+        );
+    }
+    assert_eq!(relay.num_pending(), max);
+
+    // Finished reconstructions free their slot again.
+    for _ in 0..max {
+        assert_eq!(next_block(&mut relay).await, Block::Micro(block.clone()));
+    }
+    assert_eq!(
+        relay.on_compact_block(compact_block, (), Some(agent), vec![]),
+        None
+    );
+    assert_eq!(relay.num_pending(), 1);
+}
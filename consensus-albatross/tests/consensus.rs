@@ -0,0 +1,197 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use beserial::Deserialize;
+use futures::StreamExt;
+use tokio::stream::pending;
+use tokio::sync::watch;
+
+use nimiq_block_albatross::Block;
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::{Blockchain, PushResult};
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_consensus_albatross::consensus::{
+    Consensus, ConsensusEvent, EstablishedConfig, SyncPhase,
+};
+use nimiq_consensus_albatross::sync::history::{HistorySync, HistorySyncProgress};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_genesis::NetworkId;
+use nimiq_mempool::{Mempool, MempoolConfig};
+use nimiq_network_interface::prelude::Network;
+use nimiq_network_mock::{MockHub, MockNetwork};
+
+/// Secret key of validator. Tests run with `genesis/src/genesis/unit-albatross.toml`
+const SECRET_KEY: &str =
+    "196ffdb1a8acc7cbd76a251aeac0600a1d68b3aba1eba823b5e4dc5dbdcdc730afa752c05ab4f6ef8518384ad514f403c5a088a22b17bf1bc14f8ff8decc2a512c0a200f68d7bdf5a319b30356fe8d1d75ef510aed7a8660968c216c328a0000";
+
+async fn next_event(consensus: &mut Consensus<MockNetwork>) -> Option<ConsensusEvent<MockNetwork>> {
+    tokio::time::timeout(Duration::from_secs(1), consensus.next())
+        .await
+        .expect("Consensus should emit an event")
+}
+
+/// Waits until consensus is established or lost and returns whether it is established.
+async fn next_established_change(consensus: &mut Consensus<MockNetwork>) -> bool {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match consensus.next().await {
+                Some(ConsensusEvent::Established) => return true,
+                Some(ConsensusEvent::Lost) => return false,
+                Some(_) => {}
+                None => panic!("Consensus stream ended"),
+            }
+        }
+    })
+    .await
+    .expect("Consensus should be established or lost")
+}
+
+/// Consensus with a single peer that checks the heads of its peers frequently.
+fn established_config() -> EstablishedConfig {
+    EstablishedConfig {
+        min_peers_established: 1,
+        min_blocks_established: 5,
+        head_requests_timeout: Duration::from_millis(200),
+        max_blocks_behind: 2,
+        block_timeout: None,
+    }
+}
+
+async fn mock_consensus(hub: &mut MockHub, config: EstablishedConfig) -> Consensus<MockNetwork> {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+    let net = Arc::new(hub.new_network());
+    let sync = HistorySync::<MockNetwork>::new(Arc::clone(&blockchain), net.subscribe_events());
+    Consensus::from_network_with_config(config, env, blockchain, mempool, net, sync.boxed()).await
+}
+
+/// Pushes micro blocks onto the chain without announcing them.
+fn produce_micro_blocks(blockchain: &Arc<Blockchain>, num_blocks: u32) {
+    let keypair =
+        KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(blockchain), keypair);
+    for _ in 0..num_blocks {
+        let timestamp = blockchain.time.now() + (blockchain.block_number() as u64 + 1) * 1000;
+        let block = producer
+            .next_micro_block(timestamp, 0, None, vec![], vec![0x42])
+            .unwrap();
+        assert_eq!(
+            blockchain.push(Block::Micro(block)),
+            Ok(PushResult::Extended)
+        );
+    }
+}
+
+#[tokio::test]
+async fn it_loses_consensus_when_behind_and_establishes_it_again() {
+    let mut hub = MockHub::default();
+
+    let mut consensus1 = mock_consensus(&mut hub, established_config()).await;
+    let consensus2 = mock_consensus(&mut hub, established_config()).await;
+    let blockchain2 = Arc::clone(&consensus2.blockchain);
+    consensus1.network.dial_mock(&consensus2.network);
+    tokio::spawn(consensus2.for_each(|_| async {}));
+
+    // We know the head of our peer, as both of us are at the genesis block.
+    assert!(next_established_change(&mut consensus1).await);
+
+    // Our peer moves on by more than `max_blocks_behind` blocks without announcing them.
+    produce_micro_blocks(&blockchain2, 5);
+    assert!(!next_established_change(&mut consensus1).await);
+    assert!(!consensus1.is_established());
+
+    // We catch up with the head that our peer reported.
+    assert!(next_established_change(&mut consensus1).await);
+    assert_eq!(consensus1.blockchain.head_hash(), blockchain2.head_hash());
+}
+
+#[tokio::test]
+async fn it_loses_consensus_on_block_timeout_and_establishes_it_again() {
+    let mut hub = MockHub::default();
+
+    let mut config = established_config();
+    config.block_timeout = Some(Duration::from_secs(1));
+    let mut consensus1 = mock_consensus(&mut hub, config).await;
+    let consensus2 = mock_consensus(&mut hub, established_config()).await;
+    consensus1.network.dial_mock(&consensus2.network);
+    tokio::spawn(consensus2.for_each(|_| async {}));
+
+    assert!(next_established_change(&mut consensus1).await);
+
+    // No block arrives within the timeout.
+    assert!(!next_established_change(&mut consensus1).await);
+    assert!(!consensus1.is_established());
+
+    // Consensus is only established again with a new block.
+    let blockchain1 = Arc::clone(&consensus1.blockchain);
+    produce_micro_blocks(&blockchain1, 1);
+    assert!(next_established_change(&mut consensus1).await);
+}
+
+#[tokio::test]
+async fn it_reports_sync_phase_changes() {
+    let mut hub = MockHub::default();
+
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+    let net = Arc::new(hub.new_network());
+
+    let mut consensus = Consensus::<MockNetwork>::from_network(
+        env,
+        Arc::clone(&blockchain),
+        mempool,
+        net,
+        pending().boxed(),
+    )
+    .await;
+    let proxy = consensus.proxy();
+
+    let status = proxy.sync_status();
+    assert_eq!(status.phase, SyncPhase::BlockQueue);
+    assert_eq!(status.head_hash, blockchain.head_hash());
+    assert_eq!(status.num_agents, 0);
+    assert_eq!(status.num_buffered_blocks, 0);
+    assert_eq!(status.best_peer_head_number(), None);
+
+    let (progress_tx, progress_rx) = watch::channel(HistorySyncProgress {
+        epochs_done: 0,
+        epochs_total: 2,
+        ..Default::default()
+    });
+    consensus.track_history_sync(progress_rx);
+
+    let event = next_event(&mut consensus).await;
+    assert!(matches!(
+        event,
+        Some(ConsensusEvent::HistorySyncProgress(_))
+    ));
+    let event = next_event(&mut consensus).await;
+    assert!(matches!(
+        event,
+        Some(ConsensusEvent::SyncPhaseChanged(SyncPhase::History))
+    ));
+    assert_eq!(proxy.sync_status().phase, SyncPhase::History);
+    assert_eq!(proxy.sync_status().remaining_epochs, 2);
+
+    progress_tx
+        .broadcast(HistorySyncProgress {
+            epochs_done: 2,
+            epochs_total: 2,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let event = next_event(&mut consensus).await;
+    assert!(matches!(
+        event,
+        Some(ConsensusEvent::HistorySyncProgress(_))
+    ));
+    let event = next_event(&mut consensus).await;
+    assert!(matches!(
+        event,
+        Some(ConsensusEvent::SyncPhaseChanged(SyncPhase::BlockQueue))
+    ));
+    assert_eq!(proxy.sync_status().remaining_epochs, 0);
+}
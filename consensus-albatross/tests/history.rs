@@ -6,14 +6,19 @@ use futures::StreamExt;
 use tokio::stream::pending;
 
 use nimiq_block_production_albatross::{test_utils::*, BlockProducer};
+use nimiq_blockchain_albatross::history_store::CHUNK_SIZE;
 use nimiq_blockchain_albatross::Blockchain;
 use nimiq_bls::{KeyPair, SecretKey};
 use nimiq_consensus_albatross::consensus::Consensus;
 use nimiq_consensus_albatross::consensus_agent::ConsensusAgent;
-use nimiq_consensus_albatross::messages::RequestBlockHashesFilter;
+use nimiq_consensus_albatross::messages::{
+    BatchSetInfo, BlockHashType, BlockHashes, HistoryChunk, RequestBatchSet, RequestBlockHashes,
+    RequestBlockHashesFilter, RequestHistoryChunk,
+};
+use nimiq_consensus_albatross::sync::chunk_store::HistoryChunkStore;
 use nimiq_consensus_albatross::sync::history::HistorySync;
 use nimiq_database::volatile::VolatileEnvironment;
-use nimiq_genesis::NetworkId;
+use nimiq_genesis::{NetworkId, NetworkInfo};
 use nimiq_mempool::{Mempool, MempoolConfig};
 use nimiq_network_interface::prelude::Network;
 use nimiq_network_mock::{MockHub, MockNetwork};
@@ -297,3 +302,198 @@ async fn sync_ingredients() {
         Some(true)
     );
 }
+
+/// The ways in which a malicious peer can tamper with history sync responses.
+#[derive(Clone, Copy)]
+enum Tampering {
+    /// Answers history chunk requests without a chunk.
+    WithholdHistory,
+    /// Sends macro blocks with a justification for the wrong round.
+    Justification,
+    /// Sends the checkpoint block regardless of which block was requested.
+    WrongBlock,
+    /// Omits the last extended transaction of every batch set.
+    TruncatedHistory,
+}
+
+/// Answers history sync requests on `network` with data from `blockchain`, tampered with
+/// according to `tampering`.
+fn spawn_malicious_responder(
+    network: &Arc<MockNetwork>,
+    blockchain: &Arc<Blockchain>,
+    tampering: Tampering,
+) {
+    let mut stream = network.receive_requests_from_all::<RequestBlockHashes>();
+    let chain = Arc::clone(blockchain);
+    tokio::spawn(async move {
+        while let Some((request, _peer)) = stream.next().await {
+            let response = BlockHashes {
+                hashes: vec![
+                    (BlockHashType::Election, chain.election_head_hash()),
+                    (BlockHashType::Checkpoint, chain.macro_head_hash()),
+                ],
+            };
+            let _ = request.respond(&response);
+        }
+    });
+
+    let mut stream = network.receive_requests_from_all::<RequestBatchSet>();
+    let chain = Arc::clone(blockchain);
+    tokio::spawn(async move {
+        while let Some((request, _peer)) = stream.next().await {
+            let msg = &request.request;
+            let hash = match tampering {
+                Tampering::WrongBlock => chain.macro_head_hash(),
+                _ => msg.hash,
+            };
+            let mut block = chain.get_block(&hash, true).unwrap().unwrap_macro();
+            let mut history_len =
+                chain.get_num_extended_transactions(policy::epoch_at(block.header.block_number), None);
+            match tampering {
+                Tampering::Justification => block.justification.as_mut().unwrap().round += 1,
+                Tampering::TruncatedHistory => history_len -= 1,
+                Tampering::WrongBlock | Tampering::WithholdHistory => {}
+            }
+
+            let response = BatchSetInfo {
+                block,
+                history_len: history_len as u32,
+            };
+            let _ = request.respond(&response);
+        }
+    });
+
+    let mut stream = network.receive_requests_from_all::<RequestHistoryChunk>();
+    let chain = Arc::clone(blockchain);
+    tokio::spawn(async move {
+        while let Some((request, _peer)) = stream.next().await {
+            let msg = &request.request;
+            let mut chunk_size = chain.get_num_extended_transactions(msg.epoch_number, None);
+            if let Tampering::TruncatedHistory = tampering {
+                chunk_size -= 1;
+            }
+
+            let chunk = match tampering {
+                Tampering::WithholdHistory => None,
+                _ => chain.get_chunk(msg.epoch_number, chunk_size, msg.chunk_index as usize, None),
+            };
+
+            let response = HistoryChunk {
+                chunk,
+            };
+            let _ = request.respond(&response);
+        }
+    });
+}
+
+async fn malicious_peer_is_rejected(tampering: Tampering) {
+    let mut hub = MockHub::default();
+
+    // Setup the malicious peer.
+    let env1 = VolatileEnvironment::new(10).unwrap();
+    let blockchain1 = Arc::new(Blockchain::new(env1, NetworkId::UnitAlbatross).unwrap());
+    let mempool1 = Mempool::new(Arc::clone(&blockchain1), MempoolConfig::default());
+
+    let keypair =
+        KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new(Arc::clone(&blockchain1), mempool1, keypair);
+
+    // Produce one election block and one checkpoint block.
+    produce_macro_blocks(
+        (policy::BATCHES_PER_EPOCH + 1) as usize,
+        &producer,
+        &blockchain1,
+    );
+
+    let net1 = Arc::new(hub.new_network());
+    spawn_malicious_responder(&net1, &blockchain1, tampering);
+
+    // Setup the syncing peer.
+    let env2 = VolatileEnvironment::new(10).unwrap();
+    let blockchain2 = Arc::new(Blockchain::new(env2, NetworkId::UnitAlbatross).unwrap());
+
+    let net2 = Arc::new(hub.new_network());
+    let mut sync2 =
+        HistorySync::<MockNetwork>::new(Arc::clone(&blockchain2), net2.subscribe_events());
+
+    net1.dial_mock(&net2);
+    tokio::time::delay_for(Duration::from_secs(1)).await;
+
+    // The sync must not complete with the malicious peer.
+    let sync_result = tokio::time::timeout(Duration::from_secs(3), sync2.next()).await;
+    assert!(sync_result.is_err());
+
+    // Nothing may have been adopted from the malicious peer.
+    let genesis_hash = NetworkInfo::from_network_id(NetworkId::UnitAlbatross)
+        .genesis_hash()
+        .clone();
+    assert_eq!(blockchain2.election_head_hash(), genesis_hash);
+    assert_eq!(blockchain2.macro_head_hash(), genesis_hash);
+}
+
+#[tokio::test]
+async fn rejects_batch_set_with_invalid_justification() {
+    malicious_peer_is_rejected(Tampering::Justification).await;
+}
+
+#[tokio::test]
+async fn rejects_batch_set_for_wrong_block() {
+    malicious_peer_is_rejected(Tampering::WrongBlock).await;
+}
+
+#[tokio::test]
+async fn rejects_batch_set_with_truncated_history() {
+    malicious_peer_is_rejected(Tampering::TruncatedHistory).await;
+}
+
+#[tokio::test]
+async fn resumes_from_stored_history_chunks() {
+    let mut hub = MockHub::default();
+
+    // Setup a peer that serves batch sets, but no history.
+    let env1 = VolatileEnvironment::new(10).unwrap();
+    let blockchain1 = Arc::new(Blockchain::new(env1, NetworkId::UnitAlbatross).unwrap());
+    let mempool1 = Mempool::new(Arc::clone(&blockchain1), MempoolConfig::default());
+
+    let keypair =
+        KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new(Arc::clone(&blockchain1), mempool1, keypair);
+
+    // Produce one election block and one checkpoint block.
+    produce_macro_blocks(
+        (policy::BATCHES_PER_EPOCH + 1) as usize,
+        &producer,
+        &blockchain1,
+    );
+
+    let net1 = Arc::new(hub.new_network());
+    spawn_malicious_responder(&net1, &blockchain1, Tampering::WithholdHistory);
+
+    // Setup the syncing peer as if it had downloaded the history before a restart.
+    let env2 = VolatileEnvironment::new(10).unwrap();
+    let blockchain2 = Arc::new(Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let chunk_store = HistoryChunkStore::new(env2);
+    for block in &[blockchain1.election_head().clone(), blockchain1.macro_head().clone()] {
+        let epoch_number = policy::epoch_at(block.header.block_number);
+        let history = blockchain1
+            .get_chunk(epoch_number, CHUNK_SIZE, 0, None)
+            .unwrap()
+            .history;
+        chunk_store.put_chunk(epoch_number, 0, &block.hash(), &history);
+    }
+
+    let net2 = Arc::new(hub.new_network());
+    let mut sync2 =
+        HistorySync::<MockNetwork>::new(Arc::clone(&blockchain2), net2.subscribe_events());
+
+    net1.dial_mock(&net2);
+    tokio::time::delay_for(Duration::from_secs(1)).await;
+    let _ = tokio::time::timeout(Duration::from_secs(3), sync2.next()).await;
+
+    assert_eq!(
+        blockchain2.election_head_hash(),
+        blockchain1.election_head_hash(),
+    );
+    assert_eq!(blockchain2.macro_head_hash(), blockchain1.macro_head_hash());
+}
@@ -0,0 +1,63 @@
+//! Runs simulated aggregations with different Handel configurations and prints how long it takes until all honest
+//! nodes reach the threshold, and how many messages they send.
+//!
+//! Run with `cargo bench -p nimiq-handel`.
+
+#[macro_use]
+extern crate beserial_derive;
+
+use std::time::Duration;
+
+use nimiq_handel::config::Config;
+
+#[path = "../tests/simulator/mod.rs"]
+mod simulator;
+
+use simulator::Simulation;
+
+fn run(name: &str, simulation: Simulation) {
+    let mut runtime = tokio::runtime::Builder::new().basic_scheduler().enable_all().build().unwrap();
+    let report = runtime.block_on(simulation.run());
+    println!("{:<48} {}", name, report);
+}
+
+fn main() {
+    let num_nodes = 512;
+    let default = Config::default();
+
+    for &update_count in &[1, 2, 4] {
+        for &update_interval in &[50, 100, 200] {
+            let mut simulation = Simulation::new(num_nodes);
+            simulation.config = Config {
+                update_count,
+                update_interval: Duration::from_millis(update_interval),
+                ..default.clone()
+            };
+            run(&format!("update_count={} update_interval={}ms", update_count, update_interval), simulation);
+        }
+    }
+
+    for &timeout in &[250, 500, 1000] {
+        for &peer_count in &[1, 5, 10] {
+            let mut simulation = Simulation::new(num_nodes);
+            simulation.config = Config {
+                timeout: Duration::from_millis(timeout),
+                peer_count,
+                ..default.clone()
+            };
+            run(&format!("timeout={}ms peer_count={}", timeout, peer_count), simulation);
+        }
+    }
+
+    let mut simulation = Simulation::new(num_nodes);
+    simulation.packet_loss = 0.1;
+    run("packet_loss=10%", simulation);
+
+    let mut simulation = Simulation::new(num_nodes);
+    simulation.offline = (0..num_nodes).step_by(5).collect();
+    run("offline=20%", simulation);
+
+    let mut simulation = Simulation::new(num_nodes);
+    simulation.byzantine = (0..num_nodes).step_by(5).collect();
+    run("byzantine=20%", simulation);
+}
@@ -8,6 +8,8 @@ use futures::future::BoxFuture;
 use futures::stream::BoxStream;
 use futures::task::{Context, Poll};
 use futures::{ready, select, FutureExt, Sink, Stream, StreamExt};
+use rand::rngs::StdRng;
+use rand::{thread_rng, SeedableRng};
 
 use tokio::task::JoinHandle;
 use tokio::time::{interval_at, Instant, Interval};
@@ -73,7 +75,10 @@ impl<P: Protocol, T: Clone + Debug + Eq + Serialize + Deserialize + Sized + Send
         sender: UnboundedSender<(LevelUpdateMessage<P::Contribution, T>, usize)>,
     ) -> Self {
         // invoke the partitioner to create the level structure of peers.
-        let levels: Vec<Level> = Level::create_levels(protocol.partitioner());
+        let levels: Vec<Level> = match config.rng_seed {
+            Some(seed) => Level::create_levels(protocol.partitioner(), &mut StdRng::seed_from_u64(seed)),
+            None => Level::create_levels(protocol.partitioner(), &mut thread_rng()),
+        };
 
         // Create an empty todo list  which can later be polled for the best available todo.
         let todos = Box::pin(TodoList::new(protocol.evaluator(), input_stream));
@@ -13,6 +13,12 @@ pub struct Config {
 
     /// How many peers are contacted at each level
     pub peer_count: usize,
+
+    /// Constants used by the evaluators to score contributions
+    pub scoring: ScoringConfig,
+
+    /// Seed for the order in which the peers of each level are contacted. The order is random if this is `None`.
+    pub rng_seed: Option<u64>,
 }
 
 impl Default for Config {
@@ -22,6 +28,44 @@ impl Default for Config {
             update_interval: Duration::from_millis(100),
             timeout: Duration::from_millis(500),
             peer_count: 10,
+            scoring: ScoringConfig::default(),
+            rng_seed: None,
+        }
+    }
+}
+
+/// Constants used to score contributions. A contribution that completes its level always scores higher than one that
+/// only adds to the best contribution of its level. Among those, contributions for lower levels score higher.
+#[derive(Clone, Debug)]
+pub struct ScoringConfig {
+    /// Base score of a contribution that completes its level
+    pub completing_score: usize,
+
+    /// Score subtracted per level from a contribution that completes its level
+    pub completing_level_penalty: usize,
+
+    /// Base score of a contribution that adds to the best contribution of its level
+    pub adding_score: usize,
+
+    /// Score subtracted per level from a contribution that adds to the best contribution of its level
+    pub adding_level_penalty: usize,
+
+    /// Score added per weight that a contribution adds to the best contribution of its level
+    pub added_weight_reward: usize,
+
+    /// Score subtracted per weight of the individual contributions that we have to combine with a contribution
+    pub combined_weight_penalty: usize,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig {
+            completing_score: 1_000_000,
+            completing_level_penalty: 10,
+            adding_score: 100_000,
+            adding_level_penalty: 100,
+            added_weight_reward: 10,
+            combined_weight_penalty: 1,
         }
     }
 }
@@ -2,6 +2,9 @@ use std::sync::Arc;
 
 use parking_lot::RwLock;
 
+use collections::bitset::BitSet;
+
+use crate::config::ScoringConfig;
 use crate::contribution::AggregatableContribution;
 use crate::identity::WeightRegistry;
 use crate::partitioner::Partitioner;
@@ -14,30 +17,28 @@ pub trait Evaluator<C: AggregatableContribution>: Send + Sync {
 }
 
 /// Every signature counts as a single vote
+#[derive(Debug)]
 pub struct SingleVote<S: ContributionStore, P: Partitioner> {
-    signature_store: Arc<S>,
+    store: Arc<RwLock<S>>,
     partitioner: Arc<P>,
-    threshold: usize,
+    pub threshold: usize,
+    scoring: ScoringConfig,
 }
 
 impl<S: ContributionStore, P: Partitioner> SingleVote<S, P> {
-    pub fn new(signature_store: Arc<S>, partitioner: Arc<P>, threshold: usize) -> Self {
+    pub fn new(store: Arc<RwLock<S>>, partitioner: Arc<P>, threshold: usize, scoring: ScoringConfig) -> Self {
         Self {
-            signature_store,
+            store,
             partitioner,
             threshold,
+            scoring,
         }
     }
 }
 
 impl<C: AggregatableContribution, S: ContributionStore<Contribution = C>, P: Partitioner> Evaluator<C> for SingleVote<S, P> {
-    fn evaluate(&self, _contribution: &C, _level: usize) -> usize {
-        // This is going to be used here, and we don't want any warnings
-        let _ = (&self.signature_store, &self.partitioner);
-
-        // TODO: The code from `WeightedVote` is what actually belongs here. And then the code in
-        // `WeightedVote` must be adapted to consider the weight of a signature.
-        unimplemented!();
+    fn evaluate(&self, contribution: &C, level: usize) -> usize {
+        score(&*self.store.read(), &*self.partitioner, &self.scoring, contribution, level, BitSet::len)
     }
 
     fn is_final(&self, contribution: &C) -> bool {
@@ -58,134 +59,303 @@ pub struct WeightedVote<S: ContributionStore, I: WeightRegistry, P: Partitioner>
     pub weights: Arc<I>,
     partitioner: Arc<P>,
     pub threshold: usize,
+    scoring: ScoringConfig,
 }
 
 impl<S: ContributionStore, I: WeightRegistry, P: Partitioner> WeightedVote<S, I, P> {
-    pub fn new(store: Arc<RwLock<S>>, weights: Arc<I>, partitioner: Arc<P>, threshold: usize) -> Self {
+    pub fn new(store: Arc<RwLock<S>>, weights: Arc<I>, partitioner: Arc<P>, threshold: usize, scoring: ScoringConfig) -> Self {
         Self {
             store,
             weights,
             partitioner,
             threshold,
+            scoring,
         }
     }
 }
 
 impl<C: AggregatableContribution, S: ContributionStore<Contribution = C>, I: WeightRegistry, P: Partitioner> Evaluator<C> for WeightedVote<S, I, P> {
-    /// takes an unverified contribution and scroes it in terms of usefulness with
-    ///
-    /// `0` being not useful at all, can be discarded.
-    ///
-    /// `>0` being more useful the bigger the number.
     fn evaluate(&self, contribution: &C, level: usize) -> usize {
-        // TODO: Consider weight
-        //let weight = self.weights.signature_weight(&signature)
-        //    .unwrap_or_else(|| panic!("No weight for signature: {:?}", signature));
-
-        let store = self.store.read();
-
-        // check if we already know this individual signature
-        if contribution.num_contributors() == 1 && store.individual_signature(level, contribution.contributor()).is_some() {
-            // If we already know it for this level, score it as 0
-            trace!(
-                "Individual contribution from peer {} for level {} already known",
-                level,
-                contribution.contributor(),
-            );
+        // Signers without a weight can't be verified, so they don't add anything.
+        let weight = |signers: &BitSet| self.weights.signers_weight(signers).unwrap_or(0);
+        score(&*self.store.read(), &*self.partitioner, &self.scoring, contribution, level, weight)
+    }
+
+    fn is_final(&self, signature: &C) -> bool {
+        let votes = self
+            .weights
+            .signature_weight(signature)
+            .unwrap_or_else(|| panic!("Missing weights for signature: {:?}", signature));
+
+        trace!("is_final(): votes={}, final={}", votes, votes >= self.threshold);
+        votes >= self.threshold
+    }
+
+    fn level_contains_id(&self, level: usize, id: usize) -> bool {
+        self.partitioner.range(level).unwrap().contains(&id)
+    }
+}
+
+/// Takes an unverified contribution and scores it in terms of usefulness with
+///
+/// `0` being not useful at all, can be discarded.
+///
+/// `>0` being more useful the bigger the number.
+///
+/// `weight` returns the weight of a set of signers. Whether a level is complete only depends on the number of signers.
+fn score<C, S, P, W>(store: &S, partitioner: &P, scoring: &ScoringConfig, contribution: &C, level: usize, weight: W) -> usize
+where
+    C: AggregatableContribution,
+    S: ContributionStore<Contribution = C>,
+    P: Partitioner,
+    W: Fn(&BitSet) -> usize,
+{
+    // check if we already know this individual signature
+    if contribution.num_contributors() == 1 && store.individual_signature(level, contribution.contributor()).is_some() {
+        // If we already know it for this level, score it as 0
+        trace!(
+            "Individual contribution from peer {} for level {} already known",
+            contribution.contributor(),
+            level,
+        );
+        return 0;
+    }
+
+    // number of identities at `level`, sort of maximum receivable contributions
+    let to_receive = partitioner.level_size(level);
+    let best_contribution = store.best(level);
+
+    if let Some(best_contribution) = best_contribution {
+        trace!("level = {}", level);
+        trace!("contribution = {:#?}", contribution);
+        trace!("best_contribution = {:#?}", best_contribution);
+
+        // check if the best signature for that level is already complete
+        if to_receive == best_contribution.num_contributors() {
+            trace!("Best contribution already complete");
+            return 0;
+        }
+
+        // check if the best signature is better than the new one
+        if best_contribution.contributors().is_superset(&contribution.contributors()) {
+            trace!("Best signature is better");
             return 0;
         }
+    }
 
-        // number of identities at `level`, sort of maximum receivable contributions
-        let to_receive = self.partitioner.level_size(level);
-        let best_contribution = store.best(level);
+    // the signers of the signature
+    // NOTE: We compute the full `BitSet` (also for individual signatures), since we need it in
+    // a few places here
+    let signers = if contribution.num_contributors() == 1 {
+        let mut individuals = store.individual_verified(level).clone();
+        individuals.insert(contribution.contributor());
+        individuals
+    } else {
+        contribution.contributors()
+    };
 
-        if let Some(best_contribution) = best_contribution {
-            trace!("level = {}", level);
-            trace!("contribution = {:#?}", contribution);
-            trace!("best_contribution = {:#?}", best_contribution);
+    // compute bitset of signers combined with all (verified) individual signatures that we have
+    let with_individuals = &signers | store.individual_verified(level);
 
-            // check if the best signature for that level is already complete
-            if to_receive == best_contribution.num_contributors() {
-                trace!("Best contribution already complete");
-                return 0;
-            }
+    // ---------------------------------------------
 
-            // check if the best signature is better than the new one
-            if best_contribution.contributors().is_superset(&contribution.contributors()) {
-                trace!("Best signature is better");
-                return 0;
-            }
+    // The signers of the best contribution we can build with this one, the weight it adds to the best contribution
+    // of the level, and the weight of the individual signatures we have to combine with it.
+    let (new_signers, added_weight, combined_weight) = if let Some(best_signature) = best_contribution {
+        let best_signers = best_signature.contributors();
+        let best_weight = weight(&best_signers);
+        if signers.intersection_size(&best_signers) > 0 {
+            // can't merge
+            let new_weight = weight(&with_individuals);
+            let combined_weight = new_weight.saturating_sub(weight(&signers));
+            (with_individuals, new_weight.saturating_sub(best_weight), combined_weight)
+        } else {
+            let final_sig = &with_individuals | &best_signers;
+            let new_weight = weight(&final_sig);
+            let combined_weight = weight(&(&final_sig ^ &(&best_signers | &signers)));
+            (final_sig, new_weight.saturating_sub(best_weight), combined_weight)
         }
+    } else {
+        // best is the new signature with the individual signatures
+        let new_weight = weight(&with_individuals);
+        let combined_weight = new_weight.saturating_sub(weight(&signers));
+        (with_individuals, new_weight, combined_weight)
+    };
+
+    trace!(
+        "new_total={}, added_weight={}, combined_weight={}",
+        new_signers.len(),
+        added_weight,
+        combined_weight
+    );
 
-        // the signers of the signature
-        // NOTE: This is a little bit more efficient than `signature.signers().collect()`, since
-        // `signers()` returns a boxed iterator.
-        // NOTE: We compute the full `BitSet` (also for individual signatures), since we need it in
-        // a few places here
-        let signers = if contribution.num_contributors() == 1 {
-            let mut individuals = store.individual_verified(level).clone();
-            individuals.insert(contribution.contributor());
-            individuals
+    // compute score
+    if added_weight == 0 {
+        // return signature_weight for an individual signature, otherwise 0
+        if contribution.num_contributors() == 1 {
+            weight(&contribution.contributors())
         } else {
-            contribution.contributors()
-        };
+            0
+        }
+    } else if new_signers.len() == to_receive {
+        scoring
+            .completing_score
+            .saturating_sub(level * scoring.completing_level_penalty)
+            .saturating_sub(combined_weight * scoring.combined_weight_penalty)
+    } else {
+        (scoring.adding_score + added_weight * scoring.added_weight_reward)
+            .saturating_sub(level * scoring.adding_level_penalty)
+            .saturating_sub(combined_weight * scoring.combined_weight_penalty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use beserial::{Deserialize, Serialize};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
-        // compute bitset of signers combined with all (verified) individual signatures that we have
-        let with_individuals = &signers | store.individual_verified(level);
-
-        // ---------------------------------------------
-
-        let (new_total, added_sigs, combined_sigs) = if let Some(best_signature) = best_contribution {
-            // TODO weights!
-            if signers.intersection_size(&best_signature.contributors()) > 0 {
-                // can't merge
-                let new_total = with_individuals.len();
-                (
-                    new_total,
-                    new_total.saturating_sub(best_signature.num_contributors()),
-                    new_total - signers.len(),
-                )
+    use crate::contribution::ContributionError;
+    use crate::partitioner::BinomialPartitioner;
+    use crate::store::ReplaceStore;
+
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    struct TestContribution {
+        contributors: BitSet,
+    }
+
+    impl AggregatableContribution for TestContribution {
+        fn contributors(&self) -> BitSet {
+            self.contributors.clone()
+        }
+
+        fn combine(&mut self, other_contribution: &Self) -> Result<(), ContributionError> {
+            let overlap = &self.contributors & &other_contribution.contributors;
+            if overlap.is_empty() {
+                self.contributors = &self.contributors | &other_contribution.contributors;
+                Ok(())
             } else {
-                let final_sig = &with_individuals | &best_signature.contributors();
-                let new_total = final_sig.len();
-                let combined_sigs = (final_sig ^ (&best_signature.contributors() | &signers)).len();
-                (new_total, new_total - best_signature.num_contributors(), combined_sigs)
+                Err(ContributionError::Overlapping(overlap))
             }
+        }
+    }
+
+    struct TestWeights(Vec<usize>);
+
+    impl WeightRegistry for TestWeights {
+        fn weight(&self, id: usize) -> Option<usize> {
+            self.0.get(id).copied()
+        }
+    }
+
+    type TestStore = ReplaceStore<BinomialPartitioner, TestContribution>;
+
+    /// Returns a random contribution for `level`, either an individual or an aggregated one.
+    fn random_contribution(rng: &mut StdRng, partitioner: &BinomialPartitioner, level: usize) -> Option<TestContribution> {
+        let ids: Vec<usize> = partitioner.range(level).unwrap().filter(|&id| id < partitioner.size()).collect();
+        if ids.is_empty() {
+            return None;
+        }
+
+        let contributors: BitSet = if rng.gen_bool(0.5) {
+            std::iter::once(ids[rng.gen_range(0, ids.len())]).collect()
         } else {
-            // best is the new signature with the individual signatures
-            let new_total = with_individuals.len();
-            (new_total, new_total, new_total - signers.len())
+            ids.iter().copied().filter(|_| rng.gen_bool(0.5)).collect()
         };
+        if contributors.is_empty() {
+            return None;
+        }
+        Some(TestContribution { contributors })
+    }
 
-        trace!("new_total={}, added_sigs={}, combined_sigs={}", new_total, added_sigs, combined_sigs);
+    /// Returns a partitioner with a random number of identities and a store with random contributions.
+    fn random_store(rng: &mut StdRng) -> (Arc<BinomialPartitioner>, Arc<RwLock<TestStore>>) {
+        let num_ids = rng.gen_range(2, 64);
+        let partitioner = Arc::new(BinomialPartitioner::new(rng.gen_range(0, num_ids), num_ids));
+        let store = Arc::new(RwLock::new(TestStore::new(Arc::clone(&partitioner))));
 
-        // compute score
-        // TODO: Remove magic numbers! What do they mean? I don't think this is discussed in the paper.
-        if added_sigs == 0 {
-            // return signature_weight for an individual signature, otherwise 0
-            if contribution.num_contributors() == 1 {
-                self.weights.signature_weight(contribution).unwrap_or(0)
-            } else {
-                0
+        for _ in 0..rng.gen_range(0, 32) {
+            let level = rng.gen_range(1, partitioner.levels());
+            if let Some(contribution) = random_contribution(rng, &partitioner, level) {
+                store.write().put(contribution, level);
             }
-        } else if new_total == to_receive {
-            1_000_000 - level * 10 - combined_sigs
-        } else {
-            100_000 - level * 100 + added_sigs * 10 - combined_sigs
         }
+
+        (partitioner, store)
     }
 
-    fn is_final(&self, signature: &C) -> bool {
-        let votes = self
-            .weights
-            .signature_weight(signature)
-            .unwrap_or_else(|| panic!("Missing weights for signature: {:?}", signature));
+    /// Evaluates random contributions against random stores with `SingleVote` and a `WeightedVote` that uses
+    /// `weight` for every identity, and calls `check` with the contribution and both scores.
+    fn compare_evaluators<F: Fn(&mut StdRng) -> usize>(weight: F, check: impl Fn(&TestContribution, usize, usize)) {
+        let mut rng = StdRng::seed_from_u64(0x4e51);
 
-        trace!("is_final(): votes={}, final={}", votes, votes >= self.threshold);
-        votes >= self.threshold
+        for _ in 0..200 {
+            let (partitioner, store) = random_store(&mut rng);
+            let weights = Arc::new(TestWeights((0..partitioner.size()).map(|_| weight(&mut rng)).collect()));
+            let threshold = rng.gen_range(1, partitioner.size() + 1);
+
+            let single = SingleVote::new(Arc::clone(&store), Arc::clone(&partitioner), threshold, ScoringConfig::default());
+            let weighted = WeightedVote::new(
+                Arc::clone(&store),
+                weights,
+                Arc::clone(&partitioner),
+                threshold,
+                ScoringConfig::default(),
+            );
+
+            for _ in 0..20 {
+                let level = rng.gen_range(1, partitioner.levels());
+                if let Some(contribution) = random_contribution(&mut rng, &partitioner, level) {
+                    check(&contribution, single.evaluate(&contribution, level), weighted.evaluate(&contribution, level));
+                    if weighted.weights.0.iter().all(|&weight| weight == 1) {
+                        assert_eq!(single.is_final(&contribution), weighted.is_final(&contribution));
+                    }
+                }
+            }
+        }
     }
 
-    fn level_contains_id(&self, level: usize, id: usize) -> bool {
-        self.partitioner.range(level).unwrap().contains(&id)
+    #[test]
+    fn it_scores_like_single_vote_with_unit_weights() {
+        compare_evaluators(
+            |_| 1,
+            |contribution, single, weighted| assert_eq!(single, weighted, "Scores differ for {:?}", contribution),
+        );
+    }
+
+    #[test]
+    fn it_discards_the_same_contributions_with_uniform_weights() {
+        compare_evaluators(
+            |_| 7,
+            |contribution, single, weighted| {
+                assert_eq!(single == 0, weighted == 0, "Only one evaluator discards {:?}", contribution)
+            },
+        );
+    }
+
+    #[test]
+    fn it_prefers_heavier_contributions() {
+        let partitioner = Arc::new(BinomialPartitioner::new(0, 8));
+        let store = Arc::new(RwLock::new(TestStore::new(Arc::clone(&partitioner))));
+        let weights = Arc::new(TestWeights(vec![1, 1, 1, 1, 1, 5, 1, 1]));
+
+        let single = SingleVote::new(Arc::clone(&store), Arc::clone(&partitioner), 6, ScoringConfig::default());
+        let weighted = WeightedVote::new(Arc::clone(&store), weights, Arc::clone(&partitioner), 6, ScoringConfig::default());
+
+        let light = TestContribution {
+            contributors: std::iter::once(4).collect(),
+        };
+        let heavy = TestContribution {
+            contributors: std::iter::once(5).collect(),
+        };
+
+        assert_eq!(single.evaluate(&light, 3), single.evaluate(&heavy, 3));
+        assert!(weighted.evaluate(&heavy, 3) > weighted.evaluate(&light, 3));
+        assert!(!weighted.is_final(&light));
+        assert!(weighted.is_final(&TestContribution {
+            contributors: &light.contributors | &heavy.contributors,
+        }));
     }
 }
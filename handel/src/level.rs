@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use parking_lot::RwLock;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 
 use crate::contribution::AggregatableContribution;
 use crate::partitioner::{Partitioner, PartitioningError};
@@ -45,17 +45,17 @@ impl Level {
         self.peer_ids.len()
     }
 
-    pub fn create_levels<P: Partitioner>(partitioner: Arc<P>) -> Vec<Level> {
+    /// Creates the levels of `partitioner`. The peers of each level are shuffled with `rng`.
+    pub fn create_levels<P: Partitioner, R: Rng + ?Sized>(partitioner: Arc<P>, rng: &mut R) -> Vec<Level> {
         let mut levels: Vec<Level> = Vec::new();
         let mut first_active = false;
         let mut send_expected_full_size: usize = 1;
-        let mut rng = thread_rng();
 
         for i in 0..partitioner.levels() {
             match partitioner.range(i) {
                 Ok(ids) => {
                     let mut ids = ids.collect::<Vec<usize>>();
-                    ids.shuffle(&mut rng);
+                    ids.shuffle(rng);
 
                     let size = ids.len();
                     trace!("Level {} peers: {:?}", i, ids);
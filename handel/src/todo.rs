@@ -1,6 +1,7 @@
 use core::pin::Pin;
 use core::task::{Context, Poll};
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use crate::contribution::AggregatableContribution;
@@ -35,10 +36,19 @@ impl<C: AggregatableContribution> PartialEq for TodoItem<C> {
 }
 
 impl<C: AggregatableContribution> Eq for TodoItem<C> {}
-impl<C: AggregatableContribution> std::hash::Hash for TodoItem<C> {
-    // TODO
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        std::hash::Hash::hash(&self.contribution.contributors().to_string(), state);
+
+// TodoItems are kept in a `BTreeSet`, such that equally scored items are always picked in the same order.
+impl<C: AggregatableContribution> Ord for TodoItem<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.level
+            .cmp(&other.level)
+            .then_with(|| self.contribution.contributors().iter().cmp(other.contribution.contributors().iter()))
+    }
+}
+
+impl<C: AggregatableContribution> PartialOrd for TodoItem<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -46,7 +56,7 @@ impl<C: AggregatableContribution> std::hash::Hash for TodoItem<C> {
 /// Will dry the input stream every time a TodoItem is polled.
 pub(crate) struct TodoList<C: AggregatableContribution, E: Evaluator<C>> {
     /// List of TodoItems already polled from input stream
-    list: HashSet<TodoItem<C>>,
+    list: BTreeSet<TodoItem<C>>,
     /// The evaluator used for scoring the individual todos
     evaluator: Arc<E>,
     /// The Stream where LevelUpdates can be polled from, which are subsequently converted into TodoItems
@@ -59,7 +69,7 @@ impl<C: AggregatableContribution, E: Evaluator<C>> TodoList<C, E> {
     /// * `input_stream` - Thestream on which new LevelUpdates can be polled, which will then be converted into TodoItems
     pub fn new(evaluator: Arc<E>, input_stream: BoxStream<'static, LevelUpdate<C>>) -> Self {
         Self {
-            list: HashSet::new(),
+            list: BTreeSet::new(),
             evaluator,
             input_stream,
         }
@@ -73,7 +83,7 @@ impl<C: AggregatableContribution, E: Evaluator<C>> Stream for TodoList<C, E> {
         // current best score
         let mut best_score: usize = 0;
         // retained set of todos. Same as self.list, but did not retain 0 score todos and the best todo.
-        let mut new_set: HashSet<TodoItem<C>> = HashSet::new();
+        let mut new_set: BTreeSet<TodoItem<C>> = BTreeSet::new();
         // the current best TodoItem
         let mut best_todo: Option<Self::Item> = None;
 
@@ -6,7 +6,7 @@ use beserial::{Deserialize, Serialize};
 use nimiq_bls::PublicKey;
 use nimiq_collections::bitset::BitSet;
 use nimiq_handel::aggregation::Aggregation;
-use nimiq_handel::config::Config;
+use nimiq_handel::config::{Config, ScoringConfig};
 use nimiq_handel::contribution::{AggregatableContribution, ContributionError};
 use nimiq_handel::evaluator;
 use nimiq_handel::identity;
@@ -107,6 +107,7 @@ impl Protocol {
             Arc::clone(&registry),
             partitioner.clone(),
             threshold,
+            ScoringConfig::default(),
         ));
 
         Protocol {
@@ -236,6 +237,8 @@ async fn it_can_aggregate() {
         update_interval: Duration::from_millis(500),
         timeout: Duration::from_millis(500),
         peer_count: 1,
+        scoring: ScoringConfig::default(),
+        rng_seed: None,
     };
 
     let mut hub = MockHub::default();
@@ -0,0 +1,84 @@
+#[macro_use]
+extern crate beserial_derive;
+
+use std::time::Duration;
+
+mod simulator;
+
+use simulator::Simulation;
+
+#[tokio::test]
+async fn it_reaches_the_threshold() {
+    let report = Simulation::new(32).run().await;
+
+    assert!(report.completion_time().is_some(), "Not all nodes reached the threshold: {}", report);
+    assert_eq!(report.messages_lost, 0);
+    assert_eq!(report.forged_rejected, 0);
+}
+
+#[tokio::test]
+async fn it_reaches_the_threshold_despite_packet_loss() {
+    let mut simulation = Simulation::new(32);
+    simulation.packet_loss = 0.2;
+
+    let report = simulation.run().await;
+
+    assert!(report.completion_time().is_some(), "Not all nodes reached the threshold: {}", report);
+    assert!(report.messages_lost > 0);
+}
+
+#[tokio::test]
+async fn it_reaches_the_threshold_with_offline_nodes() {
+    let mut simulation = Simulation::new(32);
+    simulation.offline = vec![3, 10, 17, 24, 31];
+
+    let report = simulation.run().await;
+
+    assert!(report.completion_time().is_some(), "Not all nodes reached the threshold: {}", report);
+    assert_eq!(report.completed(), 27);
+    assert!(report.completion_times.iter().enumerate().all(|(id, time)| time.is_none() == simulation.offline.contains(&id)));
+}
+
+#[tokio::test]
+async fn it_rejects_forged_contributions() {
+    let mut simulation = Simulation::new(32);
+    simulation.byzantine = vec![0, 7, 14, 21, 28];
+
+    let report = simulation.run().await;
+
+    assert!(report.completion_time().is_some(), "Not all nodes reached the threshold: {}", report);
+    assert!(report.forged_rejected > 0);
+    assert!(simulation.byzantine.iter().all(|&id| report.completion_times[id].is_none()));
+}
+
+#[tokio::test]
+async fn it_gives_up_without_enough_nodes() {
+    let mut simulation = Simulation::new(9);
+    simulation.offline = vec![0, 1, 2, 3];
+    simulation.max_duration = Duration::from_secs(10);
+
+    let report = simulation.run().await;
+
+    assert_eq!(report.completed(), 0);
+    assert_eq!(report.completion_time(), None);
+    assert!(report.duration >= simulation.max_duration);
+}
+
+#[test]
+fn it_is_deterministic() {
+    let mut simulation = Simulation::new(32);
+    simulation.packet_loss = 0.1;
+    simulation.byzantine = vec![5];
+    simulation.seed = 42;
+
+    let run = || {
+        let mut runtime = tokio::runtime::Builder::new().basic_scheduler().enable_all().build().unwrap();
+        runtime.block_on(simulation.run())
+    };
+    let (first, second) = (run(), run());
+
+    assert_eq!(first.completion_times, second.completion_times);
+    assert_eq!(first.messages_sent, second.messages_sent);
+    assert_eq!(first.messages_lost, second.messages_lost);
+    assert_eq!(first.forged_rejected, second.forged_rejected);
+}
@@ -0,0 +1,492 @@
+//! Deterministic simulation of Handel aggregations on a mock network.
+//!
+//! All nodes run on the current tokio runtime, whose clock is paused, such that timers and message latencies advance
+//! virtual time only. Latencies, packet loss and the peer order of every node are derived from a seed.
+//!
+//! This module is shared by the `simulation` tests and benchmark.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::channel::mpsc::unbounded;
+use futures::stream::StreamExt;
+use parking_lot::{Mutex, RwLock};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::time::{self, Instant};
+
+use beserial::{Deserialize, Serialize};
+use nimiq_bls::PublicKey;
+use nimiq_collections::bitset::BitSet;
+use nimiq_handel::aggregation::Aggregation;
+use nimiq_handel::config::Config;
+use nimiq_handel::contribution::{AggregatableContribution, ContributionError};
+use nimiq_handel::evaluator::SingleVote;
+use nimiq_handel::identity::IdentityRegistry;
+use nimiq_handel::partitioner::{BinomialPartitioner, Partitioner};
+use nimiq_handel::protocol;
+use nimiq_handel::store::ReplaceStore;
+use nimiq_handel::update::{LevelUpdate, LevelUpdateMessage};
+use nimiq_handel::verifier::{VerificationResult, Verifier};
+use nimiq_network_interface::network::Network;
+use nimiq_network_interface::peer::Peer;
+use nimiq_network_mock::{MockHub, MockNetwork, MockPeerId};
+
+/// Tag of the simulated aggregation.
+const TAG: u8 = 1;
+
+/// Returns the secret of node `id`. A contribution is valid if its sum is the sum of the secrets of its contributors.
+fn secret(id: usize) -> u64 {
+    (id as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+/// Contribution that stands in for a multi-signature. Its sum is the "signature" that the verifier checks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimContribution {
+    sum: u64,
+    contributors: BitSet,
+}
+
+impl SimContribution {
+    /// Returns the valid contribution of node `id`.
+    pub fn new(id: usize) -> Self {
+        Self::forged(std::iter::once(id).collect(), secret(id))
+    }
+
+    /// Returns a contribution that claims `contributors`, with an arbitrary `sum`.
+    pub fn forged(contributors: BitSet, sum: u64) -> Self {
+        Self { sum, contributors }
+    }
+
+    fn expected_sum(&self) -> u64 {
+        self.contributors.iter().map(secret).fold(0, u64::wrapping_add)
+    }
+}
+
+impl AggregatableContribution for SimContribution {
+    fn contributors(&self) -> BitSet {
+        self.contributors.clone()
+    }
+
+    fn combine(&mut self, other_contribution: &Self) -> Result<(), ContributionError> {
+        let overlap = &self.contributors & &other_contribution.contributors;
+        if overlap.is_empty() {
+            self.sum = self.sum.wrapping_add(other_contribution.sum);
+            self.contributors = &self.contributors | &other_contribution.contributors;
+            Ok(())
+        } else {
+            Err(ContributionError::Overlapping(overlap))
+        }
+    }
+}
+
+/// Verifies the sums of contributions and counts the forged ones.
+pub struct SimVerifier {
+    num_nodes: usize,
+    forged: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Verifier for SimVerifier {
+    type Contribution = SimContribution;
+
+    async fn verify(&self, contribution: &Self::Contribution) -> VerificationResult {
+        if let Some(signer) = contribution.contributors.iter().find(|&id| id >= self.num_nodes) {
+            return VerificationResult::UnknownSigner { signer };
+        }
+        if contribution.sum != contribution.expected_sum() {
+            self.forged.fetch_add(1, Ordering::SeqCst);
+            return VerificationResult::Forged;
+        }
+        VerificationResult::Ok
+    }
+}
+
+pub struct SimRegistry;
+
+impl IdentityRegistry for SimRegistry {
+    fn public_key(&self, _id: usize) -> Option<PublicKey> {
+        None
+    }
+}
+
+pub type SimStore = ReplaceStore<BinomialPartitioner, SimContribution>;
+
+pub struct SimProtocol {
+    verifier: Arc<SimVerifier>,
+    partitioner: Arc<BinomialPartitioner>,
+    evaluator: Arc<SingleVote<SimStore, BinomialPartitioner>>,
+    store: Arc<RwLock<SimStore>>,
+    registry: Arc<SimRegistry>,
+    node_id: usize,
+}
+
+impl SimProtocol {
+    fn new(node_id: usize, num_nodes: usize, threshold: usize, config: &Config, forged: Arc<AtomicUsize>) -> Self {
+        let partitioner = Arc::new(BinomialPartitioner::new(node_id, num_nodes));
+        let store = Arc::new(RwLock::new(SimStore::new(Arc::clone(&partitioner))));
+        let evaluator = Arc::new(SingleVote::new(
+            Arc::clone(&store),
+            Arc::clone(&partitioner),
+            threshold,
+            config.scoring.clone(),
+        ));
+
+        Self {
+            verifier: Arc::new(SimVerifier { num_nodes, forged }),
+            partitioner,
+            evaluator,
+            store,
+            registry: Arc::new(SimRegistry),
+            node_id,
+        }
+    }
+}
+
+impl fmt::Debug for SimProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SimProtocol {{ node_id: {} }}", self.node_id)
+    }
+}
+
+impl protocol::Protocol for SimProtocol {
+    type Contribution = SimContribution;
+    type Registry = SimRegistry;
+    type Verifier = SimVerifier;
+    type Store = SimStore;
+    type Evaluator = SingleVote<SimStore, BinomialPartitioner>;
+    type Partitioner = BinomialPartitioner;
+
+    fn registry(&self) -> Arc<Self::Registry> {
+        Arc::clone(&self.registry)
+    }
+    fn verifier(&self) -> Arc<Self::Verifier> {
+        Arc::clone(&self.verifier)
+    }
+    fn store(&self) -> Arc<RwLock<Self::Store>> {
+        Arc::clone(&self.store)
+    }
+    fn evaluator(&self) -> Arc<Self::Evaluator> {
+        Arc::clone(&self.evaluator)
+    }
+    fn partitioner(&self) -> Arc<Self::Partitioner> {
+        Arc::clone(&self.partitioner)
+    }
+    fn node_id(&self) -> usize {
+        self.node_id
+    }
+}
+
+type SimMessage = LevelUpdateMessage<SimContribution, u8>;
+
+/// Role of a node in the simulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Aggregates like it should.
+    Honest,
+    /// Doesn't take part at all. Messages to it are lost.
+    Offline,
+    /// Doesn't contribute, but keeps sending forged contributions to its peers.
+    Byzantine,
+}
+
+/// Parameters of a simulated aggregation.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// Number of nodes, including offline and byzantine ones.
+    pub num_nodes: usize,
+
+    /// Number of contributors that an aggregate needs to be final.
+    pub threshold: usize,
+
+    /// Handel configuration of all nodes.
+    pub config: Config,
+
+    /// Minimum latency of a message.
+    pub latency: Duration,
+
+    /// Maximum random latency that is added to `latency`.
+    pub jitter: Duration,
+
+    /// Probability that a message is lost.
+    pub packet_loss: f64,
+
+    /// IDs of the nodes that are offline.
+    pub offline: Vec<usize>,
+
+    /// IDs of the nodes that are byzantine.
+    pub byzantine: Vec<usize>,
+
+    /// Seed for latencies, packet loss and the peer order of the nodes.
+    pub seed: u64,
+
+    /// The simulation stops after this much virtual time, even if not all honest nodes reached the threshold.
+    pub max_duration: Duration,
+}
+
+impl Simulation {
+    /// Creates a simulation of `num_nodes` honest nodes that need two thirds of the contributions, with the default
+    /// configuration and a latency of 50ms to 100ms.
+    pub fn new(num_nodes: usize) -> Self {
+        Self {
+            num_nodes,
+            threshold: (2 * num_nodes + 2) / 3,
+            config: Config::default(),
+            latency: Duration::from_millis(50),
+            jitter: Duration::from_millis(50),
+            packet_loss: 0.0,
+            offline: vec![],
+            byzantine: vec![],
+            seed: 0,
+            max_duration: Duration::from_secs(60),
+        }
+    }
+
+    pub fn role(&self, id: usize) -> Role {
+        if self.offline.contains(&id) {
+            Role::Offline
+        } else if self.byzantine.contains(&id) {
+            Role::Byzantine
+        } else {
+            Role::Honest
+        }
+    }
+
+    /// Runs the simulation on the current runtime and pauses its clock. The runtime must use the basic scheduler, and
+    /// its clock must not be paused yet.
+    pub async fn run(&self) -> SimulationReport {
+        time::pause();
+        let start = Instant::now();
+
+        let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(self.seed)));
+        let stats = Arc::new(Stats::default());
+        let forged = Arc::new(AtomicUsize::new(0));
+        let completion_times: Arc<Mutex<Vec<Option<Duration>>>> = Arc::new(Mutex::new(vec![None; self.num_nodes]));
+
+        // Offline nodes don't have a network, so nothing can be delivered to them.
+        let mut hub = MockHub::default();
+        let mut networks: Vec<Option<Arc<MockNetwork>>> = Vec::with_capacity(self.num_nodes);
+        for id in 0..self.num_nodes {
+            let network = match self.role(id) {
+                Role::Offline => None,
+                _ => Some(Arc::new(hub.new_network_with_address(id as u64))),
+            };
+            if let Some(network) = &network {
+                for other in networks.iter().flatten() {
+                    network.dial_mock(other);
+                }
+            }
+            networks.push(network);
+        }
+
+        for (id, network) in networks.iter().enumerate() {
+            let network = match network {
+                Some(network) => network,
+                None => continue,
+            };
+            let sender = self.spawn_relay(network, Arc::clone(&rng), Arc::clone(&stats));
+
+            match self.role(id) {
+                Role::Honest => {
+                    let config = Config {
+                        rng_seed: Some(self.seed ^ id as u64),
+                        ..self.config.clone()
+                    };
+                    let protocol = SimProtocol::new(id, self.num_nodes, self.threshold, &config, Arc::clone(&forged));
+                    let mut aggregation = Aggregation::new(
+                        protocol,
+                        TAG,
+                        config,
+                        SimContribution::new(id),
+                        network.receive_from_all::<SimMessage>().map(|msg| msg.0.update).boxed(),
+                        Box::new(sender),
+                    );
+
+                    let threshold = self.threshold;
+                    let completion_times = Arc::clone(&completion_times);
+                    tokio::spawn(async move {
+                        while let Some(aggregate) = aggregation.next().await {
+                            if aggregate.num_contributors() >= threshold {
+                                completion_times.lock()[id].get_or_insert(start.elapsed());
+                            }
+                        }
+                    });
+                }
+                Role::Byzantine => {
+                    let partitioner = BinomialPartitioner::new(id, self.num_nodes);
+                    let interval = self.config.update_interval;
+                    tokio::spawn(async move {
+                        let mut interval = time::interval(interval);
+                        loop {
+                            interval.tick().await;
+                            for (message, recipient) in forged_updates(id, &partitioner) {
+                                if sender.unbounded_send((message, recipient)).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                    });
+                }
+                Role::Offline => unreachable!(),
+            }
+        }
+
+        // Advance the virtual time until all honest nodes reached the threshold.
+        let honest = (0..self.num_nodes).filter(|&id| self.role(id) == Role::Honest).count();
+        while start.elapsed() < self.max_duration {
+            if completion_times.lock().iter().flatten().count() == honest {
+                break;
+            }
+            time::delay_for(Duration::from_millis(10)).await;
+        }
+
+        let completion_times = completion_times.lock().clone();
+        SimulationReport {
+            completion_times,
+            honest,
+            messages_sent: stats.sent.load(Ordering::SeqCst),
+            messages_lost: stats.lost.load(Ordering::SeqCst),
+            forged_rejected: forged.load(Ordering::SeqCst),
+            duration: start.elapsed(),
+        }
+    }
+
+    /// Spawns a task that delivers the messages of the returned sender to their recipients, with the latency and
+    /// packet loss of the simulation.
+    fn spawn_relay(
+        &self,
+        network: &Arc<MockNetwork>,
+        rng: Arc<Mutex<StdRng>>,
+        stats: Arc<Stats>,
+    ) -> futures::channel::mpsc::UnboundedSender<(SimMessage, usize)> {
+        let (sender, mut receiver) = unbounded::<(SimMessage, usize)>();
+        let network = Arc::clone(network);
+        let (latency, jitter, packet_loss) = (self.latency, self.jitter, self.packet_loss);
+
+        tokio::spawn(async move {
+            while let Some((message, recipient)) = receiver.next().await {
+                stats.sent.fetch_add(1, Ordering::SeqCst);
+
+                let delay = {
+                    let mut rng = rng.lock();
+                    if rng.gen_bool(packet_loss) {
+                        None
+                    } else {
+                        Some(latency + jitter.mul_f64(rng.gen::<f64>()))
+                    }
+                };
+                let peer = network.get_peer(MockPeerId::from(recipient as u64));
+
+                match (delay, peer) {
+                    (Some(delay), Some(peer)) => {
+                        tokio::spawn(async move {
+                            time::delay_for(delay).await;
+                            // The recipient might not listen anymore, which is the same as a lost message.
+                            let _ = peer.send(&message).await;
+                        });
+                    }
+                    _ => {
+                        stats.lost.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }
+        });
+
+        sender
+    }
+}
+
+/// Returns forged level updates of a byzantine node for every peer at every level. Each update claims to contain the
+/// contributions of all nodes that the node aggregates for that level.
+fn forged_updates(node_id: usize, partitioner: &BinomialPartitioner) -> Vec<(SimMessage, usize)> {
+    let mut contributors: BitSet = std::iter::once(node_id).collect();
+    let mut updates = vec![];
+
+    for level in 1..partitioner.levels() {
+        let ids = partitioner.range(level).unwrap();
+        let aggregate = SimContribution::forged(contributors.clone(), 0);
+        let individual = SimContribution::forged(std::iter::once(node_id).collect(), 0);
+        let update = LevelUpdate::new(aggregate, Some(individual), level, node_id).with_tag(TAG);
+
+        for recipient in ids.clone().filter(|&id| id < partitioner.size()) {
+            updates.push((update.clone(), recipient));
+        }
+        contributors = ids.filter(|&id| id < partitioner.size()).chain(contributors.iter()).collect();
+    }
+
+    updates
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    sent: AtomicUsize,
+    lost: AtomicUsize,
+}
+
+/// Outcome of a simulation.
+#[derive(Clone, Debug)]
+pub struct SimulationReport {
+    /// Virtual time after which each node had an aggregate that reached the threshold. `None` for nodes that didn't
+    /// reach it, including offline and byzantine nodes.
+    pub completion_times: Vec<Option<Duration>>,
+
+    /// Number of honest nodes.
+    pub honest: usize,
+
+    /// Number of level updates that the nodes sent, including the lost ones.
+    pub messages_sent: usize,
+
+    /// Number of level updates that were lost or sent to offline nodes.
+    pub messages_lost: usize,
+
+    /// Number of forged contributions that the verifiers rejected.
+    pub forged_rejected: usize,
+
+    /// Virtual time that the simulation ran for.
+    pub duration: Duration,
+}
+
+impl SimulationReport {
+    /// Returns the number of nodes that reached the threshold.
+    pub fn completed(&self) -> usize {
+        self.completion_times.iter().flatten().count()
+    }
+
+    /// Returns the time after which all honest nodes reached the threshold, or `None` if some didn't.
+    pub fn completion_time(&self) -> Option<Duration> {
+        if self.completed() < self.honest {
+            return None;
+        }
+        self.completion_times.iter().flatten().max().cloned()
+    }
+
+    /// Returns the time after which half of the honest nodes reached the threshold, or `None` if they didn't.
+    pub fn median_completion_time(&self) -> Option<Duration> {
+        let mut times: Vec<Duration> = self.completion_times.iter().flatten().cloned().collect();
+        times.sort();
+        times.get(self.honest.saturating_sub(1) / 2).cloned()
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = |time: Option<Duration>| match time {
+            Some(time) => format!("{}ms", time.as_millis()),
+            None => "-".to_string(),
+        };
+        write!(
+            f,
+            "completed={}/{} median={} all={} sent={} lost={} forged_rejected={}",
+            self.completed(),
+            self.honest,
+            millis(self.median_completion_time()),
+            millis(self.completion_time()),
+            self.messages_sent,
+            self.messages_lost,
+            self.forged_rejected,
+        )
+    }
+}
@@ -10,10 +10,16 @@ use nimiq_mempool::Mempool;
 use nimiq_network_interface::network::Network as NetworkInterface;
 use nimiq_network_libp2p::{
     discovery::peer_contacts::{PeerContact, Services},
+    libp2p::PeerId,
     Config as NetworkConfig, Network,
 };
 use nimiq_utils::time::OffsetTime;
+use nimiq_validator_network::network_impl::ValidatorRecordValidator;
 
+#[cfg(feature = "validator")]
+use nimiq_validator::signer::{RemoteSigner, Signer};
+#[cfg(feature = "validator")]
+use nimiq_validator::signing_history::SigningHistory;
 #[cfg(feature = "validator")]
 use nimiq_validator::validator::Validator as AbstractValidator;
 #[cfg(feature = "validator")]
@@ -79,9 +85,15 @@ impl ClientInner {
         let identity_keypair = config.storage.identity_keypair()?;
         log::info!("Identity public key: {:?}", identity_keypair.public());
 
-        // Generate peer contact from identity keypair and services/protocols
+        // Generate peer contact from identity keypair and services/protocols. We advertise the listen addresses, unless
+        // other addresses are configured (e.g. a `/wss` address with the domain of our TLS certificate).
+        let advertised_addresses = if config.network.advertised_addresses.is_empty() {
+            config.network.listen_addresses.clone()
+        } else {
+            config.network.advertised_addresses.clone()
+        };
         let peer_contact = PeerContact::new(
-            config.network.listen_addresses.clone(),
+            advertised_addresses,
             identity_keypair.public(),
             Services::all(), // TODO
             None, // No need to set the timestamp as this will be set before signing anyway.
@@ -96,6 +108,13 @@ impl ClientInner {
         if let Some(min_peers) = config.network.min_peers {
             network_config.min_peers = min_peers;
         }
+        network_config.limit = config.network.limit.clone();
+        network_config.bandwidth = config.network.bandwidth.clone();
+        network_config.tls = config.network.tls.clone();
+        network_config.peer_contact_book.path = config.storage.peer_contacts_path();
+        network_config.dht.path = config.storage.dht_records_path();
+        // Every node stores and serves validator records, so all of them must check them.
+        network_config.dht_record_validator = Some(Arc::new(ValidatorRecordValidator::<PeerId>::new()));
 
         log::debug!("listen_addresses = {:?}", config.network.listen_addresses);
 
@@ -103,11 +122,14 @@ impl ClientInner {
             config.network.listen_addresses,
             Arc::clone(&time),
             network_config,
-        ).await);
+        ).await?);
 
-        // Load validator key (before we give away ownership of the storage config)
+        // Load validator key (before we give away ownership of the storage config), unless a remote signer keeps it
         #[cfg(feature = "validator")]
-        let validator_key = config.storage.validator_key()?;
+        let validator_key = match &config.validator {
+            Some(validator_config) if validator_config.remote_signer.is_none() => Some(config.storage.validator_key()?),
+            _ => None,
+        };
 
         // Open database
         let environment =
@@ -122,8 +144,10 @@ impl ClientInner {
         let wallet_store = Arc::new(WalletStore::new(environment.clone()));
 
         let sync = HistorySync::<Network>::new(Arc::clone(&blockchain), network.subscribe_events());
+        let sync_progress = sync.subscribe_progress();
 
-        let consensus = Consensus::from_network(
+        let mut consensus = Consensus::from_network_with_config(
+            config.established,
             environment.clone(),
             blockchain,
             mempool,
@@ -131,6 +155,7 @@ impl ClientInner {
             sync.boxed(),
         )
         .await;
+        consensus.track_history_sync(sync_progress);
 
         #[cfg(feature = "validator")]
         let validator = {
@@ -157,9 +182,35 @@ impl ClientInner {
                     }
                 };
 
+                let mut validator_config = config.config.clone();
+                if let Some(path) = &config.signing_history_file {
+                    let history = std::fs::read_to_string(path)
+                        .map_err(|e| Error::config_error(format!("Failed to read signing history {}: {}", path.display(), e)))?;
+                    let history = SigningHistory::from_hex(&history)
+                        .map_err(|e| Error::config_error(format!("Failed to parse signing history {}: {}", path.display(), e)))?;
+                    validator_config.signing_history = Some(history);
+                }
+
+                let signer: Arc<dyn Signer> = match (&config.remote_signer, validator_key) {
+                    (Some(address), _) => {
+                        let signer = RemoteSigner::connect(address.clone())
+                            .map_err(|e| Error::config_error(format!("Failed to connect to remote signer {}: {}", address, e)))?;
+                        log::info!("Signing with remote signer at {}", address);
+                        Arc::new(signer)
+                    }
+                    (None, Some(validator_key)) => Arc::new(validator_key),
+                    (None, None) => unreachable!("The validator key is loaded if there is no remote signer"),
+                };
+
                 let validator_network = Arc::new(ValidatorNetworkImpl::new(Arc::clone(&network)));
 
-                let validator = Arc::new(Validator::new(&consensus, validator_network, validator_key, validator_wallet_key));
+                let validator = Arc::new(Validator::new(
+                    &consensus,
+                    validator_network,
+                    signer,
+                    validator_wallet_key,
+                    validator_config,
+                ));
 
                 Some(validator)
             }
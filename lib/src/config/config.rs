@@ -7,17 +7,23 @@ use enum_display_derive::Display;
 
 #[cfg(feature = "validator")]
 use nimiq_bls::KeyPair as BlsKeyPair;
+use nimiq_consensus_albatross::EstablishedConfig;
 use nimiq_database::{
     lmdb::{open as LmdbFlags, LmdbEnvironment},
     volatile::VolatileEnvironment,
     Environment,
 };
 use nimiq_mempool::{filter::Rules as MempoolRules, MempoolConfig};
-use nimiq_network_libp2p::{Keypair as IdentityKeypair, Multiaddr};
+use nimiq_network_libp2p::{
+    bandwidth::BandwidthConfig, libp2p::core::multiaddr::Protocol as MultiaddrProtocol, limit::behaviour::LimitConfig,
+    tls::TlsConfig, Keypair as IdentityKeypair, Multiaddr,
+};
 use nimiq_primitives::networks::NetworkId;
 use nimiq_utils::file_store::FileStore;
 #[cfg(feature = "validator")]
 use nimiq_utils::key_rng::SecureGenerate;
+#[cfg(feature = "validator")]
+use nimiq_validator::signer::SignerAddress;
 
 use crate::{
     client::Client,
@@ -59,6 +65,15 @@ pub struct NetworkConfig {
     #[builder(default)]
     pub listen_addresses: Vec<Multiaddr>,
 
+    /// Addresses that are advertised to other peers in our peer contact. If this is empty, the listen addresses are
+    /// advertised.
+    ///
+    /// Browsers only connect to `/wss` addresses with a domain name that matches the TLS certificate, so nodes that
+    /// listen on e.g. `/ip4/0.0.0.0/tcp/8443/wss` should advertise `/dns4/my.domain/tcp/8443/wss`.
+    ///
+    #[builder(default)]
+    pub advertised_addresses: Vec<Multiaddr>,
+
     /// The user agent is a custom string that is sent during the handshake. Usually it contains
     /// the kind of node, Nimiq version, processor architecture and operating system. This enable
     /// gathering information on which Nimiq versions are being run on the network. A typical
@@ -73,6 +88,21 @@ pub struct NetworkConfig {
     pub seeds: Vec<Seed>,
 
     pub min_peers: Option<usize>,
+
+    /// Connection limits and IP bans.
+    ///
+    #[builder(default)]
+    pub limit: LimitConfig,
+
+    /// Per-peer bandwidth limits.
+    ///
+    #[builder(default)]
+    pub bandwidth: BandwidthConfig,
+
+    /// Certificate and private key for secure websockets. This is required to listen on `/wss` addresses.
+    ///
+    #[builder(default)]
+    pub tls: Option<TlsConfig>,
 }
 
 /// Contains which protocol to use and the configuration needed for that protocol.
@@ -115,8 +145,8 @@ pub enum ProtocolConfig {
         ///
         /// # Notes
         ///
-        /// Only PKCS#12 is supported right now, but it is planned to move away from this and use
-        /// the PEM format for certificate and private key.
+        /// This is only used by the legacy network. The libp2p network loads the certificate and
+        /// private key from PEM files, see `NetworkConfig::tls`.
         ///
         pkcs12_key_file: PathBuf,
 
@@ -143,6 +173,15 @@ pub struct ValidatorConfig {
     pub wallet_account: Option<String>,
     #[builder(default)]
     pub wallet_password: Option<String>,
+    /// Behaviour of the validator, e.g. whether it unparks itself after a slash.
+    #[builder(default)]
+    pub config: nimiq_validator::config::ValidatorConfig,
+    /// File with the signing history that was exported on another machine. It is imported on startup.
+    #[builder(default)]
+    pub signing_history_file: Option<PathBuf>,
+    /// Signer that keeps the validator key. If this is not set, the validator signs with its key file.
+    #[builder(default)]
+    pub remote_signer: Option<SignerAddress>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -155,6 +194,12 @@ pub struct FileStorageConfig {
     /// Path to peer key
     peer_key: PathBuf,
 
+    /// Path to the persisted peer contacts
+    peer_contacts: PathBuf,
+
+    /// Path to the persisted DHT records
+    dht_records: PathBuf,
+
     /// Path to validator key
     #[cfg(feature = "validator")]
     validator_key: Option<PathBuf>,
@@ -168,6 +213,8 @@ impl FileStorageConfig {
         Self {
             database_parent: path.to_path_buf(),
             peer_key: path.join("peer_key.dat"),
+            peer_contacts: path.join("peer_contacts.toml"),
+            dht_records: path.join("dht_records.dat"),
             #[cfg(feature = "validator")]
             validator_key: Some(path.join("validator_key.dat")),
         }
@@ -341,6 +388,22 @@ impl StorageConfig {
         }
     }
 
+    /// Returns the path of the file in which the peer contacts are persisted, if the storage backend supports it.
+    pub(crate) fn peer_contacts_path(&self) -> Option<PathBuf> {
+        match self {
+            StorageConfig::Filesystem(file_storage) => Some(file_storage.peer_contacts.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the file in which the DHT records are persisted, if the storage backend supports it.
+    pub(crate) fn dht_records_path(&self) -> Option<PathBuf> {
+        match self {
+            StorageConfig::Filesystem(file_storage) => Some(file_storage.dht_records.clone()),
+            _ => None,
+        }
+    }
+
     fn not_available(&self) -> Error {
         Error::Config(format!("Storage backend not implemented: {:?}", self))
     }
@@ -482,6 +545,11 @@ pub struct ClientConfig {
     #[builder(default)]
     pub consensus: ConsensusConfig,
 
+    /// Determines when consensus is considered established and when it is lost again.
+    ///
+    #[builder(default)]
+    pub established: EstablishedConfig,
+
     /// The `ProtocolConfig` that determines how the client accepts incoming connections. This
     /// will also determine how the client advertises itself to the network.
     ///
@@ -644,6 +712,9 @@ impl ClientConfigBuilder {
         self.validator = Some(Some(ValidatorConfig {
             wallet_account: Some(wallet_account),
             wallet_password,
+            config: Default::default(),
+            signing_history_file: None,
+            remote_signer: None,
         }));
         self
     }
@@ -651,10 +722,55 @@ impl ClientConfigBuilder {
     /// Applies settings from a configuration file
     pub fn config_file(&mut self, config_file: &ConfigFile) -> Result<&mut Self, Error> {
         // TODO: if the config field of `listen_addresses` is empty, we should at least add `/ip4/127.0.0.1/...`
+        let listen_addresses = config_file
+            .network
+            .listen_addresses
+            .iter()
+            .map(|addr| addr.parse())
+            .collect::<Result<Vec<Multiaddr>, _>>()?;
+
+        let tls = config_file
+            .network
+            .tls
+            .as_ref()
+            .map(|tls| {
+                if tls.identity_file.is_some() || tls.identity_password.is_some() {
+                    return Err(Error::config_error(
+                        "network.tls.identity_file and network.tls.identity_password are not supported anymore. \
+                         Convert the PKCS#12 identity to PEM and set private_key_file and certificates_file instead",
+                    ));
+                }
+                if tls.private_key_file.is_empty() || tls.certificates_file.is_empty() {
+                    return Err(Error::config_error(
+                        "network.tls requires both private_key_file and certificates_file",
+                    ));
+                }
+
+                TlsConfig::from_pem_files(&tls.private_key_file, &tls.certificates_file).map_err(|e| {
+                    Error::config_error(format!(
+                        "Failed to load TLS certificate {} and key {}: {}",
+                        tls.certificates_file, tls.private_key_file, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let limit = LimitConfig::from(config_file.network.limits.clone());
+        limit.validate().map_err(Error::config_error)?;
+
+        let listens_on_wss = listen_addresses
+            .iter()
+            .any(|addr| addr.iter().any(|protocol| matches!(protocol, MultiaddrProtocol::Wss(_))));
+        if listens_on_wss && tls.is_none() {
+            return Err(Error::config_error("Listening on a /wss address requires a [network.tls] configuration"));
+        }
+
         self.network(NetworkConfig {
-            listen_addresses: config_file
+            listen_addresses,
+
+            advertised_addresses: config_file
                 .network
-                .listen_addresses
+                .advertised_addresses
                 .iter()
                 .map(|addr| addr.parse())
                 .collect::<Result<Vec<Multiaddr>, _>>()?,
@@ -669,10 +785,17 @@ impl ClientConfigBuilder {
             seeds: vec![], // TODO
 
             min_peers: config_file.network.min_peers,
+
+            limit,
+
+            bandwidth: BandwidthConfig::from(config_file.network.bandwidth.clone()),
+
+            tls,
         });
 
         // Configure consensus
         self.consensus(config_file.consensus.consensus_type);
+        self.established(EstablishedConfig::from(config_file.consensus.clone()));
 
         // Configure network
         self.network_id(config_file.consensus.network);
@@ -685,6 +808,12 @@ impl ClientConfigBuilder {
         if let Some(path) = config_file.network.peer_key_file.as_ref() {
             file_storage.peer_key = PathBuf::from(path);
         }
+        if let Some(path) = config_file.network.peer_contacts_file.as_ref() {
+            file_storage.peer_contacts = PathBuf::from(path);
+        }
+        if let Some(path) = config_file.network.dht_records_file.as_ref() {
+            file_storage.dht_records = PathBuf::from(path);
+        }
         #[cfg(feature = "validator")]
         if let Some(validator_config) = config_file.validator.as_ref() {
             validator_config
@@ -767,9 +896,31 @@ impl ClientConfigBuilder {
         #[cfg(feature = "validator")]
         {
             if let Some(validator_config) = &config_file.validator {
+                let remote_signer = validator_config
+                    .remote_signer
+                    .as_ref()
+                    .map(|address| {
+                        address.parse().map_err(|_| {
+                            Error::config_error(format!("Invalid remote signer address: {}", address))
+                        })
+                    })
+                    .transpose()?;
+
+                let mut config: nimiq_validator::config::ValidatorConfig = validator_config.clone().into();
+                if let Some(extra_data) = &validator_config.extra_data {
+                    config.extra_data = hex::decode(extra_data)
+                        .map_err(|_| Error::config_error(format!("Invalid extra data: {}", extra_data)))?;
+                }
+                if config.micro_block_extra_data().is_none() {
+                    return Err(Error::config_error("Extra data and upgrade signal exceed 32 bytes"));
+                }
+
                 self.validator = Some(Some(ValidatorConfig {
                     wallet_account: validator_config.wallet_account.to_owned(),
                     wallet_password: validator_config.wallet_password.to_owned(),
+                    config,
+                    signing_history_file: validator_config.signing_history_file.as_ref().map(PathBuf::from),
+                    remote_signer,
                 }));
             }
         }
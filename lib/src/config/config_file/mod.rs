@@ -2,18 +2,22 @@ use std::collections::HashMap;
 use std::fs::read_to_string;
 use std::path::Path;
 use std::str::FromStr;
+use std::time::Duration;
 
 use log::LevelFilter;
 use serde_derive::Deserialize;
 use thiserror::Error;
-use nimiq_network_libp2p::Multiaddr;
+use nimiq_network_libp2p::{bandwidth::BandwidthConfig, limit::behaviour::LimitConfig, Multiaddr};
 
+use nimiq_consensus_albatross::EstablishedConfig;
 use nimiq_mempool::{
     filter::{MempoolFilter, Rules as MempoolRules},
     MempoolConfig,
 };
 use nimiq_peer_address::{address, protocol}; // TODO: probably not needed anymore
 use nimiq_primitives::{coin::Coin, networks::NetworkId};
+#[cfg(feature = "validator")]
+use nimiq_validator::config::ValidatorConfig;
 
 use crate::{
     config::{command_line::CommandLine, config, config_file::serialization::*, paths},
@@ -113,10 +117,17 @@ impl ConfigFile {
 #[serde(deny_unknown_fields)]
 pub struct NetworkSettings {
     pub peer_key_file: Option<String>,
+    pub peer_contacts_file: Option<String>,
+    pub dht_records_file: Option<String>,
 
     #[serde(default)]
     pub listen_addresses: Vec<String>,
 
+    /// Addresses that we advertise to other peers instead of the listen addresses, e.g. a `/dns4/.../wss` address
+    /// with the domain of our TLS certificate.
+    #[serde(default)]
+    pub advertised_addresses: Vec<String>,
+
     #[serde(default)]
     pub seed_nodes: Vec<Seed>,
     #[serde(default)]
@@ -125,6 +136,81 @@ pub struct NetworkSettings {
     pub tls: Option<TlsSettings>,
     pub instant_inbound: Option<bool>,
     pub min_peers: Option<usize>,
+
+    #[serde(default)]
+    pub limits: LimitSettings,
+
+    #[serde(default)]
+    pub bandwidth: BandwidthSettings,
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct LimitSettings {
+    pub peer_count_max: Option<usize>,
+    pub peer_count_per_ip_max: Option<usize>,
+    pub outbound_peer_count_per_subnet_max: Option<usize>,
+    pub inbound_peer_count_per_subnet_max: Option<usize>,
+    pub ipv4_subnet_mask: Option<u8>,
+    pub ipv6_subnet_mask: Option<u8>,
+    /// Default ban time, in seconds.
+    pub ban_time: Option<u64>,
+    /// Time in seconds for which disconnected peers can't reconnect.
+    pub disconnect_backoff_time: Option<u64>,
+    pub pending_incoming_max: Option<u32>,
+    pub pending_outgoing_max: Option<u32>,
+}
+
+impl From<LimitSettings> for LimitConfig {
+    fn from(limits: LimitSettings) -> Self {
+        let default = LimitConfig::default();
+        Self {
+            peer_count_max: limits.peer_count_max.unwrap_or(default.peer_count_max),
+            peer_count_per_ip_max: limits
+                .peer_count_per_ip_max
+                .unwrap_or(default.peer_count_per_ip_max),
+            outbound_peer_count_per_subnet_max: limits
+                .outbound_peer_count_per_subnet_max
+                .unwrap_or(default.outbound_peer_count_per_subnet_max),
+            inbound_peer_count_per_subnet_max: limits
+                .inbound_peer_count_per_subnet_max
+                .unwrap_or(default.inbound_peer_count_per_subnet_max),
+            ipv4_subnet_mask: limits.ipv4_subnet_mask.unwrap_or(default.ipv4_subnet_mask),
+            ipv6_subnet_mask: limits.ipv6_subnet_mask.unwrap_or(default.ipv6_subnet_mask),
+            default_ban_time: limits
+                .ban_time
+                .map(Duration::from_secs)
+                .unwrap_or(default.default_ban_time),
+            disconnect_backoff_time: limits
+                .disconnect_backoff_time
+                .map(Duration::from_secs)
+                .unwrap_or(default.disconnect_backoff_time),
+            pending_incoming_max: limits
+                .pending_incoming_max
+                .unwrap_or(default.pending_incoming_max),
+            pending_outgoing_max: limits
+                .pending_outgoing_max
+                .unwrap_or(default.pending_outgoing_max),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct BandwidthSettings {
+    /// Maximum number of bytes a peer may send us per period.
+    pub peer_rate_limit: Option<usize>,
+    /// Period of the per-peer rate limit, in seconds.
+    pub peer_rate_limit_period: Option<u64>,
+}
+
+impl From<BandwidthSettings> for BandwidthConfig {
+    fn from(bandwidth: BandwidthSettings) -> Self {
+        Self {
+            peer_rate_limit: bandwidth.peer_rate_limit,
+            peer_rate_limit_period: bandwidth.peer_rate_limit_period.map(Duration::from_secs),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -162,8 +248,16 @@ impl From<Protocol> for protocol::Protocol {
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TlsSettings {
-    pub identity_file: String,
-    pub identity_password: String,
+    /// PEM file containing the private key.
+    #[serde(default)]
+    pub private_key_file: String,
+    /// PEM file containing the certificate chain.
+    #[serde(default)]
+    pub certificates_file: String,
+    /// Deprecated: PKCS#12 identities are not supported anymore. Only parsed to refuse old configs with a clear error.
+    pub identity_file: Option<String>,
+    /// Deprecated: See `identity_file`.
+    pub identity_password: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Default)]
@@ -174,6 +268,39 @@ pub struct ConsensusSettings {
     pub consensus_type: ConsensusType,
     #[serde(default)]
     pub network: Network,
+    pub min_peers_established: Option<usize>,
+    pub min_blocks_established: Option<usize>,
+    /// Interval of the head requests, in seconds.
+    pub head_requests_timeout: Option<u64>,
+    pub max_blocks_behind: Option<u32>,
+    /// Consensus is lost if no block was received for this many seconds. Disabled if 0.
+    pub block_timeout: Option<u64>,
+}
+
+impl From<ConsensusSettings> for EstablishedConfig {
+    fn from(consensus: ConsensusSettings) -> Self {
+        let default = EstablishedConfig::default();
+        Self {
+            min_peers_established: consensus
+                .min_peers_established
+                .unwrap_or(default.min_peers_established),
+            min_blocks_established: consensus
+                .min_blocks_established
+                .unwrap_or(default.min_blocks_established),
+            head_requests_timeout: consensus
+                .head_requests_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(default.head_requests_timeout),
+            max_blocks_behind: consensus
+                .max_blocks_behind
+                .unwrap_or(default.max_blocks_behind),
+            block_timeout: match consensus.block_timeout {
+                Some(0) => None,
+                Some(secs) => Some(Duration::from_secs(secs)),
+                None => default.block_timeout,
+            },
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
@@ -456,4 +583,51 @@ pub struct ValidatorSettings {
     pub validator_key_file: Option<String>,
     pub wallet_account: Option<String>,
     pub wallet_password: Option<String>,
+    /// Send an `UnparkValidator` transaction when our validator is parked.
+    pub automatic_unpark: Option<bool>,
+    #[serde(deserialize_with = "deserialize_coin")]
+    #[serde(default)]
+    pub unpark_fee: Coin,
+    /// File with the signing history that was exported on another machine.
+    pub signing_history_file: Option<String>,
+    /// Address of a remote signer that keeps the validator key, either a loopback `host:port` or `unix:/path/to/socket`.
+    pub remote_signer: Option<String>,
+    /// Hex-encoded extra data of our micro blocks.
+    pub extra_data: Option<String>,
+    /// Protocol version that our micro blocks signal readiness for.
+    pub upgrade_signal: Option<u16>,
+    /// Tendermint's timeout in round 0, in milliseconds.
+    pub tendermint_timeout_init: Option<u64>,
+    /// Increase of Tendermint's timeout per round, in milliseconds.
+    pub tendermint_timeout_delta: Option<u64>,
+    /// Time to wait for a micro block before starting a view change, in milliseconds.
+    pub view_change_delay: Option<u64>,
+}
+
+#[cfg(feature = "validator")]
+impl From<ValidatorSettings> for ValidatorConfig {
+    fn from(validator: ValidatorSettings) -> Self {
+        let default = ValidatorConfig::default();
+        Self {
+            automatic_unpark: validator.automatic_unpark.unwrap_or(default.automatic_unpark),
+            unpark_fee: validator.unpark_fee,
+            // The signing history file is read by the client.
+            signing_history: None,
+            // The extra data is decoded by the client.
+            extra_data: default.extra_data,
+            upgrade_signal: validator.upgrade_signal,
+            tendermint_timeout_init: validator
+                .tendermint_timeout_init
+                .map(Duration::from_millis)
+                .unwrap_or(default.tendermint_timeout_init),
+            tendermint_timeout_delta: validator
+                .tendermint_timeout_delta
+                .map(Duration::from_millis)
+                .unwrap_or(default.tendermint_timeout_delta),
+            view_change_delay: validator
+                .view_change_delay
+                .map(Duration::from_millis)
+                .unwrap_or(default.view_change_delay),
+        }
+    }
 }
@@ -40,16 +40,13 @@ pub fn initialize_rpc_server(
 
     let mut dispatcher = ModularDispatcher::default();
 
-    /*
     #[cfg(feature = "validator")]
     {
         if let Some(validator) = client.validator() {
-            dispatcher.add(BlockProductionDispatcher::new(validator));
+            dispatcher.add(ValidatorDispatcher::new(validator.proxy()));
         }
     }
-    */
 
-    //dispatcher.add(NetworkDispatcher::new(client.consensus()));
     let wallet_dispatcher = WalletDispatcher::new(wallet_store);
     let unlocked_wallets = Arc::clone(&wallet_dispatcher.unlocked_wallets);
 
@@ -60,6 +57,7 @@ pub fn initialize_rpc_server(
     ));
     dispatcher.add(wallet_dispatcher);
     dispatcher.add(MempoolDispatcher::new(client.mempool()));
+    dispatcher.add(NetworkDispatcher::new(client.network()));
 
     Ok(Server::new(
         Config {
@@ -12,6 +12,7 @@ extern crate nimiq_utils as utils;
 
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::convert::TryInto;
 use std::sync::Arc;
 
 use parking_lot::{Mutex, RwLock, RwLockUpgradableReadGuard};
@@ -30,6 +31,13 @@ use crate::filter::{MempoolFilter, Rules};
 
 pub mod filter;
 
+/// A short transaction ID, consisting of the first eight bytes of the transaction hash.
+pub type ShortTxId = u64;
+
+pub fn short_tx_id(hash: &Blake2bHash) -> ShortTxId {
+    u64::from_be_bytes(hash.as_bytes()[..8].try_into().unwrap())
+}
+
 pub struct Mempool {
     blockchain: Arc<Blockchain>,
     pub notifier: RwLock<Notifier<'static, MempoolEvent>>,
@@ -39,6 +47,7 @@ pub struct Mempool {
 
 struct MempoolState {
     transactions_by_hash: HashMap<Blake2bHash, Arc<Transaction>>,
+    transactions_by_short_id: HashMap<ShortTxId, Blake2bHash>,
     transactions_by_sender: HashMap<Address, BTreeSet<Arc<Transaction>>>,
     transactions_by_recipient: HashMap<Address, BTreeSet<Arc<Transaction>>>,
     transactions_sorted_fee: BTreeSet<Arc<Transaction>>, // sorted by fee, ascending
@@ -75,6 +84,7 @@ impl Mempool {
             notifier: RwLock::new(Notifier::new()),
             state: RwLock::new(MempoolState {
                 transactions_by_hash: HashMap::new(),
+                transactions_by_short_id: HashMap::new(),
                 transactions_by_sender: HashMap::new(),
                 transactions_by_recipient: HashMap::new(),
                 transactions_sorted_fee: BTreeSet::new(),
@@ -305,6 +315,22 @@ impl Mempool {
         self.state.read().transactions_by_hash.get(hash).cloned()
     }
 
+    /// Looks up transactions by their short IDs. Transactions that are not in the mempool are `None`.
+    /// If several transactions share a short ID, only one of them is returned.
+    pub fn get_transactions_by_short_ids(&self, short_ids: &[ShortTxId]) -> Vec<Option<Arc<Transaction>>> {
+        let state = self.state.read();
+        short_ids
+            .iter()
+            .map(|short_id| {
+                state
+                    .transactions_by_short_id
+                    .get(short_id)
+                    .and_then(|hash| state.transactions_by_hash.get(hash))
+                    .cloned()
+            })
+            .collect()
+    }
+
     pub fn get_transactions(&self, max_count: usize, min_fee_per_byte: f64) -> Vec<Arc<Transaction>> {
         self.state
             .read()
@@ -600,6 +626,7 @@ impl Mempool {
     }
 
     fn add_transaction(state: &mut MempoolState, hash: Blake2bHash, tx: Arc<Transaction>) {
+        state.transactions_by_short_id.insert(short_tx_id(&hash), hash.clone());
         state.transactions_by_hash.insert(hash, tx.clone());
         state.transactions_sorted_fee.insert(tx.clone());
 
@@ -617,7 +644,12 @@ impl Mempool {
     }
 
     fn remove_transaction(state: &mut MempoolState, tx: &Transaction) {
-        state.transactions_by_hash.remove(&tx.hash());
+        let hash: Blake2bHash = tx.hash();
+        let short_id = short_tx_id(&hash);
+        if state.transactions_by_short_id.get(&short_id) == Some(&hash) {
+            state.transactions_by_short_id.remove(&short_id);
+        }
+        state.transactions_by_hash.remove(&hash);
         state.transactions_sorted_fee.remove(tx);
 
         let mut remove_key = false;
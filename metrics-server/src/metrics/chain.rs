@@ -58,6 +58,12 @@ impl Metrics for AlbatrossChainMetrics {
             }*/
         }
 
+        let signals = self.blockchain.upgrade_signals();
+        serializer.metric("chain_upgrade_signal_micro_blocks", signals.micro_blocks)?;
+        for (version, slots) in &signals.slots {
+            serializer.metric_with_attributes("chain_upgrade_signal_slots", *slots, attributes! {"version" => format!("{}", version)})?;
+        }
+
         self.serialize_blockchain_metrics(Arc::clone(&self.blockchain), serializer)?;
 
         Ok(())
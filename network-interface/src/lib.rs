@@ -2,7 +2,6 @@ pub mod message;
 pub mod network;
 pub mod peer;
 pub mod peer_map;
-pub mod request_response;
 
 pub mod prelude {
     pub use crate::message::*;
@@ -9,7 +9,7 @@ use crate::message::crc::ReaderComputeCrc32;
 
 mod crc;
 
-const MAGIC: u32 = 0x4204_2042;
+pub const MAGIC: u32 = 0x4204_2042;
 
 pub trait Message: Serialize + Deserialize + Send + Sync + std::fmt::Debug + 'static {
     const TYPE_ID: u64;
@@ -24,16 +24,12 @@ pub trait Message: Serialize + Deserialize + Send + Sync + std::fmt::Debug + 'st
         size += MAGIC.serialize(&mut v)?;
         size += ty.serialize(&mut v)?;
         size += serialized_size.serialize(&mut v)?;
-        let checksum_start = v.len();
         size += 0u32.serialize(&mut v)?; // crc32 placeholder
 
         size += self.serialize(&mut v)?;
 
         // Write checksum to placeholder.
-        let mut v_crc = Vec::with_capacity(4);
-        Crc32Computer::default().update(v.as_slice()).result().serialize(&mut v_crc)?;
-
-        v[checksum_start..(4 + checksum_start)].clone_from_slice(&v_crc[..4]);
+        write_checksum(&mut v)?;
 
         writer.write_all(v.as_slice())?;
         Ok(size)
@@ -112,6 +108,56 @@ pub fn peek_length(buffer: &[u8]) -> Result<usize, SerializingError> {
     Ok(n as usize)
 }
 
+pub fn peek_magic(buffer: &[u8]) -> Result<u32, SerializingError> {
+    let mut c = Cursor::new(buffer);
+    u32::deserialize(&mut c)
+}
+
+/// Returns the offset of the checksum in a serialized message, i.e. the size of magic, type and length.
+fn checksum_offset(buffer: &[u8]) -> Result<usize, SerializingError> {
+    let first_type_byte = *buffer.get(4).ok_or(SerializingError::InvalidEncoding)?;
+    let offset = 4 + uvar::serialized_size_from_first_byte(first_type_byte) + 4;
+
+    if buffer.len() < offset + 4 {
+        return Err(SerializingError::InvalidEncoding);
+    }
+
+    Ok(offset)
+}
+
+/// Computes the CRC32 checksum of a serialized message. The checksum field itself is treated as zeros.
+pub fn compute_checksum(buffer: &[u8]) -> Result<u32, SerializingError> {
+    let offset = checksum_offset(buffer)?;
+
+    Ok(Crc32Computer::default()
+        .update(&buffer[..offset])
+        .update(&[0u8; 4])
+        .update(&buffer[offset + 4..])
+        .result())
+}
+
+/// Computes the checksum of a serialized message and writes it into the message's checksum field.
+pub fn write_checksum(buffer: &mut [u8]) -> Result<(), SerializingError> {
+    let offset = checksum_offset(buffer)?;
+    let checksum = compute_checksum(buffer)?;
+
+    buffer[offset..offset + 4].copy_from_slice(&checksum.to_be_bytes());
+
+    Ok(())
+}
+
+/// Verifies the checksum of a serialized message, without deserializing it.
+pub fn verify_checksum(buffer: &[u8]) -> Result<(), SerializingError> {
+    let offset = checksum_offset(buffer)?;
+    let checksum = u32::deserialize(&mut Cursor::new(&buffer[offset..offset + 4]))?;
+
+    if compute_checksum(buffer)? != checksum {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Bad checksum").into());
+    }
+
+    Ok(())
+}
+
 pub async fn read_message<R: AsyncRead + Unpin>(mut reader: R) -> Result<Vec<u8>, SerializingError> {
     log::trace!("read_message: reading magic and first byte of type...");
     // Read message magic and first type byte.
@@ -147,11 +193,3 @@ pub async fn read_message<R: AsyncRead + Unpin>(mut reader: R) -> Result<Vec<u8>
 
     Ok(msg)
 }
-
-pub trait RequestMessage: Message {
-    fn set_request_identifier(&mut self, request_identifier: u32);
-}
-
-pub trait ResponseMessage: Message {
-    fn get_request_identifier(&self) -> u32;
-}
@@ -19,6 +19,13 @@ pub enum NetworkEvent<P> {
     PeerLeft(Arc<P>),
 }
 
+/// Validates records before they are stored in or returned from the DHT.
+pub trait DhtRecordValidator: Send + Sync {
+    /// Checks the `value` stored under `key`. Returns the timestamp of the record if it is valid, or `None` if the
+    /// record must be rejected. If there are several valid records for a key, the one with the highest timestamp wins.
+    fn validate(&self, key: &[u8], value: &[u8]) -> Option<u64>;
+}
+
 pub trait Topic {
     type Item: Serialize + Deserialize + Send + Sync + std::fmt::Debug + 'static;
 
@@ -55,7 +62,7 @@ pub trait Network: Send + Sync + 'static {
     type PeerType: Peer + 'static;
     type AddressType: std::fmt::Display + std::fmt::Debug;
     type Error: std::error::Error;
-    type PubsubId: PubsubId<<Self::PeerType as Peer>::Id>;
+    type PubsubId: PubsubId<<Self::PeerType as Peer>::Id> + Send + 'static;
 
     fn get_peer_updates(&self) -> (Vec<Arc<Self::PeerType>>, broadcast::Receiver<NetworkEvent<Self::PeerType>>);
 
@@ -77,6 +84,12 @@ pub trait Network: Send + Sync + 'static {
         ReceiveFromAll::new(self)
     }
 
+    /// Receives the requests of type `R` from all peers. Should panic if there is already a non-closed stream of
+    /// requests of this type.
+    fn receive_requests_from_all<R: RequestResponse>(&self) -> ReceiveRequestsFromAll<R, Self::PeerType> {
+        ReceiveFromAll::requests(self)
+    }
+
     async fn subscribe<T>(&self, topic: &T) -> Result<Pin<Box<dyn Stream<Item = (T::Item, Self::PubsubId)> + Send>>, Self::Error>
     where
         T: Topic + Sync;
@@ -97,6 +110,9 @@ pub trait Network: Send + Sync + 'static {
         K: AsRef<[u8]> + Send + Sync,
         V: Serialize + Send + Sync;
 
+    /// Sets the validator for DHT records. Networks that don't verify DHT records ignore it.
+    fn set_dht_record_validator(&self, _validator: Arc<dyn DhtRecordValidator>) {}
+
     async fn dial_peer(&self, peer_id: <Self::PeerType as Peer>::Id) -> Result<(), Self::Error>;
 
     async fn dial_address(&self, address: Self::AddressType) -> Result<(), Self::Error>;
@@ -106,28 +122,46 @@ pub trait Network: Send + Sync + 'static {
 
 // .next() To get next item of stream.
 
+/// Stream of the requests of type `R` from all peers.
+pub type ReceiveRequestsFromAll<R, P> = ReceiveFromAll<InboundRequest<R>, P>;
+
 /// A wrapper around `SelectAll` that automatically subscribes to new peers.
-pub struct ReceiveFromAll<T: Message, P> {
+pub struct ReceiveFromAll<T, P> {
     inner: SelectAll<Pin<Box<dyn Stream<Item = (T, Arc<P>)> + Send>>>,
     event_stream: Pin<Box<dyn FusedStream<Item = Result<NetworkEvent<P>, broadcast::RecvError>> + Send>>,
+    /// Subscribes to the items of a single peer.
+    receive: fn(&P) -> Pin<Box<dyn Stream<Item = T> + Send>>,
 }
 
 impl<T: Message, P: Peer + 'static> ReceiveFromAll<T, P> {
     pub fn new<N: Network<PeerType = P> + ?Sized>(network: &N) -> Self {
+        Self::with_receive(network, |peer| peer.receive::<T>())
+    }
+}
+
+impl<R: RequestResponse, P: Peer + 'static> ReceiveFromAll<InboundRequest<R>, P> {
+    pub fn requests<N: Network<PeerType = P> + ?Sized>(network: &N) -> Self {
+        Self::with_receive(network, |peer| peer.requests::<R>())
+    }
+}
+
+impl<T: Send + 'static, P: Peer + 'static> ReceiveFromAll<T, P> {
+    fn with_receive<N: Network<PeerType = P> + ?Sized>(network: &N, receive: fn(&P) -> Pin<Box<dyn Stream<Item = T> + Send>>) -> Self {
         let (peers, updates) = network.get_peer_updates();
         //log::trace!("peers = {:?}", peers.iter().map(|peer| peer.id()).collect::<Vec<_>>());
 
         ReceiveFromAll {
             inner: stream::select_all(peers.into_iter().map(|peer| {
                 let peer_inner = Arc::clone(&peer);
-                peer.receive::<T>().map(move |item| (item, Arc::clone(&peer_inner))).boxed()
+                receive(&peer).map(move |item| (item, Arc::clone(&peer_inner))).boxed()
             })),
             event_stream: Box::pin(updates.into_stream().fuse()),
+            receive,
         }
     }
 }
 
-impl<T: Message, P: Peer + 'static> Stream for ReceiveFromAll<T, P> {
+impl<T: Send + 'static, P: Peer + 'static> Stream for ReceiveFromAll<T, P> {
     type Item = (T, Arc<P>);
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
@@ -138,7 +172,8 @@ impl<T: Message, P: Peer + 'static> Stream for ReceiveFromAll<T, P> {
                     log::trace!("peers joined {:?}", peer.id());
                     // We have a new peer to receive from.
                     let peer_inner = Arc::clone(&peer);
-                    self.inner.push(peer.receive::<T>().map(move |item| (item, Arc::clone(&peer_inner))).boxed())
+                    let stream = (self.receive)(&peer);
+                    self.inner.push(stream.map(move |item| (item, Arc::clone(&peer_inner))).boxed())
                 }
                 #[allow(unreachable_patterns)]
                 Poll::Ready(Some(Ok(_))) => {} // Ignore others.
@@ -165,7 +200,7 @@ impl<T: Message, P: Peer + 'static> Stream for ReceiveFromAll<T, P> {
     }
 }
 
-impl<T: Message, P: Peer + 'static> FusedStream for ReceiveFromAll<T, P> {
+impl<T: Send + 'static, P: Peer + 'static> FusedStream for ReceiveFromAll<T, P> {
     fn is_terminated(&self) -> bool {
         self.event_stream.is_terminated()
     }
@@ -1,9 +1,10 @@
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::pin::Pin;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{channel::oneshot, Stream};
 use thiserror::Error;
 
 use beserial::{Deserialize, Serialize, SerializingError};
@@ -26,9 +27,69 @@ pub enum SendError {
     AlreadyClosed,
 }
 
-pub trait RequestResponse {
-    type Request: Serialize + Deserialize + Sync;
-    type Response: Serialize + Deserialize + Sync;
+/// A request type and the type of its response.
+pub trait RequestResponse: 'static {
+    type Request: Serialize + Deserialize + Send + Sync + Debug + 'static;
+    type Response: Serialize + Deserialize + Send + Sync + Debug + 'static;
+
+    /// Identifies the request type. This must be unique among all request types.
+    const TYPE_ID: u64;
+
+    /// Time after which an outbound request fails with `RequestError::Timeout`.
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Maximum number of requests of this type that can be in flight per peer, in each direction. Outbound requests
+    /// exceeding this fail with `RequestError::TooManyRequests`, inbound requests exceeding this are rejected.
+    const MAX_CONCURRENT_REQUESTS: usize = 16;
+}
+
+#[derive(Debug, Error)]
+pub enum RequestError {
+    #[error("Request timed out")]
+    Timeout,
+    #[error("Too many concurrent requests")]
+    TooManyRequests,
+    #[error("Peer doesn't handle this request type")]
+    Unsupported,
+    #[error("Peer didn't respond")]
+    NoResponse,
+    #[error("Peer connection closed")]
+    ConnectionClosed,
+    #[error("Invalid response")]
+    InvalidResponse,
+    #[error("{0}")]
+    Serialization(#[from] SerializingError),
+}
+
+/// A request received from a peer. The request is answered with [`InboundRequest::respond`]. If it is dropped
+/// without a response, the request fails with `RequestError::NoResponse` on the requesting side.
+pub struct InboundRequest<R: RequestResponse> {
+    pub request: R::Request,
+    response_tx: oneshot::Sender<Vec<u8>>,
+}
+
+impl<R: RequestResponse> InboundRequest<R> {
+    /// Creates an inbound request, whose serialized response is sent to `response_tx`.
+    pub fn new(request: R::Request, response_tx: oneshot::Sender<Vec<u8>>) -> Self {
+        Self { request, response_tx }
+    }
+
+    pub fn respond(self, response: &R::Response) -> Result<(), RequestError> {
+        let data = response.serialize_to_vec();
+        self.response_tx.send(data).map_err(|_| RequestError::ConnectionClosed)
+    }
+
+    /// Returns whether the requesting side isn't waiting for the response anymore, e.g. because the request timed
+    /// out or the peer disconnected.
+    pub fn is_cancelled(&self) -> bool {
+        self.response_tx.is_canceled()
+    }
+}
+
+impl<R: RequestResponse> Debug for InboundRequest<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("InboundRequest").field("type_id", &R::TYPE_ID).field("request", &self.request).finish()
+    }
 }
 
 #[async_trait]
@@ -55,7 +116,11 @@ pub trait Peer: Send + Sync + Hash + Eq {
 
     fn close(&self, ty: CloseReason);
 
-    async fn request<R: RequestResponse>(&self, request: &R::Request) -> Result<R::Response, Self::Error>;
+    /// Sends a request to the peer and waits for its response, at most for `R::TIMEOUT`. Dropping the returned future
+    /// cancels the request.
+    async fn request<R: RequestResponse>(&self, request: &R::Request) -> Result<R::Response, RequestError>;
 
-    fn requests<R: RequestResponse>(&self) -> Box<dyn Stream<Item = R::Request>>;
+    /// Receives the requests of type `R` from this peer. Should panic if there is already a non-closed stream of
+    /// requests of this type.
+    fn requests<R: RequestResponse>(&self) -> Pin<Box<dyn Stream<Item = InboundRequest<R>> + Send>>;
 }
@@ -0,0 +1,202 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+
+use nimiq_utils::rate_limit::RateLimit;
+
+#[derive(Clone, Debug, Default)]
+pub struct BandwidthConfig {
+    /// Maximum number of bytes that a peer may send us per `peer_rate_limit_period`. Peers exceeding this limit are
+    /// disconnected. If `None`, peers are not rate limited.
+    pub peer_rate_limit: Option<usize>,
+
+    /// Time period for the per-peer rate limit. Defaults to one minute.
+    pub peer_rate_limit_period: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrafficCounter {
+    pub bytes: u64,
+    pub messages: u64,
+}
+
+impl TrafficCounter {
+    fn record(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+        self.messages += 1;
+    }
+}
+
+/// Inbound and outbound traffic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Traffic {
+    pub inbound: TrafficCounter,
+    pub outbound: TrafficCounter,
+}
+
+/// Snapshot of the traffic totals.
+#[derive(Clone, Debug, Default)]
+pub struct BandwidthStats {
+    /// Traffic of all messages and gossip.
+    pub total: Traffic,
+
+    /// Traffic per connected peer.
+    pub peers: HashMap<PeerId, Traffic>,
+
+    /// Traffic per message `TYPE_ID`.
+    pub message_types: HashMap<u64, Traffic>,
+
+    /// Traffic per gossip topic.
+    pub topics: HashMap<String, Traffic>,
+}
+
+#[derive(Default)]
+struct Inner {
+    stats: BandwidthStats,
+    rate_limits: HashMap<PeerId, RateLimit>,
+}
+
+/// Meters the bytes and number of messages that we send and receive, per peer, per message type and per gossip topic.
+/// The sizes are the sizes of the messages on the wire, i.e. after compression, but excluding the transport's
+/// overhead.
+#[derive(Default)]
+pub struct BandwidthMeter {
+    config: BandwidthConfig,
+    inner: Mutex<Inner>,
+}
+
+impl BandwidthMeter {
+    pub fn new(config: BandwidthConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::default(),
+        }
+    }
+
+    /// Records a message received from `peer_id`. Returns `false`, if the peer exceeded its rate limit.
+    pub fn record_inbound_message(&self, peer_id: &PeerId, type_id: u64, bytes: usize) -> bool {
+        let mut inner = self.inner.lock();
+
+        inner.stats.total.inbound.record(bytes);
+        inner.stats.peers.entry(peer_id.clone()).or_default().inbound.record(bytes);
+        inner.stats.message_types.entry(type_id).or_default().inbound.record(bytes);
+
+        self.check_rate_limit(&mut inner, peer_id, bytes)
+    }
+
+    /// Records a message sent to `peer_id`.
+    pub fn record_outbound_message(&self, peer_id: &PeerId, type_id: u64, bytes: usize) {
+        let mut inner = self.inner.lock();
+
+        inner.stats.total.outbound.record(bytes);
+        inner.stats.peers.entry(peer_id.clone()).or_default().outbound.record(bytes);
+        inner.stats.message_types.entry(type_id).or_default().outbound.record(bytes);
+    }
+
+    /// Records a gossip message on `topic` that was propagated to us by `peer_id`. Returns `false`, if the peer
+    /// exceeded its rate limit.
+    pub fn record_inbound_gossip(&self, peer_id: &PeerId, topic: &str, bytes: usize) -> bool {
+        let mut inner = self.inner.lock();
+
+        inner.stats.total.inbound.record(bytes);
+        inner.stats.peers.entry(peer_id.clone()).or_default().inbound.record(bytes);
+        inner.stats.topics.entry(topic.to_owned()).or_default().inbound.record(bytes);
+
+        self.check_rate_limit(&mut inner, peer_id, bytes)
+    }
+
+    /// Records a gossip message that we published on `topic`. Gossipsub decides to which peers it is sent, so this is
+    /// only accounted per topic.
+    pub fn record_outbound_gossip(&self, topic: &str, bytes: usize) {
+        let mut inner = self.inner.lock();
+
+        inner.stats.total.outbound.record(bytes);
+        inner.stats.topics.entry(topic.to_owned()).or_default().outbound.record(bytes);
+    }
+
+    /// Removes the per-peer traffic and rate limit of a disconnected peer. The totals are kept.
+    pub fn remove_peer(&self, peer_id: &PeerId) {
+        let mut inner = self.inner.lock();
+
+        inner.stats.peers.remove(peer_id);
+        inner.rate_limits.remove(peer_id);
+    }
+
+    pub fn stats(&self) -> BandwidthStats {
+        self.inner.lock().stats.clone()
+    }
+
+    fn check_rate_limit(&self, inner: &mut Inner, peer_id: &PeerId, bytes: usize) -> bool {
+        let limit = match self.config.peer_rate_limit {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let period = self.config.peer_rate_limit_period;
+
+        inner
+            .rate_limits
+            .entry(peer_id.clone())
+            .or_insert_with(|| match period {
+                Some(period) => RateLimit::new(limit, period),
+                None => RateLimit::new_per_minute(limit),
+            })
+            .note(bytes)
+    }
+}
+
+/// A [`BandwidthMeter`] together with the peer whose messages are recorded.
+#[derive(Clone)]
+pub struct PeerBandwidthMeter {
+    pub peer_id: PeerId,
+    pub meter: Arc<BandwidthMeter>,
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::PeerId;
+
+    use super::{BandwidthConfig, BandwidthMeter, TrafficCounter};
+
+    #[test]
+    fn it_meters_traffic() {
+        let meter = BandwidthMeter::default();
+        let peer_id = PeerId::random();
+
+        assert!(meter.record_inbound_message(&peer_id, 1, 100));
+        assert!(meter.record_inbound_message(&peer_id, 2, 50));
+        meter.record_outbound_message(&peer_id, 1, 20);
+        assert!(meter.record_inbound_gossip(&peer_id, "blocks", 30));
+        meter.record_outbound_gossip("blocks", 10);
+
+        let stats = meter.stats();
+        assert_eq!(stats.total.inbound, TrafficCounter { bytes: 180, messages: 3 });
+        assert_eq!(stats.total.outbound, TrafficCounter { bytes: 30, messages: 2 });
+        assert_eq!(stats.peers[&peer_id].inbound.bytes, 180);
+        assert_eq!(stats.peers[&peer_id].outbound.bytes, 20);
+        assert_eq!(stats.message_types[&1].inbound, TrafficCounter { bytes: 100, messages: 1 });
+        assert_eq!(stats.message_types[&1].outbound, TrafficCounter { bytes: 20, messages: 1 });
+        assert_eq!(stats.topics["blocks"].inbound.bytes, 30);
+        assert_eq!(stats.topics["blocks"].outbound.bytes, 10);
+
+        meter.remove_peer(&peer_id);
+        let stats = meter.stats();
+        assert!(stats.peers.is_empty());
+        assert_eq!(stats.total.inbound.bytes, 180);
+    }
+
+    #[test]
+    fn it_rate_limits_peers() {
+        let meter = BandwidthMeter::new(BandwidthConfig {
+            peer_rate_limit: Some(100),
+            peer_rate_limit_period: None,
+        });
+        let peer_1 = PeerId::random();
+        let peer_2 = PeerId::random();
+
+        assert!(meter.record_inbound_message(&peer_1, 1, 60));
+        assert!(meter.record_inbound_gossip(&peer_1, "blocks", 40));
+        assert!(!meter.record_inbound_message(&peer_1, 1, 1));
+        assert!(meter.record_inbound_message(&peer_2, 1, 100));
+    }
+}
@@ -1,20 +1,29 @@
 use std::collections::VecDeque;
+use std::iter;
 use std::sync::Arc;
 use std::task::{Context, Poll, Waker};
 
 use libp2p::{
     core::either::{EitherError, EitherOutput},
     gossipsub::{Gossipsub, GossipsubEvent, GossipsubRpc, MessageAuthenticity},
-    kad::{handler::KademliaHandlerIn as KademliaAction, store::MemoryStore, Kademlia, KademliaEvent, QueryId},
-    swarm::{NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters},
+    kad::{handler::KademliaHandlerIn as KademliaAction, Kademlia, KademliaEvent, QueryId},
+    request_response::{
+        handler::RequestProtocol as RequestAction, ProtocolSupport, RequestResponse, RequestResponseConfig, RequestResponseEvent,
+    },
+    swarm::{NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters, ProtocolsHandlerUpgrErr},
     NetworkBehaviour,
 };
 use parking_lot::RwLock;
 
-use nimiq_network_interface::network::NetworkEvent;
+use nimiq_network_interface::{
+    network::NetworkEvent,
+    peer::{CloseReason, Peer as PeerInterface},
+};
 use nimiq_utils::time::OffsetTime;
 
 use crate::{
+    bandwidth::BandwidthMeter,
+    dht::DhtStore,
     discovery::{
         behaviour::{DiscoveryBehaviour, DiscoveryEvent},
         handler::{HandlerError as DiscoveryError, HandlerInEvent as DiscoveryAction},
@@ -30,15 +39,23 @@ use crate::{
         peer::Peer,
     },
     network::Config,
+    request_response::{
+        dispatch::PendingRequests, Frame, RequestCodec, RequestDispatch, RequestResponseProtocol,
+    },
 };
 
 pub type NimiqNetworkBehaviourAction = NetworkBehaviourAction<
-    EitherOutput<EitherOutput<EitherOutput<EitherOutput<DiscoveryAction, MessageAction>, LimitAction>, KademliaAction<QueryId>>, GossipsubRpc>,
+    EitherOutput<
+        EitherOutput<EitherOutput<EitherOutput<EitherOutput<DiscoveryAction, MessageAction>, LimitAction>, KademliaAction<QueryId>>, GossipsubRpc>,
+        RequestAction<RequestCodec>,
+    >,
     NimiqEvent,
 >;
 
-pub type NimiqNetworkBehaviourError =
-    EitherError<EitherError<EitherError<EitherError<DiscoveryError, MessageError>, LimitError>, std::io::Error>, std::io::Error>;
+pub type NimiqNetworkBehaviourError = EitherError<
+    EitherError<EitherError<EitherError<EitherError<DiscoveryError, MessageError>, LimitError>, std::io::Error>, std::io::Error>,
+    ProtocolsHandlerUpgrErr<std::io::Error>,
+>;
 
 #[derive(Debug)]
 pub enum NimiqEvent {
@@ -71,8 +88,15 @@ pub struct NimiqBehaviour {
     pub discovery: DiscoveryBehaviour,
     pub message: MessageBehaviour,
     pub limit: LimitBehaviour,
-    pub kademlia: Kademlia<MemoryStore>,
+    pub kademlia: Kademlia<DhtStore>,
     pub gossipsub: Gossipsub,
+    pub request_response: RequestResponse<RequestCodec>,
+
+    #[behaviour(ignore)]
+    pub bandwidth: Arc<BandwidthMeter>,
+
+    #[behaviour(ignore)]
+    pub(crate) requests: PendingRequests,
 
     #[behaviour(ignore)]
     events: VecDeque<NimiqEvent>,
@@ -86,30 +110,56 @@ impl NimiqBehaviour {
         let public_key = config.keypair.public();
         let peer_id = public_key.into_peer_id();
 
-        // TODO: persist to disk
-        let peer_contact_book = Arc::new(RwLock::new(PeerContactBook::new(Default::default(), config.peer_contact.sign(&config.keypair))));
+        #[allow(unused_mut)]
+        let mut peer_contact_book = PeerContactBook::new(config.peer_contact_book, config.peer_contact.sign(&config.keypair));
+        #[cfg(feature = "peer-contact-book-persistence")]
+        if let Err(e) = peer_contact_book.load() {
+            log::warn!("Failed to load peer contacts: {}", e);
+        }
+        let peer_contact_book = Arc::new(RwLock::new(peer_contact_book));
         let discovery = DiscoveryBehaviour::new(config.discovery, config.keypair.clone(), peer_contact_book, clock);
 
-        let message = MessageBehaviour::new(config.message);
+        let bandwidth = Arc::new(BandwidthMeter::new(config.bandwidth));
+        let (request_dispatch, outbound_requests) = RequestDispatch::new();
+        let message = MessageBehaviour::new(config.message, Arc::clone(&bandwidth), Arc::clone(&request_dispatch));
 
         let limit = LimitBehaviour::new(config.limit);
 
-        let store = MemoryStore::new(peer_id.clone());
-        let kademlia = Kademlia::with_config(peer_id, store, config.kademlia);
+        // We republish our own records ourselves, see `Network::republish_records`.
+        let mut kademlia_config = config.kademlia;
+        kademlia_config.set_record_ttl(Some(config.dht.record_ttl));
+        kademlia_config.set_publication_interval(None);
+
+        let store = DhtStore::new(peer_id.clone(), config.dht.path, config.dht_record_validator);
+        let kademlia = Kademlia::with_config(peer_id, store, kademlia_config);
         let gossipsub = Gossipsub::new(MessageAuthenticity::Signed(config.keypair), config.gossipsub);
 
+        let mut request_response_config = RequestResponseConfig::default();
+        request_response_config.set_request_timeout(config.requests.timeout);
+        let request_response = RequestResponse::new(
+            RequestCodec::new(config.requests.max_size),
+            iter::once((RequestResponseProtocol, ProtocolSupport::Full)),
+            request_response_config,
+        );
+        let requests = PendingRequests::new(request_dispatch, outbound_requests, Arc::clone(&bandwidth), config.requests);
+
         Self {
             discovery,
             message,
             limit,
             kademlia,
             gossipsub,
+            request_response,
+            bandwidth,
+            requests,
             events: VecDeque::new(),
             waker: None,
         }
     }
 
     fn poll_event(&mut self, cx: &mut Context, _params: &mut impl PollParameters) -> Poll<NimiqNetworkBehaviourAction> {
+        self.requests.poll(cx, &mut self.request_response);
+
         if let Some(event) = self.events.pop_front() {
             log::trace!("NimiqBehaviour: emitting event: {:?}", event);
             return Poll::Ready(NetworkBehaviourAction::GenerateEvent(event));
@@ -186,3 +236,17 @@ impl NetworkBehaviourEventProcess<GossipsubEvent> for NimiqBehaviour {
         self.emit_event(event);
     }
 }
+
+impl NetworkBehaviourEventProcess<RequestResponseEvent<Frame, Frame>> for NimiqBehaviour {
+    fn inject_event(&mut self, event: RequestResponseEvent<Frame, Frame>) {
+        log::trace!("NimiqBehaviour::request_response_event: {:?}", event);
+        self.requests.inject_event(event, &mut self.request_response);
+
+        // Peers exceeding their rate limit with requests are disconnected, as if they sent too many messages.
+        for peer_id in self.requests.take_rate_limited() {
+            if let Some(peer) = self.message.peers.get_peer(&peer_id) {
+                peer.close(CloseReason::Other);
+            }
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use libp2p::{
+    kad::{
+        record::Key,
+        store::{Error, MemoryStore, RecordStore, Result},
+        ProviderRecord, Record,
+    },
+    PeerId,
+};
+use parking_lot::RwLock;
+use thiserror::Error;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_network_interface::network::DhtRecordValidator;
+
+use crate::persistence::write_atomically;
+
+/// Shared handle to the validator that is used to check DHT records.
+pub type DhtRecordValidatorHandle = Arc<RwLock<Option<Arc<dyn DhtRecordValidator>>>>;
+
+#[derive(Clone, Debug)]
+pub struct DhtConfig {
+    /// Time after which records expire, if they're not republished.
+    pub record_ttl: Duration,
+
+    /// Interval in which we republish our own records. This must be shorter than `record_ttl`.
+    pub republish_interval: Duration,
+
+    /// File in which the DHT records are persisted. If `None`, records are only kept in memory.
+    pub path: Option<PathBuf>,
+}
+
+impl Default for DhtConfig {
+    fn default() -> Self {
+        Self {
+            record_ttl: Duration::from_secs(60 * 60 * 6),
+            republish_interval: Duration::from_secs(60 * 60),
+            path: None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DhtStoreError {
+    #[error("Invalid DHT record")]
+    InvalidRecord,
+
+    #[error("Record store error: {0:?}")]
+    Store(Error),
+}
+
+impl From<Error> for DhtStoreError {
+    fn from(e: Error) -> Self {
+        Self::Store(e)
+    }
+}
+
+/// A record as it's stored on disk.
+#[derive(Serialize, Deserialize)]
+struct PersistedRecord {
+    #[beserial(len_type(u16))]
+    key: Vec<u8>,
+
+    #[beserial(len_type(u32))]
+    value: Vec<u8>,
+
+    publisher: Option<PeerId>,
+
+    /// Expiry in *seconds* since unix epoch.
+    expires: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedRecords {
+    #[beserial(len_type(u32))]
+    records: Vec<PersistedRecord>,
+}
+
+/// Record store for Kademlia, that checks records with the [`DhtRecordValidator`] before they're stored. If a record
+/// for a key already exists, it is only replaced by a valid record with a higher timestamp.
+///
+/// The records can optionally be persisted to disk. Persisted records are checked by the validator when they're loaded.
+pub struct DhtStore {
+    inner: MemoryStore,
+
+    validator: DhtRecordValidatorHandle,
+
+    path: Option<PathBuf>,
+}
+
+impl DhtStore {
+    pub fn new(local_peer_id: PeerId, path: Option<PathBuf>, validator: Option<Arc<dyn DhtRecordValidator>>) -> Self {
+        let mut store = Self {
+            inner: MemoryStore::new(local_peer_id),
+            validator: Arc::new(RwLock::new(validator)),
+            path,
+        };
+
+        if let Err(e) = store.load() {
+            log::warn!("Failed to load DHT records: {}", e);
+        }
+
+        store
+    }
+
+    /// Returns the handle to the record validator, such that it can be set later.
+    pub fn validator(&self) -> DhtRecordValidatorHandle {
+        Arc::clone(&self.validator)
+    }
+
+    /// Validates a record. Returns `Some(timestamp)` if the record is valid. If no validator is set, all records are
+    /// valid with timestamp 0.
+    pub fn validate(&self, record: &Record) -> Option<u64> {
+        match self.validator.read().as_ref() {
+            Some(validator) => validator.validate(record.key.as_ref(), &record.value),
+            None => Some(0),
+        }
+    }
+
+    /// Picks the valid record with the highest timestamp.
+    pub fn select_record(&self, records: Vec<Record>) -> Option<Record> {
+        records
+            .into_iter()
+            .filter_map(|record| self.validate(&record).map(|timestamp| (timestamp, record)))
+            .max_by_key(|(timestamp, _)| *timestamp)
+            .map(|(_, record)| record)
+    }
+
+    /// Inserts a record, if it's valid. If a record for the key already exists, it's only replaced if the new record
+    /// isn't older.
+    pub fn insert(&mut self, record: Record) -> std::result::Result<(), DhtStoreError> {
+        let timestamp = self.validate(&record).ok_or(DhtStoreError::InvalidRecord)?;
+
+        // Only replace an existing record by a newer one.
+        if let Some(existing) = self.inner.get(&record.key) {
+            if let Some(existing_timestamp) = self.validate(&existing) {
+                if existing_timestamp > timestamp {
+                    log::debug!("Ignoring outdated DHT record: key={:?}", record.key);
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(self.inner.put(record)?)
+    }
+
+    /// Loads the records from disk. Expired and invalid records are skipped.
+    fn load(&mut self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) if path.exists() => path.clone(),
+            _ => return Ok(()),
+        };
+
+        let data = std::fs::read(&path)?;
+        let persisted: PersistedRecords =
+            Deserialize::deserialize_from_vec(&data).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let mut num_loaded = 0;
+
+        for record in persisted.records {
+            let expires = match record.expires {
+                Some(expires) => match Duration::from_secs(expires).checked_sub(now) {
+                    Some(remaining) => Some(Instant::now() + remaining),
+                    None => continue,
+                },
+                None => None,
+            };
+
+            let record = Record {
+                key: Key::from(record.key),
+                value: record.value,
+                publisher: record.publisher,
+                expires,
+            };
+
+            match self.insert(record) {
+                Ok(()) => num_loaded += 1,
+                Err(e) => log::debug!("Skipping persisted DHT record: {}", e),
+            }
+        }
+
+        log::info!("Loaded {} DHT records from {}", num_loaded, path.display());
+
+        Ok(())
+    }
+
+    /// Saves the records to disk, if a path is configured.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let now = Instant::now();
+        let unix_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+        let persisted = PersistedRecords {
+            records: self
+                .inner
+                .records()
+                .filter(|record| !record.is_expired(now))
+                .map(|record| PersistedRecord {
+                    key: record.key.to_vec(),
+                    value: record.value.clone(),
+                    publisher: record.publisher.clone(),
+                    expires: record.expires.map(|expires| (unix_time + expires.saturating_duration_since(now)).as_secs()),
+                })
+                .collect(),
+        };
+
+        write_atomically(path, persisted.serialize_to_vec())
+    }
+}
+
+impl<'a> RecordStore<'a> for DhtStore {
+    type RecordsIter = <MemoryStore as RecordStore<'a>>::RecordsIter;
+    type ProvidedIter = <MemoryStore as RecordStore<'a>>::ProvidedIter;
+
+    fn get(&'a self, k: &Key) -> Option<Cow<'_, Record>> {
+        self.inner.get(k)
+    }
+
+    fn put(&'a mut self, record: Record) -> Result<()> {
+        let key = record.key.clone();
+        match self.insert(record) {
+            Ok(()) => Ok(()),
+            Err(DhtStoreError::InvalidRecord) => {
+                log::debug!("Rejecting invalid DHT record: key={:?}", key);
+                // Kademlia only needs to know that the record wasn't stored, and its error type can't be extended.
+                // Our own callers use `insert`, which reports invalid records as such.
+                Err(Error::ValueTooLarge)
+            }
+            Err(DhtStoreError::Store(e)) => Err(e),
+        }
+    }
+
+    fn remove(&'a mut self, k: &Key) {
+        self.inner.remove(k)
+    }
+
+    fn records(&'a self) -> Self::RecordsIter {
+        self.inner.records()
+    }
+
+    fn add_provider(&'a mut self, record: ProviderRecord) -> Result<()> {
+        self.inner.add_provider(record)
+    }
+
+    fn providers(&'a self, key: &Key) -> Vec<ProviderRecord> {
+        self.inner.providers(key)
+    }
+
+    fn provided(&'a self) -> Self::ProvidedIter {
+        self.inner.provided()
+    }
+
+    fn remove_provider(&'a mut self, k: &Key, p: &PeerId) {
+        self.inner.remove_provider(k, p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    /// Accepts records whose value is an 8 byte timestamp.
+    struct TimestampValidator;
+
+    impl DhtRecordValidator for TimestampValidator {
+        fn validate(&self, _key: &[u8], value: &[u8]) -> Option<u64> {
+            Some(u64::from_be_bytes(value.try_into().ok()?))
+        }
+    }
+
+    fn record(timestamp: u64) -> Record {
+        Record::new(b"key".to_vec(), timestamp.to_be_bytes().to_vec())
+    }
+
+    fn store(path: Option<PathBuf>) -> DhtStore {
+        DhtStore::new(PeerId::random(), path, Some(Arc::new(TimestampValidator)))
+    }
+
+    #[test]
+    fn it_rejects_invalid_and_outdated_records() {
+        let mut store = store(None);
+
+        assert!(matches!(
+            store.insert(Record::new(b"key".to_vec(), b"invalid".to_vec())),
+            Err(DhtStoreError::InvalidRecord)
+        ));
+        assert!(store.put(Record::new(b"key".to_vec(), b"invalid".to_vec())).is_err());
+        assert!(store.get(&Key::new(b"key")).is_none());
+
+        store.put(record(2)).unwrap();
+        store.put(record(1)).unwrap();
+        assert_eq!(store.get(&Key::new(b"key")).unwrap().value, record(2).value);
+
+        store.put(record(3)).unwrap();
+        assert_eq!(store.get(&Key::new(b"key")).unwrap().value, record(3).value);
+    }
+
+    #[test]
+    fn it_selects_the_newest_valid_record() {
+        let store = store(None);
+
+        let records = vec![record(1), Record::new(b"key".to_vec(), vec![0xff; 3]), record(5), record(3)];
+        assert_eq!(store.select_record(records).unwrap().value, record(5).value);
+        assert!(store.select_record(vec![]).is_none());
+    }
+
+    #[test]
+    fn it_persists_records() {
+        let dir = TempDir::new("dht").unwrap();
+        let path = dir.path().join("dht_records.dat");
+
+        let mut expired = Record::new(b"expired".to_vec(), 1u64.to_be_bytes().to_vec());
+        expired.expires = Some(Instant::now());
+
+        let mut store = store(Some(path.clone()));
+        store.put(record(7)).unwrap();
+        store.put(expired).unwrap();
+        store.save().unwrap();
+
+        let loaded = self::store(Some(path));
+        assert_eq!(loaded.get(&Key::new(b"key")).unwrap().value, record(7).value);
+        assert!(loaded.get(&Key::new(b"expired")).is_none());
+    }
+
+    #[test]
+    fn it_validates_persisted_records() {
+        let dir = TempDir::new("dht").unwrap();
+        let path = dir.path().join("dht_records.dat");
+
+        // Without a validator, all records are accepted.
+        let mut store = DhtStore::new(PeerId::random(), Some(path.clone()), None);
+        store.put(record(7)).unwrap();
+        store.put(Record::new(b"invalid".to_vec(), b"invalid".to_vec())).unwrap();
+        store.save().unwrap();
+
+        let loaded = self::store(Some(path));
+        assert_eq!(loaded.get(&Key::new(b"key")).unwrap().value, record(7).value);
+        assert!(loaded.get(&Key::new(b"invalid")).is_none());
+    }
+}
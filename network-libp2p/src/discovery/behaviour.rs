@@ -139,11 +139,18 @@ impl NetworkBehaviour for DiscoveryBehaviour {
     }
 
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
-        self.peer_contact_book
+        let mut addresses: Vec<Multiaddr> = self
+            .peer_contact_book
             .read()
             .get(peer_id)
             .map(|addresses_opt| addresses_opt.addresses().cloned().collect())
-            .unwrap_or_default()
+            .unwrap_or_default();
+
+        // Prefer plain TCP, since it doesn't have the overhead of websocket framing. The swarm tries the addresses in
+        // this order.
+        addresses.sort_by_key(|address| !Protocols::from_multiaddr(address).contains(Protocols::TCP));
+
+        addresses
     }
 
     fn inject_connected(&mut self, peer_id: &PeerId) {
@@ -164,6 +171,8 @@ impl NetworkBehaviour for DiscoveryBehaviour {
         log::debug!("  - connection_id: {:?}", connection_id);
         log::debug!("  - connected_point: {:?}", connected_point);
 
+        self.peer_contact_book.read().connection_established(peer_id);
+
         // TODO: In libp2p 0.29 there is a method for this:
         // connected_point.get_remote_address()
         let remote_address = match connected_point {
@@ -178,6 +187,16 @@ impl NetworkBehaviour for DiscoveryBehaviour {
         });
     }
 
+    fn inject_connection_closed(&mut self, peer_id: &PeerId, _connection_id: &ConnectionId, _connected_point: &ConnectedPoint) {
+        self.peer_contact_book.read().connection_closed(peer_id);
+    }
+
+    fn inject_dial_failure(&mut self, peer_id: &PeerId) {
+        log::debug!("DiscoveryBehaviour::inject_dial_failure: {}", peer_id);
+
+        self.peer_contact_book.read().connection_failed(peer_id);
+    }
+
     fn inject_event(&mut self, peer_id: PeerId, _connection: ConnectionId, event: HandlerOutEvent) {
         log::debug!("DiscoveryBehaviour::inject_event: peer_id={}: {:?}", peer_id, event);
 
@@ -209,6 +228,10 @@ impl NetworkBehaviour for DiscoveryBehaviour {
                 let mut peer_address_book = self.peer_contact_book.write();
                 peer_address_book.self_update(&self.keypair);
                 peer_address_book.house_keeping();
+                #[cfg(feature = "peer-contact-book-persistence")]
+                if let Err(e) = peer_address_book.save() {
+                    log::warn!("Failed to save peer contacts: {}", e);
+                }
             }
             Poll::Ready(None) => unreachable!(),
             Poll::Pending => {}
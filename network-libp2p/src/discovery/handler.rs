@@ -115,6 +115,13 @@ pub struct DiscoveryHandler {
     /// The addresses which we observed for the other peer.
     observed_addresses: Vec<Multiaddr>,
 
+    /// The addresses which the other peer observed for us. These are only added to the peer contact book once the
+    /// peer's identity is verified.
+    self_observed_addresses: Vec<Multiaddr>,
+
+    /// Time when we sent our handshake. Used to measure the latency to the other peer.
+    handshake_sent_time: Option<Instant>,
+
     /// The challenge nonce we send to this peer.
     challenge_nonce: ChallengeNonce,
 
@@ -154,6 +161,8 @@ impl DiscoveryHandler {
             peer_contact_book,
             peer_contact: None,
             observed_addresses: vec![],
+            self_observed_addresses: vec![],
+            handshake_sent_time: None,
             challenge_nonce: ChallengeNonce::generate(),
             state: HandlerState::Init,
             services_filter: Services::empty(),
@@ -177,6 +186,8 @@ impl DiscoveryHandler {
 
     /// Get peer contacts from our contact book to send to this peer. The contacts are filtered according to the peer's
     /// protocols and service filters, they are limited to the number of peers specified by the peer.
+    ///
+    /// The contacts are chosen randomly from the best scoring contacts, such that not every peer receives the same set.
     fn get_peer_contacts(&self, peer_contact_book: &PeerContactBook) -> Vec<SignedPeerContact> {
         let n = self.peer_list_limit.unwrap() as usize;
 
@@ -184,6 +195,7 @@ impl DiscoveryHandler {
 
         peer_contact_book
             .query(self.protocols_filter, self.services_filter)
+            .take(2 * n)
             .choose_multiple(&mut rng, n)
             .into_iter()
             .map(|c| c.signed().clone())
@@ -322,6 +334,7 @@ impl ProtocolsHandler for DiscoveryHandler {
                         return Poll::Ready(ProtocolsHandlerEvent::Close(e.into()));
                     }
 
+                    self.handshake_sent_time = Some(Instant::now());
                     self.state = HandlerState::ReceiveHandshake;
                 }
 
@@ -349,8 +362,9 @@ impl ProtocolsHandler for DiscoveryHandler {
                                         }));
                                     }
 
-                                    // Update our own peer contact given the observed addresses we received
-                                    peer_contact_book.self_add_addresses(observed_addresses.clone());
+                                    // Remember the observed addresses we received. We add them to our own peer contact,
+                                    // once we verified the identity of the peer.
+                                    self.self_observed_addresses = observed_addresses.clone();
 
                                     // Send the HandshakeAck
                                     let response_signature = self.keypair.tagged_sign(&challenge_nonce);
@@ -415,8 +429,19 @@ impl ProtocolsHandler for DiscoveryHandler {
                                     let mut peer_contact_book = self.peer_contact_book.write();
 
                                     // Insert the peer into the peer contact book.
+                                    let peer_id = peer_contact.inner.peer_id();
                                     peer_contact_book.insert_filtered(peer_contact.clone(), self.config.protocols_filter, self.config.services_filter);
 
+                                    // The peer sends its HandshakeAck after receiving our handshake, so this is roughly
+                                    // the round-trip time.
+                                    if let Some(handshake_sent_time) = self.handshake_sent_time {
+                                        peer_contact_book.set_latency(&peer_id, handshake_sent_time.elapsed());
+                                    }
+
+                                    // Update our own peer contact given the observed addresses we received.
+                                    let self_observed_addresses = std::mem::take(&mut self.self_observed_addresses);
+                                    peer_contact_book.self_add_addresses(self_observed_addresses, &peer_id, &self.keypair);
+
                                     // Insert the initial set of peer contacts into the peer contact book.
                                     // TODO: This doesn't actually filter and just assumes the peer already filtered.
                                     peer_contact_book.insert_all(peer_contacts);
@@ -1,7 +1,8 @@
 use std::{
     collections::{HashMap, HashSet},
+    path::PathBuf,
     sync::Arc,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use bitflags::bitflags;
@@ -11,17 +12,28 @@ use libp2p::{
     Multiaddr, PeerId,
 };
 use parking_lot::RwLock;
+use thiserror::Error;
 
 use beserial::{Deserialize, Serialize};
 
 use nimiq_utils::tagged_signing::{TaggedKeypair, TaggedSignable, TaggedSignature};
 
+#[cfg(feature = "peer-contact-book-persistence")]
+use crate::persistence::write_atomically;
+
 /// Configuration for the peer contact book.
 #[derive(Clone, Debug)]
 pub struct PeerContactBookConfig {
     pub max_age_websocket: Duration,
     pub max_age_webrtc: Duration,
     pub max_age_dumb: Duration,
+
+    /// File to which the peer contacts are persisted. Only used with the `peer-contact-book-persistence` feature. The
+    /// contacts are saved on every house keeping and when the network shuts down.
+    pub path: Option<PathBuf>,
+
+    /// Number of distinct peers that need to report an observed address for us, before we advertise it.
+    pub min_address_confirmations: usize,
 }
 
 impl Default for PeerContactBookConfig {
@@ -30,14 +42,30 @@ impl Default for PeerContactBookConfig {
             max_age_websocket: Duration::from_secs(60 * 30),
             max_age_webrtc: Duration::from_secs(60 * 15),
             max_age_dumb: Duration::from_secs(60),
+            path: None,
+            min_address_confirmations: 3,
         }
     }
 }
 
+#[derive(Debug, Error)]
+pub enum PeerContactBookError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "peer-contact-book-persistence")]
+    #[error("Failed to serialize peer contacts: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    #[cfg(feature = "peer-contact-book-persistence")]
+    #[error("Failed to deserialize peer contacts: {0}")]
+    Deserialize(#[from] toml::de::Error),
+}
+
 impl PeerContactBookConfig {
     /// Returns the max age for this protocol
     pub fn protocols_max_age(&self, protocols: Protocols) -> Duration {
-        if protocols.contains(Protocols::WS) || protocols.contains(Protocols::WSS) {
+        if protocols.intersects(Protocols::WS | Protocols::WSS | Protocols::TCP) {
             self.max_age_websocket
         } else if protocols.contains(Protocols::RTC) {
             self.max_age_webrtc
@@ -139,6 +167,9 @@ bitflags! {
         /// WebRTC
         const RTC = 1 << 2;
 
+        /// Plain TCP (with noise and yamux on top, like all other transports)
+        const TCP = 1 << 3;
+
         /// Memory transport (for testing)
         #[cfg(test)]
         const MEM = 1 << 31;
@@ -165,6 +196,7 @@ impl Protocols {
         match protocol {
             Protocol::Ws(_) => Self::WS,
             Protocol::Wss(_) => Self::WSS,
+            Protocol::Tcp(_) => Self::TCP,
             #[cfg(test)]
             Protocol::Memory(_) => Self::MEM,
             _ => Self::empty(),
@@ -270,10 +302,24 @@ impl SignedPeerContact {
 }
 
 /// Meta information attached to peer contact info objects. This are meant to be mutable and change over time.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "peer-contact-book-persistence", derive(serde::Serialize, serde::Deserialize))]
 struct PeerContactMeta {
-    score: f32,
+    /// Number of connections to this peer that were established successfully.
+    successful_connections: u32,
+
+    /// Number of failed attempts to connect to this peer.
+    failed_connections: u32,
+
+    /// Round-trip time measured during the last discovery handshake with this peer.
+    latency: Option<Duration>,
+
+    /// Accumulated time of all closed connections to this peer.
+    uptime: Duration,
+
+    /// Time when the current connection to this peer was established.
+    #[cfg_attr(feature = "peer-contact-book-persistence", serde(skip))]
+    connected_since: Option<Instant>,
 
     /// The peers that sent us this contact. Used for scoring.
     ///
@@ -311,16 +357,26 @@ impl From<SignedPeerContact> for PeerContactInfo {
             peer_id,
             contact,
             protocols,
-            meta: RwLock::new(PeerContactMeta {
-                score: 0.,
-
-                reported_by: HashSet::new(),
-            }),
+            meta: RwLock::new(PeerContactMeta::default()),
         }
     }
 }
 
 impl PeerContactInfo {
+    /// Weight of the ratio of successful connections in the score.
+    const SCORE_WEIGHT_CONNECTIONS: f32 = 0.4;
+    /// Weight of the latency in the score.
+    const SCORE_WEIGHT_LATENCY: f32 = 0.2;
+    /// Weight of the uptime in the score.
+    const SCORE_WEIGHT_UPTIME: f32 = 0.2;
+    /// Weight of the matching services in the score.
+    const SCORE_WEIGHT_SERVICES: f32 = 0.2;
+
+    /// Latency at which a peer doesn't get any latency score anymore.
+    const MAX_SCORED_LATENCY: Duration = Duration::from_secs(1);
+    /// Uptime at which a peer gets the full uptime score.
+    const MAX_SCORED_UPTIME: Duration = Duration::from_secs(60 * 60);
+
     /// Short-hand for the plain [`PeerContact`]
     pub fn contact(&self) -> &PeerContact {
         &self.contact.inner
@@ -393,6 +449,84 @@ impl PeerContactInfo {
     pub fn matches(&self, protocols: Protocols, services: Services) -> bool {
         self.protocols.intersects(protocols) && self.services().intersects(services)
     }
+
+    /// Returns the number of successfully established connections to this peer.
+    pub fn successful_connections(&self) -> u32 {
+        self.meta.read().successful_connections
+    }
+
+    /// Returns the number of failed attempts to connect to this peer.
+    pub fn failed_connections(&self) -> u32 {
+        self.meta.read().failed_connections
+    }
+
+    /// Returns the last measured latency of this peer.
+    pub fn latency(&self) -> Option<Duration> {
+        self.meta.read().latency
+    }
+
+    /// Returns the total time we were connected to this peer, including the current connection.
+    pub fn uptime(&self) -> Duration {
+        let meta = self.meta.read();
+        meta.uptime + meta.connected_since.map(|since| since.elapsed()).unwrap_or_default()
+    }
+
+    /// Returns whether we're currently connected to this peer.
+    pub fn is_connected(&self) -> bool {
+        self.meta.read().connected_since.is_some()
+    }
+
+    /// Computes the score of this peer contact, a value between 0 and 1. The score takes into account the ratio of
+    /// successful connections, the latency, the uptime, and how many of the requested `services` the peer provides.
+    /// Unknown values are scored neutrally.
+    pub fn score(&self, services: Services) -> f32 {
+        let (successful, failed) = {
+            let meta = self.meta.read();
+            (meta.successful_connections, meta.failed_connections)
+        };
+
+        let connections_score = (successful as f32 + 1.) / (successful as f32 + failed as f32 + 2.);
+
+        let latency_score = self
+            .latency()
+            .map(|latency| 1. - latency.min(Self::MAX_SCORED_LATENCY).as_secs_f32() / Self::MAX_SCORED_LATENCY.as_secs_f32())
+            .unwrap_or(0.5);
+
+        let uptime_score = self.uptime().min(Self::MAX_SCORED_UPTIME).as_secs_f32() / Self::MAX_SCORED_UPTIME.as_secs_f32();
+
+        let services_score = if services.is_empty() {
+            1.
+        } else {
+            (self.services() & services).bits().count_ones() as f32 / services.bits().count_ones() as f32
+        };
+
+        Self::SCORE_WEIGHT_CONNECTIONS * connections_score
+            + Self::SCORE_WEIGHT_LATENCY * latency_score
+            + Self::SCORE_WEIGHT_UPTIME * uptime_score
+            + Self::SCORE_WEIGHT_SERVICES * services_score
+    }
+
+    fn connection_established(&self) {
+        let mut meta = self.meta.write();
+        meta.successful_connections = meta.successful_connections.saturating_add(1);
+        meta.connected_since = Some(Instant::now());
+    }
+
+    fn connection_closed(&self) {
+        let mut meta = self.meta.write();
+        if let Some(since) = meta.connected_since.take() {
+            meta.uptime += since.elapsed();
+        }
+    }
+
+    fn connection_failed(&self) {
+        let mut meta = self.meta.write();
+        meta.failed_connections = meta.failed_connections.saturating_add(1);
+    }
+
+    fn set_latency(&self, latency: Duration) {
+        self.meta.write().latency = Some(latency);
+    }
 }
 
 #[derive(Debug)]
@@ -402,38 +536,50 @@ pub struct PeerContactBook {
     self_peer_contact: PeerContactInfo,
 
     peer_contacts: HashMap<PeerId, Arc<PeerContactInfo>>,
+
+    /// Addresses that other peers observed for us, together with the peers that reported them.
+    observed_addresses: HashMap<Multiaddr, HashSet<PeerId>>,
 }
 
 impl PeerContactBook {
+    /// Maximum number of distinct unconfirmed observed addresses that we keep track of.
+    const MAX_OBSERVED_ADDRESSES: usize = 64;
+
     pub fn new(config: PeerContactBookConfig, self_peer_contact: SignedPeerContact) -> Self {
         Self {
             config,
             self_peer_contact: self_peer_contact.into(),
             peer_contacts: HashMap::new(),
+            observed_addresses: HashMap::new(),
         }
     }
 
-    /// Insert a peer contact or update an existing one
-    ///
-    /// # TODO
-    ///
-    ///  - Check if the peer is already known and update its information.
-    ///
+    /// Insert a peer contact or update an existing one. An existing contact is only replaced by a newer one, in which
+    /// case the meta-data of the existing contact is kept.
     pub fn insert(&mut self, contact: SignedPeerContact) {
         let info = PeerContactInfo::from(contact);
-        let peer_id = info.peer_id.clone();
 
-        log::debug!("Adding peer contact: {:?}", peer_id);
+        log::debug!("Adding peer contact: {:?}", info.peer_id);
 
-        self.peer_contacts.insert(peer_id, Arc::new(info));
+        self.insert_info(info);
     }
 
     pub fn insert_filtered(&mut self, contact: SignedPeerContact, protocols_filter: Protocols, services_filter: Services) {
         let info = PeerContactInfo::from(contact);
         if info.matches(protocols_filter, services_filter) {
-            let peer_id = info.peer_id.clone();
-            self.peer_contacts.insert(peer_id, Arc::new(info));
+            self.insert_info(info);
+        }
+    }
+
+    fn insert_info(&mut self, info: PeerContactInfo) {
+        if let Some(existing) = self.peer_contacts.get(&info.peer_id) {
+            if existing.contact().timestamp > info.contact().timestamp {
+                return;
+            }
+            *info.meta.write() = existing.meta.read().clone();
         }
+
+        self.peer_contacts.insert(info.peer_id.clone(), Arc::new(info));
     }
 
     pub fn insert_all<I: IntoIterator<Item = SignedPeerContact>>(&mut self, contacts: I) {
@@ -452,34 +598,101 @@ impl PeerContactBook {
         self.peer_contacts.get(peer_id).map(|c| Arc::clone(c))
     }
 
-    pub fn query<'a>(&'a self, protocols: Protocols, services: Services) -> impl Iterator<Item = Arc<PeerContactInfo>> + 'a {
-        // TODO: This is a naive implementation
-        // TODO: Sort by score?
-        self.peer_contacts.iter().filter_map(move |(_, contact)| {
-            if !contact.is_seed() && contact.matches(protocols, services) {
-                Some(Arc::clone(contact))
-            } else {
-                None
-            }
-        })
+    /// Returns the peer contacts that match the given protocols and services, sorted by their score (best first).
+    pub fn query(&self, protocols: Protocols, services: Services) -> impl Iterator<Item = Arc<PeerContactInfo>> {
+        let mut contacts = self
+            .peer_contacts
+            .values()
+            .filter(|contact| !contact.is_seed() && contact.matches(protocols, services))
+            .map(|contact| (contact.score(services), Arc::clone(contact)))
+            .collect::<Vec<(f32, Arc<PeerContactInfo>)>>();
+
+        contacts.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        contacts.into_iter().map(|(_, contact)| contact)
+    }
+
+    /// Records that a connection to the peer was established.
+    pub fn connection_established(&self, peer_id: &PeerId) {
+        if let Some(contact) = self.peer_contacts.get(peer_id) {
+            contact.connection_established();
+        }
+    }
+
+    /// Records that the connection to the peer was closed.
+    pub fn connection_closed(&self, peer_id: &PeerId) {
+        if let Some(contact) = self.peer_contacts.get(peer_id) {
+            contact.connection_closed();
+        }
+    }
+
+    /// Records that connecting to the peer failed.
+    pub fn connection_failed(&self, peer_id: &PeerId) {
+        if let Some(contact) = self.peer_contacts.get(peer_id) {
+            contact.connection_failed();
+        }
+    }
+
+    /// Records the latency measured for the peer.
+    pub fn set_latency(&self, peer_id: &PeerId, latency: Duration) {
+        if let Some(contact) = self.peer_contacts.get(peer_id) {
+            contact.set_latency(latency);
+        }
     }
 
-    pub fn self_add_addresses<I: IntoIterator<Item = Multiaddr>>(&mut self, addresses: I) {
-        log::info!("Addresses observed for us:");
+    /// Adds addresses that `reporter` observed for us. Once an address was reported by
+    /// [`PeerContactBookConfig::min_address_confirmations`] distinct peers, it is added to our own peer contact, which
+    /// is then signed again with `keypair`.
+    pub fn self_add_addresses<I: IntoIterator<Item = Multiaddr>>(&mut self, addresses: I, reporter: &PeerId, keypair: &Keypair) {
+        let mut confirmed = vec![];
+
         for address in addresses {
-            log::info!("  - {}", address);
+            log::debug!("Address observed for us by {}: {}", reporter, address);
+
+            // Only consider addresses for transports that we support, that we're not advertising already.
+            if Protocols::from_multiaddr(&address).is_empty() || self.self_peer_contact.contact.inner.addresses.contains(&address) {
+                continue;
+            }
+
+            if !self.observed_addresses.contains_key(&address) && self.observed_addresses.len() >= Self::MAX_OBSERVED_ADDRESSES {
+                continue;
+            }
+
+            let reporters = self.observed_addresses.entry(address.clone()).or_default();
+            reporters.insert(reporter.clone());
+
+            if reporters.len() >= self.config.min_address_confirmations {
+                confirmed.push(address);
+            }
+        }
+
+        if !confirmed.is_empty() {
+            let mut contact = self.self_peer_contact.contact.inner.clone();
+            for address in confirmed {
+                log::info!("Advertising confirmed external address: {}", address);
+                self.observed_addresses.remove(&address);
+                contact.addresses.push(address);
+            }
+            contact.addresses.sort();
+
+            self.set_self(contact, keypair);
         }
-        // TODO: We could add these observed addresses to our advertised addresses (with restrictions).
     }
 
     pub fn self_update(&mut self, keypair: &Keypair) {
         // Not really optimal to clone here, but *shrugs*
-        let mut contact = self.self_peer_contact.contact.inner.clone();
+        let contact = self.self_peer_contact.contact.inner.clone();
+
+        self.set_self(contact, keypair);
+    }
 
-        // Update timestamp
+    /// Updates the timestamp of our own peer contact and signs it.
+    fn set_self(&mut self, mut contact: PeerContact, keypair: &Keypair) {
         contact.set_current_time();
 
-        self.insert(contact.sign(keypair));
+        let signed = contact.sign(keypair);
+        self.self_peer_contact = signed.clone().into();
+        self.insert(signed);
     }
 
     pub fn get_self(&self) -> &PeerContactInfo {
@@ -511,6 +724,76 @@ impl PeerContactBook {
     }
 }
 
+/// The file format of the persisted peer contact book.
+#[cfg(feature = "peer-contact-book-persistence")]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct PersistedPeerContacts {
+    #[serde(default)]
+    peer_contacts: Vec<SignedPeerContact>,
+}
+
+#[cfg(feature = "peer-contact-book-persistence")]
+impl PeerContactBook {
+    /// Loads the peer contacts from the file specified in the config. Contacts with an invalid signature, seeds, our own
+    /// contact and contacts that exceed their max age are skipped. Returns the number of peer contacts added.
+    pub fn load(&mut self) -> Result<usize, PeerContactBookError> {
+        let path = match &self.config.path {
+            Some(path) if path.exists() => path,
+            _ => return Ok(0),
+        };
+
+        let persisted: PersistedPeerContacts = toml::from_str(&std::fs::read_to_string(path)?)?;
+
+        let unix_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let mut num_loaded = 0;
+
+        for contact in persisted.peer_contacts {
+            if contact.inner.is_seed() || !contact.verify() {
+                log::debug!("Skipping invalid persisted peer contact: {:?}", contact.inner.peer_id());
+                continue;
+            }
+
+            let info = PeerContactInfo::from(contact);
+            if info.peer_id == self.self_peer_contact.peer_id || info.exceeds_age(&self.config, unix_time) {
+                continue;
+            }
+
+            self.peer_contacts.insert(info.peer_id.clone(), Arc::new(info));
+            num_loaded += 1;
+        }
+
+        log::info!("Loaded {} peer contacts from {}", num_loaded, path.display());
+
+        Ok(num_loaded)
+    }
+
+    /// Saves the peer contacts to the file specified in the config. Seeds, our own contact and contacts that exceed
+    /// their max age are not persisted.
+    pub fn save(&self) -> Result<(), PeerContactBookError> {
+        let path = match &self.config.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let unix_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+        let persisted = PersistedPeerContacts {
+            peer_contacts: self
+                .peer_contacts
+                .values()
+                .filter(|info| !info.is_seed() && info.peer_id != self.self_peer_contact.peer_id && !info.exceeds_age(&self.config, unix_time))
+                .map(|info| info.contact.clone())
+                .collect(),
+        };
+
+        write_atomically(path, toml::to_string(&persisted)?)?;
+
+        log::debug!("Saved {} peer contacts to {}", persisted.peer_contacts.len(), path.display());
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Protocols;
@@ -519,6 +802,8 @@ mod tests {
     fn protocols_from_multiaddr() {
         assert_eq!(Protocols::from_multiaddr(&"/ip4/1.2.3.4/tcp/80/ws".parse().unwrap()), Protocols::WS);
         assert_eq!(Protocols::from_multiaddr(&"/ip4/1.2.3.4/tcp/443/wss".parse().unwrap()), Protocols::WSS);
+        assert_eq!(Protocols::from_multiaddr(&"/ip4/1.2.3.4/tcp/8443".parse().unwrap()), Protocols::TCP);
+        assert_eq!(Protocols::from_multiaddr(&"/dns4/test.local/tcp/8443".parse().unwrap()), Protocols::TCP);
     }
 
     #[test]
@@ -532,6 +817,116 @@ mod tests {
             Protocols::from_multiaddrs(vec!["/ip4/1.2.3.4/tcp/443/ws".parse().unwrap(), "/ip4/1.2.3.4/tcp/443/wss".parse().unwrap()].iter()),
             Protocols::WS | Protocols::WSS
         );
+
+        assert_eq!(
+            Protocols::from_multiaddrs(vec!["/ip4/1.2.3.4/tcp/8443".parse().unwrap(), "/ip4/1.2.3.4/tcp/443/wss".parse().unwrap()].iter()),
+            Protocols::TCP | Protocols::WSS
+        );
+    }
+
+    fn signed_contact(keypair: &libp2p::identity::Keypair, address: &str, services: super::Services) -> super::SignedPeerContact {
+        let mut contact = super::PeerContact::new(vec![address.parse().unwrap()], keypair.public(), services, None);
+        contact.set_current_time();
+        contact.sign(keypair)
+    }
+
+    #[test]
+    fn it_sorts_peer_contacts_by_score() {
+        use libp2p::identity::Keypair;
+
+        use super::{PeerContactBook, Services};
+
+        let self_keypair = Keypair::generate_ed25519();
+        let good_keypair = Keypair::generate_ed25519();
+        let bad_keypair = Keypair::generate_ed25519();
+        let good_peer_id = good_keypair.public().into_peer_id();
+        let bad_peer_id = bad_keypair.public().into_peer_id();
+
+        let mut book = PeerContactBook::new(Default::default(), signed_contact(&self_keypair, "/ip4/1.2.3.4/tcp/8443/ws", Services::all()));
+        book.insert(signed_contact(&good_keypair, "/ip4/1.2.3.5/tcp/8443/ws", Services::all()));
+        book.insert(signed_contact(&bad_keypair, "/ip4/1.2.3.6/tcp/8443/ws", Services::all()));
+
+        book.connection_failed(&bad_peer_id);
+        book.connection_failed(&bad_peer_id);
+        book.connection_established(&good_peer_id);
+
+        let order = book.query(Protocols::all(), Services::all()).map(|c| c.peer_id().clone()).collect::<Vec<_>>();
+        assert_eq!(order, vec![good_peer_id.clone(), bad_peer_id.clone()]);
+        assert!(book.get(&good_peer_id).unwrap().is_connected());
+
+        // Re-inserting a contact keeps its meta-data.
+        book.insert(signed_contact(&bad_keypair, "/ip4/1.2.3.6/tcp/8443/ws", Services::all()));
+        assert_eq!(book.get(&bad_peer_id).unwrap().failed_connections(), 2);
+
+        // Peers score higher for services that they provide.
+        let info = super::PeerContactInfo::from(signed_contact(&Keypair::generate_ed25519(), "/ip4/1.2.3.7/tcp/8443/ws", Services::FULL_BLOCKS));
+        assert!(info.score(Services::FULL_BLOCKS) > info.score(Services::all()));
+    }
+
+    #[test]
+    fn it_advertises_confirmed_observed_addresses() {
+        use libp2p::identity::Keypair;
+
+        use super::{PeerContactBook, Services};
+
+        let keypair = Keypair::generate_ed25519();
+        let mut book = PeerContactBook::new(Default::default(), signed_contact(&keypair, "/ip4/1.2.3.4/tcp/8443/ws", Services::all()));
+        let observed: libp2p::Multiaddr = "/ip4/5.6.7.8/tcp/8443/ws".parse().unwrap();
+
+        let reporter = Keypair::generate_ed25519().public().into_peer_id();
+        for _ in 0..3 {
+            book.self_add_addresses(vec![observed.clone()], &reporter, &keypair);
+        }
+        assert!(!book.get_self().addresses().any(|address| address == &observed));
+
+        for _ in 0..2 {
+            let reporter = Keypair::generate_ed25519().public().into_peer_id();
+            book.self_add_addresses(vec![observed.clone()], &reporter, &keypair);
+        }
+        assert!(book.get_self().addresses().any(|address| address == &observed));
+        assert!(book.get_self().signed().verify());
+    }
+
+    #[cfg(feature = "peer-contact-book-persistence")]
+    #[test]
+    fn it_persists_peer_contacts() {
+        use libp2p::identity::Keypair;
+        use tempdir::TempDir;
+
+        use super::{PeerContact, PeerContactBook, PeerContactBookConfig, Services};
+
+        let dir = TempDir::new("peer_contacts").unwrap();
+        let config = PeerContactBookConfig {
+            path: Some(dir.path().join("peer_contacts.toml")),
+            ..Default::default()
+        };
+
+        let contact = |keypair: &Keypair, timestamp: Option<u64>| {
+            let mut contact = PeerContact::new(vec!["/ip4/1.2.3.4/tcp/8443/ws".parse().unwrap()], keypair.public(), Services::FULL_BLOCKS, timestamp);
+            if timestamp.is_some() {
+                contact.set_current_time();
+            }
+            contact.sign(keypair)
+        };
+
+        let self_keypair = Keypair::generate_ed25519();
+        let peer_keypair = Keypair::generate_ed25519();
+        let seed_keypair = Keypair::generate_ed25519();
+        let old_keypair = Keypair::generate_ed25519();
+
+        let mut book = PeerContactBook::new(config.clone(), contact(&self_keypair, None));
+        book.self_update(&self_keypair);
+        book.insert(contact(&peer_keypair, Some(0)));
+        book.insert(contact(&seed_keypair, None));
+        book.insert(PeerContact::new(vec!["/ip4/1.2.3.5/tcp/8443/ws".parse().unwrap()], old_keypair.public(), Services::FULL_BLOCKS, Some(1)).sign(&old_keypair));
+        book.save().unwrap();
+
+        let mut book = PeerContactBook::new(config, contact(&self_keypair, None));
+        assert_eq!(book.load().unwrap(), 1);
+        assert!(book.get(&peer_keypair.public().into_peer_id()).is_some());
+        assert!(book.get(&seed_keypair.public().into_peer_id()).is_none());
+        assert!(book.get(&old_keypair.public().into_peer_id()).is_none());
+        assert!(book.get(&self_keypair.public().into_peer_id()).is_none());
     }
 }
 
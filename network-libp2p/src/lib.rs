@@ -5,18 +5,25 @@ extern crate beserial_derive;
 #[macro_use]
 extern crate log;
 
+pub mod bandwidth;
 mod behaviour;
+pub mod dht;
 pub mod discovery;
-mod limit;
+pub mod limit;
 pub mod message;
 pub mod message_codec;
 mod network;
+mod persistence;
+pub mod request_response;
 pub mod task;
+pub mod tls;
 
 pub const MESSAGE_PROTOCOL: &[u8] = b"/nimiq/message/0.0.1";
+pub const MESSAGE_PROTOCOL_DEFLATE: &[u8] = b"/nimiq/message/0.0.1+deflate";
 pub const DISCOVERY_PROTOCOL: &[u8] = b"/nimiq/discovery/0.0.1";
 pub const LIMIT_PROTOCOL: &[u8] = b"/nimiq/limit/0.0.1";
+pub const REQUEST_RESPONSE_PROTOCOL: &[u8] = b"/nimiq/request/0.0.1";
 
 pub use libp2p::{self, core::network::NetworkInfo, identity::Keypair, Multiaddr};
 
-pub use network::{Config, Network, NetworkError};
+pub use network::{Config, Network, NetworkError, PeerInfo};
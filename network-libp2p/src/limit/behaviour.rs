@@ -1,5 +1,5 @@
 use std::collections::{HashMap, VecDeque};
-use std::sync::Arc;
+use std::net::IpAddr;
 use std::time::{Duration, SystemTime};
 
 use futures::task::{Context, Poll};
@@ -8,21 +8,42 @@ use libp2p::core::connection::ConnectionId;
 use libp2p::core::multiaddr::Protocol;
 use libp2p::core::ConnectedPoint;
 use libp2p::core::Multiaddr;
-use libp2p::swarm::{NetworkBehaviour, NetworkBehaviourAction, PollParameters, ProtocolsHandler};
+use libp2p::swarm::{NetworkBehaviour, NetworkBehaviourAction, NotifyHandler, PollParameters, ProtocolsHandler};
 use libp2p::PeerId;
 
 use super::handler::{HandlerInEvent, LimitHandler};
-use crate::message::peer::Peer;
 
 #[derive(Clone, Debug)]
 pub struct LimitConfig {
-    peer_count_max: usize,
-    peer_count_per_ip_max: usize,
-    outbound_peer_count_per_subnet_max: usize,
-    inbound_peer_count_per_subnet_max: usize,
-    ipv4_subnet_mask: u8,
-    ipv6_subnet_mask: u8,
-    default_ban_time: Duration,
+    /// Maximum number of connections in total.
+    pub peer_count_max: usize,
+
+    /// Maximum number of connections from/to a single IP address.
+    pub peer_count_per_ip_max: usize,
+
+    /// Maximum number of outbound connections to a single subnet.
+    pub outbound_peer_count_per_subnet_max: usize,
+
+    /// Maximum number of inbound connections from a single subnet.
+    pub inbound_peer_count_per_subnet_max: usize,
+
+    /// Prefix length of the IPv4 subnets.
+    pub ipv4_subnet_mask: u8,
+
+    /// Prefix length of the IPv6 subnets.
+    pub ipv6_subnet_mask: u8,
+
+    /// Duration for which IPs are banned if no explicit duration is given.
+    pub default_ban_time: Duration,
+
+    /// Duration for which a peer that we disconnected on purpose can't reconnect.
+    pub disconnect_backoff_time: Duration,
+
+    /// Maximum number of incoming connections that are still being negotiated.
+    pub pending_incoming_max: u32,
+
+    /// Maximum number of outgoing connections that are still being negotiated.
+    pub pending_outgoing_max: u32,
 }
 
 impl Default for LimitConfig {
@@ -35,19 +56,73 @@ impl Default for LimitConfig {
             ipv4_subnet_mask: 24,
             ipv6_subnet_mask: 96,
             default_ban_time: Duration::from_secs(60 * 10), // 10 minutes
+            disconnect_backoff_time: Duration::from_secs(60),
+            pending_incoming_max: 32,
+            pending_outgoing_max: 16,
         }
     }
 }
 
+impl LimitConfig {
+    const IPV4_MAX_MASK: u8 = 32;
+    const IPV6_MAX_MASK: u8 = 128;
+
+    /// Checks that the subnet masks are valid prefix lengths.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ipv4_subnet_mask > Self::IPV4_MAX_MASK {
+            return Err(format!("Invalid IPv4 subnet mask: {}", self.ipv4_subnet_mask));
+        }
+        if self.ipv6_subnet_mask > Self::IPV6_MAX_MASK {
+            return Err(format!("Invalid IPv6 subnet mask: {}", self.ipv6_subnet_mask));
+        }
+        Ok(())
+    }
+}
+
+/// The reason why a connection was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitReason {
+    PeerCount,
+    PeerCountPerIp,
+    PeerCountPerSubnet,
+    Banned,
+}
+
+#[derive(Clone, Debug)]
+pub enum LimitEvent {
+    ConnectionRefused {
+        peer_id: PeerId,
+        address: Multiaddr,
+        reason: LimitReason,
+    },
+}
+
+/// A connection that counts towards the limits.
+struct ConnectionInfo {
+    peer_id: PeerId,
+    ip: IpAddr,
+    subnet: IpNetwork,
+    inbound: bool,
+}
+
+/// A banned peer.
+struct PeerBan {
+    until: SystemTime,
+    /// The IPs that were banned together with the peer. Its connections are closed by then, so they are kept here to
+    /// lift their bans with the peer's.
+    ips: Vec<IpAddr>,
+}
+
 pub struct LimitBehaviour {
     config: LimitConfig,
 
-    pub peers: HashMap<PeerId, Arc<Peer>>,
-    ip_ban: HashMap<IpNetwork, SystemTime>,
-    ip_count: HashMap<IpNetwork, usize>,
-    ipv4_count: usize,
-    ipv6_count: usize,
-    events: VecDeque<LimitEvent>,
+    connections: HashMap<ConnectionId, ConnectionInfo>,
+    ip_ban: HashMap<IpAddr, SystemTime>,
+    peer_ban: HashMap<PeerId, PeerBan>,
+    ip_count: HashMap<IpAddr, usize>,
+    inbound_subnet_count: HashMap<IpNetwork, usize>,
+    outbound_subnet_count: HashMap<IpNetwork, usize>,
+    events: VecDeque<NetworkBehaviourAction<HandlerInEvent, LimitEvent>>,
 }
 
 impl Default for LimitBehaviour {
@@ -56,21 +131,184 @@ impl Default for LimitBehaviour {
     }
 }
 
-#[derive(Clone, Debug)]
-pub enum LimitEvent {}
-
 impl LimitBehaviour {
     pub fn new(config: LimitConfig) -> Self {
         Self {
             config,
-            peers: HashMap::new(),
+            connections: HashMap::new(),
             ip_ban: HashMap::new(),
+            peer_ban: HashMap::new(),
             ip_count: HashMap::new(),
-            ipv4_count: 0,
-            ipv6_count: 0,
+            inbound_subnet_count: HashMap::new(),
+            outbound_subnet_count: HashMap::new(),
             events: VecDeque::new(),
         }
     }
+
+    pub fn config(&self) -> &LimitConfig {
+        &self.config
+    }
+
+    /// Bans the peer and the IPs it is connected from for `duration` (or the default ban time) and
+    /// closes all connections to it.
+    pub fn ban_peer(&mut self, peer_id: &PeerId, duration: Option<Duration>) {
+        let until = SystemTime::now() + duration.unwrap_or(self.config.default_ban_time);
+
+        let mut ips: Vec<IpAddr> = self
+            .connections
+            .values()
+            .filter(|info| &info.peer_id == peer_id)
+            .map(|info| info.ip)
+            .collect();
+        ips.sort();
+        ips.dedup();
+        for ip in &ips {
+            self.ip_ban.insert(*ip, until);
+        }
+        self.peer_ban.insert(peer_id.clone(), PeerBan { until, ips });
+
+        self.close_peer(peer_id, LimitReason::Banned);
+    }
+
+    /// Closes all connections to the peer and refuses new ones for the disconnect backoff time. Unlike a ban, this
+    /// doesn't affect its IPs, and an active ban of the peer is left as it is.
+    pub fn back_off_peer(&mut self, peer_id: &PeerId) {
+        let now = SystemTime::now();
+        if !self.peer_ban.get(peer_id).map_or(false, |ban| ban.until > now) {
+            // Lift an expired ban that wasn't removed yet together with its IPs.
+            self.unban_peer(peer_id);
+            self.peer_ban.insert(
+                peer_id.clone(),
+                PeerBan {
+                    until: now + self.config.disconnect_backoff_time,
+                    ips: vec![],
+                },
+            );
+        }
+
+        self.close_peer(peer_id, LimitReason::Banned);
+    }
+
+    /// Lifts the ban of the peer and of the IPs it was banned with.
+    pub fn unban_peer(&mut self, peer_id: &PeerId) {
+        if let Some(ban) = self.peer_ban.remove(peer_id) {
+            for ip in ban.ips {
+                // The IP may have been banned again since, on its own or with another peer.
+                if self.ip_ban.get(&ip) == Some(&ban.until) {
+                    self.ip_ban.remove(&ip);
+                }
+            }
+        }
+    }
+
+    /// Bans the IP for `duration` (or the default ban time) and closes all connections from/to it.
+    pub fn ban_ip(&mut self, ip: IpAddr, duration: Option<Duration>) {
+        let until = SystemTime::now() + duration.unwrap_or(self.config.default_ban_time);
+        self.ip_ban.insert(ip, until);
+
+        let peer_ids: Vec<PeerId> = self
+            .connections
+            .values()
+            .filter(|info| info.ip == ip)
+            .map(|info| info.peer_id.clone())
+            .collect();
+        for peer_id in peer_ids {
+            self.close_peer(&peer_id, LimitReason::Banned);
+        }
+    }
+
+    pub fn unban_ip(&mut self, ip: &IpAddr) {
+        self.ip_ban.remove(ip);
+    }
+
+    pub fn banned_ips(&self) -> Vec<(IpAddr, SystemTime)> {
+        self.ip_ban.iter().map(|(ip, until)| (*ip, *until)).collect()
+    }
+
+    pub fn banned_peers(&self) -> Vec<(PeerId, SystemTime)> {
+        self.peer_ban.iter().map(|(peer_id, ban)| (peer_id.clone(), ban.until)).collect()
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId, ip: &IpAddr) -> bool {
+        self.peer_ban.contains_key(peer_id) || self.ip_ban.contains_key(ip)
+    }
+
+    fn subnet(&self, ip: IpAddr) -> IpNetwork {
+        // Invalid masks are rejected with the config already, but we clamp them anyway, such that the swarm never
+        // panics because of them.
+        let mask = match ip {
+            IpAddr::V4(_) => self.config.ipv4_subnet_mask.min(LimitConfig::IPV4_MAX_MASK),
+            IpAddr::V6(_) => self.config.ipv6_subnet_mask.min(LimitConfig::IPV6_MAX_MASK),
+        };
+        IpNetwork::new_truncate(ip, mask).unwrap_or_else(|_| IpNetwork::from(ip))
+    }
+
+    /// Checks whether a new connection to `peer_id` at `ip` is admissible.
+    fn check_limits(&self, peer_id: &PeerId, ip: IpAddr, inbound: bool) -> Result<(), LimitReason> {
+        if self.is_banned(peer_id, &ip) {
+            return Err(LimitReason::Banned);
+        }
+
+        if self.connections.len() >= self.config.peer_count_max {
+            return Err(LimitReason::PeerCount);
+        }
+
+        if self.ip_count.get(&ip).cloned().unwrap_or(0) >= self.config.peer_count_per_ip_max {
+            return Err(LimitReason::PeerCountPerIp);
+        }
+
+        let subnet = self.subnet(ip);
+        let (subnet_count, subnet_limit) = if inbound {
+            (&self.inbound_subnet_count, self.config.inbound_peer_count_per_subnet_max)
+        } else {
+            (&self.outbound_subnet_count, self.config.outbound_peer_count_per_subnet_max)
+        };
+        if subnet_count.get(&subnet).cloned().unwrap_or(0) >= subnet_limit {
+            return Err(LimitReason::PeerCountPerSubnet);
+        }
+
+        Ok(())
+    }
+
+    fn close_connection(&mut self, peer_id: &PeerId, connection_id: ConnectionId, reason: LimitReason) {
+        self.events.push_back(NetworkBehaviourAction::NotifyHandler {
+            peer_id: peer_id.clone(),
+            handler: NotifyHandler::One(connection_id),
+            event: HandlerInEvent::Close(reason),
+        });
+    }
+
+    fn close_peer(&mut self, peer_id: &PeerId, reason: LimitReason) {
+        self.events.push_back(NetworkBehaviourAction::NotifyHandler {
+            peer_id: peer_id.clone(),
+            handler: NotifyHandler::All,
+            event: HandlerInEvent::Close(reason),
+        });
+    }
+
+    fn remove_expired_bans(&mut self) {
+        let now = SystemTime::now();
+        self.ip_ban.retain(|_, until| *until > now);
+        self.peer_ban.retain(|_, ban| ban.until > now);
+    }
+}
+
+/// Returns the IP address of a multiaddress, if it starts with one.
+fn ip_of(address: &Multiaddr) -> Option<IpAddr> {
+    match address.iter().next() {
+        Some(Protocol::Ip4(ip)) => Some(IpAddr::V4(ip)),
+        Some(Protocol::Ip6(ip)) => Some(IpAddr::V6(ip)),
+        _ => None,
+    }
+}
+
+fn decrement<K: std::hash::Hash + Eq>(counts: &mut HashMap<K, usize>, key: &K) {
+    if let Some(count) = counts.get_mut(key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
 }
 
 impl NetworkBehaviour for LimitBehaviour {
@@ -89,114 +327,211 @@ impl NetworkBehaviour for LimitBehaviour {
 
     fn inject_disconnected(&mut self, _peer_id: &PeerId) {}
 
-    fn inject_connection_established(&mut self, peer_id: &PeerId, _conn: &ConnectionId, endpoint: &ConnectedPoint) {
-        let mut close_connection = false;
-
-        let (address, subnet_limit) = match endpoint {
-            ConnectedPoint::Listener { send_back_addr, .. } => (send_back_addr.clone(), self.config.inbound_peer_count_per_subnet_max),
-            ConnectedPoint::Dialer { address } => (address.clone(), self.config.outbound_peer_count_per_subnet_max),
+    fn inject_connection_established(&mut self, peer_id: &PeerId, conn: &ConnectionId, endpoint: &ConnectedPoint) {
+        let (address, inbound) = match endpoint {
+            ConnectedPoint::Listener { send_back_addr, .. } => (send_back_addr.clone(), true),
+            ConnectedPoint::Dialer { address } => (address.clone(), false),
         };
 
-        // Get the IP for this new peer connection
-        let ip = match address.iter().next() {
-            Some(Protocol::Ip4(ip)) => IpNetwork::new(ip, self.config.ipv4_subnet_mask).unwrap(),
-            Some(Protocol::Ip6(ip)) => IpNetwork::new(ip, self.config.ipv6_subnet_mask).unwrap(),
-            _ => return,
+        // Connections that are not IP based (e.g. the memory transport) are not limited.
+        let ip = match ip_of(&address) {
+            Some(ip) => ip,
+            None => return,
         };
 
-        if self.ip_count.get(&ip).is_some() && self.config.peer_count_per_ip_max < *self.ip_count.get(&ip).unwrap() + 1 {
-            debug!("Max peer connections per IP limit reached, {}", ip);
-            close_connection = true;
-        }
-        if ip.is_ipv4() && (subnet_limit < self.ipv4_count + 1) {
-            debug!("Max peer connections per IPv4 subnet limit reached");
-            close_connection = true;
-        }
-        if ip.is_ipv6() && (subnet_limit < self.ipv6_count + 1) {
-            debug!("IPv6 subnet limit reached");
-            close_connection = true;
-        }
-        if self.config.peer_count_max < self.ipv4_count + self.ipv6_count + 1 {
-            debug!("Max peer connections limit reached");
-            close_connection = true;
+        self.remove_expired_bans();
+
+        if let Err(reason) = self.check_limits(peer_id, ip, inbound) {
+            debug!("Refusing connection to {} at {}: {:?}", peer_id, address, reason);
+            self.close_connection(peer_id, *conn, reason);
+            self.events.push_back(NetworkBehaviourAction::GenerateEvent(LimitEvent::ConnectionRefused {
+                peer_id: peer_id.clone(),
+                address,
+                reason,
+            }));
+            return;
         }
-        if self.ip_ban.get(&ip).is_some() {
-            debug!("Connection IP is banned, {}", ip);
-            close_connection = true;
+
+        // Count the connection.
+        let subnet = self.subnet(ip);
+        *self.ip_count.entry(ip).or_insert(0) += 1;
+        if inbound {
+            *self.inbound_subnet_count.entry(subnet).or_insert(0) += 1;
+        } else {
+            *self.outbound_subnet_count.entry(subnet).or_insert(0) += 1;
         }
+        self.connections.insert(
+            *conn,
+            ConnectionInfo {
+                peer_id: peer_id.clone(),
+                ip,
+                subnet,
+                inbound,
+            },
+        );
+    }
 
-        if !close_connection {
-            // Increment peer counts per IP
-            *self.ip_count.entry(ip).or_insert(0) += 1;
-            if ip.is_ipv4() {
-                self.ipv4_count += 1;
+    fn inject_connection_closed(&mut self, _peer_id: &PeerId, conn: &ConnectionId, _endpoint: &ConnectedPoint) {
+        // Only connections that were admitted are counted.
+        if let Some(info) = self.connections.remove(conn) {
+            decrement(&mut self.ip_count, &info.ip);
+            if info.inbound {
+                decrement(&mut self.inbound_subnet_count, &info.subnet);
             } else {
-                self.ipv6_count += 1;
+                decrement(&mut self.outbound_subnet_count, &info.subnet);
             }
         }
+    }
+
+    fn inject_event(&mut self, _peer_id: PeerId, _connection: ConnectionId, _msg: <Self::ProtocolsHandler as ProtocolsHandler>::OutEvent) {}
 
-        // Send the network event to the Swarm
-        if close_connection {
-            /*let (peer_tx, _peer_rx) = mpsc::channel(4096);
-            let peer = Arc::new(Peer::new(peer_id.clone(), peer_tx));
-            self.events.push_back(NetworkEvent::PeerDisconnect(peer));*/
-            todo!()
+    fn poll(&mut self, _cx: &mut Context<'_>, _params: &mut impl PollParameters) -> Poll<NetworkBehaviourAction<HandlerInEvent, LimitEvent>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(event);
         }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use libp2p::identity::Keypair;
+
+    use super::*;
+
+    fn peer_id() -> PeerId {
+        Keypair::generate_ed25519().public().into_peer_id()
+    }
+
+    fn connect(behaviour: &mut LimitBehaviour, peer_id: &PeerId, conn: usize, ip: Ipv4Addr, inbound: bool) {
+        let address: Multiaddr = format!("/ip4/{}/tcp/8443", ip).parse().unwrap();
+        let endpoint = if inbound {
+            ConnectedPoint::Listener {
+                local_addr: "/ip4/127.0.0.1/tcp/8443".parse().unwrap(),
+                send_back_addr: address,
+            }
+        } else {
+            ConnectedPoint::Dialer { address }
+        };
+        behaviour.inject_connection_established(peer_id, &ConnectionId::new(conn), &endpoint);
+    }
+
+    fn refused(behaviour: &mut LimitBehaviour) -> Vec<LimitReason> {
+        behaviour
+            .events
+            .drain(..)
+            .filter_map(|event| match event {
+                NetworkBehaviourAction::GenerateEvent(LimitEvent::ConnectionRefused { reason, .. }) => Some(reason),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_limits_outbound_connections_per_subnet() {
+        let mut behaviour = LimitBehaviour::default();
+
+        connect(&mut behaviour, &peer_id(), 1, Ipv4Addr::new(1, 2, 3, 4), false);
+        connect(&mut behaviour, &peer_id(), 2, Ipv4Addr::new(1, 2, 3, 5), false);
+        connect(&mut behaviour, &peer_id(), 3, Ipv4Addr::new(1, 2, 3, 6), false);
+        connect(&mut behaviour, &peer_id(), 4, Ipv4Addr::new(1, 2, 4, 6), false);
+        assert_eq!(refused(&mut behaviour), vec![LimitReason::PeerCountPerSubnet]);
+
+        // Inbound connections from the same subnet are counted separately.
+        connect(&mut behaviour, &peer_id(), 5, Ipv4Addr::new(1, 2, 3, 7), true);
+        assert!(refused(&mut behaviour).is_empty());
+    }
+
+    #[test]
+    fn it_rejects_invalid_subnet_masks() {
+        assert!(LimitConfig::default().validate().is_ok());
+        assert!(LimitConfig { ipv4_subnet_mask: 33, ..Default::default() }.validate().is_err());
+        assert!(LimitConfig { ipv6_subnet_mask: 129, ..Default::default() }.validate().is_err());
+
+        // The behaviour clamps invalid masks instead of panicking.
+        let behaviour = LimitBehaviour::new(LimitConfig { ipv4_subnet_mask: 33, ..Default::default() });
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        assert_eq!(behaviour.subnet(ip), IpNetwork::from(ip));
     }
 
-    fn inject_connection_closed(&mut self, peer_id: &PeerId, _conn: &ConnectionId, info: &ConnectedPoint) {
-        /*let peer = self
-        .peers
-        .get(peer_id)
-        .expect("Connection to unknown peer closed");*/
-        // FIXME: A peer should actually exist here, but it's never inserted into `self.peers`.
-        let peer = if let Some(peer) = self.peers.get(peer_id) { peer } else { return };
+    #[test]
+    fn it_limits_connections_per_ip() {
+        let mut behaviour = LimitBehaviour::new(LimitConfig {
+            peer_count_per_ip_max: 1,
+            ..Default::default()
+        });
 
-        let address = match info {
-            ConnectedPoint::Listener { send_back_addr, .. } => send_back_addr.clone(),
-            ConnectedPoint::Dialer { address } => address.clone(),
+        connect(&mut behaviour, &peer_id(), 1, Ipv4Addr::new(1, 2, 3, 4), true);
+        connect(&mut behaviour, &peer_id(), 2, Ipv4Addr::new(1, 2, 3, 4), true);
+        assert_eq!(refused(&mut behaviour), vec![LimitReason::PeerCountPerIp]);
+
+        // Once the connection is closed, the IP may connect again.
+        let endpoint = ConnectedPoint::Dialer {
+            address: "/ip4/1.2.3.4/tcp/8443".parse().unwrap(),
         };
+        behaviour.inject_connection_closed(&peer_id(), &ConnectionId::new(1), &endpoint);
+        connect(&mut behaviour, &peer_id(), 3, Ipv4Addr::new(1, 2, 3, 4), true);
+        assert!(refused(&mut behaviour).is_empty());
+    }
+
+    #[test]
+    fn it_refuses_banned_peers_and_ips() {
+        let mut behaviour = LimitBehaviour::default();
+        let banned_peer = peer_id();
+
+        connect(&mut behaviour, &banned_peer, 1, Ipv4Addr::new(1, 2, 3, 4), true);
+        behaviour.ban_peer(&banned_peer, None);
+        behaviour.events.clear();
 
-        // Get the IP for the closing peer connection
-        let ip = match address.iter().next() {
-            Some(Protocol::Ip4(ip)) => IpNetwork::new(ip, self.config.ipv4_subnet_mask).unwrap(),
-            Some(Protocol::Ip6(ip)) => IpNetwork::new(ip, self.config.ipv6_subnet_mask).unwrap(),
-            _ => return,
+        // Banning closes the peer's connections.
+        let endpoint = ConnectedPoint::Listener {
+            local_addr: "/ip4/127.0.0.1/tcp/8443".parse().unwrap(),
+            send_back_addr: "/ip4/1.2.3.4/tcp/8443".parse().unwrap(),
         };
+        behaviour.inject_connection_closed(&banned_peer, &ConnectionId::new(1), &endpoint);
 
-        /*if peer.banned {
-            self.ip_ban.insert(ip, SystemTime::now() + DEFAULT_BAN_TIME);
-        }*/
-        // FIXME
+        // Neither the peer nor its IP may connect again.
+        connect(&mut behaviour, &banned_peer, 2, Ipv4Addr::new(5, 6, 7, 8), true);
+        connect(&mut behaviour, &peer_id(), 3, Ipv4Addr::new(1, 2, 3, 4), true);
+        assert_eq!(refused(&mut behaviour), vec![LimitReason::Banned, LimitReason::Banned]);
 
-        // Decrement peer counts per IP
-        *self.ip_count.entry(ip).or_insert(1) -= 1;
-        if *self.ip_count.get(&ip).unwrap() == 0 {
-            self.ip_count.remove(&ip);
-        }
+        behaviour.unban_peer(&banned_peer);
+        connect(&mut behaviour, &banned_peer, 4, Ipv4Addr::new(5, 6, 7, 8), true);
+        connect(&mut behaviour, &peer_id(), 5, Ipv4Addr::new(1, 2, 3, 4), true);
+        assert!(refused(&mut behaviour).is_empty());
 
-        if ip.is_ipv4() {
-            self.ipv4_count -= 1;
-        } else {
-            self.ipv6_count -= 1;
-        }
+        // Bans expire.
+        behaviour.ban_ip(IpAddr::V4(Ipv4Addr::new(9, 9, 9, 9)), Some(Duration::from_secs(0)));
+        connect(&mut behaviour, &peer_id(), 6, Ipv4Addr::new(9, 9, 9, 9), true);
+        assert!(refused(&mut behaviour).is_empty());
     }
 
-    fn inject_event(&mut self, _peer_id: PeerId, _connection: ConnectionId, _msg: <Self::ProtocolsHandler as ProtocolsHandler>::OutEvent) {}
+    #[test]
+    fn it_backs_off_disconnected_peers_but_not_their_ips() {
+        let mut behaviour = LimitBehaviour::default();
+        let disconnected_peer = peer_id();
 
-    fn poll(&mut self, _cx: &mut Context<'_>, _params: &mut impl PollParameters) -> Poll<NetworkBehaviourAction<HandlerInEvent, LimitEvent>> {
-        let ip_ban = self.ip_ban.clone();
-        for (ip, time) in ip_ban {
-            if time < SystemTime::now() {
-                self.ip_ban.remove(&ip);
-            }
-        }
+        connect(&mut behaviour, &disconnected_peer, 1, Ipv4Addr::new(1, 2, 3, 4), true);
+        behaviour.back_off_peer(&disconnected_peer);
+        behaviour.events.clear();
 
-        // Emit custom events.
-        if let Some(event) = self.events.pop_front() {
-            return Poll::Ready(NetworkBehaviourAction::GenerateEvent(event));
-        }
+        let endpoint = ConnectedPoint::Listener {
+            local_addr: "/ip4/127.0.0.1/tcp/8443".parse().unwrap(),
+            send_back_addr: "/ip4/1.2.3.4/tcp/8443".parse().unwrap(),
+        };
+        behaviour.inject_connection_closed(&disconnected_peer, &ConnectionId::new(1), &endpoint);
 
-        Poll::Pending
+        connect(&mut behaviour, &disconnected_peer, 2, Ipv4Addr::new(5, 6, 7, 8), true);
+        connect(&mut behaviour, &peer_id(), 3, Ipv4Addr::new(1, 2, 3, 4), true);
+        assert_eq!(refused(&mut behaviour), vec![LimitReason::Banned]);
+
+        // A longer ban isn't shortened by the backoff.
+        behaviour.ban_peer(&disconnected_peer, Some(Duration::from_secs(3600)));
+        let until = behaviour.banned_peers()[0].1;
+        behaviour.back_off_peer(&disconnected_peer);
+        assert_eq!(behaviour.banned_peers(), vec![(disconnected_peer, until)]);
     }
 }
@@ -1,20 +1,35 @@
-use futures::task::{Context, Poll};
+use futures::task::{Context, Poll, Waker};
 use libp2p::swarm::{KeepAlive, ProtocolsHandler, ProtocolsHandlerEvent, ProtocolsHandlerUpgrErr, SubstreamProtocol};
 use thiserror::Error;
 
+use super::behaviour::LimitReason;
 use super::protocol::LimitProtocol;
 
 #[derive(Clone, Debug)]
-pub enum HandlerInEvent {}
+pub enum HandlerInEvent {
+    /// Close the connection, because it exceeds a limit or the peer was banned.
+    Close(LimitReason),
+}
 
 #[derive(Clone, Debug)]
 pub enum HandlerOutEvent {}
 
 #[derive(Debug, Error)]
-pub enum HandlerError {}
+pub enum HandlerError {
+    #[error("Connection limit exceeded: {0:?}")]
+    LimitExceeded(LimitReason),
+
+    #[error("Peer is banned")]
+    Banned,
+}
 
 #[derive(Default)]
-pub struct LimitHandler {}
+pub struct LimitHandler {
+    /// Set if the connection should be closed.
+    close_reason: Option<LimitReason>,
+
+    waker: Option<Waker>,
+}
 
 impl ProtocolsHandler for LimitHandler {
     type InEvent = HandlerInEvent;
@@ -26,26 +41,30 @@ impl ProtocolsHandler for LimitHandler {
     type OutboundOpenInfo = ();
 
     fn listen_protocol(&self) -> SubstreamProtocol<LimitProtocol, ()> {
-        log::debug!("LimitHandler::listen_protocol");
         SubstreamProtocol::new(LimitProtocol, ())
     }
 
     fn inject_fully_negotiated_inbound(&mut self, _protocol: (), _info: ()) {
-        log::debug!("LimitHandler::inject_fully_negotiated_inbound");
-        todo!();
+        // The protocol doesn't exchange any data.
     }
 
     fn inject_fully_negotiated_outbound(&mut self, _protocol: (), _info: ()) {
-        todo!();
+        // We never open outbound substreams.
     }
 
     fn inject_event(&mut self, event: HandlerInEvent) {
-        todo!();
+        match event {
+            HandlerInEvent::Close(reason) => {
+                self.close_reason = Some(reason);
+                if let Some(waker) = self.waker.take() {
+                    waker.wake();
+                }
+            }
+        }
     }
 
     fn inject_dial_upgrade_error(&mut self, _info: Self::OutboundOpenInfo, error: ProtocolsHandlerUpgrErr<std::io::Error>) {
-        log::warn!("DiscoveryHandler::inject_dial_upgrade_error: {:?}", error);
-        unimplemented!();
+        log::warn!("LimitHandler::inject_dial_upgrade_error: {:?}", error);
     }
 
     fn connection_keep_alive(&self) -> KeepAlive {
@@ -53,7 +72,15 @@ impl ProtocolsHandler for LimitHandler {
     }
 
     fn poll(&mut self, cx: &mut Context) -> Poll<ProtocolsHandlerEvent<Self::OutboundProtocol, (), HandlerOutEvent, HandlerError>> {
-        // We do nothing in the handler
+        if let Some(reason) = self.close_reason.take() {
+            let error = match reason {
+                LimitReason::Banned => HandlerError::Banned,
+                reason => HandlerError::LimitExceeded(reason),
+            };
+            return Poll::Ready(ProtocolsHandlerEvent::Close(error));
+        }
+
+        self.waker = Some(cx.waker().clone());
         Poll::Pending
     }
 }
@@ -9,19 +9,35 @@ use libp2p::{core::ConnectedPoint, PeerId};
 
 use nimiq_network_interface::{network::NetworkEvent, peer_map::ObservablePeerMap};
 
+use crate::{bandwidth::BandwidthMeter, message_codec::CompressionConfig, request_response::RequestDispatch};
+
 use super::{
     handler::{HandlerInEvent, HandlerOutEvent, MessageHandler},
     peer::Peer,
 };
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct MessageConfig {
-    // TODO
+    /// Compression for large messages. Compression is only used, if the peer supports it too. If `None`, messages are
+    /// never compressed.
+    pub compression: Option<CompressionConfig>,
+}
+
+impl Default for MessageConfig {
+    fn default() -> Self {
+        Self {
+            compression: Some(CompressionConfig::default()),
+        }
+    }
 }
 
 pub struct MessageBehaviour {
     config: MessageConfig,
 
+    bandwidth: Arc<BandwidthMeter>,
+
+    requests: Arc<RequestDispatch>,
+
     events: VecDeque<NetworkBehaviourAction<HandlerInEvent, NetworkEvent<Peer>>>,
 
     pub(crate) peers: ObservablePeerMap<Peer>,
@@ -29,16 +45,12 @@ pub struct MessageBehaviour {
     waker: Option<Waker>,
 }
 
-impl Default for MessageBehaviour {
-    fn default() -> Self {
-        Self::new(Default::default())
-    }
-}
-
 impl MessageBehaviour {
-    pub fn new(config: MessageConfig) -> Self {
+    pub fn new(config: MessageConfig, bandwidth: Arc<BandwidthMeter>, requests: Arc<RequestDispatch>) -> Self {
         Self {
             config,
+            bandwidth,
+            requests,
             peers: ObservablePeerMap::default(),
             events: VecDeque::new(),
             waker: None,
@@ -62,7 +74,7 @@ impl NetworkBehaviour for MessageBehaviour {
     type OutEvent = NetworkEvent<Peer>;
 
     fn new_handler(&mut self) -> Self::ProtocolsHandler {
-        MessageHandler::new(self.config.clone())
+        MessageHandler::new(self.config.clone(), Arc::clone(&self.bandwidth), Arc::clone(&self.requests))
     }
 
     fn addresses_of_peer(&mut self, _peer_id: &PeerId) -> Vec<Multiaddr> {
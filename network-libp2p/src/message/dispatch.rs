@@ -9,8 +9,66 @@ use futures::{
 };
 use parking_lot::Mutex;
 
-use beserial::SerializingError;
-use nimiq_network_interface::message::{peek_type, read_message, Message};
+use beserial::{uvar, Serialize, SerializingError};
+use nimiq_network_interface::message::{peek_magic, peek_type, read_message, verify_checksum, write_checksum, Message, MAGIC};
+
+use crate::{
+    bandwidth::PeerBandwidthMeter,
+    message_codec::{CompressionConfig, Header},
+};
+
+/// Returns the size of the message header (magic, type, length and checksum).
+fn header_size(message_type: u64) -> usize {
+    4 + uvar::from(message_type).serialized_size() + 4 + 4
+}
+
+/// Builds a message with the given magic, type and payload, and writes its checksum.
+fn build_message(magic: u32, message_type: u64, payload: &[u8]) -> Result<Vec<u8>, SerializingError> {
+    let length = header_size(message_type) + payload.len();
+
+    let mut message = Vec::with_capacity(length);
+    magic.serialize(&mut message)?;
+    uvar::from(message_type).serialize(&mut message)?;
+    (length as u32).serialize(&mut message)?;
+    0u32.serialize(&mut message)?; // crc32 placeholder
+    message.extend_from_slice(payload);
+
+    write_checksum(&mut message)?;
+
+    Ok(message)
+}
+
+/// Serializes a message, compressing its payload if compression is enabled and the payload is large enough. Compressed
+/// messages have the same header as uncompressed messages, but with `Header::MAGIC_COMPRESSED`, and the length and
+/// checksum refer to the compressed message. This way, the checksum is computed only once, over the bytes on the wire.
+fn encode_message<M: Message>(message: &M, compression: Option<&CompressionConfig>) -> Result<Vec<u8>, SerializingError> {
+    let payload = message.serialize_to_vec();
+
+    if let Some(compression) = compression {
+        if let Some(compressed) = compression.compress(&payload)? {
+            log::trace!("Compressed message from {} bytes to {} bytes", payload.len(), compressed.len());
+            return build_message(Header::MAGIC_COMPRESSED, M::TYPE_ID, &compressed);
+        }
+    }
+
+    build_message(MAGIC, M::TYPE_ID, &payload)
+}
+
+/// Returns the payload of a received message, decompressing it if necessary. The checksum must have been verified
+/// already.
+fn decode_payload(message: &[u8], compression: Option<&CompressionConfig>) -> Result<Vec<u8>, SerializingError> {
+    let payload = message.get(header_size(peek_type(message)?)..).ok_or(SerializingError::InvalidEncoding)?;
+
+    match (peek_magic(message)?, compression) {
+        (MAGIC, _) => Ok(payload.to_vec()),
+        (Header::MAGIC_COMPRESSED, Some(compression)) => compression.decompress(payload),
+        (Header::MAGIC_COMPRESSED, None) => {
+            log::warn!("Received compressed message, but compression wasn't negotiated.");
+            Err(SerializingError::InvalidEncoding)
+        }
+        _ => Err(SerializingError::InvalidEncoding),
+    }
+}
 
 /// # TODO
 ///
@@ -23,19 +81,25 @@ pub struct MessageReceiver {
     close_tx: Mutex<Option<oneshot::Sender<()>>>,
 
     error_rx: Mutex<oneshot::Receiver<SerializingError>>,
+
+    /// Meter for the received messages. This is set once we know the peer.
+    meter: Arc<Mutex<Option<PeerBandwidthMeter>>>,
 }
 
 impl MessageReceiver {
-    pub fn new<I: AsyncRead + Unpin + Send + Sync + 'static>(inbound: I) -> Self {
+    /// Creates a receiver reading from `inbound`. If `compression` is `None`, compressed messages are rejected.
+    pub fn new<I: AsyncRead + Unpin + Send + Sync + 'static>(inbound: I, compression: Option<CompressionConfig>) -> Self {
         let channels = Arc::new(Mutex::new(HashMap::new()));
         let (close_tx, close_rx) = oneshot::channel();
         let (error_tx, error_rx) = oneshot::channel();
+        let meter = Arc::new(Mutex::new(None));
 
         async_std::task::spawn({
             let channels = Arc::clone(&channels);
+            let meter = Arc::clone(&meter);
 
             async move {
-                if let Err(e) = Self::reader(inbound, close_rx, channels).await {
+                if let Err(e) = Self::reader(inbound, close_rx, channels, compression, meter).await {
                     log::warn!("Peer::reader: error: {}", e);
                     error_tx.send(e).unwrap();
                 }
@@ -46,9 +110,15 @@ impl MessageReceiver {
             channels,
             close_tx: Mutex::new(Some(close_tx)),
             error_rx: Mutex::new(error_rx),
+            meter,
         }
     }
 
+    /// Sets the meter with which received messages are recorded.
+    pub(crate) fn set_meter(&self, meter: PeerBandwidthMeter) {
+        *self.meter.lock() = Some(meter);
+    }
+
     pub(crate) fn poll_error(&self, cx: &mut Context) -> Poll<Option<SerializingError>> {
         match self.error_rx.lock().poll_unpin(cx) {
             Poll::Ready(Ok(e)) => Poll::Ready(Some(e)),
@@ -76,10 +146,15 @@ impl MessageReceiver {
         }
         channels.insert(M::TYPE_ID, Some(tx));
 
-        rx.filter_map(|buf| async move {
-            let message = M::deserialize_message(&mut Cursor::new(buf))
-                .map_err(|e| log::error!("MessageReceiver error: {}", e))
-                .ok()?;
+        // The checksum was verified by the reader already, so only the payload is passed on.
+        rx.filter_map(|payload| async move {
+            let mut reader = Cursor::new(&payload);
+            let message = M::deserialize(&mut reader).map_err(|e| log::error!("MessageReceiver error: {}", e)).ok()?;
+
+            if reader.position() as usize != payload.len() {
+                log::error!("MessageReceiver error: Incorrect message length");
+                return None;
+            }
 
             Some(message)
         })
@@ -89,6 +164,8 @@ impl MessageReceiver {
         mut inbound: I,
         close_rx: oneshot::Receiver<()>,
         channels: Arc<Mutex<HashMap<u64, Option<mpsc::Sender<Vec<u8>>>>>>,
+        compression: Option<CompressionConfig>,
+        meter: Arc<Mutex<Option<PeerBandwidthMeter>>>,
     ) -> Result<(), SerializingError> {
         let mut close_rx = close_rx.fuse();
 
@@ -105,7 +182,21 @@ impl MessageReceiver {
                 },
             };
 
+            // Verify the checksum before dispatching the message. On a mismatch we return an error, which closes the
+            // connection.
+            verify_checksum(&data)?;
+
+            // Record the message with its size on the wire. If the peer exceeds its rate limit, we close the
+            // connection.
+            if let Some(meter) = meter.lock().as_ref() {
+                if !meter.meter.record_inbound_message(&meter.peer_id, peek_type(&data)?, data.len()) {
+                    log::warn!("Peer {} exceeded its rate limit", meter.peer_id);
+                    return Err(SerializingError::LimitExceeded);
+                }
+            }
+
             let message_type = peek_type(&data)?;
+            let data = decode_payload(&data, compression.as_ref())?;
 
             log::debug!("Receiving message: type={}", message_type);
             log::debug!("Raw: {:?}", data);
@@ -149,15 +240,19 @@ where
     O: AsyncWrite,
 {
     outbound: AsyncMutex<Option<O>>,
+
+    compression: Option<CompressionConfig>,
 }
 
 impl<O> MessageSender<O>
 where
     O: AsyncWrite + Unpin,
 {
-    pub fn new(outbound: O) -> Self {
+    /// Creates a sender writing to `outbound`. If `compression` is set, large messages are compressed.
+    pub fn new(outbound: O, compression: Option<CompressionConfig>) -> Self {
         Self {
             outbound: AsyncMutex::new(Some(outbound)),
+            compression,
         }
     }
 
@@ -173,10 +268,9 @@ where
         }
     }
 
-    pub async fn send<M: Message>(&self, message: &M) -> Result<(), SerializingError> {
-        let mut serialized = Vec::with_capacity(message.serialized_message_size());
-
-        message.serialize_message(&mut serialized)?;
+    /// Sends a message and returns the number of bytes written.
+    pub async fn send<M: Message>(&self, message: &M) -> Result<usize, SerializingError> {
+        let serialized = encode_message(message, self.compression.as_ref())?;
 
         log::debug!("Sending message: {:?}", serialized);
 
@@ -184,7 +278,7 @@ where
 
         if let Some(outbound) = outbound.as_mut() {
             outbound.write_all(&serialized).await?;
-            Ok(())
+            Ok(serialized.len())
         } else {
             log::error!("Outbound already closed.");
             Err(SerializingError::IoError(std::io::Error::from(std::io::ErrorKind::NotConnected)))
@@ -204,12 +298,12 @@ impl<C> MessageDispatch<C>
 where
     C: AsyncRead + AsyncWriteExt + Send + Sync + 'static,
 {
-    pub fn new(socket: C) -> Self {
+    pub fn new(socket: C, compression: Option<CompressionConfig>) -> Self {
         let (reader, writer) = socket.split();
 
         Self {
-            inbound: MessageReceiver::new(reader),
-            outbound: MessageSender::new(writer),
+            inbound: MessageReceiver::new(reader, compression.clone()),
+            outbound: MessageSender::new(writer, compression),
         }
     }
 
@@ -223,3 +317,78 @@ where
         log::debug!("MessageDispatch::close: Closed.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use beserial::{Deserialize, Serialize};
+    use nimiq_network_interface::message::{peek_magic, verify_checksum, Message};
+
+    use super::{decode_payload, encode_message};
+    use crate::message_codec::{CompressionConfig, Header};
+
+    #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+    struct TestMessage {
+        #[beserial(len_type(u16))]
+        data: String,
+    }
+
+    impl Message for TestMessage {
+        const TYPE_ID: u64 = 4200;
+    }
+
+    #[test]
+    fn it_compresses_and_decompresses_messages() {
+        let message = TestMessage {
+            data: "Hello World! ".repeat(100),
+        };
+
+        let mut serialized = vec![];
+        message.serialize_message(&mut serialized).unwrap();
+
+        let compressed = encode_message(&message, Some(&CompressionConfig::default())).unwrap();
+        assert!(compressed.len() < serialized.len());
+        assert_eq!(peek_magic(&compressed).unwrap(), Header::MAGIC_COMPRESSED);
+        verify_checksum(&compressed).unwrap();
+
+        let payload = decode_payload(&compressed, Some(&CompressionConfig::default())).unwrap();
+        assert_eq!(TestMessage::deserialize_from_vec(&payload).unwrap(), message);
+
+        // Compressed messages are only accepted if compression was negotiated.
+        assert!(decode_payload(&compressed, None).is_err());
+
+        let compression = CompressionConfig {
+            max_decompressed_size: 100,
+            ..Default::default()
+        };
+        assert!(decode_payload(&compressed, Some(&compression)).is_err());
+    }
+
+    #[test]
+    fn it_encodes_uncompressed_messages_like_the_message_trait() {
+        let message = TestMessage {
+            data: "Hello World!".to_owned(),
+        };
+
+        let mut serialized = vec![];
+        message.serialize_message(&mut serialized).unwrap();
+
+        let encoded = encode_message(&message, None).unwrap();
+        assert_eq!(encoded, serialized);
+        assert_eq!(decode_payload(&encoded, None).unwrap(), message.serialize_to_vec());
+    }
+
+    #[test]
+    fn it_detects_corrupted_messages() {
+        let message = TestMessage {
+            data: "Hello World!".to_owned(),
+        };
+
+        let mut serialized = vec![];
+        message.serialize_message(&mut serialized).unwrap();
+        verify_checksum(&serialized).unwrap();
+
+        let n = serialized.len();
+        serialized[n - 1] ^= 0x01;
+        assert!(verify_checksum(&serialized).is_err());
+    }
+}
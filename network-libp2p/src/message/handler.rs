@@ -16,6 +16,7 @@ use thiserror::Error;
 use beserial::SerializingError;
 
 use super::{behaviour::MessageConfig, dispatch::MessageDispatch, peer::Peer, protocol::MessageProtocol};
+use crate::{bandwidth::BandwidthMeter, request_response::RequestDispatch};
 
 #[derive(Clone, Debug)]
 pub enum HandlerInEvent {
@@ -38,10 +39,12 @@ pub enum HandlerError {
 }
 
 pub struct MessageHandler {
-    // NOTE: Will probably be used later.
-    #[allow(dead_code)]
     config: MessageConfig,
 
+    bandwidth: Arc<BandwidthMeter>,
+
+    requests: Arc<RequestDispatch>,
+
     peer_id: Option<PeerId>,
 
     peer: Option<Arc<Peer>>,
@@ -58,9 +61,11 @@ pub struct MessageHandler {
 }
 
 impl MessageHandler {
-    pub fn new(config: MessageConfig) -> Self {
+    pub fn new(config: MessageConfig, bandwidth: Arc<BandwidthMeter>, requests: Arc<RequestDispatch>) -> Self {
         Self {
             config,
+            bandwidth,
+            requests,
             peer_id: None,
             peer: None,
             close_rx: None,
@@ -88,7 +93,7 @@ impl ProtocolsHandler for MessageHandler {
     type OutboundOpenInfo = ();
 
     fn listen_protocol(&self) -> SubstreamProtocol<MessageProtocol, ()> {
-        SubstreamProtocol::new(MessageProtocol::default(), ())
+        SubstreamProtocol::new(MessageProtocol::new(self.config.compression.clone()), ())
     }
 
     fn inject_fully_negotiated_inbound(&mut self, socket: MessageDispatch<NegotiatedSubstream>, _info: ()) {
@@ -127,7 +132,7 @@ impl ProtocolsHandler for MessageHandler {
                 if outbound {
                     // Next open the outbound
                     self.events.push_back(ProtocolsHandlerEvent::OutboundSubstreamRequest {
-                        protocol: SubstreamProtocol::new(MessageProtocol::default(), ()),
+                        protocol: SubstreamProtocol::new(MessageProtocol::new(self.config.compression.clone()), ()),
                     });
                     self.wake();
                 }
@@ -247,7 +252,13 @@ impl ProtocolsHandler for MessageHandler {
 
             let (close_tx, close_rx) = oneshot::channel();
 
-            let peer = Arc::new(Peer::new(peer_id, socket, close_tx));
+            let peer = Arc::new(Peer::new(
+                peer_id,
+                socket,
+                close_tx,
+                Arc::clone(&self.bandwidth),
+                Arc::clone(&self.requests),
+            ));
             log::debug!("New peer: {:?}", peer);
 
             self.close_rx = Some(close_rx);
@@ -1,18 +1,24 @@
 use std::{
     hash::{Hash, Hasher},
     pin::Pin,
+    sync::Arc,
 };
 
 use async_trait::async_trait;
-use futures::{channel::oneshot, Stream};
+use futures::{channel::oneshot, future, Stream, StreamExt};
 use libp2p::{swarm::NegotiatedSubstream, PeerId};
 use parking_lot::Mutex;
 
+use beserial::{Deserialize, Serialize};
 use nimiq_network_interface::message::Message;
-use nimiq_network_interface::peer::{CloseReason, Peer as PeerInterface, RequestResponse, SendError};
+use nimiq_network_interface::peer::{CloseReason, InboundRequest, Peer as PeerInterface, RequestError, RequestResponse, SendError};
 
 use super::dispatch::MessageDispatch;
-use crate::network::NetworkError;
+use crate::{
+    bandwidth::{BandwidthMeter, PeerBandwidthMeter},
+    network::NetworkError,
+    request_response::RequestDispatch,
+};
 
 pub struct Peer {
     pub id: PeerId,
@@ -20,14 +26,31 @@ pub struct Peer {
     pub(crate) socket: MessageDispatch<NegotiatedSubstream>,
 
     close_tx: Mutex<Option<oneshot::Sender<CloseReason>>>,
+
+    bandwidth: Arc<BandwidthMeter>,
+
+    requests: Arc<RequestDispatch>,
 }
 
 impl Peer {
-    pub fn new(id: PeerId, socket: MessageDispatch<NegotiatedSubstream>, close_tx: oneshot::Sender<CloseReason>) -> Self {
+    pub fn new(
+        id: PeerId,
+        socket: MessageDispatch<NegotiatedSubstream>,
+        close_tx: oneshot::Sender<CloseReason>,
+        bandwidth: Arc<BandwidthMeter>,
+        requests: Arc<RequestDispatch>,
+    ) -> Self {
+        socket.inbound.set_meter(PeerBandwidthMeter {
+            peer_id: id.clone(),
+            meter: Arc::clone(&bandwidth),
+        });
+
         Self {
             id,
             socket,
             close_tx: Mutex::new(Some(close_tx)),
+            bandwidth,
+            requests,
         }
     }
 }
@@ -70,7 +93,9 @@ impl PeerInterface for Peer {
     }
 
     async fn send<M: Message>(&self, message: &M) -> Result<(), SendError> {
-        Ok(self.socket.outbound.send(message).await?)
+        let bytes = self.socket.outbound.send(message).await?;
+        self.bandwidth.record_outbound_message(&self.id, M::TYPE_ID, bytes);
+        Ok(())
     }
 
     fn receive<M: Message>(&self) -> Pin<Box<dyn Stream<Item = M> + Send>> {
@@ -91,11 +116,33 @@ impl PeerInterface for Peer {
         }
     }
 
-    async fn request<R: RequestResponse>(&self, _request: &<R as RequestResponse>::Request) -> Result<R::Response, Self::Error> {
-        unimplemented!()
+    async fn request<R: RequestResponse>(&self, request: &R::Request) -> Result<R::Response, RequestError> {
+        let data = self
+            .requests
+            .request(&self.id, R::TYPE_ID, request.serialize_to_vec(), R::MAX_CONCURRENT_REQUESTS, R::TIMEOUT)
+            .await?;
+
+        Ok(R::Response::deserialize_from_vec(&data)?)
     }
 
-    fn requests<R: RequestResponse>(&self) -> Box<dyn Stream<Item = R::Request>> {
-        unimplemented!()
+    fn requests<R: RequestResponse>(&self) -> Pin<Box<dyn Stream<Item = InboundRequest<R>> + Send>> {
+        let peer_id = self.id.clone();
+
+        let requests = self
+            .requests
+            .register(self.id.clone(), R::TYPE_ID, R::MAX_CONCURRENT_REQUESTS)
+            .filter_map(move |(data, response_tx)| {
+                let request = match R::Request::deserialize_from_vec(&data) {
+                    Ok(request) => Some(InboundRequest::new(request, response_tx)),
+                    Err(e) => {
+                        // Dropping the response channel rejects the request.
+                        log::warn!("Failed to deserialize request from {}: {}", peer_id, e);
+                        None
+                    }
+                };
+                future::ready(request)
+            });
+
+        Box::pin(requests)
     }
 }
@@ -1,4 +1,4 @@
-use std::{iter, sync::Arc};
+use std::{sync::Arc, vec};
 
 use futures::{future, AsyncRead, AsyncWrite};
 use libp2p::{core::UpgradeInfo, InboundUpgrade, OutboundUpgrade};
@@ -6,19 +6,41 @@ use libp2p::{core::UpgradeInfo, InboundUpgrade, OutboundUpgrade};
 use beserial::SerializingError;
 
 use super::{dispatch::MessageDispatch, peer::Peer};
-use crate::MESSAGE_PROTOCOL;
+use crate::{message_codec::CompressionConfig, MESSAGE_PROTOCOL, MESSAGE_PROTOCOL_DEFLATE};
 
 #[derive(Debug, Default)]
 pub struct MessageProtocol {
     peer: Option<Arc<Peer>>,
+
+    /// If set, we offer the compressed variant of the protocol first.
+    compression: Option<CompressionConfig>,
+}
+
+impl MessageProtocol {
+    pub fn new(compression: Option<CompressionConfig>) -> Self {
+        Self { peer: None, compression }
+    }
+
+    /// Returns the compression config, if the negotiated protocol uses compression.
+    fn negotiated_compression(self, info: &[u8]) -> Option<CompressionConfig> {
+        if info == MESSAGE_PROTOCOL_DEFLATE {
+            self.compression
+        } else {
+            None
+        }
+    }
 }
 
 impl UpgradeInfo for MessageProtocol {
     type Info = &'static [u8];
-    type InfoIter = iter::Once<Self::Info>;
+    type InfoIter = vec::IntoIter<Self::Info>;
 
     fn protocol_info(&self) -> Self::InfoIter {
-        iter::once(MESSAGE_PROTOCOL)
+        if self.compression.is_some() {
+            vec![MESSAGE_PROTOCOL_DEFLATE, MESSAGE_PROTOCOL].into_iter()
+        } else {
+            vec![MESSAGE_PROTOCOL].into_iter()
+        }
     }
 }
 
@@ -30,8 +52,8 @@ where
     type Error = SerializingError;
     type Future = future::Ready<Result<MessageDispatch<C>, SerializingError>>;
 
-    fn upgrade_inbound(self, socket: C, _info: Self::Info) -> Self::Future {
-        future::ok(MessageDispatch::new(socket))
+    fn upgrade_inbound(self, socket: C, info: Self::Info) -> Self::Future {
+        future::ok(MessageDispatch::new(socket, self.negotiated_compression(info)))
     }
 }
 
@@ -43,7 +65,7 @@ where
     type Error = SerializingError;
     type Future = future::Ready<Result<MessageDispatch<C>, SerializingError>>;
 
-    fn upgrade_outbound(self, socket: C, _info: Self::Info) -> Self::Future {
-        future::ok(MessageDispatch::new(socket))
+    fn upgrade_outbound(self, socket: C, info: Self::Info) -> Self::Future {
+        future::ok(MessageDispatch::new(socket, self.negotiated_compression(info)))
     }
 }
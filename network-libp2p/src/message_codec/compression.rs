@@ -0,0 +1,103 @@
+use std::io::{Read, Write};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use beserial::SerializingError;
+
+#[derive(Clone, Debug)]
+pub struct CompressionConfig {
+    /// Messages with a size (in bytes) of at least this threshold are compressed. Smaller messages are sent as-is.
+    pub threshold: usize,
+
+    /// Maximum size of a decompressed message. Messages that exceed this are rejected, to protect against
+    /// decompression bombs.
+    pub max_decompressed_size: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 1024,
+            max_decompressed_size: 10_000_000,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Returns whether a message of the given size should be compressed.
+    pub fn should_compress(&self, size: usize) -> bool {
+        size >= self.threshold
+    }
+
+    /// Compresses `data`, if it's large enough and compression actually reduces its size. Returns `None` if the data
+    /// should be sent as-is.
+    pub fn compress(&self, data: &[u8]) -> Result<Option<Vec<u8>>, SerializingError> {
+        if !self.should_compress(data.len()) {
+            return Ok(None);
+        }
+
+        let compressed = compress(data)?;
+        if compressed.len() >= data.len() {
+            return Ok(None);
+        }
+
+        Ok(Some(compressed))
+    }
+
+    /// Decompresses `data`, rejecting it if it would be larger than `max_decompressed_size`.
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, SerializingError> {
+        decompress(data, self.max_decompressed_size)
+    }
+}
+
+/// Compresses `data` with deflate.
+pub fn compress(data: &[u8]) -> Result<Vec<u8>, SerializingError> {
+    let mut encoder = DeflateEncoder::new(Vec::with_capacity(data.len() / 2), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompresses `data`. Returns `SerializingError::LimitExceeded`, if the decompressed data would be larger than
+/// `max_size`.
+pub fn decompress(data: &[u8], max_size: usize) -> Result<Vec<u8>, SerializingError> {
+    let mut decompressed = Vec::new();
+
+    // Read at most one byte more than allowed, such that we can detect if the limit is exceeded without
+    // decompressing everything.
+    DeflateDecoder::new(data)
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decompressed)?;
+
+    if decompressed.len() > max_size {
+        return Err(SerializingError::LimitExceeded);
+    }
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use beserial::SerializingError;
+
+    use super::{compress, decompress};
+
+    #[test]
+    fn it_compresses_and_decompresses() {
+        let data = b"Hello World! Hello World! Hello World! Hello World!".repeat(16);
+
+        let compressed = compress(&data).unwrap();
+        assert!(compressed.len() < data.len());
+
+        assert_eq!(decompress(&compressed, data.len()).unwrap(), data);
+    }
+
+    #[test]
+    fn it_rejects_decompression_bombs() {
+        let compressed = compress(&vec![0u8; 1_000_000]).unwrap();
+
+        match decompress(&compressed, 1000) {
+            Err(SerializingError::LimitExceeded) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+}
@@ -1,15 +1,20 @@
 use beserial::{Deserialize, Serialize};
+use nimiq_utils::crc::Crc32Computer;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Header {
     pub magic: u32,
     pub size: u32,
+    /// CRC32 checksum of the data following the header (i.e. the compressed data for compressed messages).
     pub checksum: u32,
 }
 
 impl Header {
     pub const MAGIC: u32 = 0x4204_2042;
 
+    /// Magic for messages whose data is compressed.
+    pub const MAGIC_COMPRESSED: u32 = 0x4204_2043;
+
     pub const SIZE: usize = 12;
 
     pub fn new(size: u32) -> Self {
@@ -19,6 +24,23 @@ impl Header {
             checksum: 0,
         }
     }
+
+    /// Creates a header for `data`, including its checksum.
+    pub fn for_data(data: &[u8], compressed: bool) -> Self {
+        Self {
+            magic: if compressed { Self::MAGIC_COMPRESSED } else { Self::MAGIC },
+            size: data.len() as u32,
+            checksum: Self::compute_checksum(data),
+        }
+    }
+
+    pub fn compute_checksum(data: &[u8]) -> u32 {
+        Crc32Computer::default().update(data).result()
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.magic == Self::MAGIC_COMPRESSED
+    }
 }
 
 impl Default for Header {
@@ -1,7 +1,10 @@
+mod compression;
 mod header;
 mod reader;
 mod writer;
 
+pub use self::compression::CompressionConfig;
+pub use self::header::Header;
 pub use self::reader::MessageReader;
 pub use self::writer::MessageWriter;
 
@@ -10,7 +13,7 @@ mod tests {
     use beserial::{Deserialize, Serialize};
     use futures::{io::Cursor, SinkExt, StreamExt};
 
-    use super::{MessageReader, MessageWriter};
+    use super::{CompressionConfig, MessageReader, MessageWriter};
 
     #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
     struct TestMessage {
@@ -34,4 +37,37 @@ mod tests {
 
         assert_eq!(message, received);
     }
+
+    #[tokio::test]
+    async fn it_writes_and_reads_compressed_messages() {
+        let mut buf = vec![];
+
+        let messages = vec![
+            TestMessage {
+                foo: 42,
+                bar: "Hello World".to_owned(),
+            },
+            TestMessage {
+                foo: 1337,
+                bar: "Hello World".repeat(20),
+            },
+        ];
+
+        let compression = CompressionConfig {
+            threshold: 100,
+            ..Default::default()
+        };
+
+        let mut writer = MessageWriter::with_compression(&mut buf, compression.clone());
+        for message in &messages {
+            writer.send(message).await.unwrap();
+        }
+
+        let received: Vec<TestMessage> = MessageReader::with_compression(Cursor::new(buf), compression)
+            .map(|message| message.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(messages, received);
+    }
 }
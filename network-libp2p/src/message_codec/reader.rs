@@ -9,7 +9,7 @@ use pin_project::pin_project;
 
 use beserial::{Deserialize, SerializingError};
 
-use super::header::Header;
+use super::{compression::CompressionConfig, header::Header};
 
 /// Try to read, such that at most `n` bytes are in the buffer. This will return `Poll::Pending` until the buffer
 /// has `n` bytes in it. This returns `Poll::Ready(Ok(false))` in case of EOF.
@@ -84,6 +84,8 @@ pub struct MessageReader<R, M> {
 
     buffer: BytesMut,
 
+    compression: Option<CompressionConfig>,
+
     _message_type: PhantomData<M>,
 }
 
@@ -93,10 +95,19 @@ impl<R, M> MessageReader<R, M> {
             inner,
             state: ReaderState::Head,
             buffer: BytesMut::with_capacity(1024), // TODO: initial size?
+            compression: None,
             _message_type: PhantomData,
         }
     }
 
+    /// Creates a reader that accepts compressed messages. Without compression, compressed messages are rejected.
+    pub fn with_compression(inner: R, compression: CompressionConfig) -> Self {
+        Self {
+            compression: Some(compression),
+            ..Self::new(inner)
+        }
+    }
+
     pub fn into_inner(self) -> R {
         self.inner
     }
@@ -113,6 +124,7 @@ impl<R, M> MessageReader<R, M> {
             inner: self.inner,
             state: ReaderState::Head,
             buffer: self.buffer,
+            compression: self.compression,
             _message_type: PhantomData,
         }
     }
@@ -165,11 +177,21 @@ where
 
                 // Decode the header: 16 bit length big-endian
                 // This will also advance the read position after the header.
-                let header = match Deserialize::deserialize_from_vec(&self_projected.buffer) {
+                let header: Header = match Deserialize::deserialize_from_vec(&self_projected.buffer) {
                     Ok(header) => header,
                     Err(e) => return Poll::Ready(Some(Err(e))),
                 };
 
+                match header.magic {
+                    Header::MAGIC => {}
+                    Header::MAGIC_COMPRESSED if self_projected.compression.is_some() => {}
+                    Header::MAGIC_COMPRESSED => {
+                        log::warn!("MessageReader: Received compressed message, but compression wasn't negotiated.");
+                        return Poll::Ready(Some(Err(SerializingError::InvalidEncoding)));
+                    }
+                    _ => return Poll::Ready(Some(Err(SerializingError::InvalidEncoding))),
+                }
+
                 // Reset the buffer
                 self_projected.buffer.clear();
 
@@ -196,8 +218,25 @@ where
                     Poll::Ready(Ok(true)) => (),
                 }
 
+                // Verify the checksum. A mismatch is returned as an error, such that the connection gets closed.
+                // Peers running an older version always send a zero checksum, so we accept that for the transition.
+                // TODO: Reject zero checksums once all peers compute them.
+                if header.checksum != 0 && Header::compute_checksum(&self_projected.buffer) != header.checksum {
+                    log::warn!("MessageReader: Checksum mismatch");
+                    return Poll::Ready(Some(Err(SerializingError::from(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Bad checksum",
+                    )))));
+                }
+
                 // Decode the message, the read position of the buffer is already at the start of the message.
-                let message: M = match Deserialize::deserialize(&mut self_projected.buffer.reader()) {
+                let result = match self_projected.compression {
+                    Some(compression) if header.is_compressed() => {
+                        compression.decompress(&self_projected.buffer).and_then(|data| Deserialize::deserialize_from_vec(&data))
+                    }
+                    _ => Deserialize::deserialize(&mut self_projected.buffer.reader()),
+                };
+                let message: M = match result {
                     Ok(message) => message,
                     Err(e) => return Poll::Ready(Some(Err(e))),
                 };
@@ -229,11 +268,16 @@ where
 mod tests {
     use futures::{io::Cursor, StreamExt};
 
-    use beserial::{Deserialize, Serialize};
+    use std::io::Write;
+
+    use beserial::{Deserialize, Serialize, SerializingError};
     use bytes::{BufMut, BytesMut};
 
     use super::MessageReader;
-    use crate::message_codec::header::Header;
+    use crate::message_codec::{
+        compression::{compress, CompressionConfig},
+        header::Header,
+    };
 
     #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
     struct TestMessage {
@@ -242,15 +286,18 @@ mod tests {
         pub bar: String,
     }
 
-    fn put_message<M: Serialize>(buf: &mut BytesMut, message: &M) {
-        let n = message.serialized_size();
-        buf.reserve(n + Header::SIZE);
-        let header = Header::new(n as u32);
+    fn put_data(buf: &mut BytesMut, header: &Header, data: &[u8]) {
+        buf.reserve(data.len() + Header::SIZE);
 
         let mut w = buf.writer();
 
         header.serialize(&mut w).unwrap();
-        message.serialize(&mut w).unwrap();
+        w.write_all(data).unwrap();
+    }
+
+    fn put_message<M: Serialize>(buf: &mut BytesMut, message: &M) {
+        let data = message.serialize_to_vec();
+        put_data(buf, &Header::for_data(&data, false), &data);
     }
 
     #[tokio::test]
@@ -289,4 +336,65 @@ mod tests {
         assert_eq!(reader.next().await, Some(Ok(m2)));
         assert_eq!(reader.next().await, None);
     }
+
+    #[tokio::test]
+    pub async fn it_rejects_a_bad_checksum() {
+        let data = TestMessage {
+            foo: 42,
+            bar: "Hello World".to_owned(),
+        }
+        .serialize_to_vec();
+
+        let mut header = Header::for_data(&data, false);
+        header.checksum ^= 1;
+
+        let mut buf = BytesMut::new();
+        put_data(&mut buf, &header, &data);
+        let mut reader = MessageReader::<_, TestMessage>::new(Cursor::new(&buf));
+
+        assert!(matches!(reader.next().await, Some(Err(SerializingError::IoError(_)))));
+    }
+
+    #[tokio::test]
+    pub async fn it_accepts_zero_checksums_of_older_peers() {
+        let test_message = TestMessage {
+            foo: 42,
+            bar: "Hello World".to_owned(),
+        };
+        let data = test_message.serialize_to_vec();
+
+        let mut header = Header::for_data(&data, false);
+        header.checksum = 0;
+
+        let mut buf = BytesMut::new();
+        put_data(&mut buf, &header, &data);
+        let mut reader = MessageReader::<_, TestMessage>::new(Cursor::new(&buf));
+
+        assert_eq!(reader.next().await, Some(Ok(test_message)));
+    }
+
+    #[tokio::test]
+    pub async fn it_reads_compressed_messages_only_if_negotiated() {
+        let test_message = TestMessage {
+            foo: 42,
+            bar: "Hello World".repeat(20),
+        };
+
+        let data = compress(&test_message.serialize_to_vec()).unwrap();
+        let mut buf = BytesMut::new();
+        put_data(&mut buf, &Header::for_data(&data, true), &data);
+
+        let mut reader = MessageReader::with_compression(Cursor::new(&buf), CompressionConfig::default());
+        assert_eq!(reader.next().await, Some(Ok(test_message.clone())));
+
+        let mut reader = MessageReader::<_, TestMessage>::new(Cursor::new(&buf));
+        assert_eq!(reader.next().await, Some(Err(SerializingError::InvalidEncoding)));
+
+        let compression = CompressionConfig {
+            max_decompressed_size: 100,
+            ..Default::default()
+        };
+        let mut reader = MessageReader::<_, TestMessage>::with_compression(Cursor::new(&buf), compression);
+        assert_eq!(reader.next().await, Some(Err(SerializingError::LimitExceeded)));
+    }
 }
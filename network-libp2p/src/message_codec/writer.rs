@@ -1,4 +1,4 @@
-use std::{marker::PhantomData, pin::Pin};
+use std::{io::Write, marker::PhantomData, pin::Pin};
 
 use bytes::{Buf, BufMut, BytesMut};
 use futures::{
@@ -10,7 +10,7 @@ use pin_project::pin_project;
 
 use beserial::{Serialize, SerializingError};
 
-use super::header::Header;
+use super::{compression::CompressionConfig, header::Header};
 
 fn write_from_buf<'w, W>(inner: &mut W, buffer: &mut BytesMut, cx: &mut Context) -> Poll<Result<(), SerializingError>>
 where
@@ -47,6 +47,7 @@ where
 pub struct MessageWriter<W, M> {
     inner: W,
     buffer: BytesMut,
+    compression: Option<CompressionConfig>,
     _message_type: PhantomData<M>,
 }
 
@@ -55,10 +56,20 @@ impl<W, M> MessageWriter<W, M> {
         Self {
             inner,
             buffer: BytesMut::new(),
+            compression: None,
             _message_type: PhantomData,
         }
     }
 
+    /// Creates a writer that compresses messages that are larger than the configured threshold. This must only be
+    /// used if the receiver agreed to receive compressed messages.
+    pub fn with_compression(inner: W, compression: CompressionConfig) -> Self {
+        Self {
+            compression: Some(compression),
+            ..Self::new(inner)
+        }
+    }
+
     pub fn into_inner(self) -> W {
         self.inner
     }
@@ -95,19 +106,30 @@ where
         log::trace!("MessageWriter: Sending {:?}", item);
         log::trace!("MessageWriter: serialized_size = {}", item.serialized_size());
 
+        let mut data = item.serialize_to_vec();
+        let mut compressed = false;
+
+        // Compress the message, if it's large enough and compression actually reduces its size.
+        if let Some(compression) = self_projected.compression {
+            if let Some(compressed_data) = compression.compress(&data)? {
+                log::trace!("MessageWriter: compressed {} bytes to {} bytes", data.len(), compressed_data.len());
+                data = compressed_data;
+                compressed = true;
+            }
+        }
+
         // Reserve space for the header and message.
-        let n = Serialize::serialized_size(item);
-        self_projected.buffer.reserve(n + Header::SIZE);
+        self_projected.buffer.reserve(data.len() + Header::SIZE);
 
-        let header = Header::new(n as u32);
+        let header = Header::for_data(&data, compressed);
 
         let mut w = self_projected.buffer.writer();
 
         // Write header
         Serialize::serialize(&header, &mut w)?;
 
-        // Serialize the message into the buffer.
-        Serialize::serialize(item, &mut w)?;
+        // Write the message data into the buffer.
+        w.write_all(&data)?;
 
         log::trace!("MessageWriter: buffer = {:?}", self_projected.buffer);
 
@@ -154,7 +176,7 @@ mod tests {
     use beserial::{Deserialize, Serialize};
 
     use super::MessageWriter;
-    use crate::message_codec::header::Header;
+    use crate::message_codec::{compression::CompressionConfig, header::Header};
 
     #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
     struct TestMessage {
@@ -176,6 +198,33 @@ mod tests {
 
         let data = message_writer.into_inner();
 
+        let header: Header = Deserialize::deserialize_from_vec(&data).unwrap();
+        assert_eq!(header.magic, Header::MAGIC);
+        assert_eq!(header.checksum, Header::compute_checksum(&data[Header::SIZE..]));
         assert_eq!(&test_message.serialize_to_vec(), &data[Header::SIZE..])
     }
+
+    #[tokio::test]
+    pub async fn it_compresses_large_messages() {
+        let test_message = TestMessage {
+            foo: 42,
+            bar: "Hello World".repeat(20),
+        };
+
+        let compression = CompressionConfig {
+            threshold: 100,
+            ..Default::default()
+        };
+
+        let mut message_writer = MessageWriter::with_compression(vec![], compression);
+
+        message_writer.send(&test_message).await.unwrap();
+
+        let data = message_writer.into_inner();
+        let header: Header = Deserialize::deserialize_from_vec(&data).unwrap();
+
+        assert!(header.is_compressed());
+        assert_eq!(header.size as usize, data.len() - Header::SIZE);
+        assert!(data.len() < test_message.serialized_size());
+    }
 }
@@ -1,6 +1,12 @@
 #![allow(dead_code)]
 
-use std::{collections::HashMap, pin::Pin, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
 use futures::{
@@ -10,17 +16,18 @@ use futures::{
 };
 use libp2p::{
     core,
-    core::{muxing::StreamMuxerBox, network::NetworkInfo, transport::Boxed},
+    core::{muxing::StreamMuxerBox, network::NetworkInfo, transport::Boxed, ConnectedPoint},
     dns,
     gossipsub::{GossipsubConfig, GossipsubEvent, GossipsubMessage, MessageId, Topic as GossipsubTopic, TopicHash},
     identity::Keypair,
-    kad::{GetRecordOk, KademliaConfig, KademliaEvent, QueryId, QueryResult, Quorum, Record},
+    kad::{GetRecordError, GetRecordOk, KademliaConfig, KademliaEvent, PeerRecord, QueryId, QueryResult, Quorum, Record},
     noise,
     swarm::{SwarmBuilder, SwarmEvent},
     tcp, websocket, yamux, Multiaddr, PeerId, Swarm, Transport,
 };
 use thiserror::Error;
 use tokio::sync::broadcast;
+use wasm_timer::Interval;
 
 #[cfg(test)]
 use libp2p::core::transport::MemoryTransport;
@@ -28,16 +35,23 @@ use libp2p::core::transport::MemoryTransport;
 use beserial::{Deserialize, Serialize};
 use nimiq_hash::Blake2bHash;
 use nimiq_network_interface::{
-    network::{Network as NetworkInterface, NetworkEvent, PubsubId, Topic},
-    peer::{Peer as PeerInterface},
+    network::{DhtRecordValidator, Network as NetworkInterface, NetworkEvent, PubsubId, Topic},
+    peer::{CloseReason, Peer as PeerInterface},
     peer_map::ObservablePeerMap,
 };
 use nimiq_utils::time::OffsetTime;
 
 use crate::{
+    bandwidth::{BandwidthConfig, BandwidthMeter, BandwidthStats},
     behaviour::{NimiqBehaviour, NimiqEvent, NimiqNetworkBehaviourError},
-    discovery::{behaviour::DiscoveryConfig, peer_contacts::PeerContact},
+    dht::{DhtConfig, DhtRecordValidatorHandle, DhtStoreError},
+    discovery::{
+        behaviour::DiscoveryConfig,
+        peer_contacts::{PeerContact, PeerContactBookConfig, Protocols, Services},
+    },
     limit::behaviour::LimitConfig,
+    request_response::RequestConfig,
+    tls::TlsConfig,
     message::behaviour::MessageConfig,
     message::peer::Peer,
 };
@@ -49,11 +63,21 @@ pub struct Config {
 
     pub min_peers: usize,
 
+    pub peer_contact_book: PeerContactBookConfig,
     pub discovery: DiscoveryConfig,
     pub message: MessageConfig,
     pub limit: LimitConfig,
+    pub bandwidth: BandwidthConfig,
+    pub dht: DhtConfig,
+    /// Validator for DHT records. It checks records that are loaded from disk as well, so it must be set here instead of
+    /// with `set_dht_record_validator` if the records are persisted.
+    pub dht_record_validator: Option<Arc<dyn DhtRecordValidator>>,
     pub kademlia: KademliaConfig,
     pub gossipsub: GossipsubConfig,
+    pub requests: RequestConfig,
+
+    /// Certificate and key for secure websockets. This is required to listen on `/wss` addresses.
+    pub tls: Option<TlsConfig>,
 }
 
 impl Config {
@@ -64,11 +88,17 @@ impl Config {
         Self {
             keypair,
             peer_contact,
+            peer_contact_book: PeerContactBookConfig::default(),
             discovery: DiscoveryConfig::new(genesis_hash),
             message: MessageConfig::default(),
             limit: LimitConfig::default(),
+            bandwidth: BandwidthConfig::default(),
+            dht: DhtConfig::default(),
+            dht_record_validator: None,
             kademlia: KademliaConfig::default(),
             gossipsub: gossipsub_config,
+            requests: RequestConfig::default(),
+            tls: None,
             min_peers: 5,
         }
     }
@@ -91,8 +121,8 @@ pub enum NetworkError {
     #[error("Network behaviour error: {0}")]
     Behaviour(#[from] NimiqNetworkBehaviourError),
 
-    #[error("DHT store error: {0:?}")]
-    DhtStore(libp2p::kad::store::Error),
+    #[error("DHT store error: {0}")]
+    DhtStore(#[from] DhtStoreError),
 
     #[error("DHT GetRecord error: {0:?}")]
     DhtGetRecord(libp2p::kad::GetRecordError),
@@ -106,7 +136,7 @@ pub enum NetworkError {
 
 impl From<libp2p::kad::store::Error> for NetworkError {
     fn from(e: libp2p::kad::store::Error) -> Self {
-        Self::DhtStore(e)
+        Self::DhtStore(e.into())
     }
 }
 
@@ -166,22 +196,77 @@ pub enum NetworkAction {
         source: PeerId,
         output: oneshot::Sender<Result<bool, NetworkError>>,
     },
+    DisconnectPeer {
+        peer_id: PeerId,
+        reason: String,
+        output: oneshot::Sender<bool>,
+    },
+    BanPeer {
+        peer_id: PeerId,
+        duration: Option<Duration>,
+        output: oneshot::Sender<()>,
+    },
+    UnbanPeer {
+        peer_id: PeerId,
+        output: oneshot::Sender<()>,
+    },
+    BanIp {
+        ip: IpAddr,
+        duration: Option<Duration>,
+        output: oneshot::Sender<()>,
+    },
+    UnbanIp {
+        ip: IpAddr,
+        output: oneshot::Sender<()>,
+    },
+    ConnectedPeers {
+        output: oneshot::Sender<Vec<PeerInfo>>,
+    },
+    ListenAddresses {
+        output: oneshot::Sender<Vec<Multiaddr>>,
+    },
+}
+
+/// Information about a connected peer.
+#[derive(Clone, Debug)]
+pub struct PeerInfo {
+    pub peer_id: PeerId,
+
+    /// The peer's contact, if we received it during discovery.
+    pub peer_contact: Option<PeerContact>,
+
+    /// The remote address of the connection.
+    pub address: Multiaddr,
+
+    /// Whether we dialed the peer.
+    pub outbound: bool,
+
+    /// How long the peer has been connected.
+    pub connected_for: Duration,
 }
 
 
 struct TaskState {
+    dht_config: DhtConfig,
+    /// Records that we published, by key. These are republished periodically.
+    dht_published: HashMap<Vec<u8>, Vec<u8>>,
     dht_puts: HashMap<QueryId, oneshot::Sender<Result<(), NetworkError>>>,
     dht_gets: HashMap<QueryId, oneshot::Sender<Result<Option<Vec<u8>>, NetworkError>>>,
     gossip_topics: HashMap<TopicHash, (mpsc::Sender<(GossipsubMessage, MessageId, PeerId)>, bool)>,
+    /// Endpoint and time of establishment of the connection to each connected peer.
+    connections: HashMap<PeerId, (ConnectedPoint, Instant)>,
     connected_tx: Option<oneshot::Sender<()>>,
 }
 
 impl TaskState {
-    pub fn new(connected_tx: oneshot::Sender<()>) -> Self {
+    pub fn new(connected_tx: oneshot::Sender<()>, dht_config: DhtConfig) -> Self {
         Self {
+            dht_config,
+            dht_published: HashMap::new(),
             dht_puts: HashMap::new(),
             dht_gets: HashMap::new(),
             gossip_topics: HashMap::new(),
+            connections: HashMap::new(),
             connected_tx: Some(connected_tx),
         }
     }
@@ -214,6 +299,8 @@ pub struct Network {
     events_tx: broadcast::Sender<NetworkEvent<Peer>>,
     action_tx: AsyncMutex<mpsc::Sender<NetworkAction>>,
     peers: ObservablePeerMap<Peer>,
+    dht_validator: DhtRecordValidatorHandle,
+    bandwidth: Arc<BandwidthMeter>,
 }
 
 impl Network {
@@ -226,13 +313,18 @@ impl Network {
     ///             offset by exchanging their wall-time with other peers.
     ///  - `config`: The network configuration, containing key pair, and other behaviour-specific configuration.
     ///
-    pub async fn new(listen_addresses: Vec<Multiaddr>, clock: Arc<OffsetTime>, config: Config) -> Self {
+    /// Fails if the transport can't be set up, e.g. because the TLS key or certificates are invalid.
+    ///
+    pub async fn new(listen_addresses: Vec<Multiaddr>, clock: Arc<OffsetTime>, config: Config) -> std::io::Result<Self> {
         assert!(!config.gossipsub.hash_topics, "Hash topics not supported");
 
         let min_peers = config.min_peers;
+        let dht_config = config.dht.clone();
 
-        let swarm = Self::new_swarm(listen_addresses, clock, config);
+        let mut swarm = Self::new_swarm(listen_addresses, clock, config)?;
         let peers = swarm.message.peers.clone();
+        let dht_validator = swarm.kademlia.store_mut().validator();
+        let bandwidth = Arc::clone(&swarm.bandwidth);
 
         let local_peer_id = Swarm::local_peer_id(&swarm).clone();
 
@@ -241,25 +333,36 @@ impl Network {
 
         let (connected_tx, connected_rx) = oneshot::channel();
 
-        async_std::task::spawn(Self::swarm_task(swarm, events_tx.clone(), action_rx, connected_tx, min_peers));
+        async_std::task::spawn(Self::swarm_task(swarm, events_tx.clone(), action_rx, connected_tx, min_peers, dht_config));
 
         if min_peers != 0 {
             log::info!("Waiting to connect to {} peers", min_peers);
             connected_rx.await.unwrap();
         }
 
-        Self {
+        Ok(Self {
             local_peer_id,
             events_tx,
             action_tx: AsyncMutex::new(action_tx),
             peers,
-        }
+            dht_validator,
+            bandwidth,
+        })
     }
 
-    fn new_transport(keypair: &Keypair) -> std::io::Result<Boxed<(PeerId, StreamMuxerBox)>> {
+    fn new_transport(keypair: &Keypair, tls: Option<&TlsConfig>) -> std::io::Result<Boxed<(PeerId, StreamMuxerBox)>> {
         let transport = {
             // Websocket over TCP/DNS
-            let transport = websocket::WsConfig::new(dns::DnsConfig::new(tcp::TcpConfig::new().nodelay(true))?);
+            let mut transport = websocket::WsConfig::new(dns::DnsConfig::new(tcp::TcpConfig::new().nodelay(true))?);
+
+            // With a TLS config, we can also listen on `/wss` addresses.
+            if let Some(tls) = tls {
+                transport.set_tls_config(tls.to_websocket_config()?);
+            }
+
+            // Plain TCP/DNS, for addresses without `/ws` or `/wss`. The websocket transport rejects those, so which
+            // transport is used is selected by the listen or dial address.
+            let transport = transport.or_transport(dns::DnsConfig::new(tcp::TcpConfig::new().nodelay(true))?);
 
             // Memory transport for testing
             // TODO: Use websocket over the memory transport
@@ -279,17 +382,20 @@ impl Network {
             .boxed())
     }
 
-    fn new_swarm(listen_addresses: Vec<Multiaddr>, clock: Arc<OffsetTime>, config: Config) -> Swarm<NimiqBehaviour> {
+    fn new_swarm(listen_addresses: Vec<Multiaddr>, clock: Arc<OffsetTime>, config: Config) -> std::io::Result<Swarm<NimiqBehaviour>> {
         let local_peer_id = PeerId::from(config.keypair.public());
 
-        let transport = Self::new_transport(&config.keypair).unwrap();
+        let transport = Self::new_transport(&config.keypair, config.tls.as_ref())?;
+
+        let pending_incoming_max = config.limit.pending_incoming_max;
+        let pending_outgoing_max = config.limit.pending_outgoing_max;
 
         let behaviour = NimiqBehaviour::new(config, clock);
 
-        // TODO add proper config
+        // The limits for established connections are enforced by the `LimitBehaviour`.
         let mut swarm = SwarmBuilder::new(transport, behaviour, local_peer_id)
-            .incoming_connection_limit(5)
-            .outgoing_connection_limit(2)
+            .incoming_connection_limit(pending_incoming_max as usize)
+            .outgoing_connection_limit(pending_outgoing_max as usize)
             .peer_connection_limit(1)
             .build();
 
@@ -297,7 +403,31 @@ impl Network {
             Swarm::listen_on(&mut swarm, listen_addr).expect("Failed to listen on provided address");
         }
 
-        swarm
+        // Dial the peer contacts that we know from a previous run, such that we can rejoin the network without seeds.
+        Self::dial_best_peers(&mut swarm, pending_outgoing_max as usize);
+
+        Ok(swarm)
+    }
+
+    /// Dials up to `n` of the best scoring peers from the peer contact book that we're not connected to yet.
+    fn dial_best_peers(swarm: &mut NimiqSwarm, n: usize) {
+        let peer_ids = swarm
+            .discovery
+            .peer_contact_book()
+            .read()
+            .query(Protocols::all(), Services::all())
+            .filter(|contact| !contact.is_connected())
+            .map(|contact| contact.peer_id().clone())
+            .filter(|peer_id| !Swarm::is_connected(swarm, peer_id))
+            .take(n)
+            .collect::<Vec<PeerId>>();
+
+        for peer_id in peer_ids {
+            log::debug!("Dialing known peer: peer_id={}", peer_id);
+            if let Err(e) = Swarm::dial(swarm, &peer_id) {
+                log::warn!("Failed to dial known peer: {:?}", e);
+            }
+        }
     }
 
     pub fn local_peer_id(&self) -> &PeerId {
@@ -309,9 +439,11 @@ impl Network {
         events_tx: broadcast::Sender<NetworkEvent<Peer>>,
         mut action_rx: mpsc::Receiver<NetworkAction>,
         connected_tx: oneshot::Sender<()>,
-        min_peers: usize
+        min_peers: usize,
+        dht_config: DhtConfig,
     ) {
-        let mut task_state = TaskState::new(connected_tx);
+        let mut republish_timer = Interval::new(dht_config.republish_interval);
+        let mut task_state = TaskState::new(connected_tx, dht_config);
 
         loop {
             futures::select! {
@@ -328,8 +460,55 @@ impl Network {
                         break;
                     }
                 },
+                _ = republish_timer.next().fuse() => {
+                    Self::republish_records(&mut swarm, &task_state);
+                },
             };
         }
+
+        if let Err(e) = swarm.kademlia.store_mut().save() {
+            log::warn!("Failed to save DHT records: {}", e);
+        }
+
+        #[cfg(feature = "peer-contact-book-persistence")]
+        if let Err(e) = swarm.discovery.peer_contact_book().read().save() {
+            log::warn!("Failed to save peer contacts: {}", e);
+        }
+    }
+
+    /// Republishes our own records with a fresh expiry, such that they don't expire while we're online. The records are
+    /// saved to disk as well.
+    fn republish_records(swarm: &mut NimiqSwarm, state: &TaskState) {
+        log::debug!("Republishing {} DHT records", state.dht_published.len());
+
+        for (key, value) in &state.dht_published {
+            let record = Self::new_record(swarm, key.clone(), value.clone(), &state.dht_config);
+            if let Err(e) = swarm.kademlia.put_record(record, Quorum::One) {
+                log::warn!("Failed to republish DHT record: {:?}", e);
+            }
+        }
+
+        if let Err(e) = swarm.kademlia.store_mut().save() {
+            log::warn!("Failed to save DHT records: {}", e);
+        }
+    }
+
+    fn new_record(swarm: &NimiqSwarm, key: Vec<u8>, value: Vec<u8>, dht_config: &DhtConfig) -> Record {
+        Record {
+            key: key.into(),
+            value,
+            publisher: Some(Swarm::local_peer_id(swarm).clone()),
+            expires: Some(Instant::now() + dht_config.record_ttl),
+        }
+    }
+
+    /// Picks the newest valid record from the records returned by a `GetRecord` query.
+    fn select_record(swarm: &mut NimiqSwarm, records: Vec<PeerRecord>) -> Option<Vec<u8>> {
+        swarm
+            .kademlia
+            .store_mut()
+            .select_record(records.into_iter().map(|r| r.record).collect())
+            .map(|r| r.value)
     }
 
     async fn handle_event(
@@ -342,6 +521,7 @@ impl Network {
         match event {
             SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } => {
                 swarm.kademlia.add_address(&peer_id, endpoint.get_remote_address().clone());
+                state.connections.insert(peer_id.clone(), (endpoint, Instant::now()));
 
                 let num_peers = Swarm::network_info(swarm).num_connections_established;
 
@@ -358,7 +538,19 @@ impl Network {
                 }
             }
 
-            //SwarmEvent::ConnectionClosed { .. } => {},
+            SwarmEvent::ConnectionClosed { peer_id, num_established, .. } => {
+                if num_established == 0 {
+                    swarm.bandwidth.remove_peer(&peer_id);
+                    swarm.requests.remove_peer(&peer_id);
+                    state.connections.remove(&peer_id);
+                }
+
+                // Replace lost peers with the best scoring peers we know.
+                let num_peers = Swarm::network_info(swarm).num_connections_established;
+                if num_peers < min_peers {
+                    Self::dial_best_peers(swarm, min_peers - num_peers);
+                }
+            }
             SwarmEvent::Behaviour(event) => {
                 match event {
                     NimiqEvent::Message(event) => {
@@ -370,11 +562,16 @@ impl Network {
                                 match result {
                                     QueryResult::GetRecord(result) => {
                                         if let Some(output) = state.dht_gets.remove(&id) {
-                                            let result = result.map_err(Into::into).and_then(|GetRecordOk { mut records }| {
-                                                // TODO: What do we do, if we get multiple records?
-                                                let data_opt = records.pop().map(|r| r.record.value);
-                                                Ok(data_opt)
-                                            });
+                                            let result = match result {
+                                                Ok(GetRecordOk { records }) => Ok(Self::select_record(swarm, records)),
+                                                // If we didn't reach the quorum, we still use the records we got.
+                                                Err(GetRecordError::QuorumFailed { records, .. }) | Err(GetRecordError::Timeout { records, .. })
+                                                    if !records.is_empty() =>
+                                                {
+                                                    Ok(Self::select_record(swarm, records))
+                                                }
+                                                Err(e) => Err(e.into()),
+                                            };
                                             output.send(result).ok();
                                         } else {
                                             log::warn!("GetRecord query result for unknown query ID: {:?}", id);
@@ -404,6 +601,19 @@ impl Network {
                         match event {
                             GossipsubEvent::Message(peer_id, msg_id, msg) => {
                                 log::debug!("Received message {:?} from peer {:?}: {:?}", msg_id, peer_id, msg);
+
+                                // Gossip messages are only accounted for their first topic, such that the totals are
+                                // correct.
+                                if let Some(topic) = msg.topics.first() {
+                                    if !swarm.bandwidth.record_inbound_gossip(&peer_id, topic.as_str(), msg.data.len()) {
+                                        log::warn!("Peer {} exceeded its rate limit", peer_id);
+                                        if let Some(peer) = swarm.message.peers.get_peer(&peer_id) {
+                                            peer.close(CloseReason::Other);
+                                        }
+                                        return;
+                                    }
+                                }
+
                                 for topic in msg.topics.iter() {
                                     if let Some(topic_info) = state.gossip_topics.get_mut(&topic) {
                                         let (output, validate) = topic_info;
@@ -450,22 +660,22 @@ impl Network {
                 state.dht_gets.insert(query_id, output);
             }
             NetworkAction::DhtPut { key, value, output } => {
-                let local_peer_id = Swarm::local_peer_id(&swarm);
+                let record = Self::new_record(swarm, key.clone(), value.clone(), &state.dht_config);
 
-                let record = Record {
-                    key: key.into(),
-                    value,
-                    publisher: Some(local_peer_id.clone()),
-                    expires: None, // TODO: Records should expire at some point in time
-                };
+                if swarm.kademlia.store_mut().validate(&record).is_none() {
+                    output.send(Err(DhtStoreError::InvalidRecord.into())).ok();
+                } else {
+                    match swarm.kademlia.put_record(record, Quorum::One) {
+                        Ok(query_id) => {
+                            // Remember the record, such that we republish it periodically.
+                            state.dht_published.insert(key, value);
 
-                match swarm.kademlia.put_record(record, Quorum::One) {
-                    Ok(query_id) => {
-                        // Remember put operation to resolve when we receive a `QueryResult::PutRecord`
-                        state.dht_puts.insert(query_id, output);
-                    }
-                    Err(e) => {
-                        output.send(Err(e.into())).ok();
+                            // Remember put operation to resolve when we receive a `QueryResult::PutRecord`
+                            state.dht_puts.insert(query_id, output);
+                        }
+                        Err(e) => {
+                            output.send(Err(e.into())).ok();
+                        }
                     }
                 }
             }
@@ -479,8 +689,13 @@ impl Network {
             }
             NetworkAction::Publish { topic_name, data, output } => {
                 // TODO: Check if we're subscribed to the topic, otherwise we can't publish
-                let topic = GossipsubTopic::new(topic_name);
-                output.send(swarm.gossipsub.publish(&topic, data).map_err(Into::into)).ok();
+                let topic = GossipsubTopic::new(topic_name.clone());
+                let size = data.len();
+                let result = swarm.gossipsub.publish(&topic, data);
+                if result.is_ok() {
+                    swarm.bandwidth.record_outbound_gossip(&topic_name, size);
+                }
+                output.send(result.map_err(Into::into)).ok();
             }
             NetworkAction::NetworkInfo { output } => {
                 output.send(Swarm::network_info(swarm)).ok();
@@ -488,17 +703,162 @@ impl Network {
             NetworkAction::Validate { message_id, source, output } => {
                 output.send(Ok(swarm.gossipsub.validate_message(&message_id, &source))).ok();
             }
+            NetworkAction::DisconnectPeer { peer_id, reason, output } => {
+                if Swarm::is_connected(swarm, &peer_id) {
+                    log::info!("Disconnecting peer {}: {}", peer_id, reason);
+                    swarm.limit.back_off_peer(&peer_id);
+                    output.send(true).ok();
+                } else {
+                    output.send(false).ok();
+                }
+            }
+            NetworkAction::BanPeer { peer_id, duration, output } => {
+                log::info!("Banning peer {}", peer_id);
+                swarm.limit.ban_peer(&peer_id, duration);
+                output.send(()).ok();
+            }
+            NetworkAction::UnbanPeer { peer_id, output } => {
+                log::info!("Unbanning peer {}", peer_id);
+                swarm.limit.unban_peer(&peer_id);
+                output.send(()).ok();
+            }
+            NetworkAction::BanIp { ip, duration, output } => {
+                log::info!("Banning IP {}", ip);
+                swarm.limit.ban_ip(ip, duration);
+                output.send(()).ok();
+            }
+            NetworkAction::UnbanIp { ip, output } => {
+                log::info!("Unbanning IP {}", ip);
+                swarm.limit.unban_ip(&ip);
+                output.send(()).ok();
+            }
+            NetworkAction::ConnectedPeers { output } => {
+                let peer_contact_book = swarm.discovery.peer_contact_book();
+                let peer_contact_book = peer_contact_book.read();
+
+                let peers = state
+                    .connections
+                    .iter()
+                    .map(|(peer_id, (endpoint, connected_since))| PeerInfo {
+                        peer_id: peer_id.clone(),
+                        peer_contact: peer_contact_book.get(peer_id).map(|info| info.contact().clone()),
+                        address: endpoint.get_remote_address().clone(),
+                        outbound: endpoint.is_dialer(),
+                        connected_for: connected_since.elapsed(),
+                    })
+                    .collect();
+
+                output.send(peers).ok();
+            }
+            NetworkAction::ListenAddresses { output } => {
+                // The addresses we listen on, and the addresses others confirmed they can reach us on.
+                let mut addresses: Vec<Multiaddr> = Swarm::listeners(swarm).cloned().collect();
+                for address in swarm.discovery.peer_contact_book().read().get_self().addresses() {
+                    if !addresses.contains(address) {
+                        addresses.push(address.clone());
+                    }
+                }
+
+                output.send(addresses).ok();
+            }
         }
 
         Ok(())
     }
 
+    /// Returns the bytes and number of messages that were sent and received, in total and per peer, message type and
+    /// gossip topic.
+    pub fn bandwidth_stats(&self) -> BandwidthStats {
+        self.bandwidth.stats()
+    }
+
+    /// Returns information about all connected peers.
+    pub async fn connected_peers(&self) -> Result<Vec<PeerInfo>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx.lock().await.send(NetworkAction::ConnectedPeers { output: output_tx }).await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Returns the addresses we're listening on, and the external addresses that we advertise.
+    pub async fn listen_addresses(&self) -> Result<Vec<Multiaddr>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx.lock().await.send(NetworkAction::ListenAddresses { output: output_tx }).await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Closes all connections to a peer, which may not reconnect for the disconnect backoff time of the
+    /// `LimitConfig`. Returns `false` if the peer is not connected.
+    pub async fn disconnect_peer(&self, peer_id: PeerId, reason: String) -> Result<bool, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
+            .lock()
+            .await
+            .send(NetworkAction::DisconnectPeer {
+                peer_id,
+                reason,
+                output: output_tx,
+            })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
     pub async fn network_info(&self) -> Result<NetworkInfo, NetworkError> {
         let (output_tx, output_rx) = oneshot::channel();
 
         self.action_tx.lock().await.send(NetworkAction::NetworkInfo { output: output_tx }).await?;
         Ok(output_rx.await?)
     }
+
+    /// Bans the peer and the IPs it is connected from and closes all connections to it. If no
+    /// duration is given, the default ban time of the `LimitConfig` is used.
+    pub async fn ban_peer(&self, peer_id: PeerId, duration: Option<Duration>) -> Result<(), NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
+            .lock()
+            .await
+            .send(NetworkAction::BanPeer {
+                peer_id,
+                duration,
+                output: output_tx,
+            })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    pub async fn unban_peer(&self, peer_id: PeerId) -> Result<(), NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx.lock().await.send(NetworkAction::UnbanPeer { peer_id, output: output_tx }).await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Bans the IP and closes all connections from/to it. If no duration is given, the default
+    /// ban time of the `LimitConfig` is used.
+    pub async fn ban_ip(&self, ip: IpAddr, duration: Option<Duration>) -> Result<(), NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
+            .lock()
+            .await
+            .send(NetworkAction::BanIp {
+                ip,
+                duration,
+                output: output_tx,
+            })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    pub async fn unban_ip(&self, ip: IpAddr) -> Result<(), NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx.lock().await.send(NetworkAction::UnbanIp { ip, output: output_tx }).await?;
+        Ok(output_rx.await?)
+    }
 }
 
 #[async_trait]
@@ -634,6 +994,10 @@ impl NetworkInterface for Network {
         output_rx.await?
     }
 
+    fn set_dht_record_validator(&self, validator: Arc<dyn DhtRecordValidator>) {
+        *self.dht_validator.write() = Some(validator);
+    }
+
     async fn dial_peer(&self, peer_id: PeerId) -> Result<(), NetworkError> {
         let (output_tx, output_rx) = oneshot::channel();
         self.action_tx.lock().await.send(NetworkAction::Dial { peer_id, output: output_tx }).await?;
@@ -673,7 +1037,7 @@ mod tests {
     use nimiq_network_interface::{
         message::Message,
         network::Network as NetworkInterface,
-        peer::{CloseReason, Peer as PeerInterface},
+        peer::{CloseReason, Peer as PeerInterface, RequestError, RequestResponse},
     };
     use nimiq_utils::time::OffsetTime;
 
@@ -696,6 +1060,16 @@ mod tests {
         const TYPE_ID: u64 = 42;
     }
 
+    struct TestRequest;
+
+    impl RequestResponse for TestRequest {
+        type Request = TestMessage;
+        type Response = TestMessage;
+
+        const TYPE_ID: u64 = 42;
+        const TIMEOUT: Duration = Duration::from_secs(1);
+    }
+
     fn network_config(address: Multiaddr) -> Config {
         let keypair = Keypair::generate_ed25519();
 
@@ -715,6 +1089,7 @@ mod tests {
             keypair,
             peer_contact,
             min_peers: 0,
+            peer_contact_book: Default::default(),
             discovery: DiscoveryConfig {
                 genesis_hash: Default::default(),
                 update_interval: Duration::from_secs(60),
@@ -728,8 +1103,12 @@ mod tests {
             },
             message: Default::default(),
             limit: Default::default(),
+            bandwidth: Default::default(),
+            dht: Default::default(),
             kademlia: Default::default(),
             gossipsub,
+            requests: Default::default(),
+            tls: None,
         }
     }
 
@@ -760,7 +1139,7 @@ mod tests {
             self.next_address += 1;
 
             let clock = Arc::new(OffsetTime::new());
-            let net = Network::new(vec![address.clone()], clock, network_config(address.clone())).await;
+            let net = Network::new(vec![address.clone()], clock, network_config(address.clone())).await.unwrap();
             log::info!("Creating node: address={}, peer_id={}", address, net.local_peer_id);
 
             if let Some(dial_address) = self.addresses.first() {
@@ -791,8 +1170,8 @@ mod tests {
         let addr1 = multiaddr![Memory(thread_rng().gen::<u64>())];
         let addr2 = multiaddr![Memory(thread_rng().gen::<u64>())];
 
-        let net1 = Network::new(vec![addr1.clone()], Arc::new(OffsetTime::new()), network_config(addr1.clone())).await;
-        let net2 = Network::new(vec![addr2.clone()], Arc::new(OffsetTime::new()), network_config(addr2.clone())).await;
+        let net1 = Network::new(vec![addr1.clone()], Arc::new(OffsetTime::new()), network_config(addr1.clone())).await.unwrap();
+        let net2 = Network::new(vec![addr2.clone()], Arc::new(OffsetTime::new()), network_config(addr2.clone())).await.unwrap();
 
         log::info!("Network 1: address={}, peer_id={}", addr1, net1.local_peer_id);
         log::info!("Network 2: address={}, peer_id={}", addr2, net2.local_peer_id);
@@ -868,6 +1247,39 @@ mod tests {
         assert_eq!(msg2.id, 420);
     }
 
+    #[tokio::test]
+    async fn one_peer_can_request_from_another() {
+        let (net1, net2) = create_connected_networks().await;
+
+        let peer2 = net1.get_peer(net2.local_peer_id().clone()).unwrap();
+        let peer1 = net2.get_peer(net1.local_peer_id().clone()).unwrap();
+
+        // Requests fail, until the other peer handles them.
+        match peer2.request::<TestRequest>(&TestMessage { id: 1 }).await {
+            Err(RequestError::Unsupported) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+
+        let mut requests = peer1.requests::<TestRequest>();
+        tokio::spawn(async move {
+            while let Some(request) = requests.next().await {
+                // Don't answer the odd requests.
+                if request.request.id % 2 == 0 {
+                    let id = request.request.id + 1;
+                    request.respond(&TestMessage { id }).unwrap();
+                }
+            }
+        });
+
+        let response = peer2.request::<TestRequest>(&TestMessage { id: 4710 }).await.unwrap();
+        assert_eq!(response.id, 4711);
+
+        match peer2.request::<TestRequest>(&TestMessage { id: 1 }).await {
+            Err(RequestError::NoResponse) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
     fn assert_peer_left(event: &NetworkEvent<Peer>, peer_id: &PeerId) {
         if let NetworkEvent::PeerLeft(peer) = event {
             assert_eq!(&peer.id, peer_id);
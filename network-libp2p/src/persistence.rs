@@ -0,0 +1,29 @@
+use std::{io, path::Path};
+
+/// Writes `data` to `path` by writing a temporary file first and renaming it, such that we don't end up with a
+/// truncated file if we're interrupted.
+pub(crate) fn write_atomically<P: AsRef<Path>, D: AsRef<[u8]>>(path: P, data: D) -> io::Result<()> {
+    let path = path.as_ref();
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, data)?;
+    std::fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::write_atomically;
+
+    #[test]
+    fn it_replaces_the_file() {
+        let dir = TempDir::new("persistence").unwrap();
+        let path = dir.path().join("data.bin");
+
+        write_atomically(&path, b"first").unwrap();
+        write_atomically(&path, b"second").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        assert!(!path.with_extension("tmp").exists());
+    }
+}
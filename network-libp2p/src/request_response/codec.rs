@@ -0,0 +1,269 @@
+use std::io;
+
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::{core::ProtocolName, request_response::RequestResponseCodec};
+
+use beserial::{Deserialize, Serialize, SerializingError};
+use nimiq_network_interface::peer::RequestError;
+
+use crate::{
+    message_codec::{CompressionConfig, Header},
+    REQUEST_RESPONSE_PROTOCOL,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct RequestResponseProtocol;
+
+impl ProtocolName for RequestResponseProtocol {
+    fn protocol_name(&self) -> &[u8] {
+        REQUEST_RESPONSE_PROTOCOL
+    }
+}
+
+/// A serialized request, together with the `TYPE_ID` of its request type.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestData {
+    pub type_id: u64,
+    #[beserial(len_type(u32))]
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum ResponseStatus {
+    Ok = 0,
+    /// We don't handle requests of this type.
+    Unsupported = 1,
+    /// Too many requests of this type are already in flight.
+    TooManyRequests = 2,
+    /// The request was dropped without a response.
+    NoResponse = 3,
+}
+
+/// A serialized response. The data is empty, unless the status is `Ok`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResponseData {
+    pub status: ResponseStatus,
+    #[beserial(len_type(u32))]
+    pub data: Vec<u8>,
+}
+
+impl ResponseData {
+    pub fn ok(data: Vec<u8>) -> Self {
+        Self {
+            status: ResponseStatus::Ok,
+            data,
+        }
+    }
+
+    pub fn error(status: ResponseStatus) -> Self {
+        Self { status, data: vec![] }
+    }
+
+    pub fn into_result(self) -> Result<Vec<u8>, RequestError> {
+        match self.status {
+            ResponseStatus::Ok => Ok(self.data),
+            ResponseStatus::Unsupported => Err(RequestError::Unsupported),
+            ResponseStatus::TooManyRequests => Err(RequestError::TooManyRequests),
+            ResponseStatus::NoResponse => Err(RequestError::NoResponse),
+        }
+    }
+}
+
+/// A request or response as it's sent on the wire: A message codec [`Header`] followed by the serialized, and possibly
+/// compressed, `RequestData` or `ResponseData`.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    header: Header,
+    data: Vec<u8>,
+}
+
+impl Frame {
+    /// Serializes `item` and compresses it, if it's large enough.
+    pub fn encode<T: Serialize>(item: &T, compression: &CompressionConfig) -> Result<Self, SerializingError> {
+        let data = item.serialize_to_vec();
+        let (data, compressed) = match compression.compress(&data)? {
+            Some(compressed_data) => (compressed_data, true),
+            None => (data, false),
+        };
+
+        Ok(Self {
+            header: Header::for_data(&data, compressed),
+            data,
+        })
+    }
+
+    /// Decompresses the data, if necessary, and deserializes it. The checksum was already verified when the frame was
+    /// read.
+    pub fn decode<T: Deserialize>(&self, compression: &CompressionConfig) -> Result<T, SerializingError> {
+        if self.header.is_compressed() {
+            Deserialize::deserialize_from_vec(&compression.decompress(&self.data)?)
+        } else {
+            Deserialize::deserialize_from_vec(&self.data)
+        }
+    }
+
+    /// Size of the frame on the wire.
+    pub fn wire_size(&self) -> usize {
+        Header::SIZE + self.data.len()
+    }
+}
+
+/// Reads and writes the [`Frame`]s of requests and responses.
+#[derive(Clone, Debug)]
+pub struct RequestCodec {
+    /// Maximum size of the data of a frame.
+    max_size: usize,
+}
+
+impl RequestCodec {
+    pub fn new(max_size: usize) -> Self {
+        Self { max_size }
+    }
+
+    async fn read<T>(&self, io: &mut T) -> io::Result<Frame>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let mut header = [0u8; Header::SIZE];
+        io.read_exact(&mut header).await?;
+        let header: Header = Deserialize::deserialize_from_vec(&header).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        if header.magic != Header::MAGIC && !header.is_compressed() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid magic"));
+        }
+        if header.size as usize > self.max_size {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Frame too large"));
+        }
+
+        let mut data = vec![0u8; header.size as usize];
+        io.read_exact(&mut data).await?;
+
+        if Header::compute_checksum(&data) != header.checksum {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Bad checksum"));
+        }
+
+        Ok(Frame { header, data })
+    }
+
+    async fn write<T>(&self, io: &mut T, frame: Frame) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        io.write_all(&frame.header.serialize_to_vec()).await?;
+        io.write_all(&frame.data).await?;
+        io.close().await
+    }
+}
+
+#[async_trait]
+impl RequestResponseCodec for RequestCodec {
+    type Protocol = RequestResponseProtocol;
+    type Request = Frame;
+    type Response = Frame;
+
+    async fn read_request<T>(&mut self, _protocol: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        self.read(io).await
+    }
+
+    async fn read_response<T>(&mut self, _protocol: &Self::Protocol, io: &mut T) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        self.read(io).await
+    }
+
+    async fn write_request<T>(&mut self, _protocol: &Self::Protocol, io: &mut T, request: Self::Request) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        self.write(io, request).await
+    }
+
+    async fn write_response<T>(&mut self, _protocol: &Self::Protocol, io: &mut T, response: Self::Response) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        self.write(io, response).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::io::Cursor;
+    use libp2p::request_response::RequestResponseCodec;
+
+    use nimiq_network_interface::peer::RequestError;
+
+    use super::{Frame, RequestCodec, RequestData, RequestResponseProtocol, ResponseData, ResponseStatus};
+    use crate::message_codec::{CompressionConfig, Header};
+
+    async fn write_and_read(codec: &mut RequestCodec, frame: Frame) -> std::io::Result<Frame> {
+        let mut io = Cursor::new(Vec::new());
+        codec.write_request(&RequestResponseProtocol, &mut io, frame).await.unwrap();
+
+        io.set_position(0);
+        codec.read_request(&RequestResponseProtocol, &mut io).await
+    }
+
+    #[tokio::test]
+    async fn it_writes_and_reads_requests() {
+        let mut codec = RequestCodec::new(1024);
+        let compression = CompressionConfig::default();
+
+        let frame = Frame::encode(&RequestData { type_id: 42, data: vec![1, 2, 3] }, &compression).unwrap();
+        let request: RequestData = write_and_read(&mut codec, frame).await.unwrap().decode(&compression).unwrap();
+        assert_eq!(request.type_id, 42);
+        assert_eq!(request.data, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn it_compresses_large_responses() {
+        let mut codec = RequestCodec::new(1024);
+        let compression = CompressionConfig {
+            threshold: 100,
+            ..Default::default()
+        };
+
+        let frame = Frame::encode(&ResponseData::ok(vec![0; 10_000]), &compression).unwrap();
+        assert!(frame.header.is_compressed());
+        assert!(frame.wire_size() < 1024);
+
+        let response: ResponseData = write_and_read(&mut codec, frame).await.unwrap().decode(&compression).unwrap();
+        assert_eq!(response.into_result().unwrap(), vec![0; 10_000]);
+    }
+
+    #[tokio::test]
+    async fn it_rejects_requests_exceeding_the_max_size() {
+        let mut codec = RequestCodec::new(16);
+
+        let frame = Frame::encode(&RequestData { type_id: 1, data: vec![0; 32] }, &CompressionConfig::default()).unwrap();
+        assert!(write_and_read(&mut codec, frame).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_rejects_frames_with_a_bad_checksum() {
+        let mut codec = RequestCodec::new(1024);
+
+        let mut frame = Frame::encode(&RequestData { type_id: 1, data: vec![1, 2, 3] }, &CompressionConfig::default()).unwrap();
+        frame.header = Header {
+            checksum: frame.header.checksum ^ 1,
+            ..frame.header
+        };
+        assert!(write_and_read(&mut codec, frame).await.is_err());
+    }
+
+    #[test]
+    fn it_converts_response_status_to_errors() {
+        assert_eq!(ResponseData::ok(vec![1]).into_result().unwrap(), vec![1]);
+
+        match ResponseData::error(ResponseStatus::TooManyRequests).into_result() {
+            Err(RequestError::TooManyRequests) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+}
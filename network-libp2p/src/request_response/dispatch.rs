@@ -0,0 +1,439 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, BoxFuture, Either},
+    stream::FuturesUnordered,
+    FutureExt, StreamExt,
+};
+use libp2p::{
+    request_response::{OutboundFailure, RequestId, RequestResponse, RequestResponseEvent, RequestResponseMessage, ResponseChannel},
+    PeerId,
+};
+use parking_lot::Mutex;
+use wasm_timer::Delay;
+
+use nimiq_network_interface::peer::RequestError;
+
+use super::codec::{Frame, RequestCodec, RequestData, ResponseData, ResponseStatus};
+use crate::{bandwidth::BandwidthMeter, message_codec::CompressionConfig};
+
+#[derive(Clone, Debug)]
+pub struct RequestConfig {
+    /// Maximum size of a request or response on the wire.
+    pub max_size: usize,
+
+    /// Time after which requests are aborted by the transport. The request types' own timeouts should be shorter.
+    pub timeout: Duration,
+
+    /// Compression for large requests and responses. Every peer that speaks the protocol supports it.
+    pub compression: CompressionConfig,
+}
+
+impl Default for RequestConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 10_000_000,
+            timeout: Duration::from_secs(60),
+            compression: CompressionConfig::default(),
+        }
+    }
+}
+
+/// Counts a request as in flight until it is dropped.
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    /// Returns `None`, if `max` requests are already in flight.
+    fn acquire(counter: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| if n < max { Some(n + 1) } else { None })
+            .ok()?;
+        Some(Self(Arc::clone(counter)))
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Serialized inbound request and the channel to which its serialized response is sent.
+pub(crate) type InboundRequestData = (Vec<u8>, oneshot::Sender<Vec<u8>>);
+
+struct InboundHandler {
+    tx: mpsc::Sender<InboundRequestData>,
+    max_concurrent: usize,
+    in_flight: Arc<AtomicUsize>,
+}
+
+pub(crate) struct OutboundRequest {
+    peer_id: PeerId,
+    request: RequestData,
+    output: oneshot::Sender<Result<Vec<u8>, RequestError>>,
+}
+
+/// Shared by the peers and the network behaviour. Peers send their requests through it and register the streams for
+/// their inbound requests.
+pub struct RequestDispatch {
+    outbound_tx: mpsc::UnboundedSender<OutboundRequest>,
+    outbound_in_flight: Mutex<HashMap<(PeerId, u64), Arc<AtomicUsize>>>,
+    inbound_handlers: Mutex<HashMap<(PeerId, u64), InboundHandler>>,
+}
+
+impl RequestDispatch {
+    pub(crate) fn new() -> (Arc<Self>, mpsc::UnboundedReceiver<OutboundRequest>) {
+        let (outbound_tx, outbound_rx) = mpsc::unbounded();
+
+        let dispatch = Self {
+            outbound_tx,
+            outbound_in_flight: Mutex::default(),
+            inbound_handlers: Mutex::default(),
+        };
+
+        (Arc::new(dispatch), outbound_rx)
+    }
+
+    /// Sends a serialized request to `peer_id` and waits for the serialized response.
+    pub async fn request(
+        &self,
+        peer_id: &PeerId,
+        type_id: u64,
+        data: Vec<u8>,
+        max_concurrent: usize,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, RequestError> {
+        let counter = Arc::clone(self.outbound_in_flight.lock().entry((peer_id.clone(), type_id)).or_default());
+        let _in_flight = InFlight::acquire(&counter, max_concurrent).ok_or(RequestError::TooManyRequests)?;
+
+        let (output, response_rx) = oneshot::channel();
+        self.outbound_tx
+            .unbounded_send(OutboundRequest {
+                peer_id: peer_id.clone(),
+                request: RequestData { type_id, data },
+                output,
+            })
+            .map_err(|_| RequestError::ConnectionClosed)?;
+
+        // If this future is dropped, the network notices that the output channel is closed and discards the request.
+        match future::select(response_rx, Delay::new(timeout)).await {
+            Either::Left((Ok(result), _)) => result,
+            Either::Left((Err(_), _)) => Err(RequestError::ConnectionClosed),
+            Either::Right(_) => Err(RequestError::Timeout),
+        }
+    }
+
+    /// Registers the stream of inbound requests of type `type_id` from `peer_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is already a non-closed stream for these requests.
+    pub(crate) fn register(&self, peer_id: PeerId, type_id: u64, max_concurrent: usize) -> mpsc::Receiver<InboundRequestData> {
+        let mut handlers = self.inbound_handlers.lock();
+
+        let key = (peer_id, type_id);
+        if let Some(handler) = handlers.get(&key) {
+            if !handler.tx.is_closed() {
+                panic!("Request stream for type {} of peer {} already registered", type_id, key.0);
+            }
+        }
+
+        let (tx, rx) = mpsc::channel(max_concurrent);
+        handlers.insert(
+            key,
+            InboundHandler {
+                tx,
+                max_concurrent,
+                in_flight: Arc::default(),
+            },
+        );
+
+        rx
+    }
+
+    /// Removes the request streams of a disconnected peer, which ends them.
+    pub(crate) fn remove_peer(&self, peer_id: &PeerId) {
+        self.outbound_in_flight.lock().retain(|(id, _), _| id != peer_id);
+        self.inbound_handlers.lock().retain(|(id, _), _| id != peer_id);
+    }
+
+    /// Passes an inbound request to its stream. Returns the receiver for the response, or the status with which the
+    /// request is rejected.
+    fn dispatch_inbound(&self, peer_id: &PeerId, request: RequestData) -> Result<(InFlight, oneshot::Receiver<Vec<u8>>), ResponseStatus> {
+        let mut handlers = self.inbound_handlers.lock();
+
+        let key = (peer_id.clone(), request.type_id);
+        let handler = handlers.get_mut(&key).ok_or(ResponseStatus::Unsupported)?;
+
+        let in_flight = InFlight::acquire(&handler.in_flight, handler.max_concurrent).ok_or(ResponseStatus::TooManyRequests)?;
+
+        let (response_tx, response_rx) = oneshot::channel();
+        if let Err(e) = handler.tx.try_send((request.data, response_tx)) {
+            if e.is_disconnected() {
+                handlers.remove(&key);
+                return Err(ResponseStatus::Unsupported);
+            }
+            return Err(ResponseStatus::TooManyRequests);
+        }
+
+        Ok((in_flight, response_rx))
+    }
+}
+
+/// An inbound request whose response is ready to be sent.
+struct InboundResponse {
+    channel: ResponseChannel<Frame>,
+    peer_id: PeerId,
+    type_id: u64,
+    response: ResponseData,
+}
+
+/// The requests that the network behaviour is currently processing. Requests and responses are metered and count
+/// towards the peers' rate limits like messages do.
+pub(crate) struct PendingRequests {
+    dispatch: Arc<RequestDispatch>,
+
+    outbound_rx: mpsc::UnboundedReceiver<OutboundRequest>,
+
+    /// Outbound requests waiting for their response, with their type ID.
+    outbound: HashMap<RequestId, (u64, oneshot::Sender<Result<Vec<u8>, RequestError>>)>,
+
+    /// Inbound requests waiting for their response to be sent.
+    inbound: FuturesUnordered<BoxFuture<'static, InboundResponse>>,
+
+    /// Peers that exceeded their rate limit and must be disconnected.
+    rate_limited: Vec<PeerId>,
+
+    bandwidth: Arc<BandwidthMeter>,
+
+    config: RequestConfig,
+}
+
+impl PendingRequests {
+    pub(crate) fn new(
+        dispatch: Arc<RequestDispatch>,
+        outbound_rx: mpsc::UnboundedReceiver<OutboundRequest>,
+        bandwidth: Arc<BandwidthMeter>,
+        config: RequestConfig,
+    ) -> Self {
+        Self {
+            dispatch,
+            outbound_rx,
+            outbound: HashMap::new(),
+            inbound: FuturesUnordered::new(),
+            rate_limited: Vec::new(),
+            bandwidth,
+            config,
+        }
+    }
+
+    pub(crate) fn remove_peer(&self, peer_id: &PeerId) {
+        self.dispatch.remove_peer(peer_id);
+    }
+
+    /// Returns the peers that exceeded their rate limit since the last call.
+    pub(crate) fn take_rate_limited(&mut self) -> Vec<PeerId> {
+        std::mem::take(&mut self.rate_limited)
+    }
+
+    /// Meters an inbound request or response. Returns `false` and remembers the peer, if it exceeded its rate limit.
+    fn record_inbound(&mut self, peer_id: &PeerId, type_id: u64, frame: &Frame) -> bool {
+        if self.bandwidth.record_inbound_message(peer_id, type_id, frame.wire_size()) {
+            true
+        } else {
+            log::warn!("Peer {} exceeded its rate limit", peer_id);
+            self.rate_limited.push(peer_id.clone());
+            false
+        }
+    }
+
+    fn send_response(&self, behaviour: &mut RequestResponse<RequestCodec>, response: InboundResponse) {
+        match Frame::encode(&response.response, &self.config.compression) {
+            Ok(frame) => {
+                self.bandwidth
+                    .record_outbound_message(&response.peer_id, response.type_id, frame.wire_size());
+                behaviour.send_response(response.channel, frame);
+            }
+            Err(e) => log::error!("Failed to encode response to {}: {}", response.peer_id, e),
+        }
+    }
+
+    pub(crate) fn inject_event(&mut self, event: RequestResponseEvent<Frame, Frame>, behaviour: &mut RequestResponse<RequestCodec>) {
+        match event {
+            RequestResponseEvent::Message {
+                peer,
+                message: RequestResponseMessage::Request { request, channel, .. },
+            } => {
+                let data: RequestData = match request.decode(&self.config.compression) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        log::warn!("Received invalid request from {}: {}", peer, e);
+                        return;
+                    }
+                };
+                if !self.record_inbound(&peer, data.type_id, &request) {
+                    return;
+                }
+
+                let type_id = data.type_id;
+                match self.dispatch.dispatch_inbound(&peer, data) {
+                    Ok((in_flight, response_rx)) => {
+                        let timeout = Delay::new(self.config.timeout);
+                        self.inbound.push(
+                            async move {
+                                let response = match future::select(response_rx, timeout).await {
+                                    Either::Left((Ok(data), _)) => ResponseData::ok(data),
+                                    _ => ResponseData::error(ResponseStatus::NoResponse),
+                                };
+                                drop(in_flight);
+                                InboundResponse {
+                                    channel,
+                                    peer_id: peer,
+                                    type_id,
+                                    response,
+                                }
+                            }
+                            .boxed(),
+                        );
+                    }
+                    Err(status) => {
+                        log::debug!("Rejecting request from {}: {:?}", peer, status);
+                        let response = InboundResponse {
+                            channel,
+                            peer_id: peer,
+                            type_id,
+                            response: ResponseData::error(status),
+                        };
+                        self.send_response(behaviour, response);
+                    }
+                }
+            }
+            RequestResponseEvent::Message {
+                message: RequestResponseMessage::Response { request_id, response },
+                peer,
+            } => {
+                if let Some((type_id, output)) = self.outbound.remove(&request_id) {
+                    // If the peer exceeded its rate limit, the output is dropped and the request fails.
+                    if self.record_inbound(&peer, type_id, &response) {
+                        let result = response
+                            .decode::<ResponseData>(&self.config.compression)
+                            .map_err(RequestError::from)
+                            .and_then(ResponseData::into_result);
+                        // The requester might have given up already.
+                        output.send(result).ok();
+                    }
+                }
+            }
+            RequestResponseEvent::OutboundFailure { peer, request_id, error } => {
+                log::debug!("Request to {} failed: {:?}", peer, error);
+
+                if let Some((_, output)) = self.outbound.remove(&request_id) {
+                    let error = match error {
+                        OutboundFailure::Timeout => RequestError::Timeout,
+                        OutboundFailure::UnsupportedProtocols => RequestError::Unsupported,
+                        OutboundFailure::DialFailure | OutboundFailure::ConnectionClosed => RequestError::ConnectionClosed,
+                    };
+                    output.send(Err(error)).ok();
+                }
+            }
+            event => log::debug!("Request/response event: {:?}", event),
+        }
+    }
+
+    pub(crate) fn poll(&mut self, cx: &mut Context, behaviour: &mut RequestResponse<RequestCodec>) {
+        let mut progress = false;
+
+        while let Poll::Ready(Some(request)) = self.outbound_rx.poll_next_unpin(cx) {
+            // The request was cancelled before we could send it.
+            if request.output.is_canceled() {
+                continue;
+            }
+
+            let type_id = request.request.type_id;
+            let frame = match Frame::encode(&request.request, &self.config.compression) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    request.output.send(Err(e.into())).ok();
+                    continue;
+                }
+            };
+            self.bandwidth.record_outbound_message(&request.peer_id, type_id, frame.wire_size());
+
+            let request_id = behaviour.send_request(&request.peer_id, frame);
+            self.outbound.insert(request_id, (type_id, request.output));
+            progress = true;
+        }
+
+        while let Poll::Ready(Some(response)) = self.inbound.poll_next_unpin(cx) {
+            self.send_response(behaviour, response);
+            progress = true;
+        }
+
+        // Forget the requests that were cancelled by the requester.
+        self.outbound.retain(|_, (_, output)| !output.is_canceled());
+
+        // The request/response behaviour has already been polled, so it needs another poll to process what we passed
+        // to it.
+        if progress {
+            cx.waker().wake_by_ref();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use libp2p::PeerId;
+
+    use super::{RequestDispatch, ResponseStatus};
+    use crate::request_response::RequestData;
+
+    fn request(type_id: u64) -> RequestData {
+        RequestData { type_id, data: vec![1, 2, 3] }
+    }
+
+    #[test]
+    fn it_dispatches_inbound_requests() {
+        let (dispatch, _outbound_rx) = RequestDispatch::new();
+        let peer_id = PeerId::random();
+
+        assert_eq!(dispatch.dispatch_inbound(&peer_id, request(1)).err(), Some(ResponseStatus::Unsupported));
+
+        let mut requests = dispatch.register(peer_id.clone(), 1, 1);
+        let (in_flight, mut response_rx) = dispatch.dispatch_inbound(&peer_id, request(1)).ok().unwrap();
+
+        // Only one request may be in flight.
+        assert_eq!(dispatch.dispatch_inbound(&peer_id, request(1)).err(), Some(ResponseStatus::TooManyRequests));
+
+        let (data, response_tx) = futures::executor::block_on(requests.next()).unwrap();
+        assert_eq!(data, vec![1, 2, 3]);
+        response_tx.send(vec![4, 5, 6]).unwrap();
+        assert_eq!(response_rx.try_recv().unwrap(), Some(vec![4, 5, 6]));
+
+        drop(in_flight);
+        assert!(dispatch.dispatch_inbound(&peer_id, request(1)).is_ok());
+
+        // Requests are rejected once the peer is gone.
+        dispatch.remove_peer(&peer_id);
+        assert_eq!(dispatch.dispatch_inbound(&peer_id, request(1)).err(), Some(ResponseStatus::Unsupported));
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_on_duplicate_request_streams() {
+        let (dispatch, _outbound_rx) = RequestDispatch::new();
+        let peer_id = PeerId::random();
+
+        let _requests = dispatch.register(peer_id.clone(), 1, 1);
+        dispatch.register(peer_id, 1, 1);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod codec;
+pub mod dispatch;
+
+pub use codec::{Frame, RequestCodec, RequestData, RequestResponseProtocol, ResponseData, ResponseStatus};
+pub use dispatch::{RequestConfig, RequestDispatch};
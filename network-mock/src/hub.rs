@@ -1,6 +1,6 @@
 use std::{collections::HashMap, hash::Hash, sync::{atomic::AtomicBool, Arc}};
 
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use parking_lot::Mutex;
 use tokio::sync::broadcast;
 
@@ -14,6 +14,9 @@ pub(crate) struct SenderKey {
     pub message_type: u64,
 }
 
+/// Sender for serialized requests, together with the channel for the serialized response.
+pub(crate) type RequestSender = mpsc::Sender<(Vec<u8>, oneshot::Sender<Vec<u8>>)>;
+
 #[derive(Debug, Default)]
 pub(crate) struct MockHubInner {
     /// Peer maps of all networks.
@@ -22,6 +25,9 @@ pub(crate) struct MockHubInner {
     /// Senders for direct message sending
     pub network_senders: HashMap<SenderKey, mpsc::Sender<Vec<u8>>>,
 
+    /// Senders for requests. The message type of the key is the request type.
+    pub request_senders: HashMap<SenderKey, RequestSender>,
+
     /// Senders for gossipsub topics
     ///
     /// The data is Arc'd, such that cloning is cheap, and we need only a borrow when we deserialize.
@@ -58,7 +58,7 @@ pub mod tests {
     use nimiq_network_interface::{
         message::Message,
         network::{Network, NetworkEvent, Topic},
-        peer::Peer,
+        peer::{Peer, RequestError, RequestResponse},
     };
 
     use super::{MockHub, MockPeer, MockPeerId};
@@ -223,4 +223,74 @@ pub mod tests {
         assert_eq!(msg1.id, 1337);
         assert_eq!(msg2.id, 420);
     }
+
+    struct TestRequest;
+
+    impl RequestResponse for TestRequest {
+        type Request = TestMessage;
+        type Response = TestMessage;
+
+        const TYPE_ID: u64 = 42;
+        const TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+    }
+
+    #[tokio::test]
+    async fn one_peer_can_request_from_another() {
+        let mut hub = MockHub::new();
+        let net1 = hub.new_network();
+        let net2 = hub.new_network();
+        net1.dial_mock(&net2);
+
+        let peer2 = net1.get_peer(net2.peer_id()).unwrap();
+        let peer1 = net2.get_peer(net1.peer_id()).unwrap();
+
+        match peer1.request::<TestRequest>(&TestMessage { id: 1 }).await {
+            Err(RequestError::Unsupported) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+
+        let mut requests = peer2.requests::<TestRequest>();
+        tokio::spawn(async move {
+            while let Some(request) = requests.next().await {
+                // Don't answer the odd requests.
+                if request.request.id % 2 == 0 {
+                    let id = request.request.id + 1;
+                    request.respond(&TestMessage { id }).unwrap();
+                }
+            }
+        });
+
+        let response = peer1.request::<TestRequest>(&TestMessage { id: 4710 }).await.unwrap();
+        assert_eq!(response.id, 4711);
+
+        match peer1.request::<TestRequest>(&TestMessage { id: 1 }).await {
+            Err(RequestError::NoResponse) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn requests_can_only_be_received_once() {
+        let mut hub = MockHub::new();
+        let net1 = hub.new_network();
+        let net2 = hub.new_network();
+        net1.dial_mock(&net2);
+
+        let peer2 = net1.get_peer(net2.peer_id()).unwrap();
+        let _requests = peer2.requests::<TestRequest>();
+        let _requests = peer2.requests::<TestRequest>();
+    }
+
+    #[tokio::test]
+    async fn requests_can_be_received_again_after_dropping_the_stream() {
+        let mut hub = MockHub::new();
+        let net1 = hub.new_network();
+        let net2 = hub.new_network();
+        net1.dial_mock(&net2);
+
+        let peer2 = net1.get_peer(net2.peer_id()).unwrap();
+        drop(peer2.requests::<TestRequest>());
+        let _requests = peer2.requests::<TestRequest>();
+    }
 }
@@ -226,7 +226,8 @@ impl Network for MockNetwork {
     }
 
     async fn validate_message(&self, _id: Self::PubsubId) -> Result<bool, Self::Error> {
-        unimplemented!()
+        // Published messages are delivered to all subscribers right away, so there is nothing left to forward.
+        Ok(true)
     }
 
     async fn dht_get<K, V>(&self, k: &K) -> Result<Option<V>, Self::Error>
@@ -7,15 +7,16 @@ use std::{
 
 use async_trait::async_trait;
 use futures::{
-    channel::mpsc,
+    channel::{mpsc, oneshot},
     sink::SinkExt,
     stream::{Stream, StreamExt},
 };
 use parking_lot::Mutex;
 
+use beserial::{Deserialize, Serialize};
 use nimiq_network_interface::{
     message::Message,
-    peer::{CloseReason, Peer, RequestResponse, SendError},
+    peer::{CloseReason, InboundRequest, Peer, RequestError, RequestResponse, SendError},
 };
 
 use crate::{
@@ -108,14 +109,66 @@ impl Peer for MockPeer {
 
         // Drops senders and thus the receiver stream will end
         hub.network_senders.retain(|k, _sender| k.network_recipient != self.network_address);
+        hub.request_senders.retain(|k, _sender| k.network_recipient != self.network_address);
     }
 
-    async fn request<R: RequestResponse>(&self, _request: &<R as RequestResponse>::Request) -> Result<R::Response, Self::Error> {
-        unimplemented!("In the mean-time you can use `nimiq_network_interface::request_response`");
+    async fn request<R: RequestResponse>(&self, request: &R::Request) -> Result<R::Response, RequestError> {
+        let k = SenderKey {
+            network_recipient: self.peer_id.into(),
+            sender_peer: self.network_address.into(),
+            message_type: R::TYPE_ID,
+        };
+
+        let mut sender = self.hub.lock().request_senders.get(&k).cloned().ok_or(RequestError::Unsupported)?;
+
+        log::trace!("Sending request: {:?}", request);
+
+        let (response_tx, response_rx) = oneshot::channel();
+        sender.try_send((request.serialize_to_vec(), response_tx)).map_err(|e| {
+            if e.is_disconnected() {
+                RequestError::Unsupported
+            } else {
+                RequestError::TooManyRequests
+            }
+        })?;
+
+        let data = tokio::time::timeout(R::TIMEOUT, response_rx)
+            .await
+            .map_err(|_| RequestError::Timeout)?
+            .map_err(|_| RequestError::NoResponse)?;
+
+        Ok(R::Response::deserialize_from_vec(&data)?)
     }
 
-    fn requests<R: RequestResponse>(&self) -> Box<dyn Stream<Item = R::Request>> {
-        unimplemented!("In the mean-time you can use `nimiq_network_interface::request_response`");
+    fn requests<R: RequestResponse>(&self) -> Pin<Box<dyn Stream<Item = InboundRequest<R>> + Send>> {
+        let mut hub = self.hub.lock();
+
+        log::debug!("Peer {} handling requests of type={} from peer {}", self.network_address, R::TYPE_ID, self.peer_id);
+
+        let key = SenderKey {
+            network_recipient: self.network_address,
+            sender_peer: self.peer_id,
+            message_type: R::TYPE_ID,
+        };
+        if let Some(sender) = hub.request_senders.get(&key) {
+            if !sender.is_closed() {
+                panic!("Request stream for type {} of peer {} already registered", R::TYPE_ID, self.peer_id);
+            }
+        }
+
+        let (tx, rx) = mpsc::channel(R::MAX_CONCURRENT_REQUESTS);
+        hub.request_senders.insert(key, tx);
+
+        rx.filter_map(|(data, response_tx)| async move {
+            match R::Request::deserialize_from_vec(&data) {
+                Ok(request) => Some(InboundRequest::new(request, response_tx)),
+                Err(e) => {
+                    log::warn!("Failed to deserialize request: {}", e);
+                    None
+                }
+            }
+        })
+        .boxed()
     }
 }
 
@@ -25,6 +25,7 @@ pub use macro_block::*;
 pub use micro_block::*;
 pub use multisig::*;
 pub use signed::*;
+pub use signalling::*;
 pub use tendermint::*;
 pub use view_change::*;
 
@@ -34,6 +35,7 @@ mod macro_block;
 mod micro_block;
 mod multisig;
 mod signed;
+mod signalling;
 mod tendermint;
 mod view_change;
 
@@ -88,6 +90,10 @@ pub enum BlockError {
     InvalidSeed,
     #[error("Invalid view number")]
     InvalidViewNumber,
+    #[error("Invalid block number")]
+    InvalidBlockNumber,
+    #[error("Invalid parent election hash")]
+    InvalidParentElectionHash,
     #[error("Invalid history root")]
     InvalidHistoryRoot,
     #[error("Incorrect validators")]
@@ -41,7 +41,8 @@ pub struct MicroHeader {
     /// The seed of the block. This is the BLS signature of the seed of the immediately preceding
     /// block (either micro or macro) using the validator key of the block producer.
     pub seed: VrfSeed,
-    /// The extra data of the block. It is simply 32 raw bytes. No planned use.
+    /// The extra data of the block. It is up to 32 raw bytes, chosen by the block producer. It may start with an
+    /// `UpgradeSignal`.
     #[beserial(len_type(u8, limit = 32))]
     pub extra_data: Vec<u8>,
     /// The root of the Merkle tree of the blockchain state. It just acts as a commitment to the
@@ -84,12 +85,15 @@ impl MicroBlock {
 }
 
 impl MicroHeader {
+    /// The maximum size, in bytes, of the extra data.
+    pub const MAX_EXTRA_DATA_SIZE: usize = 32;
+
     /// Returns the size, in bytes, of a Micro block header. This represents the maximum possible
     /// size since we assume that the extra_data field is completely filled.
     pub const SIZE: usize =
         /*version*/
         2 + /*block_number*/ 4 + /*view_number*/ 4 + /*timestamp*/ 8
-            + /*parent_hash*/ 32 + /*seed*/ CompressedSignature::SIZE + /*extra_data*/ 32 +
+            + /*parent_hash*/ 32 + /*seed*/ CompressedSignature::SIZE + /*extra_data*/ Self::MAX_EXTRA_DATA_SIZE +
             /*state_root*/ 32 + /*body_root*/ 32;
 }
 
@@ -0,0 +1,54 @@
+use std::convert::TryInto;
+
+/// Signals that the producer of a micro block is ready for a protocol version, e.g. one with different policy
+/// constants. It is placed at the start of the block's extra data, which may contain arbitrary data after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpgradeSignal {
+    pub version: u16,
+}
+
+impl UpgradeSignal {
+    /// Marks extra data that starts with an upgrade signal.
+    pub const PREFIX: &'static [u8] = b"NQv";
+
+    /// Size of the signal in the extra data.
+    pub const SIZE: usize = Self::PREFIX.len() + /*version*/ 2;
+
+    pub fn new(version: u16) -> Self {
+        Self { version }
+    }
+
+    /// Parses the signal at the start of `extra_data`, if there is one.
+    pub fn from_extra_data(extra_data: &[u8]) -> Option<Self> {
+        if extra_data.len() < Self::SIZE || !extra_data.starts_with(Self::PREFIX) {
+            return None;
+        }
+        let version = extra_data[Self::PREFIX.len()..Self::SIZE].try_into().unwrap();
+        Some(Self::new(u16::from_be_bytes(version)))
+    }
+
+    /// Returns extra data that consists of this signal, followed by `data`.
+    pub fn to_extra_data(&self, data: &[u8]) -> Vec<u8> {
+        let mut extra_data = Vec::with_capacity(Self::SIZE + data.len());
+        extra_data.extend_from_slice(Self::PREFIX);
+        extra_data.extend_from_slice(&self.version.to_be_bytes());
+        extra_data.extend_from_slice(data);
+        extra_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_upgrade_signals() {
+        let extra_data = UpgradeSignal::new(2).to_extra_data(b"pool");
+        assert_eq!(extra_data, b"NQv\x00\x02pool".to_vec());
+        assert_eq!(UpgradeSignal::from_extra_data(&extra_data), Some(UpgradeSignal::new(2)));
+
+        assert_eq!(UpgradeSignal::from_extra_data(b""), None);
+        assert_eq!(UpgradeSignal::from_extra_data(b"NQv\x00"), None);
+        assert_eq!(UpgradeSignal::from_extra_data(b"pool NQv\x00\x02"), None);
+    }
+}
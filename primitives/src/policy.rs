@@ -15,6 +15,9 @@ pub const SLOTS: u16 = 512;
 /// ceil(x/y) = (x+y-1)/y
 pub const TWO_THIRD_SLOTS: u16 = (2 * SLOTS + 3 - 1) / 3;
 
+/// The targeted time between two consecutive blocks, in milliseconds.
+pub const BLOCK_SEPARATION_TIME: u64 = 1000;
+
 /// Length of a batch including the macro block
 pub const BATCH_LENGTH: u32 = 32; // TODO Set
 
@@ -28,14 +31,6 @@ pub const EPOCH_LENGTH: u32 = BATCH_LENGTH * BATCHES_PER_EPOCH;
 /// system time. We only care about drifting to the future.
 pub const TIMESTAMP_MAX_DRIFT: u64 = 600000;
 
-/// Tendermint's initial timeout, in milliseconds.
-/// See https://arxiv.org/abs/1807.04938v3 for more information.
-pub const TENDERMINT_TIMEOUT_INIT: u64 = 1000; // TODO: Set
-
-/// Tendermint's timeout delta, in milliseconds.
-/// See https://arxiv.org/abs/1807.04938v3 for more information.
-pub const TENDERMINT_TIMEOUT_DELTA: u64 = 1000; // TODO: Set
-
 /// Minimum stake for stakers in Lunas (1 NIM = 100,000 Lunas).
 /// A staker is someone who delegates their stake to a validator.
 pub const MIN_STAKE: u64 = 1;
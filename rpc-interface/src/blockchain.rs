@@ -6,7 +6,7 @@ use nimiq_account::Account;
 use futures::stream::BoxStream;
 
 use crate::{
-    types::{Block, OrLatest, SlashedSlots, Slot, Stakes},
+    types::{Block, OrLatest, SlashedSlots, Slot, Stakes, UpgradeSignals},
 };
 
 #[cfg_attr(feature = "proxy", nimiq_jsonrpc_derive::proxy(name = "BlockchainProxy", rename_all = "camelCase"))]
@@ -29,6 +29,8 @@ pub trait BlockchainInterface {
     // TODO: Previously called `slot_state`. Where is this used?
     async fn slashed_slots(&mut self) -> Result<SlashedSlots, Self::Error>;
 
+    async fn upgrade_signals(&mut self) -> Result<UpgradeSignals, Self::Error>;
+
     async fn get_raw_transaction_info(&mut self, raw_tx: String) -> Result<(), Self::Error>;
 
     async fn get_transaction_by_hash(&mut self, hash: Blake2bHash) -> Result<(), Self::Error>;
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 
 use crate::{
-    types::TransactionParameters,
+    types::{HistorySyncProgress, SyncStatus, TransactionParameters},
 };
 
 
@@ -15,4 +15,8 @@ pub trait ConsensusInterface {
     async fn create_raw_transaction(&mut self, tx_params: TransactionParameters) -> Result<String, Self::Error>;
 
     async fn send_transaction(&mut self, tx_params: TransactionParameters) -> Result<String, Self::Error>;
+
+    async fn get_history_sync_progress(&mut self) -> Result<HistorySyncProgress, Self::Error>;
+
+    async fn get_sync_status(&mut self) -> Result<SyncStatus, Self::Error>;
 }
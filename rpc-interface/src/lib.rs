@@ -1,6 +1,8 @@
 pub mod blockchain;
 pub mod consensus;
 pub mod mempool;
+pub mod network;
+pub mod validator;
 pub mod wallet;
 pub mod types;
 pub mod error;
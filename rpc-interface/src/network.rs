@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+
+use crate::types::{BandwidthStats, Peer};
+
+
+#[cfg_attr(feature = "proxy", nimiq_jsonrpc_derive::proxy(name = "NetworkProxy", rename_all="camelCase"))]
+#[async_trait]
+pub trait NetworkInterface {
+    type Error;
+
+    async fn get_peer_id(&mut self) -> Result<String, Self::Error>;
+
+    async fn get_listen_addresses(&mut self) -> Result<Vec<String>, Self::Error>;
+
+    async fn get_peer_count(&mut self) -> Result<usize, Self::Error>;
+
+    async fn get_peer_list(&mut self) -> Result<Vec<Peer>, Self::Error>;
+
+    /// Dials a peer. `address` is either a multiaddress or a peer ID.
+    async fn connect(&mut self, address: String) -> Result<(), Self::Error>;
+
+    /// Disconnects a peer and logs the reason. The peer may not reconnect for a while.
+    async fn disconnect(&mut self, peer_id: String, reason: Option<String>) -> Result<(), Self::Error>;
+
+    /// Bans a peer for `duration` seconds, or the default ban time.
+    async fn ban_peer(&mut self, peer_id: String, duration: Option<u64>) -> Result<(), Self::Error>;
+
+    async fn unban_peer(&mut self, peer_id: String) -> Result<(), Self::Error>;
+
+    /// Bans an IP address for `duration` seconds, or the default ban time.
+    async fn ban_ip(&mut self, ip: String, duration: Option<u64>) -> Result<(), Self::Error>;
+
+    async fn unban_ip(&mut self, ip: String) -> Result<(), Self::Error>;
+
+    async fn get_bandwidth_stats(&mut self) -> Result<BandwidthStats, Self::Error>;
+}
@@ -2,6 +2,7 @@
 ///!
 ///! [1] https://github.com/nimiq/core-js/wiki/JSON-RPC-API#common-data-types
 use std::{
+    collections::HashMap,
     fmt::{Display, Formatter, self},
     str::FromStr,
     borrow::Cow,
@@ -535,6 +536,48 @@ pub struct SlashedSlots {
     pub previous: BitSet,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeSignals {
+    pub epoch_number: u32,
+
+    pub micro_blocks: u32,
+
+    /// Number of slots per signalled protocol version.
+    pub versions: Vec<VersionSignal>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionSignal {
+    pub version: u16,
+
+    pub slots: u16,
+
+    /// Number of slots that signal this or a later version.
+    pub slots_ready: u16,
+}
+
+impl From<nimiq_blockchain_albatross::UpgradeSignals> for UpgradeSignals {
+    fn from(signals: nimiq_blockchain_albatross::UpgradeSignals) -> Self {
+        let versions = signals
+            .slots
+            .iter()
+            .map(|(&version, &slots)| VersionSignal {
+                version,
+                slots,
+                slots_ready: signals.slots_ready_for(version),
+            })
+            .collect();
+
+        UpgradeSignals {
+            epoch_number: signals.epoch_number,
+            micro_blocks: signals.micro_blocks,
+            versions,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Stakes {
@@ -600,6 +643,147 @@ impl Validator {
 }
 
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySyncProgress {
+    pub epochs_done: usize,
+
+    pub epochs_total: usize,
+
+    pub chunks_done: usize,
+
+    pub chunks_total: usize,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncPhase {
+    History,
+    BlockQueue,
+    Established,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub phase: SyncPhase,
+
+    pub head_block_number: u32,
+
+    pub head_hash: Blake2bHash,
+
+    pub best_peer_head_block_number: Option<u32>,
+
+    pub peer_head_block_numbers: Vec<u32>,
+
+    pub num_agents: usize,
+
+    pub num_buffered_blocks: usize,
+
+    pub remaining_epochs: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Peer {
+    pub peer_id: String,
+
+    /// Addresses advertised in the peer's contact, if we know it.
+    pub addresses: Vec<String>,
+
+    /// Services advertised in the peer's contact as bitmask, if we know it.
+    pub services: Option<u32>,
+
+    /// Remote address of the connection.
+    pub connection_address: String,
+
+    /// Whether we dialed the peer.
+    pub outbound: bool,
+
+    /// Seconds since the connection was established.
+    pub connected_for: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Traffic {
+    pub bytes_received: u64,
+
+    pub bytes_sent: u64,
+
+    pub messages_received: u64,
+
+    pub messages_sent: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthStats {
+    pub total: Traffic,
+
+    /// Traffic per connected peer, by peer ID.
+    pub peers: HashMap<String, Traffic>,
+
+    /// Traffic per message type ID.
+    pub message_types: HashMap<u64, Traffic>,
+
+    /// Traffic per gossip topic.
+    pub topics: HashMap<String, Traffic>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidatorStakingState {
+    Active,
+    Parked,
+    Inactive,
+    NoStake,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingSlot {
+    pub block_number: u32,
+
+    pub view_number: u32,
+
+    pub slot_number: u16,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStatus {
+    pub public_key: CompressedPublicKey,
+
+    /// Index in the current epoch's validator list, if the validator is active.
+    pub validator_id: Option<u16>,
+
+    pub num_slots: Option<u16>,
+
+    pub staking_state: ValidatorStakingState,
+
+    /// Slots of the validator in the current batch that are already known.
+    pub upcoming_slots: Vec<UpcomingSlot>,
+
+    pub micro_blocks_produced: u64,
+
+    pub micro_blocks_missed: u64,
+
+    pub view_changes: u64,
+
+    /// Average number of Tendermint rounds per macro block.
+    pub tendermint_rounds_per_macro_block: Option<f64>,
+
+    pub last_tendermint_rounds: Option<u32>,
+
+    /// Number of fork proofs waiting to be included in a block.
+    pub fork_proofs: usize,
+
+    pub rewards: Coin,
+
+    pub slashes: u64,
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionParameters {
@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+
+use crate::types::ValidatorStatus;
+
+
+#[cfg_attr(feature = "proxy", nimiq_jsonrpc_derive::proxy(name = "ValidatorProxy", rename_all="camelCase"))]
+#[async_trait]
+pub trait ValidatorInterface {
+    type Error;
+
+    async fn get_validator_status(&mut self) -> Result<ValidatorStatus, Self::Error>;
+
+    /// Returns the serialized history of what the validator signed, to move it to another machine.
+    async fn export_signing_history(&mut self) -> Result<String, Self::Error>;
+
+    /// Imports the signing history that was exported on another machine.
+    async fn import_signing_history(&mut self, history: String) -> Result<(), Self::Error>;
+}
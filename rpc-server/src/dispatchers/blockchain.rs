@@ -7,7 +7,7 @@ use nimiq_blockchain_albatross::{Blockchain, BlockchainEvent};
 use nimiq_hash::Blake2bHash;
 use nimiq_primitives::policy;
 use nimiq_rpc_interface::{
-    types::{Block, OrLatest, SlashedSlots, Slot, Stake, Stakes, Validator},
+    types::{Block, OrLatest, SlashedSlots, Slot, Stake, Stakes, UpgradeSignals, Validator},
     blockchain::BlockchainInterface,
 };
 use nimiq_keys::Address;
@@ -99,6 +99,10 @@ impl BlockchainInterface for BlockchainDispatcher {
         })
     }
 
+    async fn upgrade_signals(&mut self) -> Result<UpgradeSignals, Error> {
+        Ok(self.blockchain.upgrade_signals().into())
+    }
+
     async fn get_raw_transaction_info(&mut self, _raw_tx: String) -> Result<(), Error> {
         Err(Error::NotImplemented)
     }
@@ -4,13 +4,16 @@ use async_trait::async_trait;
 use parking_lot::RwLock;
 
 use beserial::{Deserialize, Serialize};
-use nimiq_consensus_albatross::ConsensusProxy;
+use nimiq_consensus_albatross::{ConsensusProxy, SyncPhase};
 use nimiq_hash::Blake2bHash;
 use nimiq_hash::Hash;
 use nimiq_mempool::ReturnCode;
 use nimiq_network_libp2p::Network;
 use nimiq_primitives::account::AccountType;
-use nimiq_rpc_interface::{consensus::ConsensusInterface, types::TransactionParameters};
+use nimiq_rpc_interface::{
+    consensus::ConsensusInterface,
+    types::{self, HistorySyncProgress, SyncStatus, TransactionParameters},
+};
 use nimiq_transaction::Transaction;
 
 use crate::{error::Error, wallets::UnlockedWallets};
@@ -88,4 +91,32 @@ impl ConsensusInterface for ConsensusDispatcher {
 
         Ok(self.push_transaction(tx).await?.to_hex())
     }
+
+    async fn get_history_sync_progress(&mut self) -> Result<HistorySyncProgress, Error> {
+        let progress = self.consensus.history_sync_progress();
+        Ok(HistorySyncProgress {
+            epochs_done: progress.epochs_done,
+            epochs_total: progress.epochs_total,
+            chunks_done: progress.chunks_done,
+            chunks_total: progress.chunks_total,
+        })
+    }
+
+    async fn get_sync_status(&mut self) -> Result<SyncStatus, Error> {
+        let status = self.consensus.sync_status();
+        Ok(SyncStatus {
+            phase: match status.phase {
+                SyncPhase::History => types::SyncPhase::History,
+                SyncPhase::BlockQueue => types::SyncPhase::BlockQueue,
+                SyncPhase::Established => types::SyncPhase::Established,
+            },
+            head_block_number: status.head_block_number,
+            head_hash: status.head_hash.clone(),
+            best_peer_head_block_number: status.best_peer_head_number(),
+            peer_head_block_numbers: status.peer_head_numbers,
+            num_agents: status.num_agents,
+            num_buffered_blocks: status.num_buffered_blocks,
+            remaining_epochs: status.remaining_epochs,
+        })
+    }
 }
@@ -1,9 +1,15 @@
 mod blockchain;
 mod consensus;
 mod mempool;
+mod network;
+#[cfg(feature = "validator")]
+mod validator;
 mod wallet;
 
 pub use blockchain::BlockchainDispatcher;
 pub use consensus::ConsensusDispatcher;
 pub use mempool::MempoolDispatcher;
+pub use network::NetworkDispatcher;
+#[cfg(feature = "validator")]
+pub use validator::ValidatorDispatcher;
 pub use wallet::WalletDispatcher;
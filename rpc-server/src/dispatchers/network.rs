@@ -0,0 +1,135 @@
+use std::{net::IpAddr, str::FromStr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+use nimiq_network_interface::network::Network as _;
+use nimiq_network_libp2p::{bandwidth, libp2p::PeerId, Multiaddr, Network};
+use nimiq_rpc_interface::{
+    network::NetworkInterface,
+    types::{BandwidthStats, Peer, Traffic},
+};
+
+use crate::error::Error;
+
+
+fn traffic(traffic: &bandwidth::Traffic) -> Traffic {
+    Traffic {
+        bytes_received: traffic.inbound.bytes,
+        bytes_sent: traffic.outbound.bytes,
+        messages_received: traffic.inbound.messages,
+        messages_sent: traffic.outbound.messages,
+    }
+}
+
+fn parse_peer_id(peer_id: &str) -> Result<PeerId, Error> {
+    PeerId::from_str(peer_id).map_err(|_| Error::InvalidPeerId(peer_id.to_owned()))
+}
+
+fn parse_ip(ip: &str) -> Result<IpAddr, Error> {
+    IpAddr::from_str(ip).map_err(|_| Error::InvalidAddress(ip.to_owned()))
+}
+
+pub struct NetworkDispatcher {
+    network: Arc<Network>,
+}
+
+impl NetworkDispatcher {
+    pub fn new(network: Arc<Network>) -> Self {
+        Self { network }
+    }
+}
+
+#[nimiq_jsonrpc_derive::service(rename_all="camelCase")]
+#[async_trait]
+impl NetworkInterface for NetworkDispatcher {
+    type Error = Error;
+
+    async fn get_peer_id(&mut self) -> Result<String, Error> {
+        Ok(self.network.local_peer_id().to_string())
+    }
+
+    async fn get_listen_addresses(&mut self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .network
+            .listen_addresses()
+            .await?
+            .iter()
+            .map(|address| address.to_string())
+            .collect())
+    }
+
+    async fn get_peer_count(&mut self) -> Result<usize, Error> {
+        Ok(self.network.get_peers().len())
+    }
+
+    async fn get_peer_list(&mut self) -> Result<Vec<Peer>, Error> {
+        Ok(self
+            .network
+            .connected_peers()
+            .await?
+            .into_iter()
+            .map(|peer| Peer {
+                peer_id: peer.peer_id.to_string(),
+                addresses: peer
+                    .peer_contact
+                    .as_ref()
+                    .map(|contact| contact.addresses.iter().map(|address| address.to_string()).collect())
+                    .unwrap_or_default(),
+                services: peer.peer_contact.as_ref().map(|contact| contact.services.bits()),
+                connection_address: peer.address.to_string(),
+                outbound: peer.outbound,
+                connected_for: peer.connected_for.as_secs(),
+            })
+            .collect())
+    }
+
+    async fn connect(&mut self, address: String) -> Result<(), Error> {
+        if address.starts_with('/') {
+            let address = Multiaddr::from_str(&address).map_err(|_| Error::InvalidAddress(address.clone()))?;
+            self.network.dial_address(address).await?;
+        } else {
+            self.network.dial_peer(parse_peer_id(&address)?).await?;
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&mut self, peer_id: String, reason: Option<String>) -> Result<(), Error> {
+        let reason = format!("{} (via RPC)", reason.as_deref().unwrap_or("no reason given"));
+
+        if self.network.disconnect_peer(parse_peer_id(&peer_id)?, reason).await? {
+            Ok(())
+        } else {
+            Err(Error::PeerNotConnected(peer_id))
+        }
+    }
+
+    async fn ban_peer(&mut self, peer_id: String, duration: Option<u64>) -> Result<(), Error> {
+        Ok(self
+            .network
+            .ban_peer(parse_peer_id(&peer_id)?, duration.map(Duration::from_secs))
+            .await?)
+    }
+
+    async fn unban_peer(&mut self, peer_id: String) -> Result<(), Error> {
+        Ok(self.network.unban_peer(parse_peer_id(&peer_id)?).await?)
+    }
+
+    async fn ban_ip(&mut self, ip: String, duration: Option<u64>) -> Result<(), Error> {
+        Ok(self.network.ban_ip(parse_ip(&ip)?, duration.map(Duration::from_secs)).await?)
+    }
+
+    async fn unban_ip(&mut self, ip: String) -> Result<(), Error> {
+        Ok(self.network.unban_ip(parse_ip(&ip)?).await?)
+    }
+
+    async fn get_bandwidth_stats(&mut self) -> Result<BandwidthStats, Error> {
+        let stats = self.network.bandwidth_stats();
+
+        Ok(BandwidthStats {
+            total: traffic(&stats.total),
+            peers: stats.peers.iter().map(|(peer_id, t)| (peer_id.to_string(), traffic(t))).collect(),
+            message_types: stats.message_types.iter().map(|(type_id, t)| (*type_id, traffic(t))).collect(),
+            topics: stats.topics.iter().map(|(topic, t)| (topic.clone(), traffic(t))).collect(),
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+
+use nimiq_rpc_interface::{
+    types::{self, UpcomingSlot, ValidatorStatus},
+    validator::ValidatorInterface,
+};
+use nimiq_validator::{signing_history::SigningHistory, status::ValidatorProxy, validator::ValidatorStakingState};
+
+use crate::error::Error;
+
+
+pub struct ValidatorDispatcher {
+    validator: ValidatorProxy,
+}
+
+impl ValidatorDispatcher {
+    pub fn new(validator: ValidatorProxy) -> Self {
+        Self { validator }
+    }
+}
+
+#[nimiq_jsonrpc_derive::service(rename_all="camelCase")]
+#[async_trait]
+impl ValidatorInterface for ValidatorDispatcher {
+    type Error = Error;
+
+    async fn get_validator_status(&mut self) -> Result<ValidatorStatus, Error> {
+        let status = self.validator.status();
+        let statistics = &status.statistics;
+
+        Ok(ValidatorStatus {
+            public_key: self.validator.validator_key().clone(),
+            validator_id: status.validator_id,
+            num_slots: status.num_slots,
+            staking_state: match status.staking_state {
+                ValidatorStakingState::Active => types::ValidatorStakingState::Active,
+                ValidatorStakingState::Parked => types::ValidatorStakingState::Parked,
+                ValidatorStakingState::Inactive => types::ValidatorStakingState::Inactive,
+                ValidatorStakingState::NoStake => types::ValidatorStakingState::NoStake,
+            },
+            upcoming_slots: self
+                .validator
+                .upcoming_slots()
+                .into_iter()
+                .map(|slot| UpcomingSlot {
+                    block_number: slot.block_number,
+                    view_number: slot.view_number,
+                    slot_number: slot.slot_number,
+                })
+                .collect(),
+            micro_blocks_produced: statistics.micro_blocks_produced,
+            micro_blocks_missed: statistics.micro_blocks_missed,
+            view_changes: statistics.view_changes,
+            tendermint_rounds_per_macro_block: if statistics.macro_blocks > 0 {
+                Some(statistics.tendermint_rounds as f64 / statistics.macro_blocks as f64)
+            } else {
+                None
+            },
+            last_tendermint_rounds: statistics.last_tendermint_rounds,
+            fork_proofs: status.fork_proofs,
+            rewards: statistics.rewards,
+            slashes: statistics.slashes,
+        })
+    }
+
+    async fn export_signing_history(&mut self) -> Result<String, Error> {
+        Ok(self.validator.export_signing_history().to_hex())
+    }
+
+    async fn import_signing_history(&mut self, history: String) -> Result<(), Error> {
+        let history = SigningHistory::from_hex(&history)?;
+        self.validator.import_signing_history(&history);
+        Ok(())
+    }
+}
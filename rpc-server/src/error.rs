@@ -44,6 +44,15 @@ pub enum Error {
 
     #[error("Transaction rejected: {0:?}")]
     TransactionRejected(nimiq_mempool::ReturnCode),
+
+    #[error("Invalid peer ID: {0}")]
+    InvalidPeerId(String),
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Peer not connected: {0}")]
+    PeerNotConnected(String),
 }
 
 impl From<Error> for nimiq_jsonrpc_core::RpcError {
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::time;
+
+use nimiq_tendermint::*;
+
+mod simulator;
+
+use simulator::*;
+
+/// Number of random seeds that are simulated.
+const NUM_SEEDS: u64 = 2000;
+
+async fn run_checked(simulation: &Simulation) -> Outcome {
+    let outcome = simulation.run().await;
+    if let Err(e) = outcome
+        .check_safety(simulation)
+        .and_then(|_| outcome.check_liveness(simulation))
+    {
+        panic!(
+            "Seed {}: {}\n{:#?}\n{:#?}",
+            simulation.seed, e, simulation, outcome
+        );
+    }
+    outcome
+}
+
+#[tokio::test]
+async fn it_decides_without_faults() {
+    time::pause();
+
+    let outcome = run_checked(&Simulation::new(4, 0)).await;
+
+    assert_eq!(outcome.round(), Some(0));
+    assert!(outcome.decisions.iter().all(Option::is_some));
+}
+
+#[tokio::test]
+async fn it_times_out_silent_proposers() {
+    time::pause();
+
+    let mut simulation = Simulation::new(7, 1);
+    simulation.behaviours[0] = Behaviour::Silent;
+    simulation.behaviours[1] = Behaviour::Silent;
+
+    let outcome = run_checked(&simulation).await;
+
+    assert_eq!(outcome.round(), Some(2));
+}
+
+#[tokio::test]
+async fn it_survives_equivocating_proposers() {
+    time::pause();
+
+    for seed in 0..10 {
+        let mut simulation = Simulation::new(4, seed);
+        simulation.behaviours[0] = Behaviour::Equivocating;
+
+        run_checked(&simulation).await;
+    }
+}
+
+#[tokio::test]
+async fn it_survives_delayed_and_missing_votes() {
+    time::pause();
+
+    for seed in 0..10 {
+        let mut simulation = Simulation::new(7, seed);
+        simulation.gst = Duration::from_secs(10);
+        simulation.drop_probability = 0.5;
+
+        run_checked(&simulation).await;
+    }
+}
+
+#[tokio::test]
+async fn it_restarts_from_state_after_crashes() {
+    time::pause();
+
+    for seed in 0..10 {
+        let mut simulation = Simulation::new(4, seed);
+        for (validator, crash) in simulation.crashes.iter_mut().enumerate() {
+            *crash = Some(Crash {
+                at: Duration::from_millis(300 * validator as u64 + 50 * seed),
+                downtime: Duration::from_secs(2),
+            });
+        }
+
+        run_checked(&simulation).await;
+    }
+}
+
+#[tokio::test]
+async fn it_rejects_invalid_states() {
+    let mut state = TendermintState::new();
+    state.round = 1;
+    state.locked_round = Some(2);
+
+    let simulation = Simulation::new(4, 0);
+    let mut tendermint = Box::pin(simulation.validator(0, Some(state)));
+
+    assert!(matches!(
+        tendermint.next().await,
+        Some(TendermintReturn::Error(TendermintError::BadInitState))
+    ));
+}
+
+#[tokio::test]
+async fn it_is_deterministic() {
+    time::pause();
+
+    for seed in 0..10 {
+        let simulation = Simulation::random(seed);
+        assert_eq!(simulation.run().await, simulation.run().await);
+    }
+}
+
+#[tokio::test]
+async fn it_is_safe_and_live_with_random_faults() {
+    time::pause();
+
+    for seed in 0..NUM_SEEDS {
+        run_checked(&Simulation::random(seed)).await;
+    }
+}
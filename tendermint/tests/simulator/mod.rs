@@ -0,0 +1,735 @@
+//! Deterministic simulation of Tendermint instances on an in-memory network.
+//!
+//! All validators run on the current tokio runtime, whose clock must be paused, such that timeouts
+//! and message delays advance virtual time only. Whether and when a message is delivered only
+//! depends on the seed, its sender, recipient, round and step, so a simulation always plays out the
+//! same way.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use beserial::Serialize;
+use futures::future::join_all;
+use futures::{pin_mut, Stream, StreamExt};
+use tokio::time::{self, Instant};
+
+use nimiq_hash::{Blake2bHash, Hash, SerializeContent};
+use nimiq_primitives::policy::{SLOTS, TWO_THIRD_SLOTS};
+use nimiq_tendermint::*;
+
+/// How often waiting validators check the network for new messages.
+const TICK: Duration = Duration::from_millis(20);
+
+/// Marks the second proposal of an equivocating proposer.
+const EQUIVOCATION: u64 = 1 << 63;
+
+/// Proposal of the simulation. The proposal of an honest proposer contains the round and its ID.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SimProposal(pub u64);
+
+impl SimProposal {
+    fn new(round: u32, proposer: usize) -> Self {
+        SimProposal((round as u64) << 16 | proposer as u64)
+    }
+
+    fn proposer(&self) -> usize {
+        (self.0 & 0xffff) as usize
+    }
+}
+
+impl SerializeContent for SimProposal {
+    fn serialize_content<W: io::Write>(&self, writer: &mut W) -> io::Result<usize> {
+        Ok(self.0.serialize(writer)?)
+    }
+}
+
+impl Hash for SimProposal {}
+
+/// Block that a validator decided on. The proof consists of the IDs of the validators that
+/// precommitted it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimBlock {
+    pub proposal: SimProposal,
+    pub round: u32,
+    pub signers: Vec<usize>,
+}
+
+/// How a validator behaves.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Behaviour {
+    Honest,
+    /// Runs Tendermint, but doesn't send any messages.
+    Silent,
+    /// Sends different proposals to the validators with odd and even IDs, and signs every vote for
+    /// every value.
+    Equivocating,
+}
+
+/// The validator crashes at `at` and restarts from its last state after `downtime`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Crash {
+    pub at: Duration,
+    pub downtime: Duration,
+}
+
+/// A scripted simulation. Times are relative to its start.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// Seed for the delays and losses of messages.
+    pub seed: u64,
+
+    /// Behaviour of each validator. Their slots are split as evenly as possible.
+    pub behaviours: Vec<Behaviour>,
+
+    /// Crash of each validator, if it crashes.
+    pub crashes: Vec<Option<Crash>>,
+
+    /// Global stabilization time. After it, every message is delivered within `max_delay`.
+    pub gst: Duration,
+
+    /// Maximum delay of a message that is sent after the GST.
+    pub max_delay: Duration,
+
+    /// Maximum delay of a message that is sent before the GST.
+    pub max_delay_before_gst: Duration,
+
+    /// Probability that a message that is sent before the GST is missing until the GST.
+    pub drop_probability: f64,
+
+    /// Timeout for proposals in round 0.
+    pub propose_timeout: Duration,
+
+    /// Timeout for votes in round 0.
+    pub vote_timeout: Duration,
+
+    /// Increase of the timeouts per round.
+    pub timeout_delta: Duration,
+
+    /// The simulation stops after this time, even if not all validators decided.
+    pub max_duration: Duration,
+}
+
+impl Simulation {
+    /// Creates a simulation of `num_validators` honest validators on a synchronous network.
+    pub fn new(num_validators: usize, seed: u64) -> Self {
+        Self {
+            seed,
+            behaviours: vec![Behaviour::Honest; num_validators],
+            crashes: vec![None; num_validators],
+            gst: Duration::from_secs(0),
+            max_delay: Duration::from_millis(100),
+            max_delay_before_gst: Duration::from_secs(2),
+            drop_probability: 0.0,
+            propose_timeout: Duration::from_secs(1),
+            vote_timeout: Duration::from_secs(1),
+            timeout_delta: Duration::from_millis(500),
+            max_duration: Duration::from_secs(120),
+        }
+    }
+
+    /// Creates a simulation with random faults. It has up to `f` silent or equivocating validators,
+    /// crashes of other validators and a random GST, before which messages are delayed or
+    /// missing.
+    pub fn random(seed: u64) -> Self {
+        let mut rng = SimRng(seed);
+        let num_validators = [4, 7, 10][rng.below(3)];
+        let mut simulation = Self::new(num_validators, seed);
+
+        for _ in 0..rng.below(simulation.max_faulty() + 1) {
+            let validator = rng.below(num_validators);
+            simulation.behaviours[validator] = if rng.chance(0.5) {
+                Behaviour::Silent
+            } else {
+                Behaviour::Equivocating
+            };
+        }
+
+        simulation.gst = rng.duration(Duration::from_secs(5));
+        simulation.drop_probability = 0.2;
+
+        for _ in 0..rng.below(3) {
+            let validator = rng.below(num_validators);
+            simulation.crashes[validator] = Some(Crash {
+                at: rng.duration(simulation.gst + Duration::from_secs(5)),
+                downtime: Duration::from_millis(100) + rng.duration(Duration::from_secs(3)),
+            });
+        }
+
+        simulation
+    }
+
+    pub fn num_validators(&self) -> usize {
+        self.behaviours.len()
+    }
+
+    /// Returns the number of slots of `validator`.
+    pub fn slots(&self, validator: usize) -> usize {
+        let n = self.num_validators();
+        SLOTS as usize / n + if validator < SLOTS as usize % n { 1 } else { 0 }
+    }
+
+    /// Returns the maximum number of faulty validators, whose slots are always less than a third.
+    pub fn max_faulty(&self) -> usize {
+        (self.num_validators() - 1) / 3
+    }
+
+    fn propose_timeout(&self, round: u32) -> Duration {
+        self.propose_timeout + self.timeout_delta * round
+    }
+
+    fn vote_timeout(&self, round: u32) -> Duration {
+        self.vote_timeout + self.timeout_delta * round
+    }
+
+    fn network(&self) -> Arc<Network> {
+        Arc::new(Network {
+            simulation: self.clone(),
+            start: Instant::now(),
+            state: Mutex::new(NetworkState::default()),
+        })
+    }
+
+    /// Starts Tendermint for validator `id` on a new network, without any other validator running.
+    pub fn validator(
+        &self,
+        id: usize,
+        state: Option<TendermintState<SimProposal, Vec<usize>>>,
+    ) -> impl Stream<Item = TendermintReturn<SimProposal, Vec<usize>, SimBlock>> {
+        let deps = SimValidator {
+            id,
+            network: self.network(),
+        };
+        expect_block(deps, state)
+    }
+
+    /// Runs the simulation until all validators decided or `max_duration` passed.
+    pub async fn run(&self) -> Outcome {
+        let network = self.network();
+
+        let (decisions, adoptions) =
+            join_all((0..self.num_validators()).map(|id| run_validator(Arc::clone(&network), id)))
+                .await
+                .into_iter()
+                .unzip();
+
+        let state = network.state.lock().unwrap();
+        Outcome {
+            decisions,
+            adoptions,
+            honest_equivocations: state.honest_equivocations,
+            errors: state.errors.clone(),
+        }
+    }
+}
+
+/// A decision of a validator by Tendermint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decision {
+    pub block: SimBlock,
+    pub at: Duration,
+}
+
+/// Outcome of a simulation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// Decision of each validator, if it decided by itself.
+    pub decisions: Vec<Option<Decision>>,
+
+    /// Time at which each validator received a block that another validator decided on, if it
+    /// did. Such a block would let it move on, so it counts for liveness, but not for safety.
+    pub adoptions: Vec<Option<Duration>>,
+
+    /// Number of times that an honest validator signed two different votes for the same round and
+    /// step.
+    pub honest_equivocations: usize,
+
+    /// Errors that Tendermint returned.
+    pub errors: Vec<(usize, TendermintError)>,
+}
+
+impl Outcome {
+    /// Checks that no two honest validators decided differently, that each of their decisions has
+    /// 2f+1 precommits for a proposal of a validator, and that they never equivocated.
+    pub fn check_safety(&self, simulation: &Simulation) -> Result<(), String> {
+        if self.honest_equivocations > 0 {
+            return Err(format!(
+                "{} equivocations of honest validators",
+                self.honest_equivocations
+            ));
+        }
+        if let Some((id, error)) = self.errors.first() {
+            return Err(format!("Validator {} failed: {:?}", id, error));
+        }
+
+        let mut decided: Option<&SimBlock> = None;
+        for (id, decision) in self.decisions.iter().enumerate() {
+            let block = match decision {
+                Some(decision) if simulation.behaviours[id] == Behaviour::Honest => &decision.block,
+                _ => continue,
+            };
+
+            let slots: usize = block
+                .signers
+                .iter()
+                .map(|&signer| simulation.slots(signer))
+                .sum();
+            if slots < TWO_THIRD_SLOTS as usize {
+                return Err(format!(
+                    "Validator {} decided with {} slots: {:?}",
+                    id, slots, block
+                ));
+            }
+            if block.proposal.proposer() >= simulation.num_validators() {
+                return Err(format!(
+                    "Validator {} decided on an unknown proposal: {:?}",
+                    id, block
+                ));
+            }
+            match decided {
+                Some(other) if other.proposal != block.proposal => {
+                    return Err(format!(
+                        "Conflicting decisions: {:?} and {:?}",
+                        other, block
+                    ));
+                }
+                _ => decided = Some(block),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every honest validator decided or received a decided block.
+    pub fn check_liveness(&self, simulation: &Simulation) -> Result<(), String> {
+        for id in 0..simulation.num_validators() {
+            if simulation.behaviours[id] == Behaviour::Honest
+                && self.decisions[id].is_none()
+                && self.adoptions[id].is_none()
+            {
+                return Err(format!("Validator {} didn't decide", id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the round of the decision, if any validator decided.
+    pub fn round(&self) -> Option<u32> {
+        self.decisions
+            .iter()
+            .flatten()
+            .map(|decision| decision.block.round)
+            .next()
+    }
+}
+
+/// Deterministic pseudo random numbers (SplitMix64).
+struct SimRng(u64);
+
+impl SimRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        self.unit() < probability
+    }
+
+    fn duration(&mut self, max: Duration) -> Duration {
+        max.mul_f64(self.unit())
+    }
+}
+
+/// Identifies the messages of a round and step for the delivery schedule.
+fn message_tag(round: u32, step: Step) -> u64 {
+    let step = match step {
+        Step::Propose => 0,
+        Step::Prevote => 1,
+        Step::Precommit => 2,
+    };
+    (round as u64) << 2 | step
+}
+
+/// Tag of the messages that announce decided blocks.
+const BLOCK_TAG: u64 = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Vote {
+    For(Option<Blake2bHash>),
+    /// A vote for every value, i.e. an equivocation.
+    Any,
+}
+
+#[derive(Default)]
+struct NetworkState {
+    /// (round, recipient) -> (proposal, valid round, delivery time)
+    proposals: BTreeMap<(u32, usize), (SimProposal, Option<u32>, Duration)>,
+
+    /// (round, message tag) -> validator -> (vote, time sent)
+    votes: BTreeMap<(u32, u64), BTreeMap<usize, (Vote, Duration)>>,
+
+    /// validator -> (decided block, time sent)
+    blocks: BTreeMap<usize, (SimBlock, Duration)>,
+
+    honest_equivocations: usize,
+
+    errors: Vec<(usize, TendermintError)>,
+}
+
+struct Network {
+    simulation: Simulation,
+    start: Instant,
+    state: Mutex<NetworkState>,
+}
+
+impl Network {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn behaviour(&self, validator: usize) -> Behaviour {
+        self.simulation.behaviours[validator]
+    }
+
+    /// Returns when a message from `from` to `to` that was sent at `sent` is delivered.
+    fn delivery_time(&self, sent: Duration, from: usize, to: usize, tag: u64) -> Duration {
+        if from == to {
+            return sent;
+        }
+
+        let mut rng = SimRng(self.simulation.seed ^ (from as u64) << 48 ^ (to as u64) << 32 ^ tag);
+        if sent >= self.simulation.gst {
+            sent + rng.duration(self.simulation.max_delay)
+        } else if rng.chance(self.simulation.drop_probability) {
+            self.simulation.gst + rng.duration(self.simulation.max_delay)
+        } else {
+            sent + rng.duration(self.simulation.max_delay_before_gst)
+        }
+    }
+
+    fn broadcast_proposal(
+        &self,
+        from: usize,
+        round: u32,
+        proposal: SimProposal,
+        valid_round: Option<u32>,
+    ) {
+        let now = self.now();
+        let mut state = self.state.lock().unwrap();
+        for to in 0..self.simulation.num_validators() {
+            let proposal = match self.behaviour(from) {
+                Behaviour::Honest => proposal,
+                Behaviour::Silent => return,
+                Behaviour::Equivocating if to % 2 == 1 => SimProposal(proposal.0 | EQUIVOCATION),
+                Behaviour::Equivocating => proposal,
+            };
+            let delivery_time =
+                self.delivery_time(now, from, to, message_tag(round, Step::Propose));
+            // A proposer that restarted can't take back its first proposal.
+            state
+                .proposals
+                .entry((round, to))
+                .or_insert((proposal, valid_round, delivery_time));
+        }
+    }
+
+    fn proposal(&self, to: usize, round: u32) -> Option<(SimProposal, Option<u32>)> {
+        let now = self.now();
+        let state = self.state.lock().unwrap();
+        match state.proposals.get(&(round, to)) {
+            Some(&(proposal, valid_round, delivery_time)) if delivery_time <= now => {
+                Some((proposal, valid_round))
+            }
+            _ => None,
+        }
+    }
+
+    fn vote(&self, from: usize, round: u32, step: Step, proposal: Option<Blake2bHash>) {
+        let vote = match self.behaviour(from) {
+            Behaviour::Honest => Vote::For(proposal),
+            Behaviour::Silent => return,
+            Behaviour::Equivocating => Vote::Any,
+        };
+
+        let now = self.now();
+        let mut state = self.state.lock().unwrap();
+        let votes = state
+            .votes
+            .entry((round, message_tag(round, step)))
+            .or_default();
+        let equivocation = match votes.get(&from) {
+            Some((previous, _)) => *previous != vote,
+            None => {
+                votes.insert(from, (vote, now));
+                false
+            }
+        };
+        if equivocation {
+            state.honest_equivocations += 1;
+        }
+    }
+
+    /// Returns the votes for `round` and `step` that `to` received so far.
+    fn aggregation(
+        &self,
+        to: usize,
+        round: u32,
+        step: Step,
+    ) -> BTreeMap<Option<Blake2bHash>, (Vec<usize>, usize)> {
+        let now = self.now();
+        let tag = message_tag(round, step);
+        let state = self.state.lock().unwrap();
+
+        let mut aggregation: BTreeMap<Option<Blake2bHash>, (Vec<usize>, usize)> = BTreeMap::new();
+        aggregation.insert(None, (vec![], 0));
+        let mut equivocators = vec![];
+
+        for (&from, (vote, sent)) in state.votes.get(&(round, tag)).into_iter().flatten() {
+            if self.delivery_time(*sent, from, to, tag) > now {
+                continue;
+            }
+            match vote {
+                Vote::For(proposal) => {
+                    let (signers, slots) = aggregation.entry(proposal.clone()).or_default();
+                    signers.push(from);
+                    *slots += self.simulation.slots(from);
+                }
+                Vote::Any => equivocators.push(from),
+            }
+        }
+
+        for (signers, slots) in aggregation.values_mut() {
+            for &from in &equivocators {
+                signers.push(from);
+                *slots += self.simulation.slots(from);
+            }
+        }
+
+        aggregation
+    }
+
+    /// Returns the highest round above `round` that f+1 slots voted in or above, as far as `to`
+    /// knows.
+    fn new_round(&self, to: usize, round: u32) -> Option<u32> {
+        let now = self.now();
+        let state = self.state.lock().unwrap();
+
+        let mut latest_rounds: BTreeMap<usize, u32> = BTreeMap::new();
+        for (&(vote_round, tag), votes) in state.votes.range((round + 1, 0)..) {
+            for (&from, (_, sent)) in votes {
+                if self.delivery_time(*sent, from, to, tag) <= now {
+                    let latest = latest_rounds.entry(from).or_insert(vote_round);
+                    *latest = (*latest).max(vote_round);
+                }
+            }
+        }
+
+        let mut latest_rounds: Vec<(u32, usize)> = latest_rounds
+            .into_iter()
+            .map(|(from, round)| (round, from))
+            .collect();
+        latest_rounds.sort_by(|a, b| b.cmp(a));
+
+        let mut slots = 0;
+        for (vote_round, from) in latest_rounds {
+            slots += self.simulation.slots(from);
+            if slots > (SLOTS - TWO_THIRD_SLOTS) as usize {
+                return Some(vote_round);
+            }
+        }
+        None
+    }
+
+    fn publish_block(&self, from: usize, block: &SimBlock) {
+        if self.behaviour(from) != Behaviour::Silent {
+            let now = self.now();
+            self.state
+                .lock()
+                .unwrap()
+                .blocks
+                .entry(from)
+                .or_insert((block.clone(), now));
+        }
+    }
+
+    /// Returns a block that another validator decided on and that `to` received.
+    fn block(&self, to: usize) -> Option<SimBlock> {
+        let now = self.now();
+        let state = self.state.lock().unwrap();
+        state
+            .blocks
+            .iter()
+            .find(|(&from, (_, sent))| self.delivery_time(*sent, from, to, BLOCK_TAG) <= now)
+            .map(|(_, (block, _))| block.clone())
+    }
+}
+
+struct SimValidator {
+    id: usize,
+    network: Arc<Network>,
+}
+
+#[async_trait]
+impl TendermintOutsideDeps for SimValidator {
+    type ProposalTy = SimProposal;
+    type ProofTy = Vec<usize>;
+    type ResultTy = SimBlock;
+
+    fn verify_state(&self, state: &TendermintState<Self::ProposalTy, Self::ProofTy>) -> bool {
+        state.locked_round.is_some() == state.locked_value.is_some()
+            && state.valid_round.is_some() == state.valid_value.is_some()
+            && state
+                .locked_round
+                .map_or(true, |round| round <= state.round)
+            && state.valid_round.map_or(true, |round| round <= state.round)
+    }
+
+    fn is_our_turn(&self, round: u32) -> bool {
+        round as usize % self.network.simulation.num_validators() == self.id
+    }
+
+    fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError> {
+        Ok(SimProposal::new(round, self.id))
+    }
+
+    fn assemble_block(
+        &self,
+        round: u32,
+        proposal: Self::ProposalTy,
+        proof: Self::ProofTy,
+    ) -> Result<Self::ResultTy, TendermintError> {
+        Ok(SimBlock {
+            proposal,
+            round,
+            signers: proof,
+        })
+    }
+
+    async fn broadcast_proposal(
+        &mut self,
+        round: u32,
+        proposal: Self::ProposalTy,
+        valid_round: Option<u32>,
+    ) -> Result<(), TendermintError> {
+        self.network
+            .broadcast_proposal(self.id, round, proposal, valid_round);
+        Ok(())
+    }
+
+    async fn await_proposal(
+        &mut self,
+        round: u32,
+    ) -> Result<ProposalResult<Self::ProposalTy>, TendermintError> {
+        let deadline = self.network.now() + self.network.simulation.propose_timeout(round);
+        loop {
+            if let Some((proposal, valid_round)) = self.network.proposal(self.id, round) {
+                return Ok(ProposalResult::Proposal(proposal, valid_round));
+            }
+            if self.network.now() >= deadline {
+                return Ok(ProposalResult::Timeout);
+            }
+            time::delay_for(TICK).await;
+        }
+    }
+
+    async fn broadcast_and_aggregate(
+        &mut self,
+        round: u32,
+        step: Step,
+        proposal: Option<Blake2bHash>,
+    ) -> Result<AggregationResult<Self::ProofTy>, TendermintError> {
+        let deadline = self.network.now() + self.network.simulation.vote_timeout(round);
+        self.network.vote(self.id, round, step, proposal);
+
+        loop {
+            let aggregation = self.network.aggregation(self.id, round, step);
+            let complete = aggregation
+                .values()
+                .any(|&(_, slots)| slots >= TWO_THIRD_SLOTS as usize);
+            if complete || self.network.now() >= deadline {
+                return Ok(AggregationResult::Aggregation(aggregation));
+            }
+            if let Some(new_round) = self.network.new_round(self.id, round) {
+                return Ok(AggregationResult::NewRound(new_round));
+            }
+            time::delay_for(TICK).await;
+        }
+    }
+
+    async fn get_aggregation(
+        &mut self,
+        round: u32,
+        step: Step,
+    ) -> Result<AggregationResult<Self::ProofTy>, TendermintError> {
+        Ok(AggregationResult::Aggregation(
+            self.network.aggregation(self.id, round, step),
+        ))
+    }
+}
+
+/// Runs Tendermint for validator `id` until it decides or `max_duration` passed, and records when
+/// it received the block of another validator. Crashes drop the running instance, which is
+/// restarted from the last state update after the downtime.
+async fn run_validator(network: Arc<Network>, id: usize) -> (Option<Decision>, Option<Duration>) {
+    let crash = network.simulation.crashes[id];
+    let mut crashed = false;
+    let mut state: Option<TendermintState<SimProposal, Vec<usize>>> = None;
+    let mut adopted_at = None;
+
+    loop {
+        let deps = SimValidator {
+            id,
+            network: Arc::clone(&network),
+        };
+        let tendermint = expect_block(deps, state.clone());
+        pin_mut!(tendermint);
+
+        loop {
+            let now = network.now();
+            if now >= network.simulation.max_duration {
+                return (None, adopted_at);
+            }
+            if adopted_at.is_none() && network.block(id).is_some() {
+                adopted_at = Some(now);
+            }
+            if !crashed && crash.map_or(false, |crash| now >= crash.at) {
+                crashed = true;
+                break;
+            }
+
+            tokio::select! {
+                item = tendermint.next() => match item {
+                    Some(TendermintReturn::StateUpdate(new_state)) => state = Some(new_state),
+                    Some(TendermintReturn::Result(block)) => {
+                        network.publish_block(id, &block);
+                        let decision = Decision { block, at: network.now() };
+                        return (Some(decision), adopted_at);
+                    }
+                    Some(TendermintReturn::Error(error)) => {
+                        network.state.lock().unwrap().errors.push((id, error));
+                        return (None, adopted_at);
+                    }
+                    None => return (None, adopted_at),
+                },
+                _ = time::delay_for(TICK) => {}
+            }
+        }
+
+        // The validator is down until it restarts from its last state.
+        time::delay_for(crash.unwrap().downtime).await;
+    }
+}
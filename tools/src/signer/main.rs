@@ -0,0 +1,75 @@
+extern crate nimiq_bls as bls;
+extern crate nimiq_database as database;
+extern crate nimiq_validator as validator;
+
+use std::process::exit;
+use std::str::FromStr;
+use std::thread;
+
+use clap::{crate_authors, crate_description, crate_version, App, Arg};
+use failure::Error;
+use failure::Fail;
+
+use bls::KeyPair;
+use database::lmdb::{open, LmdbEnvironment};
+use nimiq_utils::file_store::FileStore;
+use validator::signer::{SignerAddress, SignerServer};
+
+fn run_app() -> Result<(), Error> {
+    let matches = App::new("Remote signer")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .arg(
+            Arg::with_name("listen")
+                .short("l")
+                .long("listen")
+                .value_name("ADDRESS")
+                .help("Listen on ADDRESS, either a loopback `host:port` or `unix:/path/to/socket`. Connections are not authenticated, so use an SSH tunnel to sign for another machine.")
+                .default_value("127.0.0.1:8650"),
+        )
+        .arg(
+            Arg::with_name("key_file")
+                .short("k")
+                .long("key-file")
+                .value_name("FILE")
+                .help("Sign with the BLS key pair in FILE, e.g. the `validator_key.dat` of a validator.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("database")
+                .short("d")
+                .long("database")
+                .value_name("PATH")
+                .help("Keep the signing history in the database at PATH, such that it survives restarts.")
+                .takes_value(true)
+                .required(true),
+        )
+        .get_matches();
+
+    let address = SignerAddress::from_str(matches.value_of("listen").unwrap())?;
+    let key_pair: KeyPair = FileStore::new(matches.value_of("key_file").ok_or(AppError::KeyFile)?).load()?;
+    let env = LmdbEnvironment::new(matches.value_of("database").unwrap(), 1024 * 1024 * 10, 1, open::NOMETASYNC)?;
+
+    let address = SignerServer::new(key_pair, env).spawn(&address)?;
+    println!("Listening on {}", address);
+    loop {
+        thread::park();
+    }
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    });
+}
+
+#[derive(Debug, Fail)]
+enum AppError {
+    #[fail(display = "Key file is missing")]
+    KeyFile,
+}
@@ -1,5 +1,5 @@
 use beserial::{Deserialize, Serialize};
-use bls::KeyPair as BlsKeyPair;
+use bls::{KeyPair as BlsKeyPair, Signature as BlsSignature};
 use keys::KeyPair;
 use transaction::account::staking_contract::{IncomingStakingTransactionData, OutgoingStakingTransactionProof};
 use transaction::{SignatureProof, Transaction};
@@ -27,9 +27,14 @@ impl SignallingProofBuilder {
     /// This method sets the required signalling `signature` proof by signing the transaction
     /// using a BLS key pair `validator_key_pair`.
     pub fn sign_with_validator_key_pair(&mut self, validator_key_pair: &BlsKeyPair) -> &mut Self {
-        // Set validator signature first.
-        let mut data: IncomingStakingTransactionData = Deserialize::deserialize_from_vec(&self.transaction.data[..]).unwrap();
         let validator_signature = validator_key_pair.sign(&self.transaction.serialize_content().as_slice());
+        self.with_validator_signature(validator_signature)
+    }
+
+    /// This method sets the required signalling `signature` proof to a `validator_signature` of the
+    /// transaction that was created elsewhere, e.g. by a remote signer.
+    pub fn with_validator_signature(&mut self, validator_signature: BlsSignature) -> &mut Self {
+        let mut data: IncomingStakingTransactionData = Deserialize::deserialize_from_vec(&self.transaction.data[..]).unwrap();
         data.set_validator_signature(validator_signature.compress());
         self.data = Some(data);
         self
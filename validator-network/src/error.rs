@@ -27,6 +27,10 @@ where
     #[error("Unknown validator: {0}")]
     UnknownValidator(usize),
 
+    /// Our validator record could not be signed, e.g. because the remote signer is unavailable.
+    #[error("Failed to sign validator record")]
+    Signing,
+
     #[error("Network error: {0}")]
     Network(#[from] TNetworkError),
 
@@ -14,7 +14,7 @@ use nimiq_network_interface::{
     message::Message,
     peer::Peer,
 };
-use nimiq_bls::{CompressedPublicKey, SecretKey};
+use nimiq_bls::{CompressedPublicKey, Signature};
 
 pub use crate::error::NetworkError;
 
@@ -26,7 +26,7 @@ pub type MessageStream<TMessage, TPeerId> = Pin<Box<dyn Stream<Item = (TMessage,
 pub trait ValidatorNetwork: Send + Sync {
     type Error: std::error::Error;
     type PeerType: Peer;
-    type PubsubId: PubsubId<<Self::PeerType as Peer>::Id>;
+    type PubsubId: PubsubId<<Self::PeerType as Peer>::Id> + Send + 'static;
 
     /// Tells the validator network the validator keys for the current set of active validators. The keys must be
     /// ordered, such that the k-th entry is the validator with ID k.
@@ -48,10 +48,20 @@ pub trait ValidatorNetwork: Send + Sync {
         topic: &TTopic,
     ) -> Result<Pin<Box<dyn Stream<Item = (TTopic::Item, Self::PubsubId)> + Send>>, Self::Error>;
 
+    /// Marks a message that was received on a topic with validation as valid, such that it is forwarded to other
+    /// peers. Messages that are never validated are not forwarded.
+    async fn validate_message(&self, id: Self::PubsubId) -> Result<bool, Self::Error>;
+
     /// registers a cache for the specified message type.
     /// Incoming messages of this type shuld be held in a FIFO queue of total size `buffer_size`, each with a lifetime of `lifetime`
     /// `lifetime` or `buffer_size` of 0 should disable the cache.
     fn cache<M: Message>(&self, buffer_size: usize, lifetime: Duration);
 
-    async fn set_public_key(&self, public_key: &CompressedPublicKey, secret_key: &SecretKey) -> Result<(), Self::Error>;
+    /// Publishes the record that maps our validator key to our peer ID. The serialized record is signed by `sign`,
+    /// which returns `None` if it can't be signed.
+    async fn set_public_key(
+        &self,
+        public_key: &CompressedPublicKey,
+        sign: &(dyn Fn(&[u8]) -> Option<Signature> + Sync),
+    ) -> Result<(), Self::Error>;
 }
@@ -1,16 +1,21 @@
 use std::{
     collections::{btree_map::Entry, BTreeMap},
+    marker::PhantomData,
     pin::Pin,
     sync::Arc,
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use async_trait::async_trait;
 use beserial::{Deserialize, Serialize};
 use futures::{future::join_all, lock::Mutex, Stream, StreamExt};
 
-use nimiq_bls::{CompressedPublicKey, PublicKey, Signature, SecretKey};
-use nimiq_network_interface::{message::Message, network::Network, network::Topic, peer::Peer};
+use nimiq_bls::{CompressedPublicKey, PublicKey, Signature};
+use nimiq_network_interface::{
+    message::Message,
+    network::{DhtRecordValidator, Network, Topic},
+    peer::Peer,
+};
 use nimiq_utils::tagged_signing::TaggedSignable;
 
 use super::{MessageStream, NetworkError, ValidatorNetwork};
@@ -36,26 +41,31 @@ where
     pub peer_id: TPeerId,
     //public_key: PublicKey,
     // TODO: other info?
+
+    /// Time when this record was created in *seconds* since unix epoch. If there are several records for a validator,
+    /// the newest one wins.
+    pub timestamp: u64,
 }
 
 impl<TPeerId> ValidatorRecord<TPeerId>
 where
     TPeerId: Serialize + Deserialize,
 {
-    pub fn new(peer_id: TPeerId) -> Self {
+    pub fn new(peer_id: TPeerId, timestamp: u64) -> Self {
         Self {
             peer_id,
+            timestamp,
         }
     }
 
-    pub fn sign(self, secret_key: &SecretKey) -> SignedValidatorRecord<TPeerId> {
+    pub fn sign(self, sign: &(dyn Fn(&[u8]) -> Option<Signature> + Sync)) -> Option<SignedValidatorRecord<TPeerId>> {
         let data = self.serialize_to_vec();
-        let signature = secret_key.sign(&data);
+        let signature = sign(&data)?;
 
-        SignedValidatorRecord {
+        Some(SignedValidatorRecord {
             record: self,
             signature,
-        }
+        })
     }
 }
 
@@ -77,6 +87,39 @@ where
     }
 }
 
+/// Checks the signature of validator records before they are accepted from or returned by the DHT.
+pub struct ValidatorRecordValidator<TPeerId> {
+    _peer_id: PhantomData<fn() -> TPeerId>,
+}
+
+impl<TPeerId> ValidatorRecordValidator<TPeerId> {
+    pub fn new() -> Self {
+        Self { _peer_id: PhantomData }
+    }
+}
+
+impl<TPeerId> Default for ValidatorRecordValidator<TPeerId> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TPeerId> DhtRecordValidator for ValidatorRecordValidator<TPeerId>
+where
+    TPeerId: Serialize + Deserialize,
+{
+    fn validate(&self, key: &[u8], value: &[u8]) -> Option<u64> {
+        let public_key = CompressedPublicKey::deserialize_from_vec(key).ok()?.uncompress().ok()?;
+        let record = SignedValidatorRecord::<TPeerId>::deserialize_from_vec(value).ok()?;
+
+        if record.verify(&public_key) {
+            Some(record.record.timestamp)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct State<TPeerId> {
     validator_keys: Vec<CompressedPublicKey>,
@@ -96,9 +139,11 @@ where
 impl<N> ValidatorNetworkImpl<N>
 where
     N: Network,
-    <<N as Network>::PeerType as Peer>::Id: Send + Sync + Serialize + Deserialize + Clone,
+    <<N as Network>::PeerType as Peer>::Id: Send + Sync + Serialize + Deserialize + Clone + 'static,
 {
     pub fn new(network: Arc<N>) -> Self {
+        network.set_dht_record_validator(Arc::new(ValidatorRecordValidator::<PeerId<N>>::new()));
+
         Self {
             network,
             state: Mutex::new(State {
@@ -154,8 +199,9 @@ where
 impl<N> ValidatorNetwork for ValidatorNetworkImpl<N>
 where
     N: Network,
-    <<N as Network>::PeerType as Peer>::Id: Send + Sync + Serialize + Deserialize + Clone,
+    <<N as Network>::PeerType as Peer>::Id: Send + Sync + Serialize + Deserialize + Clone + 'static,
     <N as Network>::Error: Send,
+    <N as Network>::PubsubId: Send + 'static,
 {
     type Error = NetworkError<<N as Network>::Error>;
     type PeerType = <N as Network>::PeerType;
@@ -218,14 +264,25 @@ where
         Ok(self.network.subscribe(topic).await?)
     }
 
+    async fn validate_message(&self, id: Self::PubsubId) -> Result<bool, Self::Error> {
+        Ok(self.network.validate_message(id).await?)
+    }
+
     fn cache<M: Message>(&self, _buffer_size: usize, _lifetime: Duration) {
         unimplemented!()
     }
 
-    async fn set_public_key(&self, public_key: &CompressedPublicKey, secret_key: &SecretKey) -> Result<(), Self::Error> {
+    async fn set_public_key(
+        &self,
+        public_key: &CompressedPublicKey,
+        sign: &(dyn Fn(&[u8]) -> Option<Signature> + Sync),
+    ) -> Result<(), Self::Error> {
         let peer_id = self.network.get_local_peer_id().clone();
-        let record = ValidatorRecord::new(peer_id);
-        self.network.dht_put(public_key, &record.sign(secret_key)).await?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let record = ValidatorRecord::new(peer_id, timestamp)
+            .sign(sign)
+            .ok_or(NetworkError::Signing)?;
+        self.network.dht_put(public_key, &record).await?;
 
         Ok(())
     }
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use beserial::{Deserialize, Serialize};
 use nimiq_block_albatross::{MultiSignature, TendermintVote};
-use nimiq_bls::{AggregateSignature, SecretKey};
+use nimiq_bls::{AggregateSignature, Signature};
 use nimiq_collections::bitset::BitSet;
 use nimiq_hash::Blake2bHash;
 
@@ -15,9 +15,10 @@ pub struct TendermintContribution {
 }
 
 impl TendermintContribution {
-    pub(crate) fn from_vote(vote: TendermintVote, secret_key: &SecretKey, validator_slots: Vec<u16>) -> Self {
-        // sign the hash
-        let signature = AggregateSignature::from_signatures(&[secret_key.sign(&vote).multiply(validator_slots.len() as u16)]);
+    /// Creates the contribution of a single validator from its `signature` of the `vote`.
+    pub(crate) fn from_vote(vote: TendermintVote, signature: &Signature, validator_slots: Vec<u16>) -> Self {
+        // count the signature once per slot
+        let signature = AggregateSignature::from_signatures(&[signature.multiply(validator_slots.len() as u16)]);
 
         // get the slots of the validator ad insert them into the bitset
         let mut signers = BitSet::new();
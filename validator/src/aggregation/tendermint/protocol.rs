@@ -2,6 +2,7 @@ use parking_lot::RwLock;
 use std::sync::Arc;
 
 use nimiq_block_albatross::TendermintIdentifier;
+use nimiq_handel::config::ScoringConfig;
 use nimiq_handel::evaluator::WeightedVote;
 use nimiq_handel::partitioner::BinomialPartitioner;
 use nimiq_handel::protocol::Protocol;
@@ -24,14 +25,27 @@ pub(crate) struct TendermintAggregationProtocol {
 }
 
 impl TendermintAggregationProtocol {
-    pub(super) fn new(validators: Arc<ValidatorRegistry>, node_id: usize, threshold: usize, id: TendermintIdentifier, validator_merkle_root: Vec<u8>) -> Self {
+    pub(super) fn new(
+        validators: Arc<ValidatorRegistry>,
+        node_id: usize,
+        threshold: usize,
+        id: TendermintIdentifier,
+        validator_merkle_root: Vec<u8>,
+        scoring: ScoringConfig,
+    ) -> Self {
         let partitioner = Arc::new(BinomialPartitioner::new(node_id, validators.len()));
 
         let store = Arc::new(RwLock::new(ReplaceStore::<BinomialPartitioner, <Self as Protocol>::Contribution>::new(
             Arc::clone(&partitioner),
         )));
 
-        let evaluator = Arc::new(WeightedVote::new(Arc::clone(&store), validators.clone(), Arc::clone(&partitioner), threshold));
+        let evaluator = Arc::new(WeightedVote::new(
+            Arc::clone(&store),
+            validators.clone(),
+            Arc::clone(&partitioner),
+            threshold,
+            scoring,
+        ));
 
         let verifier = Arc::new(TendermintVerifier::new(validators.clone(), id, validator_merkle_root));
 
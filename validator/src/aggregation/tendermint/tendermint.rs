@@ -13,8 +13,8 @@ use futures::task::{Context, Poll};
 
 use futures_locks::RwLock;
 
+use nimiq_block_production_albatross::Signer;
 use nimiq_block_albatross::{create_pk_tree_root, MultiSignature, TendermintIdentifier, TendermintStep, TendermintVote};
-use nimiq_bls::SecretKey;
 use nimiq_collections::bitset::BitSet;
 use nimiq_hash::Blake2bHash;
 use nimiq_primitives::policy;
@@ -29,6 +29,8 @@ use nimiq_handel::update::{LevelUpdate, LevelUpdateMessage};
 use nimiq_tendermint::{AggregationResult, TendermintError};
 use nimiq_validator_network::ValidatorNetwork;
 
+use crate::signer::sign_blocking;
+
 use super::super::network_sink::NetworkSink;
 use super::super::registry::ValidatorRegistry;
 
@@ -92,6 +94,8 @@ impl<N: ValidatorNetwork> TendermintAggregations<N> {
         // TODO: TendermintAggregationEvent
         if !self.aggregation_descriptors.contains_key(&(id.round_number, id.step)) {
             debug!("starting aggregation for {:?}", &id);
+            let config = Config::default();
+
             // crate the correct protocol instance
             let protocol = TendermintAggregationProtocol::new(
                 self.validator_registry.clone(),
@@ -99,12 +103,13 @@ impl<N: ValidatorNetwork> TendermintAggregations<N> {
                 1, // To be removed
                 id.clone(),
                 validator_merkle_root,
+                config.scoring.clone(),
             );
 
             let (sender, receiver) = mpsc::unbounded_channel::<LevelUpdate<TendermintContribution>>();
 
             // create the aggregation
-            let aggregation = Aggregation::new(protocol, id.clone(), Config::default(), own_contribution, receiver.boxed(), output_sink);
+            let aggregation = Aggregation::new(protocol, id.clone(), config, own_contribution, receiver.boxed(), output_sink);
 
             // create the stream closer and wrap in Arc so it can be shared borrow
             let stream_closer = Arc::new(AtomicBool::new(true));
@@ -224,7 +229,7 @@ pub struct HandelTendermintAdapter<N: ValidatorNetwork> {
     pending_new_round: RwLock<Option<u32>>,
     validator_merkle_root: Vec<u8>,
     block_height: u32,
-    secret_key: SecretKey,
+    signer: Arc<dyn Signer>,
     validator_id: u16,
     validator_registry: Arc<ValidatorRegistry>,
     network: Arc<N>,
@@ -233,7 +238,13 @@ pub struct HandelTendermintAdapter<N: ValidatorNetwork> {
 
 impl<N: ValidatorNetwork + 'static> HandelTendermintAdapter<N>
 where <<N as ValidatorNetwork>::PeerType as network_interface::peer::Peer>::Id: 'static {
-    pub fn new(validator_id: u16, active_validators: ValidatorSlots, block_height: u32, network: Arc<N>, secret_key: SecretKey) -> Self {
+    pub fn new(
+        validator_id: u16,
+        active_validators: ValidatorSlots,
+        block_height: u32,
+        network: Arc<N>,
+        signer: Arc<dyn Signer>,
+    ) -> Self {
         let validator_merkle_root = create_pk_tree_root(&active_validators);
 
         // the input stream is all levelUpdateMessages concerning a TendemrintContribution and TendemrintIdentifier.
@@ -259,7 +270,7 @@ where <<N as ValidatorNetwork>::PeerType as network_interface::peer::Peer>::Id:
             pending_new_round: pending_new_round.clone(),
             validator_merkle_root,
             block_height,
-            secret_key,
+            signer,
             validator_id,
             validator_registry: validator_registry.clone(),
             network,
@@ -397,6 +408,32 @@ where <<N as ValidatorNetwork>::PeerType as network_interface::peer::Peer>::Id:
         proposal_hash: Option<Blake2bHash>,
     ) -> Result<AggregationResult<MultiSignature>, TendermintError> {
         let step = step.into();
+
+        // Assemble identifier from availablle information
+        let id = TendermintIdentifier {
+            block_number: self.block_height,
+            round_number: round,
+            step,
+        };
+
+        // Construct the vote so it can be hashed and signed
+        let vote = TendermintVote {
+            proposal_hash: proposal_hash.clone(),
+            id: id.clone(),
+            validator_merkle_root: self.validator_merkle_root.clone(),
+        };
+
+        // The signer refuses to sign a vote which conflicts with one that we signed before, e.g. before a restart.
+        let signature = sign_blocking(&self.signer, {
+            let vote = vote.clone();
+            move |signer| signer.sign_tendermint_vote(&vote)
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to sign Tendermint vote: {}", e);
+            TendermintError::AggregationError
+        })?;
+
         // make sure that there is no currently ongoing aggregation from a previous call to `broadcast_and_aggregate` which has not yet been awaited.
         // if there is none make sure to set this one with the same lock to prevent a race condition
         let mut aggregate_receiver = {
@@ -418,22 +455,8 @@ where <<N as ValidatorNetwork>::PeerType as network_interface::peer::Peer>::Id:
             }
         };
 
-        // Assemble identifier from availablle information
-        let id = TendermintIdentifier {
-            block_number: self.block_height,
-            round_number: round,
-            step,
-        };
-
-        // Construct the vote so it can be hashed and signed
-        let vote = TendermintVote {
-            proposal_hash: proposal_hash.clone(),
-            id: id.clone(),
-            validator_merkle_root: self.validator_merkle_root.clone(),
-        };
-
         // Create the signed contribution of this validator
-        let own_contribution = TendermintContribution::from_vote(vote, &self.secret_key, self.validator_registry.get_slots(self.validator_id));
+        let own_contribution = TendermintContribution::from_vote(vote, &signature, self.validator_registry.get_slots(self.validator_id));
 
         let output_sink = Box::new(NetworkSink::<LevelUpdateMessage<TendermintContribution, TendermintIdentifier>, N>::new(
             self.network.clone(),
@@ -9,7 +9,7 @@ use beserial::WriteBytesExt;
 use block_albatross::{MultiSignature, SignedViewChange, ViewChange, ViewChangeProof};
 use collections::BitSet;
 use handel::aggregation::Aggregation;
-use handel::config::Config;
+use handel::config::{Config, ScoringConfig};
 use handel::contribution::AggregatableContribution;
 use handel::evaluator::WeightedVote;
 use handel::identity::WeightRegistry;
@@ -40,7 +40,7 @@ struct ViewChangeAggregationProtocol {
 }
 
 impl ViewChangeAggregationProtocol {
-    pub fn new(validators: ValidatorSlots, node_id: usize, threshold: usize, message_hash: Blake2sHash) -> Self {
+    pub fn new(validators: ValidatorSlots, node_id: usize, threshold: usize, message_hash: Blake2sHash, scoring: ScoringConfig) -> Self {
         let partitioner = Arc::new(BinomialPartitioner::new(node_id, validators.len()));
 
         let store = Arc::new(RwLock::new(ReplaceStore::<BinomialPartitioner, MultiSignature>::new(Arc::clone(&partitioner))));
@@ -52,6 +52,7 @@ impl ViewChangeAggregationProtocol {
             Arc::clone(&registry),
             Arc::clone(&partitioner),
             threshold,
+            scoring,
         ));
 
         ViewChangeAggregationProtocol {
@@ -115,7 +116,15 @@ impl<N: ValidatorNetwork + 'static> ViewChangeAggregation<N> {
         // TODO expose this somewehere else so we don't need to clone here.
         let weights = ValidatorRegistry::new(active_validators.clone());
 
-        let protocol = ViewChangeAggregationProtocol::new(active_validators.clone(), validator_id as usize, policy::TWO_THIRD_SLOTS as usize, message_hash);
+        let config = Config::default();
+
+        let protocol = ViewChangeAggregationProtocol::new(
+            active_validators.clone(),
+            validator_id as usize,
+            policy::TWO_THIRD_SLOTS as usize,
+            message_hash,
+            config.scoring.clone(),
+        );
 
         let slots = active_validators.get_slots(validator_id);
 
@@ -137,7 +146,7 @@ impl<N: ValidatorNetwork + 'static> ViewChangeAggregation<N> {
         let mut aggregation = Aggregation::new(
             protocol,
             view_change.message,
-            Config::default(),
+            config,
             own_contribution,
             Box::pin(
                 network
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use block_albatross::{MicroHeader, UpgradeSignal};
+use primitives::coin::Coin;
+
+use crate::signing_history::SigningHistory;
+
+#[derive(Clone, Debug)]
+pub struct ValidatorConfig {
+    /// Whether an `UnparkValidator` transaction is sent automatically when our validator gets parked after a slash.
+    /// This requires the wallet key of the validator.
+    pub automatic_unpark: bool,
+
+    /// Fee of the automatically sent `UnparkValidator` transactions.
+    pub unpark_fee: Coin,
+
+    /// Signing history from another machine that this validator ran on before. It is imported before the validator
+    /// signs anything.
+    pub signing_history: Option<SigningHistory>,
+
+    /// Arbitrary data that we include in the extra data of our micro blocks, after the upgrade signal.
+    pub extra_data: Vec<u8>,
+
+    /// Protocol version that we signal readiness for in our micro blocks.
+    pub upgrade_signal: Option<u16>,
+
+    /// Tendermint's timeout in round 0. See https://arxiv.org/abs/1807.04938v3 for more information.
+    pub tendermint_timeout_init: Duration,
+
+    /// Increase of Tendermint's timeout per round.
+    pub tendermint_timeout_delta: Duration,
+
+    /// Time that we wait for a micro block before we start a view change.
+    pub view_change_delay: Duration,
+}
+
+impl ValidatorConfig {
+    /// Returns the extra data of our micro blocks, i.e. the upgrade signal followed by `extra_data`. Returns `None` if
+    /// it doesn't fit into a micro block header.
+    pub fn micro_block_extra_data(&self) -> Option<Vec<u8>> {
+        let extra_data = match self.upgrade_signal {
+            Some(version) => UpgradeSignal::new(version).to_extra_data(&self.extra_data),
+            None => self.extra_data.clone(),
+        };
+
+        if extra_data.len() > MicroHeader::MAX_EXTRA_DATA_SIZE {
+            return None;
+        }
+        Some(extra_data)
+    }
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            automatic_unpark: true,
+            unpark_fee: Coin::ZERO,
+            signing_history: None,
+            extra_data: vec![],
+            upgrade_signal: None,
+            tendermint_timeout_init: Duration::from_secs(1),
+            tendermint_timeout_delta: Duration::from_secs(1),
+            view_change_delay: Duration::from_secs(10),
+        }
+    }
+}
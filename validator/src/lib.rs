@@ -5,6 +5,7 @@
 extern crate log;
 #[macro_use]
 extern crate beserial_derive;
+extern crate nimiq_account as account;
 extern crate nimiq_block_albatross as block_albatross;
 extern crate nimiq_block_production_albatross as block_production_albatross;
 extern crate nimiq_blockchain_albatross as blockchain_albatross;
@@ -23,12 +24,18 @@ extern crate nimiq_network_albatross as network;
 extern crate nimiq_network_interface as network_interface;
 extern crate nimiq_primitives as primitives;
 extern crate nimiq_tendermint as tendermint;
+extern crate nimiq_transaction as transaction;
+extern crate nimiq_transaction_builder as transaction_builder;
 extern crate nimiq_utils as utils;
 extern crate nimiq_vrf as vrf;
 
 mod aggregation;
+pub mod config;
 mod r#macro;
 mod micro;
+pub mod signer;
+pub mod signing_history;
 mod slash;
+pub mod status;
 mod tendermint_outside_deps;
 pub mod validator;
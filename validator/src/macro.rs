@@ -1,19 +1,19 @@
 use std::io;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::stream::{BoxStream, Stream, StreamExt};
 use futures::task::{Context, Poll};
 
 use beserial::{Deserialize, Serialize};
 use nimiq_block_albatross::{MacroBlock, MacroHeader, MultiSignature, TendermintStep};
-use nimiq_block_production_albatross::BlockProducer;
+use nimiq_block_production_albatross::{BlockProducer, Signer};
 use nimiq_blockchain_albatross::blockchain::Blockchain;
 use nimiq_database::{FromDatabaseValue, IntoDatabaseValue};
 use nimiq_tendermint::{Checkpoint, Step, TendermintOutsideDeps, TendermintReturn, TendermintState};
 use nimiq_validator_network::ValidatorNetwork;
 
-
 use crate::tendermint_outside_deps::TendermintInterface;
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -56,9 +56,11 @@ impl ProduceMacroBlock {
         blockchain: Arc<Blockchain>,
         network: Arc<TValidatorNetwork>,
         block_producer: BlockProducer,
-        signing_key: bls::KeyPair, // probably SecretKey is enough (it is for the handel part of it).
+        signer: Arc<dyn Signer>,
         validator_id: u16,
         state: Option<PersistedMacroState<TValidatorNetwork>>,
+        timeout_init: Duration,
+        timeout_delta: Duration,
     ) -> Self {
         // get validators for current epoch
         let active_validators = blockchain.current_validators().clone();
@@ -66,13 +68,15 @@ impl ProduceMacroBlock {
         // create the TendermintOutsideDeps instance
         // Replace here with the actual OutSide Deps instead of the Mocked ones.
         let deps = TendermintInterface::new(
-            signing_key,
+            signer,
             validator_id,
             network,
             active_validators,
             blockchain.clone(),
             block_producer,
             blockchain.head().block_number() + 1,
+            timeout_init,
+            timeout_delta,
         );
 
         let state_opt = state.map(|s| TendermintState {
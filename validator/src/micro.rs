@@ -2,13 +2,13 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
-use futures::future::BoxFuture;
+use futures::future::{self, BoxFuture};
 use futures::task::{Context, Poll};
 use futures::{ready, FutureExt, Stream};
 use tokio::time;
 
-use block_albatross::{ForkProof, MicroBlock, SignedViewChange, ViewChange, ViewChangeProof};
-use block_production_albatross::BlockProducer;
+use block_albatross::{ForkProof, MicroBlock, MicroJustification, SignedViewChange, ViewChange, ViewChangeProof};
+use block_production_albatross::{BlockProducer, Signer};
 use blockchain_albatross::Blockchain;
 use mempool::Mempool;
 use nimiq_validator_network::ValidatorNetwork;
@@ -16,6 +16,7 @@ use utils::time::systemtime_to_timestamp;
 use vrf::VrfSeed;
 
 use crate::aggregation::view_change::ViewChangeAggregation;
+use crate::signer::sign_blocking;
 
 pub(crate) enum ProduceMicroBlockEvent {
     MicroBlock(MicroBlock),
@@ -27,12 +28,13 @@ struct NextProduceMicroBlockEvent<TValidatorNetwork> {
     blockchain: Arc<Blockchain>,
     mempool: Arc<Mempool>,
     network: Arc<TValidatorNetwork>,
-    signing_key: bls::KeyPair,
+    signer: Arc<dyn Signer>,
     validator_id: u16,
     fork_proofs: Vec<ForkProof>,
     view_number: u32,
     view_change_proof: Option<ViewChangeProof>,
     view_change_delay: Duration,
+    extra_data: Vec<u8>,
     block_number: u32,
     prev_seed: VrfSeed,
 }
@@ -42,12 +44,13 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         blockchain: Arc<Blockchain>,
         mempool: Arc<Mempool>,
         network: Arc<TValidatorNetwork>,
-        signing_key: bls::KeyPair,
+        signer: Arc<dyn Signer>,
         validator_id: u16,
         fork_proofs: Vec<ForkProof>,
         view_number: u32,
         view_change_proof: Option<ViewChangeProof>,
         view_change_delay: Duration,
+        extra_data: Vec<u8>,
     ) -> Self {
         let (block_number, prev_seed) = {
             let head = blockchain.head();
@@ -58,73 +61,118 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
             blockchain,
             mempool,
             network,
-            signing_key,
+            signer,
             validator_id,
             fork_proofs,
             view_number,
             view_change_proof,
             view_change_delay,
+            extra_data,
             block_number,
             prev_seed,
         }
     }
 
     async fn next(mut self) -> (ProduceMicroBlockEvent, NextProduceMicroBlockEvent<TValidatorNetwork>) {
-        let event = if self.is_our_turn() {
+        if self.is_our_turn() {
             info!("Our turn at #{}:{}, producing micro block", self.block_number, self.view_number);
-            ProduceMicroBlockEvent::MicroBlock(self.produce_micro_block())
+            if let Some(block) = self.produce_micro_block().await {
+                return (ProduceMicroBlockEvent::MicroBlock(block), self);
+            }
         } else {
             debug!("Not our turn at #{}:{}, waiting for micro block", self.block_number, self.view_number);
-            time::delay_for(self.view_change_delay).await;
-            info!(
-                "No micro block received within timeout at #{}:{}, starting view change",
-                self.block_number, self.view_number
-            );
-            let (new_view_number, view_change_proof) = self.change_view().await;
-            info!(
-                "View change completed for #{}:{}, new view is {}",
-                self.block_number, self.view_number, new_view_number
-            );
-            self.view_number = new_view_number;
-            self.view_change_proof = Some(view_change_proof.clone());
-            ProduceMicroBlockEvent::ViewChange(new_view_number, view_change_proof)
+        }
+
+        time::delay_for(self.view_change_delay).await;
+        info!(
+            "No micro block received within timeout at #{}:{}, starting view change",
+            self.block_number, self.view_number
+        );
+        let (new_view_number, view_change_proof) = match self.change_view().await {
+            Some(result) => result,
+            // We must not take part in this view change, so we can only wait for a block.
+            None => future::pending().await,
         };
-        (event, self)
+        info!(
+            "View change completed for #{}:{}, new view is {}",
+            self.block_number, self.view_number, new_view_number
+        );
+        self.view_number = new_view_number;
+        self.view_change_proof = Some(view_change_proof.clone());
+        (ProduceMicroBlockEvent::ViewChange(new_view_number, view_change_proof), self)
     }
 
     fn is_our_turn(&self) -> bool {
         let (slot, _) = self.blockchain.get_slot_owner_at(self.block_number, self.view_number, None);
-        &self.signing_key.public_key.compress() == slot.validator_slot.public_key().compressed()
+        &self.signer.public_key().compress() == slot.validator_slot.public_key().compressed()
     }
 
-    fn produce_micro_block(&self) -> MicroBlock {
-        let producer = BlockProducer::new(Arc::clone(&self.blockchain), Arc::clone(&self.mempool), self.signing_key.clone());
-
-        let _lock = self.blockchain.lock();
-        let timestamp = u64::max(self.blockchain.head().header().timestamp(), systemtime_to_timestamp(SystemTime::now()));
-        producer.next_micro_block(
-            timestamp,
-            self.view_number,
-            self.view_change_proof.clone(),
-            self.fork_proofs.clone(),
-            vec![], // TODO
-        )
+    /// Produces our micro block, unless the signer refuses to sign it, e.g. because we already signed a conflicting
+    /// one before a restart. We sign without holding the blockchain lock.
+    async fn produce_micro_block(&self) -> Option<MicroBlock> {
+        let prev_seed = self.prev_seed.clone();
+        let seed = sign_blocking(&self.signer, move |signer| signer.sign_seed(&prev_seed))
+            .await
+            .map_err(|e| error!("Failed to sign seed: {}", e))
+            .ok()?;
+
+        let mut block = {
+            let _lock = self.blockchain.lock();
+
+            // The chain might have moved on while we were signing the seed.
+            if self.blockchain.block_number() + 1 != self.block_number || self.blockchain.head().seed() != &self.prev_seed {
+                debug!("Chain head changed at #{}:{}, not producing micro block", self.block_number, self.view_number);
+                return None;
+            }
+
+            let producer = BlockProducer::new(Arc::clone(&self.blockchain), Arc::clone(&self.mempool), Arc::clone(&self.signer));
+            let timestamp = u64::max(self.blockchain.head().header().timestamp(), systemtime_to_timestamp(SystemTime::now()));
+            producer.next_unsigned_micro_block(timestamp, self.view_number, seed, self.fork_proofs.clone(), self.extra_data.clone())
+        };
+
+        let header = block.header.clone();
+        let signature = sign_blocking(&self.signer, move |signer| signer.sign_micro_header(&header))
+            .await
+            .map_err(|e| error!("Failed to produce micro block: {}", e))
+            .ok()?;
+        block.justification = Some(MicroJustification {
+            signature: signature.compress(),
+            view_change_proof: self.view_change_proof.clone(),
+        });
+
+        Some(block)
     }
 
-    async fn change_view(&self) -> (u32, ViewChangeProof) {
+    async fn change_view(&self) -> Option<(u32, ViewChangeProof)> {
         let new_view_number = self.view_number + 1;
         let view_change = ViewChange {
             block_number: self.block_number,
             new_view_number,
             prev_seed: self.prev_seed.clone(),
         };
-        let signed_view_change = SignedViewChange::from_message(view_change, &self.signing_key.secret_key, self.validator_id);
+        let signature = match sign_blocking(&self.signer, {
+            let view_change = view_change.clone();
+            move |signer| signer.sign_view_change(&view_change)
+        })
+        .await
+        {
+            Ok(signature) => signature,
+            Err(e) => {
+                error!("Failed to sign view change: {}", e);
+                return None;
+            }
+        };
+        let signed_view_change = SignedViewChange {
+            message: view_change,
+            signer_idx: self.validator_id,
+            signature,
+        };
 
         // TODO get at init time?
         let active_validators = self.blockchain.current_validators().clone();
         let view_change_proof = ViewChangeAggregation::start(signed_view_change, self.validator_id, active_validators, Arc::clone(&self.network)).await;
 
-        (new_view_number, view_change_proof)
+        Some((new_view_number, view_change_proof))
     }
 }
 
@@ -137,23 +185,25 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> ProduceMicroBlock<TValidator
         blockchain: Arc<Blockchain>,
         mempool: Arc<Mempool>,
         network: Arc<TValidatorNetwork>,
-        signing_key: bls::KeyPair,
+        signer: Arc<dyn Signer>,
         validator_id: u16,
         fork_proofs: Vec<ForkProof>,
         view_number: u32,
         view_change_proof: Option<ViewChangeProof>,
         view_change_delay: Duration,
+        extra_data: Vec<u8>,
     ) -> Self {
         let next_event = NextProduceMicroBlockEvent::new(
             blockchain,
             mempool,
             network,
-            signing_key,
+            signer,
             validator_id,
             fork_proofs,
             view_number,
             view_change_proof,
             view_change_delay,
+            extra_data,
         )
         .next()
         .boxed();
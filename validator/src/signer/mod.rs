@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use tokio::task;
+
+use block_albatross::{MicroHeader, TendermintProposal, TendermintStep, TendermintVote, ViewChange};
+use bls::{PublicKey, Signature};
+use transaction::account::staking_contract::IncomingStakingTransactionData;
+use transaction::Transaction;
+use vrf::VrfSeed;
+
+use crate::signing_history::SigningGuard;
+
+pub use block_production_albatross::{Signer, SignerError};
+
+mod protocol;
+pub mod remote;
+pub mod server;
+mod transport;
+
+pub use self::remote::RemoteSigner;
+pub use self::server::SignerServer;
+pub use self::transport::SignerAddress;
+
+/// Signs on the blocking thread pool. A `RemoteSigner` waits for its signing process, which must neither stall the
+/// async workers nor happen while we hold the blockchain lock.
+pub(crate) async fn sign_blocking<T, F>(signer: &Arc<dyn Signer>, sign: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn Signer) -> T + Send + 'static,
+{
+    let signer = Arc::clone(signer);
+    task::spawn_blocking(move || sign(signer.as_ref()))
+        .await
+        .expect("Signing task panicked")
+}
+
+/// Wraps a signer and refuses to sign anything that conflicts with the signing history or that a validator never
+/// needs to sign. The validator signs through it, and so does the `SignerServer` for its remote validators.
+pub struct GuardedSigner<S> {
+    signer: S,
+    guard: Arc<SigningGuard>,
+}
+
+impl<S: Signer> GuardedSigner<S> {
+    /// Maximum length of a serialized peer ID in a validator record.
+    const MAX_PEER_ID_SIZE: usize = 64;
+
+    pub fn new(signer: S, guard: Arc<SigningGuard>) -> Self {
+        Self { signer, guard }
+    }
+
+    pub fn signing_guard(&self) -> &Arc<SigningGuard> {
+        &self.guard
+    }
+
+    /// A validator record consists of a peer ID with a `u8` length prefix and a `u64` timestamp.
+    fn is_validator_record(record: &[u8]) -> bool {
+        match record.first() {
+            Some(&len) => len as usize <= Self::MAX_PEER_ID_SIZE && record.len() == 1 + len as usize + 8,
+            None => false,
+        }
+    }
+
+    fn is_own_unpark_transaction(&self, transaction: &Transaction) -> bool {
+        match IncomingStakingTransactionData::parse(transaction) {
+            Ok(IncomingStakingTransactionData::UnparkValidator { validator_key, signature }) => {
+                validator_key == self.signer.public_key().compress() && signature == Default::default()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<S: Signer> Signer for GuardedSigner<S> {
+    fn public_key(&self) -> &PublicKey {
+        self.signer.public_key()
+    }
+
+    fn sign_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        self.signer.sign_seed(prev_seed)
+    }
+
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Signature, SignerError> {
+        self.guard
+            .record_micro_block(header)
+            .map_err(|e| SignerError::Refused(e.to_string()))?;
+        self.signer.sign_micro_header(header)
+    }
+
+    fn sign_view_change(&self, view_change: &ViewChange) -> Result<Signature, SignerError> {
+        self.guard
+            .record_view_change(view_change)
+            .map_err(|e| SignerError::Refused(e.to_string()))?;
+        self.signer.sign_view_change(view_change)
+    }
+
+    fn sign_tendermint_proposal(&self, proposal: &TendermintProposal) -> Result<Signature, SignerError> {
+        self.guard
+            .record_tendermint_proposal(proposal)
+            .map_err(|e| SignerError::Refused(e.to_string()))?;
+        self.signer.sign_tendermint_proposal(proposal)
+    }
+
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<Signature, SignerError> {
+        if vote.id.step == TendermintStep::Propose {
+            return Err(SignerError::InvalidMessage);
+        }
+        self.guard
+            .record_tendermint_vote(vote)
+            .map_err(|e| SignerError::Refused(e.to_string()))?;
+        self.signer.sign_tendermint_vote(vote)
+    }
+
+    fn sign_validator_record(&self, record: &[u8]) -> Result<Signature, SignerError> {
+        // The record is signed without a prefix, so it must not be mistaken for any other message.
+        if !Self::is_validator_record(record) {
+            return Err(SignerError::InvalidMessage);
+        }
+        self.signer.sign_validator_record(record)
+    }
+
+    fn sign_unpark_transaction(&self, transaction: &Transaction) -> Result<Signature, SignerError> {
+        if !self.is_own_unpark_transaction(transaction) {
+            return Err(SignerError::InvalidMessage);
+        }
+        self.signer.sign_unpark_transaction(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use block_albatross::TendermintIdentifier;
+    use bls::KeyPair;
+    use database::volatile::VolatileEnvironment;
+    use hash::Blake2bHash;
+    use utils::key_rng::SecureGenerate;
+
+    use super::*;
+
+    fn vote(step: TendermintStep, round_number: u32, proposal: u8) -> TendermintVote {
+        TendermintVote {
+            proposal_hash: Some(Blake2bHash::from([proposal; 32])),
+            id: TendermintIdentifier {
+                block_number: 32,
+                round_number,
+                step,
+            },
+            validator_merkle_root: vec![],
+        }
+    }
+
+    #[test]
+    fn it_refuses_unexpected_messages() {
+        let key = KeyPair::generate(&mut StdRng::seed_from_u64(0));
+        let guard = Arc::new(SigningGuard::open(VolatileEnvironment::new(10).unwrap()));
+        let signer = GuardedSigner::new(key, guard);
+
+        let mut record = vec![3, 1, 2, 3];
+        record.extend_from_slice(&[0; 8]);
+        assert!(signer.sign_validator_record(&record).is_ok());
+        assert_eq!(signer.sign_validator_record(&record[..11]).unwrap_err(), SignerError::InvalidMessage);
+        assert_eq!(signer.sign_validator_record(&[]).unwrap_err(), SignerError::InvalidMessage);
+
+        assert_eq!(
+            signer.sign_tendermint_vote(&vote(TendermintStep::Propose, 0, 1)).unwrap_err(),
+            SignerError::InvalidMessage
+        );
+        assert!(signer.sign_tendermint_vote(&vote(TendermintStep::PreVote, 1, 1)).is_ok());
+        assert!(signer.sign_tendermint_vote(&vote(TendermintStep::PreVote, 1, 1)).is_ok());
+        assert!(matches!(
+            signer.sign_tendermint_vote(&vote(TendermintStep::PreVote, 1, 2)),
+            Err(SignerError::Refused(_))
+        ));
+        assert!(signer.sign_tendermint_vote(&vote(TendermintStep::PreCommit, 1, 2)).is_ok());
+    }
+}
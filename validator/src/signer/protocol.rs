@@ -0,0 +1,225 @@
+//! The protocol between a `RemoteSigner` and a `SignerServer`. The client sends a request and waits for its response
+//! before it sends the next one. Each message is prefixed with its length as `u32`.
+
+use std::io::{Read, Write};
+
+use beserial::{Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, SerializeWithLength, SerializingError, WriteBytesExt};
+use block_albatross::{MicroHeader, TendermintProposal, TendermintVote, ViewChange};
+use bls::{PublicKey, Signature};
+use transaction::Transaction;
+use vrf::VrfSeed;
+
+use super::SignerError;
+
+/// Maximum size of a message. The largest request is an unpark transaction.
+const MAX_MESSAGE_SIZE: u32 = 16 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+enum RequestType {
+    PublicKey = 0,
+    Seed = 1,
+    MicroHeader = 2,
+    ViewChange = 3,
+    TendermintProposal = 4,
+    TendermintVote = 5,
+    ValidatorRecord = 6,
+    UnparkTransaction = 7,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum SignerRequest {
+    PublicKey,
+    Seed(VrfSeed),
+    MicroHeader(MicroHeader),
+    ViewChange(ViewChange),
+    TendermintProposal(TendermintProposal),
+    TendermintVote(TendermintVote),
+    ValidatorRecord(Vec<u8>),
+    UnparkTransaction(Transaction),
+}
+
+impl Serialize for SignerRequest {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let size = match self {
+            SignerRequest::PublicKey => RequestType::PublicKey.serialize(writer)?,
+            SignerRequest::Seed(seed) => RequestType::Seed.serialize(writer)? + seed.serialize(writer)?,
+            SignerRequest::MicroHeader(header) => RequestType::MicroHeader.serialize(writer)? + header.serialize(writer)?,
+            SignerRequest::ViewChange(view_change) => RequestType::ViewChange.serialize(writer)? + view_change.serialize(writer)?,
+            SignerRequest::TendermintProposal(proposal) => {
+                RequestType::TendermintProposal.serialize(writer)? + proposal.serialize(writer)?
+            }
+            SignerRequest::TendermintVote(vote) => {
+                RequestType::TendermintVote.serialize(writer)?
+                    + vote.proposal_hash.serialize(writer)?
+                    + vote.id.serialize(writer)?
+                    + SerializeWithLength::serialize::<u16, _>(&vote.validator_merkle_root, writer)?
+            }
+            SignerRequest::ValidatorRecord(record) => {
+                RequestType::ValidatorRecord.serialize(writer)? + SerializeWithLength::serialize::<u16, _>(record, writer)?
+            }
+            SignerRequest::UnparkTransaction(transaction) => {
+                RequestType::UnparkTransaction.serialize(writer)? + transaction.serialize(writer)?
+            }
+        };
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            SignerRequest::PublicKey => 0,
+            SignerRequest::Seed(seed) => seed.serialized_size(),
+            SignerRequest::MicroHeader(header) => header.serialized_size(),
+            SignerRequest::ViewChange(view_change) => view_change.serialized_size(),
+            SignerRequest::TendermintProposal(proposal) => proposal.serialized_size(),
+            SignerRequest::TendermintVote(vote) => {
+                vote.proposal_hash.serialized_size()
+                    + vote.id.serialized_size()
+                    + SerializeWithLength::serialized_size::<u16>(&vote.validator_merkle_root)
+            }
+            SignerRequest::ValidatorRecord(record) => SerializeWithLength::serialized_size::<u16>(record),
+            SignerRequest::UnparkTransaction(transaction) => transaction.serialized_size(),
+        }
+    }
+}
+
+impl Deserialize for SignerRequest {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let request_type: RequestType = Deserialize::deserialize(reader)?;
+        Ok(match request_type {
+            RequestType::PublicKey => SignerRequest::PublicKey,
+            RequestType::Seed => SignerRequest::Seed(Deserialize::deserialize(reader)?),
+            RequestType::MicroHeader => SignerRequest::MicroHeader(Deserialize::deserialize(reader)?),
+            RequestType::ViewChange => SignerRequest::ViewChange(Deserialize::deserialize(reader)?),
+            RequestType::TendermintProposal => SignerRequest::TendermintProposal(Deserialize::deserialize(reader)?),
+            RequestType::TendermintVote => SignerRequest::TendermintVote(TendermintVote {
+                proposal_hash: Deserialize::deserialize(reader)?,
+                id: Deserialize::deserialize(reader)?,
+                validator_merkle_root: DeserializeWithLength::deserialize::<u16, _>(reader)?,
+            }),
+            RequestType::ValidatorRecord => SignerRequest::ValidatorRecord(DeserializeWithLength::deserialize::<u16, _>(reader)?),
+            RequestType::UnparkTransaction => SignerRequest::UnparkTransaction(Deserialize::deserialize(reader)?),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+enum ResponseType {
+    PublicKey = 0,
+    Seed = 1,
+    Signature = 2,
+    Refused = 3,
+    InvalidMessage = 4,
+    Unavailable = 5,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum SignerResponse {
+    PublicKey(PublicKey),
+    Seed(VrfSeed),
+    Signature(Signature),
+    Error(SignerError),
+}
+
+impl Serialize for SignerResponse {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let size = match self {
+            SignerResponse::PublicKey(public_key) => ResponseType::PublicKey.serialize(writer)? + public_key.serialize(writer)?,
+            SignerResponse::Seed(seed) => ResponseType::Seed.serialize(writer)? + seed.serialize(writer)?,
+            SignerResponse::Signature(signature) => ResponseType::Signature.serialize(writer)? + signature.serialize(writer)?,
+            SignerResponse::Error(SignerError::Refused(reason)) => {
+                ResponseType::Refused.serialize(writer)? + SerializeWithLength::serialize::<u16, _>(reason, writer)?
+            }
+            SignerResponse::Error(SignerError::InvalidMessage) => ResponseType::InvalidMessage.serialize(writer)?,
+            SignerResponse::Error(SignerError::Unavailable(reason)) => {
+                ResponseType::Unavailable.serialize(writer)? + SerializeWithLength::serialize::<u16, _>(reason, writer)?
+            }
+        };
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            SignerResponse::PublicKey(public_key) => public_key.serialized_size(),
+            SignerResponse::Seed(seed) => seed.serialized_size(),
+            SignerResponse::Signature(signature) => signature.serialized_size(),
+            SignerResponse::Error(SignerError::Refused(reason)) => SerializeWithLength::serialized_size::<u16>(reason),
+            SignerResponse::Error(SignerError::InvalidMessage) => 0,
+            SignerResponse::Error(SignerError::Unavailable(reason)) => SerializeWithLength::serialized_size::<u16>(reason),
+        }
+    }
+}
+
+impl Deserialize for SignerResponse {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let response_type: ResponseType = Deserialize::deserialize(reader)?;
+        Ok(match response_type {
+            ResponseType::PublicKey => SignerResponse::PublicKey(Deserialize::deserialize(reader)?),
+            ResponseType::Seed => SignerResponse::Seed(Deserialize::deserialize(reader)?),
+            ResponseType::Signature => SignerResponse::Signature(Deserialize::deserialize(reader)?),
+            ResponseType::Refused => {
+                SignerResponse::Error(SignerError::Refused(DeserializeWithLength::deserialize::<u16, _>(reader)?))
+            }
+            ResponseType::InvalidMessage => SignerResponse::Error(SignerError::InvalidMessage),
+            ResponseType::Unavailable => {
+                SignerResponse::Error(SignerError::Unavailable(DeserializeWithLength::deserialize::<u16, _>(reader)?))
+            }
+        })
+    }
+}
+
+pub(crate) fn write_message<W: Write, M: Serialize>(writer: &mut W, message: &M) -> Result<(), SerializingError> {
+    let data = message.serialize_to_vec();
+    Serialize::serialize(&(data.len() as u32), writer)?;
+    writer.write_all(&data)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn read_message<R: Read, M: Deserialize>(reader: &mut R) -> Result<M, SerializingError> {
+    let size: u32 = Deserialize::deserialize(reader)?;
+    if size > MAX_MESSAGE_SIZE {
+        return Err(SerializingError::LimitExceeded);
+    }
+
+    let mut data = vec![0; size as usize];
+    reader.read_exact(&mut data)?;
+    Deserialize::deserialize_from_vec(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use block_albatross::{TendermintIdentifier, TendermintStep};
+    use hash::Blake2bHash;
+
+    use super::*;
+
+    #[test]
+    fn it_frames_messages() {
+        let vote = TendermintVote {
+            proposal_hash: Some(Blake2bHash::from([1; 32])),
+            id: TendermintIdentifier {
+                block_number: 32,
+                round_number: 2,
+                step: TendermintStep::PreCommit,
+            },
+            validator_merkle_root: vec![1, 2, 3],
+        };
+
+        let mut buf = Vec::new();
+        write_message(&mut buf, &SignerRequest::TendermintVote(vote.clone())).unwrap();
+        write_message(&mut buf, &SignerResponse::Error(SignerError::Refused("conflict".to_string()))).unwrap();
+
+        let mut reader = &buf[..];
+        match read_message(&mut reader).unwrap() {
+            SignerRequest::TendermintVote(received) => assert_eq!(received, vote),
+            request => panic!("Unexpected request: {:?}", request),
+        }
+        match read_message(&mut reader).unwrap() {
+            SignerResponse::Error(error) => assert_eq!(error, SignerError::Refused("conflict".to_string())),
+            response => panic!("Unexpected response: {:?}", response),
+        }
+        assert!(reader.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use block_albatross::{MicroHeader, TendermintProposal, TendermintVote, ViewChange};
+use bls::{PublicKey, Signature};
+use transaction::Transaction;
+use vrf::VrfSeed;
+
+use super::protocol::{read_message, write_message, SignerRequest, SignerResponse};
+use super::transport::{Connection, SignerAddress};
+use super::{Signer, SignerError};
+
+/// Signs with a validator key that is kept by a `SignerServer` in a separate process. The server checks every message
+/// against its own signing history, so it never signs conflicting messages, even if this validator is compromised or
+/// runs twice.
+pub struct RemoteSigner {
+    address: SignerAddress,
+    public_key: PublicKey,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl RemoteSigner {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Connects to the signer at `address` and fetches the public key that it signs for.
+    pub fn connect(address: SignerAddress) -> Result<Self, SignerError> {
+        let mut connection = Connection::connect(&address, Self::TIMEOUT).map_err(|e| SignerError::Unavailable(e.to_string()))?;
+        let public_key = match Self::exchange(&mut connection, &SignerRequest::PublicKey)? {
+            SignerResponse::PublicKey(public_key) => public_key,
+            response => return Err(Self::unexpected(response)),
+        };
+
+        Ok(Self {
+            address,
+            public_key,
+            connection: Mutex::new(Some(connection)),
+        })
+    }
+
+    pub fn address(&self) -> &SignerAddress {
+        &self.address
+    }
+
+    fn exchange(connection: &mut Connection, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+        write_message(connection, request)
+            .and_then(|_| read_message(connection))
+            .map_err(|e| SignerError::Unavailable(e.to_string()))
+    }
+
+    fn unexpected(response: SignerResponse) -> SignerError {
+        match response {
+            SignerResponse::Error(e) => e,
+            _ => SignerError::Unavailable("Unexpected response".to_string()),
+        }
+    }
+
+    fn request(&self, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+        let mut connection = self.connection.lock();
+
+        // Reconnect if the connection failed during a previous request.
+        if connection.is_none() {
+            debug!("Reconnecting to signer at {}", self.address);
+            *connection = Some(Connection::connect(&self.address, Self::TIMEOUT).map_err(|e| SignerError::Unavailable(e.to_string()))?);
+        }
+
+        let result = Self::exchange(connection.as_mut().unwrap(), request);
+        if result.is_err() {
+            *connection = None;
+        }
+        result
+    }
+
+    fn request_signature(&self, request: SignerRequest) -> Result<Signature, SignerError> {
+        match self.request(&request)? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn sign_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        match self.request(&SignerRequest::Seed(prev_seed.clone()))? {
+            SignerResponse::Seed(seed) => Ok(seed),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::MicroHeader(header.clone()))
+    }
+
+    fn sign_view_change(&self, view_change: &ViewChange) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::ViewChange(view_change.clone()))
+    }
+
+    fn sign_tendermint_proposal(&self, proposal: &TendermintProposal) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::TendermintProposal(proposal.clone()))
+    }
+
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::TendermintVote(vote.clone()))
+    }
+
+    fn sign_validator_record(&self, record: &[u8]) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::ValidatorRecord(record.to_vec()))
+    }
+
+    fn sign_unpark_transaction(&self, transaction: &Transaction) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::UnparkTransaction(transaction.clone()))
+    }
+}
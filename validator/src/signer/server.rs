@@ -0,0 +1,99 @@
+use std::io;
+use std::sync::Arc;
+use std::thread;
+
+use beserial::SerializingError;
+use bls::KeyPair;
+use database::Environment;
+
+use crate::signing_history::SigningGuard;
+
+use super::protocol::{read_message, write_message, SignerRequest, SignerResponse};
+use super::transport::{Connection, Listener, SignerAddress};
+use super::{GuardedSigner, Signer};
+
+/// Serves `RemoteSigner`s. It keeps the validator key and its own signing history, such that it refuses to sign
+/// conflicting messages, no matter how many validators connect to it.
+pub struct SignerServer {
+    signer: GuardedSigner<KeyPair>,
+}
+
+impl SignerServer {
+    /// Creates a server that signs with `key_pair`. The signing history is kept in `env`.
+    pub fn new(key_pair: KeyPair, env: Environment) -> Self {
+        Self {
+            signer: GuardedSigner::new(key_pair, Arc::new(SigningGuard::open(env))),
+        }
+    }
+
+    pub fn signing_guard(&self) -> &Arc<SigningGuard> {
+        self.signer.signing_guard()
+    }
+
+    /// Listens on `address` and serves every connection on its own thread. Returns the address that it listens on,
+    /// which contains the actual port if `address` has port 0.
+    pub fn spawn(self, address: &SignerAddress) -> io::Result<SignerAddress> {
+        let listener = Listener::bind(address)?;
+        let local_address = listener.local_address()?;
+        info!("Signer listening on {}", local_address);
+
+        let server = Arc::new(self);
+        thread::spawn(move || loop {
+            match listener.accept() {
+                Ok(connection) => {
+                    let server = Arc::clone(&server);
+                    thread::spawn(move || server.serve(connection));
+                }
+                Err(e) => warn!("Failed to accept signer connection: {}", e),
+            }
+        });
+
+        Ok(local_address)
+    }
+
+    /// Answers the requests on `connection` until it is closed.
+    fn serve(&self, mut connection: Connection) {
+        loop {
+            let request = match read_message(&mut connection) {
+                Ok(request) => request,
+                Err(SerializingError::IoError(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => return,
+                Err(e) => {
+                    debug!("Closing signer connection after invalid request: {}", e);
+                    return;
+                }
+            };
+
+            let response = self.handle(request);
+            if let Err(e) = write_message(&mut connection, &response) {
+                debug!("Closing signer connection: {}", e);
+                return;
+            }
+        }
+    }
+
+    fn handle(&self, request: SignerRequest) -> SignerResponse {
+        let result = match request {
+            SignerRequest::PublicKey => return SignerResponse::PublicKey(*self.signer.public_key()),
+            SignerRequest::Seed(prev_seed) => {
+                return match self.signer.sign_seed(&prev_seed) {
+                    Ok(seed) => SignerResponse::Seed(seed),
+                    Err(e) => SignerResponse::Error(e),
+                };
+            }
+            SignerRequest::MicroHeader(header) => self.signer.sign_micro_header(&header),
+            SignerRequest::ViewChange(view_change) => self.signer.sign_view_change(&view_change),
+            SignerRequest::TendermintProposal(proposal) => self.signer.sign_tendermint_proposal(&proposal),
+            SignerRequest::TendermintVote(vote) => self.signer.sign_tendermint_vote(&vote),
+            SignerRequest::ValidatorRecord(record) => self.signer.sign_validator_record(&record),
+            SignerRequest::UnparkTransaction(transaction) => self.signer.sign_unpark_transaction(&transaction),
+        };
+
+        match result {
+            Ok(signature) => SignerResponse::Signature(signature),
+            Err(e) => {
+                warn!("Refusing to sign: {}", e);
+                SignerResponse::Error(e)
+            }
+        }
+    }
+}
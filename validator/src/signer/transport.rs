@@ -0,0 +1,173 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{AddrParseError, SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Address of a remote signer, either `host:port` or `unix:/path/to/socket`.
+///
+/// The connection is neither authenticated nor encrypted, so TCP addresses must be loopback addresses. To sign on
+/// another machine, forward the port or socket through an authenticated and encrypted tunnel, e.g. with SSH.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerAddress {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for SignerAddress {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(unix)]
+        {
+            if let Some(path) = s.strip_prefix("unix:") {
+                return Ok(SignerAddress::Unix(PathBuf::from(path)));
+            }
+        }
+        Ok(SignerAddress::Tcp(s.parse()?))
+    }
+}
+
+impl fmt::Display for SignerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerAddress::Tcp(address) => write!(f, "{}", address),
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+pub(crate) enum Connection {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl SignerAddress {
+    /// Fails for TCP addresses that are reachable from other machines.
+    fn check_local(&self) -> io::Result<()> {
+        match self {
+            SignerAddress::Tcp(address) if !address.ip().is_loopback() => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Signer address {} is not a loopback address", address),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Connection {
+    /// Connects to `address`. Reads and writes on the connection fail after `timeout`.
+    pub fn connect(address: &SignerAddress, timeout: Duration) -> io::Result<Self> {
+        address.check_local()?;
+        match address {
+            SignerAddress::Tcp(address) => {
+                let stream = TcpStream::connect_timeout(address, timeout)?;
+                stream.set_nodelay(true)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                Ok(Connection::Tcp(stream))
+            }
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                Ok(Connection::Unix(stream))
+            }
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+pub(crate) enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+impl Listener {
+    pub fn bind(address: &SignerAddress) -> io::Result<Self> {
+        address.check_local()?;
+        match address {
+            SignerAddress::Tcp(address) => Ok(Listener::Tcp(TcpListener::bind(address)?)),
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => Ok(Listener::Unix(UnixListener::bind(path)?, path.clone())),
+        }
+    }
+
+    /// The address that we listen on. For TCP, this contains the actual port if we were bound to port 0.
+    pub fn local_address(&self) -> io::Result<SignerAddress> {
+        match self {
+            Listener::Tcp(listener) => Ok(SignerAddress::Tcp(listener.local_addr()?)),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => Ok(SignerAddress::Unix(path.clone())),
+        }
+    }
+
+    pub fn accept(&self) -> io::Result<Connection> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                Ok(Connection::Tcp(stream))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, _) => {
+                let (stream, _) = listener.accept()?;
+                Ok(Connection::Unix(stream))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_only_uses_loopback_addresses() {
+        let public: SignerAddress = "0.0.0.0:0".parse().unwrap();
+        assert_eq!(Listener::bind(&public).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            Connection::connect(&public, Duration::from_secs(1)).err().unwrap().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        let listener = Listener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        assert!(Connection::connect(&listener.local_address().unwrap(), Duration::from_secs(1)).is_ok());
+    }
+}
@@ -0,0 +1,454 @@
+use std::io;
+use std::sync::Arc;
+
+use failure::Fail;
+use parking_lot::Mutex;
+
+use beserial::{Deserialize, Serialize, SerializingError};
+use block_albatross::{MicroHeader, TendermintProposal, TendermintStep, TendermintVote, ViewChange};
+use database::{Database, Environment, FromDatabaseValue, IntoDatabaseValue, ReadTransaction, WriteTransaction};
+use hash::{Blake2bHash, Hash};
+use primitives::policy;
+
+/// The kinds of messages whose signing is guarded. Each kind has its own history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigningKind {
+    MicroBlock,
+    ViewChange,
+    Proposal,
+    PreVote,
+    PreCommit,
+}
+
+impl SigningKind {
+    const ALL: [SigningKind; 5] = [
+        SigningKind::MicroBlock,
+        SigningKind::ViewChange,
+        SigningKind::Proposal,
+        SigningKind::PreVote,
+        SigningKind::PreCommit,
+    ];
+
+    /// Whether the kind belongs to a Tendermint round, which must not be signed anymore once a later round started.
+    fn is_tendermint(self) -> bool {
+        matches!(self, SigningKind::Proposal | SigningKind::PreVote | SigningKind::PreCommit)
+    }
+}
+
+impl From<TendermintStep> for SigningKind {
+    fn from(step: TendermintStep) -> Self {
+        match step {
+            TendermintStep::Propose => SigningKind::Proposal,
+            TendermintStep::PreVote => SigningKind::PreVote,
+            TendermintStep::PreCommit => SigningKind::PreCommit,
+        }
+    }
+}
+
+/// A message that we signed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRecord {
+    pub block_number: u32,
+    /// The view number of micro blocks and view changes, or the round of Tendermint messages.
+    pub view_number: u32,
+    /// Hash of the signed message. Signing the very same message again is not a conflict.
+    pub hash: Blake2bHash,
+}
+
+impl SignedRecord {
+    fn position(&self) -> (u32, u32) {
+        (self.block_number, self.view_number)
+    }
+}
+
+#[derive(Clone, Debug, Fail, PartialEq, Eq)]
+pub enum SigningConflict {
+    #[fail(display = "{:?} at #{}:{} is for a round that was left at #{}:{}", kind, block_number, view_number, signed_block_number, signed_view_number)]
+    Outdated {
+        kind: SigningKind,
+        block_number: u32,
+        view_number: u32,
+        signed_block_number: u32,
+        signed_view_number: u32,
+    },
+    #[fail(display = "A different {:?} was already signed at #{}:{}", kind, block_number, view_number)]
+    Equivocation { kind: SigningKind, block_number: u32, view_number: u32 },
+}
+
+/// The recently signed messages of each kind, ordered by position. Records are kept as long as a signature conflicting
+/// with them could still be punished, i.e. for the batch of the highest one and the batch before.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningHistory {
+    #[beserial(len_type(u16))]
+    pub micro_blocks: Vec<SignedRecord>,
+    #[beserial(len_type(u16))]
+    pub view_changes: Vec<SignedRecord>,
+    #[beserial(len_type(u16))]
+    pub proposals: Vec<SignedRecord>,
+    #[beserial(len_type(u16))]
+    pub prevotes: Vec<SignedRecord>,
+    #[beserial(len_type(u16))]
+    pub precommits: Vec<SignedRecord>,
+}
+
+impl SigningHistory {
+    fn get(&self, kind: SigningKind) -> &Vec<SignedRecord> {
+        match kind {
+            SigningKind::MicroBlock => &self.micro_blocks,
+            SigningKind::ViewChange => &self.view_changes,
+            SigningKind::Proposal => &self.proposals,
+            SigningKind::PreVote => &self.prevotes,
+            SigningKind::PreCommit => &self.precommits,
+        }
+    }
+
+    fn get_mut(&mut self, kind: SigningKind) -> &mut Vec<SignedRecord> {
+        match kind {
+            SigningKind::MicroBlock => &mut self.micro_blocks,
+            SigningKind::ViewChange => &mut self.view_changes,
+            SigningKind::Proposal => &mut self.proposals,
+            SigningKind::PreVote => &mut self.prevotes,
+            SigningKind::PreCommit => &mut self.precommits,
+        }
+    }
+
+    /// Returns the latest Tendermint message that we signed for the macro block at `block_number`.
+    fn latest_tendermint_record(&self, block_number: u32) -> Option<&SignedRecord> {
+        SigningKind::ALL
+            .iter()
+            .filter(|kind| kind.is_tendermint())
+            .flat_map(|&kind| self.get(kind))
+            .filter(|signed| signed.block_number == block_number)
+            .max_by_key(|signed| signed.view_number)
+    }
+
+    /// Checks whether signing `record` conflicts with what we signed before. A message conflicts with a different one of
+    /// the same kind at the same position, and Tendermint messages also conflict with any later round of the same
+    /// macro block. Messages at lower positions can be signed, e.g. after a rebranch to a shorter chain.
+    pub fn check(&self, kind: SigningKind, record: &SignedRecord) -> Result<(), SigningConflict> {
+        let equivocation = self
+            .get(kind)
+            .iter()
+            .any(|signed| signed.position() == record.position() && signed.hash != record.hash);
+        if equivocation {
+            return Err(SigningConflict::Equivocation {
+                kind,
+                block_number: record.block_number,
+                view_number: record.view_number,
+            });
+        }
+
+        if kind.is_tendermint() {
+            if let Some(latest) = self.latest_tendermint_record(record.block_number) {
+                if record.view_number < latest.view_number {
+                    return Err(SigningConflict::Outdated {
+                        kind,
+                        block_number: record.block_number,
+                        view_number: record.view_number,
+                        signed_block_number: latest.block_number,
+                        signed_view_number: latest.view_number,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a record. Returns whether the history changed.
+    fn insert(&mut self, kind: SigningKind, record: SignedRecord) -> bool {
+        let records = self.get_mut(kind);
+        if records.contains(&record) {
+            return false;
+        }
+
+        records.push(record);
+        records.sort_by_key(SignedRecord::position);
+
+        // Fork proofs are only valid in the batch of the fork and the next one, and a rebranch never crosses a macro
+        // block. Older records can't protect us from anything anymore.
+        let latest_batch = records.last().map_or(0, |latest| policy::batch_at(latest.block_number));
+        records.retain(|signed| policy::batch_at(signed.block_number) + 1 >= latest_batch);
+        true
+    }
+
+    /// Merges another history into this one. Records that conflict with our own are skipped.
+    /// Returns whether this history changed.
+    pub fn merge(&mut self, other: &SigningHistory) -> bool {
+        let mut changed = false;
+        for &kind in SigningKind::ALL.iter() {
+            for record in other.get(kind) {
+                let conflicts = self
+                    .get(kind)
+                    .iter()
+                    .any(|signed| signed.position() == record.position() && signed.hash != record.hash);
+                if !conflicts {
+                    changed |= self.insert(kind, record.clone());
+                }
+            }
+        }
+        changed
+    }
+
+    /// Serializes the history as hex string, e.g. to move it to another machine.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.serialize_to_vec())
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, SerializingError> {
+        let bytes = hex::decode(s.trim()).map_err(|_| SerializingError::InvalidEncoding)?;
+        Deserialize::deserialize_from_vec(&bytes)
+    }
+}
+
+impl IntoDatabaseValue for SigningHistory {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for SigningHistory {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+/// Keeps the validator from signing conflicting messages, even across restarts. Every signed message is recorded in the
+/// `ValidatorState` database before it leaves the validator.
+pub struct SigningGuard {
+    env: Environment,
+    database: Arc<Database>,
+    history: Mutex<SigningHistory>,
+}
+
+impl SigningGuard {
+    const DB_NAME: &'static str = "ValidatorState";
+    const HISTORY_KEY: &'static str = "signingHistory";
+
+    /// Loads the history from the `ValidatorState` database, which the caller already opened. A database can only be
+    /// opened once per environment, so the validator shares it with the other state it persists.
+    pub fn new(env: Environment, database: Arc<Database>) -> Self {
+        let history = ReadTransaction::new(&env)
+            .get(&database, Self::HISTORY_KEY)
+            .unwrap_or_default();
+
+        Self {
+            env,
+            database,
+            history: Mutex::new(history),
+        }
+    }
+
+    /// Opens the `ValidatorState` database and loads the history from it. Only use this if nothing else opened the
+    /// database in `env`.
+    pub fn open(env: Environment) -> Self {
+        let database = Arc::new(env.open_database(Self::DB_NAME.to_string()));
+        Self::new(env, database)
+    }
+
+    /// Returns the current history for export.
+    pub fn history(&self) -> SigningHistory {
+        self.history.lock().clone()
+    }
+
+    /// Imports the history of another machine, which this validator ran on before.
+    pub fn import(&self, history: &SigningHistory) {
+        let mut own = self.history.lock();
+        if own.merge(history) {
+            self.persist(&own);
+        }
+    }
+
+    /// Records that we sign the micro block `header`, unless this conflicts with a micro block that we signed before.
+    pub fn record_micro_block(&self, header: &MicroHeader) -> Result<(), SigningConflict> {
+        self.record(
+            SigningKind::MicroBlock,
+            SignedRecord {
+                block_number: header.block_number,
+                view_number: header.view_number,
+                hash: header.hash(),
+            },
+        )
+    }
+
+    /// Records that we sign `view_change`, unless this conflicts with a view change that we signed before.
+    pub fn record_view_change(&self, view_change: &ViewChange) -> Result<(), SigningConflict> {
+        self.record(
+            SigningKind::ViewChange,
+            SignedRecord {
+                block_number: view_change.block_number,
+                view_number: view_change.new_view_number,
+                hash: view_change.hash(),
+            },
+        )
+    }
+
+    /// Records that we sign `proposal`, unless this conflicts with a proposal or vote that we signed before. A proposal
+    /// of a value from an earlier round carries that round's header, so it can't be told apart from the original
+    /// proposal and isn't recorded.
+    pub fn record_tendermint_proposal(&self, proposal: &TendermintProposal) -> Result<(), SigningConflict> {
+        if proposal.valid_round.is_some() {
+            return Ok(());
+        }
+
+        self.record(
+            SigningKind::Proposal,
+            SignedRecord {
+                block_number: proposal.value.block_number,
+                view_number: proposal.value.view_number,
+                hash: proposal.value.hash(),
+            },
+        )
+    }
+
+    /// Records that we sign `vote`, unless this conflicts with a proposal or vote that we signed before.
+    pub fn record_tendermint_vote(&self, vote: &TendermintVote) -> Result<(), SigningConflict> {
+        self.record(
+            vote.id.step.into(),
+            SignedRecord {
+                block_number: vote.id.block_number,
+                view_number: vote.id.round_number,
+                hash: vote.hash(),
+            },
+        )
+    }
+
+    fn record(&self, kind: SigningKind, record: SignedRecord) -> Result<(), SigningConflict> {
+        let mut history = self.history.lock();
+        history.check(kind, &record)?;
+
+        if history.insert(kind, record) {
+            self.persist(&history);
+        }
+        Ok(())
+    }
+
+    fn persist(&self, history: &SigningHistory) {
+        let mut txn = WriteTransaction::new(&self.env);
+        txn.put_reserve(&self.database, Self::HISTORY_KEY, history);
+        txn.commit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::volatile::VolatileEnvironment;
+
+    use super::*;
+
+    fn record(block_number: u32, view_number: u32, hash: u8) -> SignedRecord {
+        SignedRecord {
+            block_number,
+            view_number,
+            hash: Blake2bHash::from([hash; 32]),
+        }
+    }
+
+    #[test]
+    fn it_refuses_conflicting_messages() {
+        let mut history = SigningHistory::default();
+        history.micro_blocks = vec![record(10, 1, 1)];
+
+        assert!(history.check(SigningKind::MicroBlock, &record(10, 1, 1)).is_ok());
+        assert!(history.check(SigningKind::MicroBlock, &record(10, 2, 2)).is_ok());
+        assert!(history.check(SigningKind::MicroBlock, &record(11, 0, 2)).is_ok());
+        assert_eq!(
+            history.check(SigningKind::MicroBlock, &record(10, 1, 2)),
+            Err(SigningConflict::Equivocation {
+                kind: SigningKind::MicroBlock,
+                block_number: 10,
+                view_number: 1,
+            })
+        );
+
+        // Lower positions can be signed again after a rebranch.
+        assert!(history.check(SigningKind::MicroBlock, &record(10, 0, 2)).is_ok());
+        assert!(history.check(SigningKind::MicroBlock, &record(9, 5, 2)).is_ok());
+
+        // Other kinds have their own history.
+        assert!(history.check(SigningKind::ViewChange, &record(10, 1, 2)).is_ok());
+    }
+
+    #[test]
+    fn it_refuses_tendermint_messages_for_rounds_that_were_left() {
+        let mut history = SigningHistory::default();
+        history.prevotes = vec![record(32, 2, 1)];
+
+        assert!(history.check(SigningKind::PreCommit, &record(32, 2, 1)).is_ok());
+        assert!(history.check(SigningKind::Proposal, &record(32, 3, 1)).is_ok());
+        assert!(matches!(
+            history.check(SigningKind::PreVote, &record(32, 1, 2)),
+            Err(SigningConflict::Outdated { .. })
+        ));
+        assert!(matches!(
+            history.check(SigningKind::Proposal, &record(32, 1, 2)),
+            Err(SigningConflict::Outdated { .. })
+        ));
+        assert!(matches!(
+            history.check(SigningKind::PreVote, &record(32, 2, 2)),
+            Err(SigningConflict::Equivocation { .. })
+        ));
+
+        // Rounds of other macro blocks are independent.
+        assert!(history.check(SigningKind::PreVote, &record(64, 0, 2)).is_ok());
+    }
+
+    #[test]
+    fn it_forgets_records_that_cant_be_punished_anymore() {
+        let mut history = SigningHistory::default();
+        assert!(history.insert(SigningKind::MicroBlock, record(1, 0, 1)));
+        assert!(history.insert(SigningKind::MicroBlock, record(policy::BATCH_LENGTH + 1, 0, 1)));
+        assert!(!history.insert(SigningKind::MicroBlock, record(1, 0, 1)));
+        assert_eq!(history.micro_blocks.len(), 2);
+
+        assert!(history.insert(SigningKind::MicroBlock, record(2 * policy::BATCH_LENGTH + 1, 0, 1)));
+        assert_eq!(
+            history.micro_blocks,
+            vec![record(policy::BATCH_LENGTH + 1, 0, 1), record(2 * policy::BATCH_LENGTH + 1, 0, 1)]
+        );
+    }
+
+    #[test]
+    fn it_merges_histories() {
+        let mut history = SigningHistory::default();
+        history.micro_blocks = vec![record(10, 1, 1)];
+        history.prevotes = vec![record(32, 0, 1)];
+
+        let mut other = SigningHistory::default();
+        other.micro_blocks = vec![record(9, 3, 2), record(10, 1, 2)];
+        other.prevotes = vec![record(32, 1, 2)];
+        other.precommits = vec![record(32, 0, 2)];
+
+        assert!(history.merge(&other));
+        assert_eq!(history.micro_blocks, vec![record(9, 3, 2), record(10, 1, 1)]);
+        assert!(history.view_changes.is_empty());
+        assert_eq!(history.prevotes, vec![record(32, 0, 1), record(32, 1, 2)]);
+        assert_eq!(history.precommits, vec![record(32, 0, 2)]);
+
+        assert!(!history.merge(&other));
+        assert_eq!(SigningHistory::from_hex(&history.to_hex()).unwrap(), history);
+    }
+
+    #[test]
+    fn it_persists_the_history() {
+        let env = VolatileEnvironment::new(10).unwrap();
+
+        let guard = SigningGuard::open(env.clone());
+        let mut imported = SigningHistory::default();
+        imported.view_changes = vec![record(10, 2, 1)];
+        guard.import(&imported);
+
+        // The database must be closed before it's opened again.
+        drop(guard);
+        let guard = SigningGuard::open(env);
+        assert_eq!(guard.history(), imported);
+    }
+}
@@ -1,22 +1,94 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
+use std::io;
+use std::sync::Arc;
 
-use beserial::Serialize;
+use beserial::{Deserialize, Serialize};
 use block_albatross::{Block, ForkProof, MacroBlock, MacroHeader, MicroBlock};
+use database::{Database, Environment, FromDatabaseValue, IntoDatabaseValue, ReadTransaction, WriteTransaction};
+use network_interface::network::Topic;
 
-#[derive(Default)]
+/// Topic on which validators gossip the fork proofs that they detected.
+#[derive(Clone, Debug, Default)]
+pub struct ForkProofTopic;
+
+impl Topic for ForkProofTopic {
+    type Item = ForkProof;
+
+    fn topic(&self) -> String {
+        "fork-proofs".to_owned()
+    }
+
+    // Fork proofs are only forwarded after the validator verified them.
+    fn validate(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedForkProofs {
+    #[beserial(len_type(u16))]
+    fork_proofs: Vec<ForkProof>,
+}
+
+impl IntoDatabaseValue for PersistedForkProofs {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for PersistedForkProofs {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+/// Fork proofs that are not yet included in the chain. The pool is persisted in the `ValidatorState` database, such
+/// that the proofs survive a restart.
 pub struct ForkProofPool {
-    fork_proofs: HashSet<ForkProof>,
+    env: Environment,
+    database: Arc<Database>,
+    // Blocks need their fork proofs in order.
+    fork_proofs: BTreeSet<ForkProof>,
 }
 
 impl ForkProofPool {
-    pub fn new() -> Self {
-        Self::default()
+    const FORK_PROOFS_KEY: &'static str = "forkProofs";
+
+    /// Loads the fork proofs that were persisted before. The `ValidatorState` database is opened by the validator and
+    /// shared, since it can only be opened once per environment.
+    pub fn new(env: Environment, database: Arc<Database>) -> Self {
+        let persisted: PersistedForkProofs = ReadTransaction::new(&env)
+            .get(&database, Self::FORK_PROOFS_KEY)
+            .unwrap_or_default();
+
+        Self {
+            env,
+            database,
+            fork_proofs: persisted.fork_proofs.into_iter().collect(),
+        }
     }
 
     /// Adds a fork proof if it is not yet part of the pool.
     /// Returns whether it has been added.
     pub fn insert(&mut self, fork_proof: ForkProof) -> bool {
-        self.fork_proofs.insert(fork_proof)
+        let inserted = self.fork_proofs.insert(fork_proof);
+        if inserted {
+            self.persist();
+        }
+        inserted
+    }
+
+    /// Returns the number of fork proofs in the pool.
+    pub fn len(&self) -> usize {
+        self.fork_proofs.len()
     }
 
     /// Checks whether a fork proof is already part of the pool.
@@ -26,6 +98,7 @@ impl ForkProofPool {
 
     /// Applies a block to the pool, removing processed fork proofs.
     pub fn apply_block(&mut self, block: &Block) {
+        let len = self.fork_proofs.len();
         match block {
             Block::Micro(MicroBlock { body: Some(extrinsics), .. }) => {
                 for fork_proof in extrinsics.fork_proofs.iter() {
@@ -42,22 +115,29 @@ impl ForkProofPool {
             }
             _ => {}
         }
+        if self.fork_proofs.len() != len {
+            self.persist();
+        }
     }
 
     /// Reverts a block, re-adding fork proofs.
     pub fn revert_block(&mut self, block: &Block) {
         if let Block::Micro(MicroBlock { body: Some(extrinsics), .. }) = block {
+            let len = self.fork_proofs.len();
             for fork_proof in extrinsics.fork_proofs.iter() {
                 self.fork_proofs.insert(fork_proof.clone());
             }
+            if self.fork_proofs.len() != len {
+                self.persist();
+            }
         }
     }
 
-    /// Returns a list of current fork proofs.
-    pub fn get_fork_proofs_for_block(&self, max_size: usize) -> Vec<ForkProof> {
+    /// Returns the fork proofs that are valid in the block at `block_number`, in order.
+    pub fn get_fork_proofs_for_block(&self, block_number: u32, max_size: usize) -> Vec<ForkProof> {
         let mut proofs = Vec::new();
         let mut size = 0;
-        for proof in self.fork_proofs.iter() {
+        for proof in self.fork_proofs.iter().filter(|proof| proof.is_valid_at(block_number)) {
             if size + proof.serialized_size() < max_size {
                 proofs.push(proof.clone());
                 size += proof.serialized_size();
@@ -65,4 +145,90 @@ impl ForkProofPool {
         }
         proofs
     }
+
+    fn persist(&self) {
+        let persisted = PersistedForkProofs {
+            fork_proofs: self.fork_proofs.iter().cloned().collect(),
+        };
+        let mut txn = WriteTransaction::new(&self.env);
+        txn.put_reserve(&self.database, Self::FORK_PROOFS_KEY, &persisted);
+        txn.commit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use block_albatross::MicroHeader;
+    use bls::KeyPair;
+    use database::volatile::VolatileEnvironment;
+    use primitives::policy;
+    use utils::key_rng::SecureGenerate;
+
+    use super::*;
+
+    fn open_database(env: &Environment) -> Arc<Database> {
+        Arc::new(env.open_database("ValidatorState".to_string()))
+    }
+
+    fn fork_proof(key: &KeyPair, block_number: u32) -> ForkProof {
+        let header1 = MicroHeader {
+            version: 1,
+            block_number,
+            view_number: 0,
+            timestamp: 0,
+            parent_hash: Default::default(),
+            seed: Default::default(),
+            extra_data: vec![],
+            state_root: Default::default(),
+            body_root: Default::default(),
+        };
+        let mut header2 = header1.clone();
+        header2.timestamp = 1;
+
+        ForkProof {
+            justification1: key.sign(&header1).compress(),
+            justification2: key.sign(&header2).compress(),
+            header1,
+            header2,
+        }
+    }
+
+    #[test]
+    fn it_persists_fork_proofs() {
+        let key = KeyPair::generate(&mut StdRng::seed_from_u64(0));
+        let env = VolatileEnvironment::new(10).unwrap();
+
+        let mut pool = ForkProofPool::new(env.clone(), open_database(&env));
+        assert!(pool.insert(fork_proof(&key, 1)));
+        assert!(!pool.insert(fork_proof(&key, 1)));
+        assert!(pool.insert(fork_proof(&key, 2)));
+
+        // The database must be closed before it's opened again.
+        drop(pool);
+        let pool = ForkProofPool::new(env.clone(), open_database(&env));
+        assert_eq!(pool.len(), 2);
+        assert!(pool.contains(&fork_proof(&key, 1)));
+        assert!(pool.contains(&fork_proof(&key, 2)));
+    }
+
+    #[test]
+    fn it_only_returns_fork_proofs_that_are_valid_in_the_block() {
+        let key = KeyPair::generate(&mut StdRng::seed_from_u64(0));
+        let env = VolatileEnvironment::new(10).unwrap();
+        let mut pool = ForkProofPool::new(env.clone(), open_database(&env));
+        let old = fork_proof(&key, 1);
+        let recent = fork_proof(&key, policy::BATCH_LENGTH + 1);
+        let current = fork_proof(&key, 2 * policy::BATCH_LENGTH + 1);
+        pool.insert(current.clone());
+        pool.insert(old);
+        pool.insert(recent.clone());
+
+        let fork_proofs = pool.get_fork_proofs_for_block(2 * policy::BATCH_LENGTH + 2, usize::MAX);
+        let mut expected = vec![recent, current];
+        expected.sort();
+        assert_eq!(fork_proofs, expected);
+    }
 }
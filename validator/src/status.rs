@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use blockchain_albatross::Blockchain;
+use bls::CompressedPublicKey;
+use primitives::coin::Coin;
+
+use crate::signing_history::{SigningGuard, SigningHistory};
+use crate::validator::ValidatorStakingState;
+
+/// What the validator did since it was started.
+#[derive(Clone, Debug, Default)]
+pub struct ValidatorStatistics {
+    /// Micro blocks that we produced and that extended our chain.
+    pub micro_blocks_produced: u64,
+
+    /// Micro blocks of our slots that were skipped by a view change.
+    pub micro_blocks_missed: u64,
+
+    /// View changes that we completed as part of the view change aggregation.
+    pub view_changes: u64,
+
+    /// Macro blocks that were finalized while we were active.
+    pub macro_blocks: u64,
+
+    /// Total number of Tendermint rounds that these macro blocks needed.
+    pub tendermint_rounds: u64,
+
+    /// Tendermint rounds of the last of these macro blocks.
+    pub last_tendermint_rounds: Option<u32>,
+
+    /// Sum of the reward inherents to our reward address.
+    pub rewards: Coin,
+
+    /// Number of slash inherents for our slots.
+    pub slashes: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ValidatorStatus {
+    /// Our index in the current epoch's validator list, if we are an active validator.
+    pub validator_id: Option<u16>,
+
+    /// Number of slots we have in the current epoch.
+    pub num_slots: Option<u16>,
+
+    pub staking_state: ValidatorStakingState,
+
+    /// Number of fork proofs that wait to be included in a block.
+    pub fork_proofs: usize,
+
+    /// The view that we are in for the next block.
+    pub view_number: u32,
+
+    pub statistics: ValidatorStatistics,
+}
+
+impl Default for ValidatorStatus {
+    fn default() -> Self {
+        Self {
+            validator_id: None,
+            num_slots: None,
+            staking_state: ValidatorStakingState::NoStake,
+            fork_proofs: 0,
+            view_number: 0,
+            statistics: ValidatorStatistics::default(),
+        }
+    }
+}
+
+/// A slot that we own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpcomingSlot {
+    pub block_number: u32,
+    pub view_number: u32,
+    pub slot_number: u16,
+}
+
+/// Gives access to the status of a validator without owning it.
+#[derive(Clone)]
+pub struct ValidatorProxy {
+    pub blockchain: Arc<Blockchain>,
+    pub(crate) validator_key: CompressedPublicKey,
+    pub(crate) status: Arc<RwLock<ValidatorStatus>>,
+    pub(crate) signing_guard: Arc<SigningGuard>,
+}
+
+impl ValidatorProxy {
+    pub fn validator_key(&self) -> &CompressedPublicKey {
+        &self.validator_key
+    }
+
+    pub fn status(&self) -> ValidatorStatus {
+        self.status.read().clone()
+    }
+
+    /// Returns the history of what we signed, to move the validator to another machine.
+    pub fn export_signing_history(&self) -> SigningHistory {
+        self.signing_guard.history()
+    }
+
+    /// Imports the signing history of another machine that this validator ran on before.
+    pub fn import_signing_history(&self, history: &SigningHistory) {
+        self.signing_guard.import(history)
+    }
+
+    /// Returns our slots in the current batch that are already known. The slot owner of a block is determined by the
+    /// seed of its predecessor, so these are at most the slot of the next block in the current view.
+    pub fn upcoming_slots(&self) -> Vec<UpcomingSlot> {
+        let block_number = self.blockchain.block_number() + 1;
+        let view_number = self.status.read().view_number;
+
+        let (slot, slot_number) = self.blockchain.get_slot_owner_at(block_number, view_number, None);
+        if slot.public_key().compressed() == &self.validator_key {
+            vec![UpcomingSlot {
+                block_number,
+                view_number,
+                slot_number,
+            }]
+        } else {
+            vec![]
+        }
+    }
+}
@@ -8,15 +8,14 @@ use block_albatross::{
     Block, BlockHeader, MacroBlock, MacroBody, MacroHeader, MultiSignature,
     SignedTendermintProposal, TendermintProof, TendermintProposal,
 };
-use block_production_albatross::BlockProducer;
+use block_production_albatross::{BlockProducer, Signer};
 use blockchain_albatross::Blockchain;
-use bls::{KeyPair, PublicKey};
+use bls::PublicKey;
 use database::WriteTransaction;
 use hash::{Blake2bHash, Hash};
 use network_interface::network::Topic;
 use nimiq_primitives::slot::ValidatorSlots;
 use nimiq_validator_network::ValidatorNetwork;
-use primitives::policy::{TENDERMINT_TIMEOUT_DELTA, TENDERMINT_TIMEOUT_INIT};
 use primitives::slot::SlotCollection;
 use tendermint::{
     AggregationResult, ProposalResult, Step, TendermintError, TendermintOutsideDeps,
@@ -25,6 +24,7 @@ use tendermint::{
 use utils::time::OffsetTime;
 
 use crate::aggregation::tendermint::HandelTendermintAdapter;
+use crate::signer::sign_blocking;
 
 // TODO create stream immediately
 
@@ -54,14 +54,17 @@ pub struct TendermintInterface<N: ValidatorNetwork> {
     pub blockchain: Arc<Blockchain>,
     // The aggregation adapter allows Tendermint to use Handel functions and networking.
     pub aggregation_adapter: HandelTendermintAdapter<N>,
-    // This validator's key pair.
-    pub validator_key: KeyPair,
+    // Signs with this validator's key.
+    pub signer: Arc<dyn Signer>,
     // Just a field to temporarily store a block body. Since the body of a macro block is completely
     // deterministic, our Tendermint proposal only contains the block header. If the validator needs
     // the body, it is supposed for him to calculate it from the header and his current state.
     // However, calculating the body is an expensive operation. To avoid having to calculate the
     // body several times, we can cache it here.
     pub cache_body: Option<MacroBody>,
+    // Tendermint's timeout in round 0 and its increase per round.
+    pub timeout_init: Duration,
+    pub timeout_delta: Duration,
 
     proposal_stream:
         Option<BoxStream<'static, (SignedTendermintProposal, <N as ValidatorNetwork>::PubsubId)>>,
@@ -92,7 +95,7 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
                 .get_slot_owner_at(self.blockchain.block_number() + 1, round, None);
 
         // Get our public key.
-        let our_public_key = self.validator_key.public_key.compress();
+        let our_public_key = self.signer.public_key().compress();
 
         // Compare the two public keys.
         slot.public_key().compressed() == &our_public_key
@@ -101,9 +104,13 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
     /// Produces a proposal. Evidently, used when we are the proposer.
     fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError> {
         // Call the block producer to produce the next macro block (minus the justification, of course).
-        let block =
-            self.block_producer
-                .next_macro_block_proposal(self.offset_time.now(), round, vec![]);
+        let block = self
+            .block_producer
+            .next_macro_block_proposal(self.offset_time.now(), round, vec![])
+            .map_err(|err| {
+                error!("Tendermint - get_value: Failed to produce proposal: {}", err);
+                TendermintError::CannotProduceProposal
+            })?;
 
         // Cache the block body for future use.
         self.cache_body = block.body;
@@ -171,7 +178,7 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
         let (validator_index, _) = self
             .blockchain
             .current_validators()
-            .find_idx_and_num_slots_by_public_key(&self.validator_key.public_key.compress())
+            .find_idx_and_num_slots_by_public_key(&self.signer.public_key().compress())
             .ok_or(TendermintError::ProposalBroadcastError)?;
 
         // Create the Tendermint proposal message.
@@ -181,11 +188,20 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
         };
 
         // Sign the message with our validator key.
-        let signed_proposal = SignedTendermintProposal::from_message(
-            proposal_message,
-            &self.validator_key.secret_key,
-            validator_index,
-        );
+        let signature = sign_blocking(&self.signer, {
+            let proposal_message = proposal_message.clone();
+            move |signer| signer.sign_tendermint_proposal(&proposal_message)
+        })
+        .await
+        .map_err(|err| {
+            error!("Tendermint - broadcast_proposal: Failed to sign proposal: {}", err);
+            TendermintError::ProposalBroadcastError
+        })?;
+        let signed_proposal = SignedTendermintProposal {
+            message: proposal_message,
+            signer_idx: validator_index,
+            signature,
+        };
 
         // Broadcast the signed proposal to the network.
         if let Err(err) = self.network.publish(&ProposalTopic, signed_proposal).await {
@@ -223,9 +239,7 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
         let validator_key = *slot.public_key().uncompress_unchecked();
 
         // Calculate the timeout duration.
-        let timeout = Duration::from_millis(
-            TENDERMINT_TIMEOUT_INIT + round as u64 * TENDERMINT_TIMEOUT_DELTA,
-        );
+        let timeout = self.timeout_init + self.timeout_delta * round;
 
         // This waits for a proposal from the proposer until it timeouts.
         let await_res = tokio::time::timeout(
@@ -361,13 +375,15 @@ impl<N: ValidatorNetwork + 'static> TendermintInterface<N> {
     }
 
     pub fn new(
-        validator_key: KeyPair,
+        signer: Arc<dyn Signer>,
         validator_id: u16,
         network: Arc<N>,
         active_validators: ValidatorSlots,
         blockchain: Arc<Blockchain>,
         block_producer: BlockProducer,
         block_height: u32,
+        timeout_init: Duration,
+        timeout_delta: Duration,
     ) -> Self {
         // Create the aggregation object.
         let aggregation_adapter = HandelTendermintAdapter::new(
@@ -375,15 +391,17 @@ impl<N: ValidatorNetwork + 'static> TendermintInterface<N> {
             active_validators,
             block_height,
             network.clone(),
-            validator_key.secret_key,
+            Arc::clone(&signer),
         );
 
         // Create the instance and return it.
         Self {
-            validator_key,
+            signer,
             network,
             aggregation_adapter,
             cache_body: None,
+            timeout_init,
+            timeout_delta,
             block_producer,
             blockchain,
             offset_time: OffsetTime::default(),
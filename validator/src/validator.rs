@@ -1,33 +1,67 @@
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
 
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
 use futures::task::{Context, Poll};
-use futures::{Future, StreamExt};
+use futures::{Future, FutureExt, StreamExt};
+use parking_lot::RwLock;
 use tokio::sync::{broadcast, mpsc};
 
-use block_albatross::{Block, BlockType, ViewChangeProof};
+use account::{Inherent, InherentType, StakingContract};
+use beserial::Deserialize;
+use block_albatross::{Block, BlockType, ForkProof, MacroBlock, MicroBlock, ViewChangeProof, ViewChanges};
+use blockchain_albatross::history_store::ExtTxData;
 use blockchain_albatross::{BlockchainEvent, ForkEvent, PushResult};
 use bls::CompressedPublicKey;
-use consensus_albatross::{sync::block_queue::BlockTopic, Consensus, ConsensusEvent, ConsensusProxy};
+use consensus_albatross::{
+    sync::{block_queue::BlockTopic, compact_block::{CompactBlockTopic, CompactMicroBlock}},
+    Consensus, ConsensusEvent, ConsensusProxy,
+};
 use database::{Database, Environment, ReadTransaction, WriteTransaction};
+use genesis::NetworkInfo;
 use hash::Blake2bHash;
+use keys::Address;
 use network_interface::network::Network;
-use nimiq_block_production_albatross::BlockProducer;
+use nimiq_block_production_albatross::{BlockProducer, Signer};
 use nimiq_tendermint::TendermintReturn;
 use nimiq_validator_network::ValidatorNetwork;
+use primitives::{coin::Coin, policy, slot::SlashedSlot};
+use transaction_builder::proof::staking_contract::SignallingProofBuilder;
+use transaction_builder::{Recipient, TransactionBuilder};
 
+use crate::config::ValidatorConfig;
 use crate::micro::{ProduceMicroBlock, ProduceMicroBlockEvent};
 use crate::r#macro::{PersistedMacroState, ProduceMacroBlock};
-use crate::slash::ForkProofPool;
+use crate::signer::{sign_blocking, GuardedSigner};
+use crate::signing_history::SigningGuard;
+use crate::slash::{ForkProofPool, ForkProofTopic};
+use crate::status::{ValidatorProxy, ValidatorStatus};
 
-enum ValidatorStakingState {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidatorStakingState {
     Active,
     Parked,
     Inactive,
     NoStake,
 }
 
+impl ValidatorStakingState {
+    fn from_staking_contract(staking_contract: &StakingContract, validator_key: &CompressedPublicKey) -> Self {
+        if staking_contract.current_epoch_parking.contains(validator_key)
+            || staking_contract.previous_epoch_parking.contains(validator_key)
+        {
+            ValidatorStakingState::Parked
+        } else if staking_contract.active_validators_by_key.contains_key(validator_key) {
+            ValidatorStakingState::Active
+        } else if staking_contract.inactive_validators_by_key.contains_key(validator_key) {
+            ValidatorStakingState::Inactive
+        } else {
+            ValidatorStakingState::NoStake
+        }
+    }
+}
+
 struct ActiveEpochState {
     validator_id: u16,
 }
@@ -41,26 +75,45 @@ struct ProduceMicroBlockState {
     view_change_proof: Option<ViewChangeProof>,
 }
 
+struct StakingState {
+    /// Reward address of our validator in the staking contract.
+    reward_address: Option<Address>,
+    /// Block number at which we last sent an `UnparkValidator` transaction.
+    unpark_sent_at: Option<u32>,
+}
+
 pub struct Validator<TNetwork: Network, TValidatorNetwork: ValidatorNetwork + 'static> {
     pub consensus: ConsensusProxy<TNetwork>,
     network: Arc<TValidatorNetwork>,
-    signing_key: bls::KeyPair,
+    /// Signs with our validator key. Everything that we sign is checked against the signing history first.
+    signer: Arc<dyn Signer>,
+    signing_guard: Arc<SigningGuard>,
     wallet_key: Option<keys::KeyPair>,
-    database: Database,
+    config: ValidatorConfig,
+    /// Extra data of our micro blocks.
+    extra_data: Vec<u8>,
+    database: Arc<Database>,
     env: Environment,
 
     consensus_event_rx: broadcast::Receiver<ConsensusEvent<TNetwork>>,
     blockchain_event_rx: mpsc::UnboundedReceiver<BlockchainEvent>,
     fork_event_rx: mpsc::UnboundedReceiver<ForkEvent>,
+    /// Fork proofs that other validators gossiped.
+    fork_proof_rx: mpsc::UnboundedReceiver<(ForkProof, TValidatorNetwork::PubsubId)>,
+    /// Publishes our fork proofs and forwards the ones of other validators.
+    fork_proof_tasks: FuturesUnordered<BoxFuture<'static, ()>>,
 
     epoch_state: Option<ActiveEpochState>,
     blockchain_state: BlockchainState,
+    staking_state: StakingState,
 
     macro_producer: Option<ProduceMacroBlock>,
     macro_state: Option<PersistedMacroState<TValidatorNetwork>>,
 
     micro_producer: Option<ProduceMicroBlock<TValidatorNetwork>>,
     micro_state: ProduceMicroBlockState,
+
+    status: Arc<RwLock<ValidatorStatus>>,
 }
 
 impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
@@ -68,21 +121,28 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
 {
     const MACRO_STATE_DB_NAME: &'static str = "ValidatorState";
     const MACRO_STATE_KEY: &'static str = "validatorState";
-    const VIEW_CHANGE_DELAY: Duration = Duration::from_secs(10);
     const FORK_PROOFS_MAX_SIZE: usize = 1_000; // bytes
+    /// Number of blocks after which an `UnparkValidator` transaction is sent again if we are still parked.
+    pub const UNPARK_RETRY_BLOCKS: u32 = policy::BATCH_LENGTH;
 
     pub fn new(
         consensus: &Consensus<TNetwork>,
         network: Arc<TValidatorNetwork>,
-        signing_key: bls::KeyPair,
+        signer: Arc<dyn Signer>,
         wallet_key: Option<keys::KeyPair>,
+        config: ValidatorConfig,
     ) -> Self {
         let consensus_event_rx = consensus.subscribe_events();
         let blockchain_event_rx = consensus.blockchain.notifier.write().as_stream();
         let fork_event_rx = consensus.blockchain.fork_notifier.write().as_stream();
+        let fork_proof_rx = Self::subscribe_fork_proofs(&network);
+
+        let env = consensus.env.clone();
+        // Other parts of the validator keep their state in the same database, which can only be opened once.
+        let database = Arc::new(env.open_database(Self::MACRO_STATE_DB_NAME.to_string()));
 
         let blockchain_state = BlockchainState {
-            fork_proofs: ForkProofPool::new(),
+            fork_proofs: ForkProofPool::new(env.clone(), Arc::clone(&database)),
         };
 
         let micro_state = ProduceMicroBlockState {
@@ -90,34 +150,56 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
             view_change_proof: None,
         };
 
-        let env = consensus.env.clone();
-        let database = env.open_database(Self::MACRO_STATE_DB_NAME.to_string());
+        let staking_state = StakingState {
+            reward_address: None,
+            unpark_sent_at: None,
+        };
 
         let macro_state: Option<PersistedMacroState<TValidatorNetwork>> = {
             let read_transaction = ReadTransaction::new(&env);
             read_transaction.get(&database, Self::MACRO_STATE_KEY)
         };
 
+        let signing_guard = Arc::new(SigningGuard::new(env.clone(), Arc::clone(&database)));
+        if let Some(history) = &config.signing_history {
+            info!("Importing signing history");
+            signing_guard.import(history);
+        }
+        let signer = Arc::new(GuardedSigner::new(signer, Arc::clone(&signing_guard)));
+
+        let extra_data = config.micro_block_extra_data().unwrap_or_else(|| {
+            warn!("Extra data doesn't fit into micro blocks, producing them without it");
+            vec![]
+        });
+
         let mut this = Self {
             consensus: consensus.proxy(),
             network,
-            signing_key,
+            signer,
+            signing_guard,
             wallet_key,
+            config,
+            extra_data,
             database,
             env,
 
             consensus_event_rx,
             blockchain_event_rx,
             fork_event_rx,
+            fork_proof_rx,
+            fork_proof_tasks: FuturesUnordered::new(),
 
             epoch_state: None,
             blockchain_state,
+            staking_state,
 
             macro_producer: None,
             macro_state,
 
             micro_producer: None,
             micro_state,
+
+            status: Arc::default(),
         };
         this.init();
         this
@@ -126,15 +208,23 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
     fn init(&mut self) {
         self.init_epoch();
         self.init_block_producer();
+        self.update_staking_state();
     }
 
     fn init_epoch(&mut self) {
-        self.epoch_state = self
+        let slots = self
             .consensus
             .blockchain
             .current_validators()
-            .find_idx_and_num_slots_by_public_key(&self.signing_key.public_key.compress())
-            .map(|(validator_id, _)| ActiveEpochState { validator_id });
+            .find_idx_and_num_slots_by_public_key(&self.signer.public_key().compress());
+        self.epoch_state = slots.map(|(validator_id, _)| ActiveEpochState { validator_id });
+
+        {
+            let mut status = self.status.write();
+            status.validator_id = slots.map(|(validator_id, _)| validator_id);
+            status.num_slots = slots.map(|(_, num_slots)| num_slots);
+        }
+
         let validator_keys: Vec<CompressedPublicKey> = self
             .consensus
             .blockchain
@@ -142,14 +232,21 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
             .iter()
             .map(|slot_band| slot_band.public_key().compressed().clone())
             .collect();
-        let key = self.signing_key.clone();
+        let public_key = self.signer.public_key().compress();
+        let signer = Arc::clone(&self.signer);
         let nw = self.network.clone();
 
         // TODO might better be done without the task.
         // However we have an entire batch to execute the task so it should not be extremely bad.
         // Also the setting up of our own public key record should probably not be done here but in `init` instead.
         tokio::spawn(async move {
-            if let Err(err) = nw.set_public_key(&key.public_key.compress(), &key.secret_key).await {
+            let sign = |record: &[u8]| {
+                signer
+                    .sign_validator_record(record)
+                    .map_err(|err| error!("Failed to sign DHT record: {}", err))
+                    .ok()
+            };
+            if let Err(err) = nw.set_public_key(&public_key, &sign).await {
                 error!("could not set up DHT rwcord: {:?}", err);
             }
             nw.set_validators(validator_keys).await;
@@ -170,7 +267,7 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
                 let block_producer = BlockProducer::new(
                     self.consensus.blockchain.clone(),
                     self.consensus.mempool.clone(),
-                    self.signing_key.clone(),
+                    Arc::clone(&self.signer),
                 );
 
                 // Take the current state and see if it is applicable to the current height.
@@ -193,9 +290,11 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
                     self.consensus.blockchain.clone(),
                     self.network.clone(),
                     block_producer,
-                    self.signing_key.clone(),
+                    Arc::clone(&self.signer),
                     self.validator_id(),
                     state,
+                    self.config.tendermint_timeout_init,
+                    self.config.tendermint_timeout_delta,
                 ));
             }
             BlockType::Micro => {
@@ -203,21 +302,26 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
                     view_number: self.consensus.blockchain.view_number(),
                     view_change_proof: None,
                 };
+                self.status.write().view_number = self.micro_state.view_number;
 
                 let fork_proofs = self
                     .blockchain_state
                     .fork_proofs
-                    .get_fork_proofs_for_block(Self::FORK_PROOFS_MAX_SIZE);
+                    .get_fork_proofs_for_block(
+                        self.consensus.blockchain.block_number() + 1,
+                        Self::FORK_PROOFS_MAX_SIZE,
+                    );
                 self.micro_producer = Some(ProduceMicroBlock::new(
                     Arc::clone(&self.consensus.blockchain),
                     Arc::clone(&self.consensus.mempool),
                     Arc::clone(&self.network),
-                    self.signing_key.clone(),
+                    Arc::clone(&self.signer),
                     self.validator_id(),
                     fork_proofs,
                     self.micro_state.view_number,
                     self.micro_state.view_change_proof.clone(),
-                    Self::VIEW_CHANGE_DELAY,
+                    self.config.view_change_delay,
+                    self.extra_data.clone(),
                 ));
             }
         }
@@ -237,6 +341,7 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
         }
 
         self.init_block_producer();
+        self.update_staking_state();
     }
 
     fn on_blockchain_extended(&mut self, hash: &Blake2bHash) {
@@ -246,6 +351,8 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
             .get_block(hash, true)
             .expect("Head block not found");
         self.blockchain_state.fork_proofs.apply_block(&block);
+        self.record_block(&block, false);
+        self.status.write().fork_proofs = self.blockchain_state.fork_proofs.len();
     }
 
     fn on_blockchain_rebranched(
@@ -255,16 +362,281 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
     ) {
         for (_hash, block) in old_chain.iter() {
             self.blockchain_state.fork_proofs.revert_block(block);
+            self.record_block(block, true);
         }
         for (_hash, block) in new_chain.iter() {
             self.blockchain_state.fork_proofs.apply_block(&block);
+            self.record_block(block, false);
         }
+        self.status.write().fork_proofs = self.blockchain_state.fork_proofs.len();
+    }
+
+    /// Records the slashes, rewards and Tendermint rounds of a block that was added to the chain, or removes them
+    /// again if the block was reverted.
+    fn record_block(&mut self, block: &Block, reverted: bool) {
+        let validator_key = self.signer.public_key().compress();
+
+        match block {
+            Block::Micro(MicroBlock {
+                header,
+                body: Some(body),
+                ..
+            }) => {
+                let blockchain = &self.consensus.blockchain;
+                let first_view_number = match blockchain.get_block(&header.parent_hash, false) {
+                    Some(parent) => parent.next_view_number(),
+                    None => return,
+                };
+
+                let is_ours = |inherent: &Inherent| {
+                    SlashedSlot::deserialize_from_vec(&inherent.data)
+                        .map(|slot| slot.validator_key.compressed() == &validator_key)
+                        .unwrap_or(false)
+                };
+
+                // Every view change skipped the block of a slot, which results in a slash inherent.
+                let missed = ViewChanges::new(header.block_number, first_view_number, header.view_number)
+                    .map(|view_changes| blockchain.inherents_from_view_changes(&view_changes, None))
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|&inherent| is_ours(inherent))
+                    .count() as u64;
+                let forks = body
+                    .fork_proofs
+                    .iter()
+                    .filter(|fork_proof| is_ours(&blockchain.inherent_from_fork_proof(fork_proof, None)))
+                    .count() as u64;
+
+                let mut status = self.status.write();
+                if reverted {
+                    status.statistics.micro_blocks_missed = status.statistics.micro_blocks_missed.saturating_sub(missed);
+                    status.statistics.slashes = status.statistics.slashes.saturating_sub(missed + forks);
+                } else {
+                    if missed + forks > 0 {
+                        warn!("Our validator was slashed in block #{}", header.block_number);
+                    }
+                    status.statistics.micro_blocks_missed += missed;
+                    status.statistics.slashes += missed + forks;
+                }
+            }
+            // Macro blocks are final, so they are never reverted.
+            Block::Macro(MacroBlock {
+                header, justification, ..
+            }) if !reverted => {
+                let reward = match &self.staking_state.reward_address {
+                    Some(reward_address) => self
+                        .consensus
+                        .blockchain
+                        .history_store
+                        .get_block_transactions(header.block_number, None)
+                        .into_iter()
+                        .filter_map(|ext_tx| match ext_tx.data {
+                            ExtTxData::Inherent(inherent) => Some(inherent),
+                            _ => None,
+                        })
+                        .filter(|inherent| inherent.ty == InherentType::Reward && &inherent.target == reward_address)
+                        .fold(Coin::ZERO, |sum, inherent| sum + inherent.value),
+                    None => Coin::ZERO,
+                };
+
+                let is_active = self.is_active();
+                let mut status = self.status.write();
+                status.statistics.rewards += reward;
+
+                if let (true, Some(justification)) = (is_active, justification) {
+                    let rounds = justification.round + 1;
+                    status.statistics.macro_blocks += 1;
+                    status.statistics.tendermint_rounds += rounds as u64;
+                    status.statistics.last_tendermint_rounds = Some(rounds);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Re-evaluates our state in the staking contract and unparks our validator, if it was parked.
+    fn update_staking_state(&mut self) {
+        let validator_key = self.signer.public_key().compress();
+        let staking_contract = self.consensus.blockchain.get_staking_contract();
+        let state = ValidatorStakingState::from_staking_contract(&staking_contract, &validator_key);
+        self.staking_state.reward_address = staking_contract
+            .get_validator(&validator_key)
+            .map(|validator| validator.reward_address.clone());
+
+        let previous_state = std::mem::replace(&mut self.status.write().staking_state, state);
+        if state != previous_state {
+            info!("Validator staking state changed: {:?} -> {:?}", previous_state, state);
+        }
+
+        if state != ValidatorStakingState::Parked {
+            self.staking_state.unpark_sent_at = None;
+            return;
+        }
+
+        if !self.config.automatic_unpark {
+            return;
+        }
+
+        let block_number = self.consensus.blockchain.block_number();
+        if let Some(sent_at) = self.staking_state.unpark_sent_at {
+            if block_number < sent_at + Self::UNPARK_RETRY_BLOCKS {
+                return;
+            }
+        }
+
+        let wallet_key = match &self.wallet_key {
+            Some(wallet_key) => wallet_key.clone(),
+            None => {
+                warn!("Our validator is parked, but it can't be unparked without the wallet key");
+                return;
+            }
+        };
+        let mut proof_builder = match self.unpark_transaction(&wallet_key, block_number) {
+            Some(proof_builder) => proof_builder,
+            None => {
+                error!("Failed to build unpark transaction");
+                return;
+            }
+        };
+        self.staking_state.unpark_sent_at = Some(block_number);
+
+        info!("Our validator is parked, sending unpark transaction");
+        let signer = Arc::clone(&self.signer);
+        let consensus = self.consensus.clone();
+        tokio::spawn(async move {
+            let validator_signature = match sign_blocking(&signer, {
+                let transaction = proof_builder.transaction.clone();
+                move |signer| signer.sign_unpark_transaction(&transaction)
+            })
+            .await
+            {
+                Ok(validator_signature) => validator_signature,
+                Err(e) => {
+                    error!("Failed to sign unpark transaction: {}", e);
+                    return;
+                }
+            };
+            proof_builder.with_validator_signature(validator_signature);
+            let transaction = match proof_builder.generate().map(|proof_builder| proof_builder.unwrap_basic()) {
+                Some(mut proof_builder) => {
+                    proof_builder.sign_with_key_pair(&wallet_key);
+                    proof_builder.generate()
+                }
+                None => None,
+            };
+            let transaction = match transaction {
+                Some(transaction) => transaction,
+                None => {
+                    error!("Failed to sign unpark transaction with the wallet key");
+                    return;
+                }
+            };
+            match consensus.send_transaction(transaction).await {
+                Ok(result) => debug!("Unpark transaction sent: {:?}", result),
+                Err(e) => error!("Failed to send unpark transaction: {:?}", e),
+            }
+        });
+    }
+
+    /// Builds an `UnparkValidator` transaction, which still needs to be signed with our validator and wallet keys.
+    fn unpark_transaction(&self, wallet_key: &keys::KeyPair, validity_start_height: u32) -> Option<SignallingProofBuilder> {
+        let network_id = self.consensus.blockchain.network_id;
+        let staking_contract = NetworkInfo::from_network_id(network_id)
+            .validator_registry_address()
+            .expect("No ValidatorRegistry");
+
+        let mut recipient = Recipient::new_staking_builder(staking_contract.clone());
+        recipient.unpark_validator(self.signer.public_key());
+
+        let mut builder = TransactionBuilder::with_required(
+            Address::from(&wallet_key.public),
+            recipient.generate()?,
+            Coin::ZERO,
+            validity_start_height,
+            network_id,
+        );
+        builder.with_fee(self.config.unpark_fee);
+
+        Some(builder.generate().ok()?.unwrap_signalling())
+    }
+
+    /// Subscribes to the fork proofs that other validators gossip.
+    fn subscribe_fork_proofs(
+        network: &Arc<TValidatorNetwork>,
+    ) -> mpsc::UnboundedReceiver<(ForkProof, TValidatorNetwork::PubsubId)> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let nw = Arc::clone(network);
+
+        // TODO might better be done without the task.
+        tokio::spawn(async move {
+            let mut fork_proofs = match nw.subscribe(&ForkProofTopic).await {
+                Ok(fork_proofs) => fork_proofs,
+                Err(err) => {
+                    error!("Failed to subscribe to fork proofs: {}", err);
+                    return;
+                }
+            };
+            while let Some(fork_proof) = fork_proofs.next().await {
+                if tx.send(fork_proof).is_err() {
+                    break;
+                }
+            }
+        });
+
+        rx
     }
 
     fn on_fork_event(&mut self, event: ForkEvent) {
         match event {
-            ForkEvent::Detected(fork_proof) => self.blockchain_state.fork_proofs.insert(fork_proof),
+            ForkEvent::Detected(fork_proof) => {
+                if self.blockchain_state.fork_proofs.insert(fork_proof.clone()) {
+                    // Let the other validators include the proof, in case we don't produce a block in time.
+                    let nw = self.network.clone();
+                    self.fork_proof_tasks.push(
+                        async move {
+                            trace!("publishing fork proof: {:?}", &fork_proof);
+                            if let Err(e) = nw.publish(&ForkProofTopic, fork_proof).await {
+                                error!("Failed to publish fork proof: {}", e);
+                            }
+                        }
+                        .boxed(),
+                    );
+                }
+            }
         };
+        self.status.write().fork_proofs = self.blockchain_state.fork_proofs.len();
+    }
+
+    /// Adds a fork proof that another validator gossiped to the pool, if it is valid and can still be included in
+    /// the chain. Only then is it forwarded to our peers.
+    fn on_fork_proof(&mut self, fork_proof: ForkProof, id: TValidatorNetwork::PubsubId) {
+        let blockchain = &self.consensus.blockchain;
+        // We can only verify proofs of blocks up to our head.
+        if fork_proof.block_number() > blockchain.block_number() || !fork_proof.is_valid_at(blockchain.block_number() + 1) {
+            return;
+        }
+
+        // Proofs in our pool have been verified before.
+        if !self.blockchain_state.fork_proofs.contains(&fork_proof) {
+            let (slot, _) = blockchain.get_slot_owner_at(fork_proof.block_number(), fork_proof.view_number(), None);
+            if let Err(e) = fork_proof.verify(&slot.public_key().uncompress_unchecked()) {
+                debug!("Discarding invalid fork proof: {:?}", e);
+                return;
+            }
+
+            self.blockchain_state.fork_proofs.insert(fork_proof);
+            self.status.write().fork_proofs = self.blockchain_state.fork_proofs.len();
+        }
+
+        let nw = self.network.clone();
+        self.fork_proof_tasks.push(
+            async move {
+                if let Err(e) = nw.validate_message(id).await {
+                    error!("Failed to validate fork proof message: {}", e);
+                }
+            }
+            .boxed(),
+        );
     }
 
     fn poll_macro(&mut self, cx: &mut Context<'_>) {
@@ -328,11 +700,16 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
                         .map_err(|e| error!("Failed to push our block onto the chain: {:?}", e))
                         .ok();
                     if result == Some(PushResult::Extended) || result == Some(PushResult::Rebranched) {
+                        self.status.write().statistics.micro_blocks_produced += 1;
+
                         // todo get rid of spawn
                         let nw = self.network.clone();
                         tokio::spawn(async move {
                             trace!("publishing micro block: {:?}", &block_copy);
-                            if let Err(_) = nw.publish(&BlockTopic, Block::Micro(block_copy)).await {
+                            // Peers reconstruct the body from their mempools.
+                            let compact_block = CompactMicroBlock::from_block(&block_copy)
+                                .expect("Produced micro block without body");
+                            if let Err(_) = nw.publish(&CompactBlockTopic, compact_block).await {
                                 error!("Failed to publish Block");
                             }
                         });
@@ -341,6 +718,10 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
                 ProduceMicroBlockEvent::ViewChange(new_view_number, view_change_proof) => {
                     self.micro_state.view_number = new_view_number;
                     self.micro_state.view_change_proof = Some(view_change_proof);
+
+                    let mut status = self.status.write();
+                    status.view_number = new_view_number;
+                    status.statistics.view_changes += 1;
                 }
             }
         }
@@ -357,8 +738,21 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
             .validator_id
     }
 
-    pub fn signing_key(&self) -> bls::KeyPair {
-        self.signing_key.clone()
+    pub fn public_key(&self) -> &bls::PublicKey {
+        self.signer.public_key()
+    }
+
+    pub fn staking_state(&self) -> ValidatorStakingState {
+        self.status.read().staking_state
+    }
+
+    pub fn proxy(&self) -> ValidatorProxy {
+        ValidatorProxy {
+            blockchain: Arc::clone(&self.consensus.blockchain),
+            validator_key: self.signer.public_key().compress(),
+            status: Arc::clone(&self.status),
+            signing_guard: Arc::clone(&self.signing_guard),
+        }
     }
 }
 
@@ -391,6 +785,14 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork> Future
             }
         }
 
+        // Process gossiped fork proofs.
+        while let Poll::Ready(Some((fork_proof, id))) = self.fork_proof_rx.poll_next_unpin(cx) {
+            if self.consensus.is_established() {
+                self.on_fork_proof(fork_proof, id);
+            }
+        }
+        while let Poll::Ready(Some(())) = self.fork_proof_tasks.poll_next_unpin(cx) {}
+
         // If we are an active validator, participate in block production.
         if self.consensus.is_established() && self.is_active() {
             if self.macro_producer.is_some() {
@@ -4,21 +4,32 @@ use rand::SeedableRng;
 use tokio::sync::broadcast;
 use tokio::time;
 
-use nimiq_blockchain_albatross::Blockchain;
-use nimiq_bls::KeyPair;
+use nimiq_block_albatross::{
+    Block, ForkProof, MicroHeader, TendermintProposal, TendermintVote, ViewChange,
+};
+use nimiq_block_production_albatross::test_utils::sign_macro_block;
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::{Blockchain, BlockchainEvent};
+use nimiq_bls::{KeyPair, PublicKey, Signature};
 use nimiq_build_tools::genesis::{GenesisBuilder, GenesisInfo};
 use nimiq_consensus_albatross::sync::history::HistorySync;
 use nimiq_consensus_albatross::{Consensus as AbstractConsensus, ConsensusEvent};
 use nimiq_database::volatile::VolatileEnvironment;
-use nimiq_keys::{Address, SecureGenerate};
+use nimiq_keys::{Address, KeyPair as WalletKeyPair, SecureGenerate};
 use nimiq_mempool::{Mempool, MempoolConfig};
 use nimiq_network_interface::network::Network;
 use nimiq_network_mock::{MockHub, MockNetwork};
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy;
+use nimiq_transaction::account::staking_contract::IncomingStakingTransactionData;
+use nimiq_transaction::Transaction;
 use nimiq_utils::time::OffsetTime;
-use nimiq_validator::validator::Validator as AbstractValidator;
+use nimiq_validator::config::ValidatorConfig;
+use nimiq_validator::signer::{RemoteSigner, Signer, SignerError, SignerServer};
+use nimiq_validator::validator::{Validator as AbstractValidator, ValidatorStakingState};
 use nimiq_validator_network::network_impl::ValidatorNetworkImpl;
+use nimiq_vrf::VrfSeed;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -52,7 +63,7 @@ async fn mock_consensus(hub: &mut MockHub, peer_id: u64, genesis_info: GenesisIn
 async fn mock_validator(
     hub: &mut MockHub,
     peer_id: u64,
-    signing_key: KeyPair,
+    signer: Arc<dyn Signer>,
     genesis_info: GenesisInfo,
 ) -> (Validator, Consensus) {
     let consensus = mock_consensus(hub, peer_id, genesis_info).await;
@@ -61,8 +72,9 @@ async fn mock_validator(
         Validator::new(
             &consensus,
             validator_network,
-            signing_key,
+            signer,
             None,
+            ValidatorConfig::default(),
         ),
         consensus,
     )
@@ -90,7 +102,7 @@ async fn mock_validators(hub: &mut MockHub, num_validators: usize) -> Vec<Valida
     let mut validators = vec![];
     let mut consensus = vec![];
     for (id, key) in keys.into_iter().enumerate() {
-        let (v, c) = mock_validator(hub, id as u64, key, genesis.clone()).await;
+        let (v, c) = mock_validator(hub, id as u64, Arc::new(key), genesis.clone()).await;
         validators.push(v);
         consensus.push(c);
     }
@@ -133,7 +145,7 @@ fn validator_for_slot(
     validators
         .iter()
         .find(|validator| {
-            &validator.signing_key().public_key.compress() == slot.public_key().compressed()
+            &validator.public_key().compress() == slot.public_key().compressed()
         })
         .unwrap()
 }
@@ -152,19 +164,60 @@ async fn one_validator_can_create_micro_blocks() {
         .generate()
         .unwrap();
 
-    let (validator, mut consensus1) = mock_validator(&mut hub, 1, key, genesis.clone()).await;
+    let (validator, mut consensus1) =
+        mock_validator(&mut hub, 1, Arc::new(key), genesis.clone()).await;
 
     log::debug!("Establishing consensus...");
     consensus1.force_established();
     assert_eq!(consensus1.is_established(), true);
 
     log::debug!("Spawning validator...");
+    let proxy = validator.proxy();
     tokio::spawn(validator);
 
     let events1 = consensus1.blockchain.notifier.write().as_stream();
     events1.take(10).for_each(|_| future::ready(())).await;
 
     assert!(consensus1.blockchain.block_number() >= 10);
+
+    let status = proxy.status();
+    assert_eq!(status.validator_id, Some(0));
+    assert_eq!(status.staking_state, ValidatorStakingState::Active);
+    assert!(status.statistics.micro_blocks_produced > 0);
+    assert_eq!(status.statistics.slashes, 0);
+}
+
+#[tokio::test]
+async fn one_validator_can_create_micro_blocks_with_remote_signer() {
+    let mut hub = MockHub::default();
+
+    let key = KeyPair::generate(&mut seeded_rng(0));
+    let genesis = GenesisBuilder::default()
+        .with_genesis_validator(
+            key.public_key,
+            Address::default(),
+            Coin::from_u64_unchecked(10000),
+        )
+        .generate()
+        .unwrap();
+
+    // The signer keeps the key and its signing history in a separate database.
+    let server = SignerServer::new(key, VolatileEnvironment::new(10).unwrap());
+    let address = server.spawn(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let signer = RemoteSigner::connect(address).unwrap();
+
+    let (validator, mut consensus1) =
+        mock_validator(&mut hub, 1, Arc::new(signer), genesis.clone()).await;
+    consensus1.force_established();
+
+    let proxy = validator.proxy();
+    tokio::spawn(validator);
+
+    let events1 = consensus1.blockchain.notifier.write().as_stream();
+    events1.take(10).for_each(|_| future::ready(())).await;
+
+    assert!(consensus1.blockchain.block_number() >= 10);
+    assert!(proxy.status().statistics.micro_blocks_produced > 0);
 }
 
 #[tokio::test]
@@ -214,3 +267,239 @@ async fn four_validators_can_view_change() {
     assert!(blockchain.block_number() > 1);
     assert_eq!(blockchain.view_number(), 1);
 }
+
+#[tokio::test]
+async fn validators_gossip_and_include_fork_proofs() {
+    let mut hub = MockHub::default();
+
+    let mut rng = seeded_rng(0);
+    let producer_key = KeyPair::generate(&mut rng);
+    let detector_key = KeyPair::generate(&mut rng);
+    let genesis = GenesisBuilder::default()
+        .with_genesis_validator(
+            producer_key.public_key,
+            Address::default(),
+            Coin::from_u64_unchecked(10000),
+        )
+        .generate()
+        .unwrap();
+
+    // Only the producer is in the validator set, so only it produces blocks.
+    let (producer, mut consensus1) =
+        mock_validator(&mut hub, 1, Arc::new(producer_key.clone()), genesis.clone()).await;
+    let (detector, mut consensus2) =
+        mock_validator(&mut hub, 2, Arc::new(detector_key), genesis).await;
+    consensus1.network.dial_mock(&consensus2.network);
+    consensus1.force_established();
+    consensus2.force_established();
+
+    // The producer equivocates at block 1, but only the detector sees both blocks.
+    let block_producer =
+        BlockProducer::new_without_mempool(Arc::clone(&consensus2.blockchain), producer_key);
+    let block = block_producer
+        .next_micro_block(consensus2.blockchain.time.now(), 0, None, vec![], vec![0x41])
+        .unwrap();
+    let fork = block_producer
+        .next_micro_block(consensus2.blockchain.time.now(), 0, None, vec![], vec![0x42])
+        .unwrap();
+    consensus1.blockchain.push(Block::Micro(block.clone())).unwrap();
+    consensus2.blockchain.push(Block::Micro(block)).unwrap();
+    consensus2.blockchain.push(Block::Micro(fork)).unwrap();
+
+    let blockchain = Arc::clone(&consensus1.blockchain);
+    let events = blockchain.notifier.write().as_stream();
+
+    tokio::spawn(producer);
+    tokio::spawn(detector);
+
+    // The producer only learns about the fork from the gossiped proof.
+    let mut events = events.filter_map(|event| {
+        future::ready(match event {
+            BlockchainEvent::Extended(hash) => blockchain.get_block(&hash, true),
+            _ => None,
+        })
+    });
+    time::timeout(Duration::from_secs(20), async {
+        while let Some(block) = events.next().await {
+            if let Block::Micro(micro_block) = block {
+                if !micro_block.body.unwrap().fork_proofs.is_empty() {
+                    return;
+                }
+            }
+        }
+        panic!("Blockchain events ended");
+    })
+    .await
+    .unwrap();
+}
+
+/// Signs as our validator, but refuses to sign blocks, such that the test decides which blocks
+/// are produced.
+struct NonProducingSigner(KeyPair);
+
+impl NonProducingSigner {
+    fn refuse<T>() -> Result<T, SignerError> {
+        Err(SignerError::Refused(
+            "The test produces the blocks".to_owned(),
+        ))
+    }
+}
+
+impl Signer for NonProducingSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.0.public_key
+    }
+
+    fn sign_seed(&self, _prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        Self::refuse()
+    }
+
+    fn sign_micro_header(&self, _header: &MicroHeader) -> Result<Signature, SignerError> {
+        Self::refuse()
+    }
+
+    fn sign_view_change(&self, _view_change: &ViewChange) -> Result<Signature, SignerError> {
+        Self::refuse()
+    }
+
+    fn sign_tendermint_proposal(
+        &self,
+        _proposal: &TendermintProposal,
+    ) -> Result<Signature, SignerError> {
+        Self::refuse()
+    }
+
+    fn sign_tendermint_vote(&self, _vote: &TendermintVote) -> Result<Signature, SignerError> {
+        Self::refuse()
+    }
+
+    fn sign_validator_record(&self, record: &[u8]) -> Result<Signature, SignerError> {
+        Signer::sign_validator_record(&self.0, record)
+    }
+
+    fn sign_unpark_transaction(&self, transaction: &Transaction) -> Result<Signature, SignerError> {
+        Signer::sign_unpark_transaction(&self.0, transaction)
+    }
+}
+
+/// Pushes blocks until the chain reaches `block_number`.
+fn produce_blocks_until(
+    producer: &BlockProducer,
+    key: &KeyPair,
+    blockchain: &Arc<Blockchain>,
+    block_number: u32,
+) {
+    while blockchain.block_number() < block_number {
+        let next_block_number = blockchain.block_number() + 1;
+        let timestamp = blockchain.time.now() + next_block_number as u64 * 1000;
+        let block = if policy::is_macro_block_at(next_block_number) {
+            let proposal = producer
+                .next_macro_block_proposal(timestamp, 0, vec![])
+                .unwrap();
+            Block::Macro(sign_macro_block(key, proposal.header, proposal.body))
+        } else {
+            let block = producer
+                .next_micro_block(timestamp, 0, None, vec![], vec![0x42])
+                .unwrap();
+            Block::Micro(block)
+        };
+        blockchain.push(block).unwrap();
+    }
+}
+
+/// Waits until the mempool contains `count` `UnparkValidator` transactions for `validator_key`
+/// and returns their validity start heights.
+async fn unpark_transactions(
+    mempool: &Mempool,
+    validator_key: &PublicKey,
+    count: usize,
+) -> Vec<u32> {
+    let validator_key = validator_key.compress();
+    time::timeout(Duration::from_secs(5), async {
+        loop {
+            let mut heights: Vec<u32> = mempool
+                .get_transactions(usize::MAX, 0.0)
+                .into_iter()
+                .filter(|tx| {
+                    matches!(
+                        IncomingStakingTransactionData::parse(tx),
+                        Ok(IncomingStakingTransactionData::UnparkValidator { validator_key: key, .. })
+                            if key == validator_key
+                    )
+                })
+                .map(|tx| tx.validity_start_height)
+                .collect();
+            if heights.len() >= count {
+                heights.sort();
+                return heights;
+            }
+            time::delay_for(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Unpark transactions should reach the mempool")
+}
+
+#[tokio::test]
+async fn parked_validator_sends_unpark_transactions() {
+    let mut hub = MockHub::default();
+
+    let key = KeyPair::generate(&mut seeded_rng(0));
+    let wallet_key = WalletKeyPair::generate_default_csprng();
+    let wallet_address = Address::from(&wallet_key.public);
+    let genesis = GenesisBuilder::default()
+        .with_genesis_validator(
+            key.public_key,
+            wallet_address.clone(),
+            Coin::from_u64_unchecked(10000),
+        )
+        .with_basic_account(wallet_address, Coin::from_u64_unchecked(1000))
+        .generate()
+        .unwrap();
+
+    let mut consensus = mock_consensus(&mut hub, 1, genesis).await;
+    consensus.force_established();
+    let validator = Validator::new(
+        &consensus,
+        Arc::new(ValidatorNetworkImpl::new(consensus.network.clone())),
+        Arc::new(NonProducingSigner(key.clone())),
+        Some(wallet_key),
+        ValidatorConfig::default(),
+    );
+    let proxy = validator.proxy();
+    tokio::spawn(validator);
+
+    // Our validator equivocates at block 1 and is slashed for it in block 2, which parks it.
+    let blockchain = Arc::clone(&consensus.blockchain);
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), key.clone());
+    let timestamp = blockchain.time.now() + 1000;
+    let block = producer
+        .next_micro_block(timestamp, 0, None, vec![], vec![0x41])
+        .unwrap();
+    let fork = producer
+        .next_micro_block(timestamp, 0, None, vec![], vec![0x42])
+        .unwrap();
+    let fork_proof = ForkProof {
+        header1: block.header.clone(),
+        header2: fork.header,
+        justification1: block.justification.clone().unwrap().signature,
+        justification2: fork.justification.unwrap().signature,
+    };
+    blockchain.push(Block::Micro(block)).unwrap();
+    let block = producer
+        .next_micro_block(timestamp + 1000, 0, None, vec![fork_proof], vec![0x42])
+        .unwrap();
+    blockchain.push(Block::Micro(block)).unwrap();
+
+    // The unpark transaction is never included, because the test produces blocks without
+    // transactions.
+    let heights = unpark_transactions(&consensus.mempool, &key.public_key, 1).await;
+    assert_eq!(heights, vec![2]);
+    assert_eq!(proxy.status().staking_state, ValidatorStakingState::Parked);
+
+    // The validator is still parked, so it sends the transaction again.
+    let retry_at = 2 + Validator::UNPARK_RETRY_BLOCKS;
+    produce_blocks_until(&producer, &key, &blockchain, retry_at);
+    let heights = unpark_transactions(&consensus.mempool, &key.public_key, 2).await;
+    assert_eq!(heights, vec![2, retry_at]);
+}